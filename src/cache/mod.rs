@@ -1,5 +1,5 @@
 pub mod models;
 pub mod sqlite;
 
-pub use models::DbItem;
+pub use models::{ActivityBucket, CacheDump, DbHistoryEntry, DbItem};
 pub use sqlite::SqliteCache;
@@ -1,8 +1,11 @@
+use crate::sync::diff::ListChange;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
 /// Database representation of a shopping list
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct DbList {
     pub id: String,
     pub name: String,
@@ -10,7 +13,7 @@ pub struct DbList {
 }
 
 /// Database representation of a list item
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct DbItem {
     pub id: String,
     pub list_id: String,
@@ -20,7 +23,64 @@ pub struct DbItem {
     pub category: Option<String>,
     pub is_checked: bool,
     pub user_id: Option<String>,
-    pub last_seen: i64, // Unix timestamp
+    pub last_seen: i64,  // Unix timestamp
+    pub first_seen: i64, // Unix timestamp
+}
+
+/// Database representation of a recorded change
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct DbHistoryEntry {
+    pub id: i64,
+    pub list_id: String,
+    pub list_name: String,
+    pub change_type: String,
+    pub user_id: Option<String>,
+    pub created_at: i64, // Unix timestamp
+    pub payload: String, // JSON-serialized ListChange
+    pub dedup_key: Option<String>,
+}
+
+/// Database representation of a persisted user display name
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct DbUser {
+    pub user_id: String,
+    pub display_name: String,
+    pub last_updated: i64, // Unix timestamp
+}
+
+/// Database representation of a user a list is shared with
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct DbSharedUser {
+    pub list_id: String,
+    pub user_id: String,
+}
+
+/// Full contents of the cache, as written by `cache-export`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheDump {
+    pub version: u32,
+    pub exported_at: i64, // Unix timestamp
+    pub lists: Vec<DbList>,
+    pub items: Vec<DbItem>,
+    #[serde(default)]
+    pub shared_users: Vec<DbSharedUser>,
+    #[serde(default)]
+    pub users: Vec<DbUser>,
+    #[serde(default)]
+    pub history: Vec<DbHistoryEntry>,
+}
+
+impl CacheDump {
+    /// Format version written by this build
+    pub const VERSION: u32 = 1;
+}
+
+/// Number of recorded changes in one weekday and hour-of-day bucket
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct ActivityBucket {
+    pub weekday: i64, // 0 = Sunday
+    pub hour: i64,
+    pub count: i64,
 }
 
 impl DbList {
@@ -48,6 +108,7 @@ impl DbItem {
         is_checked: bool,
         user_id: Option<String>,
     ) -> Self {
+        let now = Utc::now().timestamp();
         Self {
             id,
             list_id,
@@ -57,7 +118,8 @@ impl DbItem {
             category,
             is_checked,
             user_id,
-            last_seen: Utc::now().timestamp(),
+            last_seen: now,
+            first_seen: now,
         }
     }
 
@@ -66,6 +128,23 @@ impl DbItem {
     }
 }
 
+impl DbHistoryEntry {
+    /// Create a history entry for a change detected now
+    pub fn from_change(change: &ListChange, dedup_bucket_secs: u64) -> Result<Self> {
+        let created_at = Utc::now().timestamp();
+        Ok(Self {
+            id: 0,
+            list_id: change.list_id().to_string(),
+            list_name: change.list_name().to_string(),
+            change_type: change.change_type().to_string(),
+            user_id: change.user_id().map(str::to_string),
+            created_at,
+            payload: serde_json::to_string(change).context("Failed to serialize change")?,
+            dedup_key: Some(change.dedup_key(created_at, dedup_bucket_secs)?),
+        })
+    }
+}
+
 /// Convert anylist_rs::ListItem to DbItem
 impl From<&anylist_rs::ListItem> for DbItem {
     fn from(item: &anylist_rs::ListItem) -> Self {
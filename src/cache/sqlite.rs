@@ -1,17 +1,33 @@
-use super::models::{DbItem, DbList};
+use super::models::{
+    ActivityBucket, CacheDump, DbHistoryEntry, DbItem, DbList, DbSharedUser, DbUser,
+};
+use crate::config::CacheConfig;
 use anyhow::{Context, Result};
 use chrono::Utc;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::sqlite::{Sqlite, SqliteConnectOptions, SqliteConnection, SqlitePool, SqlitePoolOptions};
+use sqlx::Executor;
+use std::collections::HashMap;
 use std::str::FromStr;
-use tracing::{debug, info};
+use std::time::Duration;
+use tracing::{debug, info, warn};
 
 pub struct SqliteCache {
     pool: SqlitePool,
+    max_connections: u32,
+    acquire_timeout: Duration,
 }
 
 impl SqliteCache {
-    /// Create a new SQLite cache and initialize the database
+    /// Create a new SQLite cache with default pool settings and initialize the database
+    #[cfg(test)]
     pub async fn new(database_path: &str) -> Result<Self> {
+        Self::from_config(&CacheConfig::new(database_path)).await
+    }
+
+    /// Create a new SQLite cache from configuration and initialize the database
+    pub async fn from_config(config: &CacheConfig) -> Result<Self> {
+        let database_path = config.database_path.as_str();
+
         // Check if database file already exists
         let db_exists = std::path::Path::new(database_path).exists();
 
@@ -21,17 +37,30 @@ impl SqliteCache {
             info!("Creating new database at: {}", database_path);
         }
 
+        // Foreign keys must be on for deleting a list to cascade to its items
         let options = SqliteConnectOptions::from_str(database_path)?
-            .create_if_missing(true);
+            .create_if_missing(true)
+            .foreign_keys(true);
 
+        let acquire_timeout = Duration::from_secs(config.acquire_timeout_secs);
         let pool = SqlitePoolOptions::new()
-            .max_connections(5)
+            .max_connections(config.max_connections)
+            .acquire_timeout(acquire_timeout)
             .connect_with(options)
             .await
             .context("Failed to connect to SQLite database")?;
 
-        let cache = Self { pool };
+        let cache = Self {
+            pool,
+            max_connections: config.max_connections,
+            acquire_timeout,
+        };
+        let schema_version = cache.schema_version().await?;
+        check_schema_version(schema_version, config.allow_schema_downgrade)?;
         cache.run_migrations().await?;
+        if schema_version.unwrap_or(0) < SCHEMA_VERSION {
+            cache.set_schema_version(SCHEMA_VERSION).await?;
+        }
 
         // Log cache statistics if database existed
         if db_exists {
@@ -40,6 +69,13 @@ impl SqliteCache {
                 "Cache loaded: {} lists with {} total items",
                 stats.total_lists, stats.total_items
             );
+            match stats.last_successful_sync {
+                Some(last_sync) => info!(
+                    "Last successful sync was {}s ago",
+                    (Self::current_timestamp() - last_sync).max(0)
+                ),
+                None => info!("No successful sync recorded yet"),
+            }
         } else {
             info!("New database initialized successfully");
         }
@@ -47,6 +83,32 @@ impl SqliteCache {
         Ok(cache)
     }
 
+    /// Open an existing cache read-only for reports, without running
+    /// migrations or otherwise writing to it
+    pub async fn open_read_only(config: &CacheConfig) -> Result<Self> {
+        let database_path = config.database_path.as_str();
+        let options = SqliteConnectOptions::from_str(database_path)?
+            .read_only(true)
+            .foreign_keys(true);
+
+        let acquire_timeout = Duration::from_secs(config.acquire_timeout_secs);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(acquire_timeout)
+            .connect_with(options)
+            .await
+            .with_context(|| format!("Failed to open cache at {} read-only", database_path))?;
+
+        let cache = Self {
+            pool,
+            max_connections: config.max_connections,
+            acquire_timeout,
+        };
+        let schema_version = cache.schema_version().await?;
+        check_schema_version(schema_version, config.allow_schema_downgrade)?;
+        Ok(cache)
+    }
+
     /// Run database migrations to create tables
     async fn run_migrations(&self) -> Result<()> {
         info!("Running database migrations");
@@ -78,6 +140,7 @@ impl SqliteCache {
                 is_checked BOOLEAN NOT NULL,
                 user_id TEXT,
                 last_seen INTEGER NOT NULL,
+                first_seen INTEGER NOT NULL DEFAULT 0,
                 FOREIGN KEY (list_id) REFERENCES lists(id) ON DELETE CASCADE
             )
             "#,
@@ -92,6 +155,129 @@ impl SqliteCache {
             .execute(&self.pool)
             .await;
 
+        // Migration: Add first_seen column, backfilled from last_seen
+        let _ = sqlx::query("ALTER TABLE items ADD COLUMN first_seen INTEGER NOT NULL DEFAULT 0")
+            .execute(&self.pool)
+            .await;
+        sqlx::query("UPDATE items SET first_seen = last_seen WHERE first_seen = 0")
+            .execute(&self.pool)
+            .await
+            .context("Failed to backfill first_seen")?;
+
+        // Create users table for persisted user-id to display-name mapping
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS users (
+                user_id TEXT PRIMARY KEY,
+                display_name TEXT NOT NULL,
+                last_updated INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create users table")?;
+
+        // Create shared users table to track who each list is shared with
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS list_shared_users (
+                list_id TEXT NOT NULL,
+                user_id TEXT NOT NULL,
+                PRIMARY KEY (list_id, user_id),
+                FOREIGN KEY (list_id) REFERENCES lists(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create list_shared_users table")?;
+
+        // Create change history table; entries outlive deleted lists
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS change_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                list_id TEXT NOT NULL,
+                list_name TEXT NOT NULL,
+                change_type TEXT NOT NULL,
+                user_id TEXT,
+                created_at INTEGER NOT NULL,
+                payload TEXT NOT NULL,
+                dedup_key TEXT
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create change_history table")?;
+
+        // Migration: Add dedup_key column to history tables created before it existed
+        let _ = sqlx::query("ALTER TABLE change_history ADD COLUMN dedup_key TEXT")
+            .execute(&self.pool)
+            .await;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_change_history_created_at ON change_history(created_at)",
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create change_history index")?;
+
+        // Create list mutes table, keyed by lowercased list name
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS list_mutes (
+                list_name TEXT PRIMARY KEY,
+                muted_until INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create list_mutes table")?;
+
+        // Create table of changes waiting for approval on the review topic
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS pending_reviews (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                list_name TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                payload TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create pending_reviews table")?;
+
+        // Create table of recently sent changes, keyed by their fingerprint
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS sent_notifications (
+                fingerprint TEXT PRIMARY KEY,
+                sent_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create sent_notifications table")?;
+
+        // Create key-value table for service state that outlives restarts
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create meta table")?;
+
         // Create index on list_id for faster lookups
         sqlx::query(
             r#"
@@ -106,6 +292,27 @@ impl SqliteCache {
         Ok(())
     }
 
+    /// Schema version recorded in the database, if any
+    ///
+    /// Databases created before versioning have no `meta` table or no entry.
+    async fn schema_version(&self) -> Result<Option<i64>> {
+        let has_meta: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'meta')",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to check for meta table")?;
+        if !has_meta {
+            return Ok(None);
+        }
+
+        self.get_meta_i64(SCHEMA_VERSION_KEY).await
+    }
+
+    async fn set_schema_version(&self, version: i64) -> Result<()> {
+        self.set_meta_i64(SCHEMA_VERSION_KEY, version).await
+    }
+
     /// Get a cached list by ID
     pub async fn get_list(&self, list_id: &str) -> Result<Option<DbList>> {
         let list = sqlx::query_as::<_, DbList>(
@@ -122,7 +329,7 @@ impl SqliteCache {
     /// Get all cached items for a list
     pub async fn get_items(&self, list_id: &str) -> Result<Vec<DbItem>> {
         let items = sqlx::query_as::<_, DbItem>(
-            "SELECT id, list_id, name, details, quantity, category, is_checked, user_id, last_seen FROM items WHERE list_id = ?",
+            "SELECT id, list_id, name, details, quantity, category, is_checked, user_id, last_seen, first_seen FROM items WHERE list_id = ?",
         )
         .bind(list_id)
         .fetch_all(&self.pool)
@@ -145,7 +352,15 @@ impl SqliteCache {
     }
 
     /// Upsert a list (insert or update)
+    #[cfg(test)]
     pub async fn upsert_list(&self, list: &DbList) -> Result<()> {
+        Self::upsert_list_with(&self.pool, list).await
+    }
+
+    async fn upsert_list_with<'e, E: Executor<'e, Database = Sqlite>>(
+        executor: E,
+        list: &DbList,
+    ) -> Result<()> {
         sqlx::query(
             r#"
             INSERT INTO lists (id, name, last_updated)
@@ -158,7 +373,7 @@ impl SqliteCache {
         .bind(&list.id)
         .bind(&list.name)
         .bind(list.last_updated)
-        .execute(&self.pool)
+        .execute(executor)
         .await
         .context("Failed to upsert list")?;
 
@@ -167,11 +382,19 @@ impl SqliteCache {
     }
 
     /// Upsert an item (insert or update)
+    #[cfg(test)]
     pub async fn upsert_item(&self, item: &DbItem) -> Result<()> {
+        Self::upsert_item_with(&self.pool, item).await
+    }
+
+    async fn upsert_item_with<'e, E: Executor<'e, Database = Sqlite>>(
+        executor: E,
+        item: &DbItem,
+    ) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO items (id, list_id, name, details, quantity, category, is_checked, user_id, last_seen)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO items (id, list_id, name, details, quantity, category, is_checked, user_id, last_seen, first_seen)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(id) DO UPDATE SET
                 list_id = excluded.list_id,
                 name = excluded.name,
@@ -192,7 +415,8 @@ impl SqliteCache {
         .bind(item.is_checked)
         .bind(&item.user_id)
         .bind(item.last_seen)
-        .execute(&self.pool)
+        .bind(item.first_seen)
+        .execute(executor)
         .await
         .context("Failed to upsert item")?;
 
@@ -200,51 +424,263 @@ impl SqliteCache {
         Ok(())
     }
 
+    /// Get the IDs of users a cached list is shared with
+    pub async fn get_shared_users(&self, list_id: &str) -> Result<Vec<String>> {
+        let user_ids = sqlx::query_scalar(
+            "SELECT user_id FROM list_shared_users WHERE list_id = ? ORDER BY user_id",
+        )
+        .bind(list_id)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch shared users from cache")?;
+
+        Ok(user_ids)
+    }
+
+    /// Replace the set of users a list is shared with
+    #[cfg(test)]
+    pub async fn set_shared_users(&self, list_id: &str, user_ids: &[String]) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin shared users transaction")?;
+
+        Self::set_shared_users_with(&mut tx, list_id, user_ids).await?;
+
+        tx.commit()
+            .await
+            .context("Failed to commit shared users transaction")?;
+        Ok(())
+    }
+
+    async fn set_shared_users_with(
+        conn: &mut SqliteConnection,
+        list_id: &str,
+        user_ids: &[String],
+    ) -> Result<()> {
+        sqlx::query("DELETE FROM list_shared_users WHERE list_id = ?")
+            .bind(list_id)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to clear shared users")?;
+
+        for user_id in user_ids {
+            sqlx::query(
+                "INSERT OR IGNORE INTO list_shared_users (list_id, user_id) VALUES (?, ?)",
+            )
+            .bind(list_id)
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to insert shared user")?;
+        }
+
+        debug!("Stored {} shared users for list {}", user_ids.len(), list_id);
+        Ok(())
+    }
+
+    /// Mute notifications for a list until a Unix timestamp, replacing any existing mute
+    pub async fn set_mute(&self, list_name: &str, muted_until: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO list_mutes (list_name, muted_until)
+            VALUES (?, ?)
+            ON CONFLICT(list_name) DO UPDATE SET muted_until = excluded.muted_until
+            "#,
+        )
+        .bind(mute_key(list_name))
+        .bind(muted_until)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store list mute")?;
+
+        debug!("Muted list {} until {}", list_name, muted_until);
+        Ok(())
+    }
+
+    /// Get the timestamp a list is muted until, if it has a mute
+    pub async fn get_mute(&self, list_name: &str) -> Result<Option<i64>> {
+        let muted_until =
+            sqlx::query_scalar("SELECT muted_until FROM list_mutes WHERE list_name = ?")
+                .bind(mute_key(list_name))
+                .fetch_optional(&self.pool)
+                .await
+                .context("Failed to fetch list mute from cache")?;
+
+        Ok(muted_until)
+    }
+
+    /// Remove a list's mute, returning whether it had one
+    pub async fn clear_mute(&self, list_name: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM list_mutes WHERE list_name = ?")
+            .bind(mute_key(list_name))
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete list mute")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record the time of the last sync pass that processed every list without errors
+    pub async fn set_last_successful_sync(&self, timestamp: i64) -> Result<()> {
+        self.set_meta_i64(LAST_SUCCESSFUL_SYNC_KEY, timestamp).await
+    }
+
+    /// Time of the last fully successful sync pass, if one was recorded
+    pub async fn last_successful_sync(&self) -> Result<Option<i64>> {
+        self.get_meta_i64(LAST_SUCCESSFUL_SYNC_KEY).await
+    }
+
+    /// Record the time the last list change event arrived
+    pub async fn set_last_event(&self, timestamp: i64) -> Result<()> {
+        self.set_meta_i64(LAST_EVENT_KEY, timestamp).await
+    }
+
+    /// Time the last list change event arrived, if one was recorded
+    pub async fn last_event(&self) -> Result<Option<i64>> {
+        self.get_meta_i64(LAST_EVENT_KEY).await
+    }
+
+    /// Record the time up to which changes were included in a digest
+    pub async fn set_last_digest(&self, timestamp: i64) -> Result<()> {
+        self.set_meta_i64(LAST_DIGEST_KEY, timestamp).await
+    }
+
+    /// Time up to which changes were included in the last digest, if one was sent
+    pub async fn last_digest(&self) -> Result<Option<i64>> {
+        self.get_meta_i64(LAST_DIGEST_KEY).await
+    }
+
+    async fn set_meta_i64(&self, key: &str, value: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO meta (key, value)
+            VALUES (?, ?)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value
+            "#,
+        )
+        .bind(key)
+        .bind(value.to_string())
+        .execute(&self.pool)
+        .await
+        .with_context(|| format!("Failed to store {}", key))?;
+
+        Ok(())
+    }
+
+    async fn get_meta_i64(&self, key: &str) -> Result<Option<i64>> {
+        let value: Option<String> = sqlx::query_scalar("SELECT value FROM meta WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await
+            .with_context(|| format!("Failed to fetch {}", key))?;
+
+        value
+            .map(|value| {
+                value
+                    .parse()
+                    .with_context(|| format!("Invalid {}: {}", key, value))
+            })
+            .transpose()
+    }
+
     /// Sync a complete list with the cache
-    /// This will upsert the list and all its items, and mark items as seen
+    /// This will upsert the list, its shared users and all its items, mark items as seen,
+    /// and delete cached items that are no longer on the list, all in one transaction.
+    /// The deletion is cache housekeeping only; removals are notified from the diff.
     pub async fn sync_list(&self, list: &anylist_rs::List) -> Result<()> {
-        let db_list = DbList::from(list);
-        self.upsert_list(&db_list).await?;
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin list sync transaction")?;
 
+        Self::upsert_list_with(&mut *tx, &DbList::from(list)).await?;
+
+        let shared_user_ids: Vec<String> =
+            list.shared_users.iter().map(|u| u.user_id.clone()).collect();
+        Self::set_shared_users_with(&mut tx, &list.id, &shared_user_ids).await?;
+
+        let seen_at = Self::next_seen_at_with(&mut *tx, &list.id).await?;
         for item in &list.items {
-            let db_item = DbItem::from(item);
-            self.upsert_item(&db_item).await?;
+            // Items without an ID would overwrite each other; they are never diffed either
+            if item.id.is_empty() {
+                warn!("Not caching item {} of list {} without an ID", item.name, list.name);
+                continue;
+            }
+            let mut db_item = DbItem::from(item);
+            db_item.last_seen = seen_at;
+            Self::upsert_item_with(&mut *tx, &db_item).await?;
         }
 
-        debug!("Synced list: {} ({} items)", list.name, list.items.len());
+        let removed = Self::delete_stale_items_with(&mut *tx, &list.id, seen_at).await?;
+
+        tx.commit()
+            .await
+            .context("Failed to commit list sync transaction")?;
+
+        debug!(
+            "Synced list: {} ({} items, {} removed)",
+            list.name,
+            list.items.len(),
+            removed
+        );
         Ok(())
     }
 
-    /// Delete items that haven't been seen since the given timestamp
-    /// This is used to detect removed items
-    pub async fn delete_stale_items(&self, list_id: &str, since: i64) -> Result<Vec<DbItem>> {
-        // First, fetch the items that will be deleted
-        let stale_items = sqlx::query_as::<_, DbItem>(
-            "SELECT id, list_id, name, details, quantity, category, is_checked, user_id, last_seen FROM items WHERE list_id = ? AND last_seen < ?",
-        )
-        .bind(list_id)
-        .bind(since)
-        .fetch_all(&self.pool)
-        .await
-        .context("Failed to fetch stale items")?;
+    /// Timestamp to mark items seen in a sync of the given list
+    /// This is strictly newer than any previous sync of the list, so items
+    /// missing from a sync in the same second are still detected as stale.
+    #[cfg(test)]
+    pub async fn next_seen_at(&self, list_id: &str) -> Result<i64> {
+        Self::next_seen_at_with(&self.pool, list_id).await
+    }
 
-        // Then delete them
-        if !stale_items.is_empty() {
-            sqlx::query("DELETE FROM items WHERE list_id = ? AND last_seen < ?")
+    async fn next_seen_at_with<'e, E: Executor<'e, Database = Sqlite>>(
+        executor: E,
+        list_id: &str,
+    ) -> Result<i64> {
+        let latest: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(last_seen) FROM items WHERE list_id = ?")
                 .bind(list_id)
-                .bind(since)
-                .execute(&self.pool)
+                .fetch_one(executor)
                 .await
-                .context("Failed to delete stale items")?;
+                .context("Failed to fetch latest sync timestamp")?;
+
+        let now = Self::current_timestamp();
+        Ok(latest.map_or(now, |latest| now.max(latest + 1)))
+    }
+
+    /// Delete items that haven't been seen since the given timestamp
+    /// This keeps the cache from growing with items no longer on the list;
+    /// it is housekeeping only, as removals are detected by diffing.
+    /// Returns the number of deleted items.
+    #[cfg(test)]
+    pub async fn delete_stale_items(&self, list_id: &str, since: i64) -> Result<u64> {
+        Self::delete_stale_items_with(&self.pool, list_id, since).await
+    }
+
+    async fn delete_stale_items_with<'e, E: Executor<'e, Database = Sqlite>>(
+        executor: E,
+        list_id: &str,
+        since: i64,
+    ) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM items WHERE list_id = ? AND last_seen < ?")
+            .bind(list_id)
+            .bind(since)
+            .execute(executor)
+            .await
+            .context("Failed to delete stale items")?;
 
+        if result.rows_affected() > 0 {
             debug!(
                 "Deleted {} stale items from list {}",
-                stale_items.len(),
+                result.rows_affected(),
                 list_id
             );
         }
-
-        Ok(stale_items)
+        Ok(result.rows_affected())
     }
 
     /// Delete a list and all its items
@@ -261,59 +697,576 @@ impl SqliteCache {
         Ok(())
     }
 
-    /// Get the current timestamp for marking items as seen
-    pub fn current_timestamp() -> i64 {
-        Utc::now().timestamp()
-    }
+    /// Delete items and shared users whose list no longer exists
+    /// These can be left behind by databases written without foreign key enforcement.
+    /// Returns the number of deleted items.
+    pub async fn delete_orphans(&self) -> Result<u64> {
+        let items = sqlx::query("DELETE FROM items WHERE list_id NOT IN (SELECT id FROM lists)")
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete orphaned items")?
+            .rows_affected();
 
-    /// Get cache statistics
-    pub async fn get_stats(&self) -> Result<CacheStats> {
-        let total_lists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM lists")
-            .fetch_one(&self.pool)
+        sqlx::query("DELETE FROM list_shared_users WHERE list_id NOT IN (SELECT id FROM lists)")
+            .execute(&self.pool)
             .await
-            .context("Failed to count lists")?;
+            .context("Failed to delete orphaned shared users")?;
 
-        let total_items: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items")
-            .fetch_one(&self.pool)
+        debug!("Deleted {} orphaned items", items);
+        Ok(items)
+    }
+
+    /// Upsert a user's display name
+    pub async fn upsert_user_name(&self, user_id: &str, display_name: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO users (user_id, display_name, last_updated)
+            VALUES (?, ?, ?)
+            ON CONFLICT(user_id) DO UPDATE SET
+                display_name = excluded.display_name,
+                last_updated = excluded.last_updated
+            "#,
+        )
+        .bind(user_id)
+        .bind(display_name)
+        .bind(Self::current_timestamp())
+        .execute(&self.pool)
+        .await
+        .context("Failed to upsert user name")?;
+
+        Ok(())
+    }
+
+    /// Get the persisted display name for a single user
+    pub async fn get_user_name(&self, user_id: &str) -> Result<Option<String>> {
+        let name = sqlx::query_scalar("SELECT display_name FROM users WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
             .await
-            .context("Failed to count items")?;
+            .context("Failed to fetch user name from cache")?;
 
-        Ok(CacheStats {
-            total_lists: total_lists as usize,
-            total_items: total_items as usize,
-        })
+        Ok(name)
     }
-}
 
-/// Cache statistics
-pub struct CacheStats {
-    pub total_lists: usize,
-    pub total_items: usize,
-}
+    /// Get all persisted user-id to display-name mappings
+    pub async fn get_user_names(&self) -> Result<HashMap<String, String>> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT user_id, display_name FROM users")
+                .fetch_all(&self.pool)
+                .await
+                .context("Failed to fetch user names from cache")?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        Ok(rows.into_iter().collect())
+    }
 
-    #[tokio::test]
-    async fn test_cache_operations() {
-        // Use in-memory database for testing
-        let cache = SqliteCache::new("sqlite::memory:")
+    /// Record changes in the history table
+    pub async fn record_history(&self, entries: &[DbHistoryEntry]) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
             .await
-            .expect("Failed to create cache");
+            .context("Failed to begin history transaction")?;
 
-        // Test list operations
-        let list = DbList::new("test-list-1".to_string(), "Test List".to_string());
-        cache.upsert_list(&list).await.expect("Failed to upsert list");
+        for entry in entries {
+            sqlx::query(
+                r#"
+                INSERT INTO change_history
+                    (list_id, list_name, change_type, user_id, created_at, payload, dedup_key)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&entry.list_id)
+            .bind(&entry.list_name)
+            .bind(&entry.change_type)
+            .bind(&entry.user_id)
+            .bind(entry.created_at)
+            .bind(&entry.payload)
+            .bind(&entry.dedup_key)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to insert history entry")?;
+        }
 
-        let fetched = cache
-            .get_list("test-list-1")
+        tx.commit()
             .await
-            .expect("Failed to get list")
-            .expect("List not found");
-        assert_eq!(fetched.name, "Test List");
+            .context("Failed to commit history transaction")?;
 
-        // Test item operations
+        Ok(())
+    }
+
+    /// History entries recorded after `since`, oldest first
+    pub async fn history_since(&self, since: i64) -> Result<Vec<DbHistoryEntry>> {
+        sqlx::query_as::<_, DbHistoryEntry>(
+            r#"
+            SELECT id, list_id, list_name, change_type, user_id, created_at, payload, dedup_key
+            FROM change_history
+            WHERE created_at > ?
+            ORDER BY id
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read history")
+    }
+
+    /// Read the `limit` most recent history entries, oldest first
+    pub async fn recent_history(&self, limit: u32) -> Result<Vec<DbHistoryEntry>> {
+        let mut entries = sqlx::query_as::<_, DbHistoryEntry>(
+            r#"
+            SELECT id, list_id, list_name, change_type, user_id, created_at, payload, dedup_key
+            FROM change_history
+            ORDER BY id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read history")?;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Store a change awaiting approval, returning the ID to approve or reject it by
+    pub async fn add_pending_review(&self, list_name: &str, payload: &str) -> Result<i64> {
+        let result = sqlx::query(
+            "INSERT INTO pending_reviews (list_name, created_at, payload) VALUES (?, ?, ?)",
+        )
+        .bind(list_name)
+        .bind(Self::current_timestamp())
+        .bind(payload)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store pending review")?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Remove a pending review, returning its payload if it was still pending
+    ///
+    /// Taking it in one statement means an approval and a rejection racing for
+    /// the same change can't both act on it.
+    pub async fn take_pending_review(&self, id: i64) -> Result<Option<String>> {
+        sqlx::query_scalar("DELETE FROM pending_reviews WHERE id = ? RETURNING payload")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to take pending review")
+    }
+
+    /// Delete reviews created before `before`, returning how many were removed
+    pub async fn prune_pending_reviews(&self, before: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM pending_reviews WHERE created_at < ?")
+            .bind(before)
+            .execute(&self.pool)
+            .await
+            .context("Failed to prune pending reviews")?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Whether a change with this fingerprint was sent at or after `since`
+    pub async fn was_sent_since(&self, fingerprint: &str, since: i64) -> Result<bool> {
+        sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sent_notifications WHERE fingerprint = ? AND sent_at >= ?)",
+        )
+        .bind(fingerprint)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to check sent notifications")
+    }
+
+    /// Record that a change with this fingerprint was sent at `sent_at`
+    pub async fn mark_sent(&self, fingerprint: &str, sent_at: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sent_notifications (fingerprint, sent_at)
+            VALUES (?, ?)
+            ON CONFLICT(fingerprint) DO UPDATE SET sent_at = excluded.sent_at
+            "#,
+        )
+        .bind(fingerprint)
+        .bind(sent_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record sent notification")?;
+
+        Ok(())
+    }
+
+    /// Delete markers of changes sent before `before`, returning how many were removed
+    pub async fn prune_sent_markers(&self, before: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM sent_notifications WHERE sent_at < ?")
+            .bind(before)
+            .execute(&self.pool)
+            .await
+            .context("Failed to prune sent notifications")?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Read the lists, items, shared users, users and change history into a
+    /// single dump
+    ///
+    /// List mutes, pending reviews, sent markers and the meta table are
+    /// short-lived or rebuilt by the service, so they are left out.
+    pub async fn export(&self) -> Result<CacheDump> {
+        let lists = sqlx::query_as::<_, DbList>(
+            "SELECT id, name, last_updated FROM lists ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to export lists")?;
+
+        let items = sqlx::query_as::<_, DbItem>(
+            "SELECT id, list_id, name, details, quantity, category, is_checked, user_id, last_seen, first_seen FROM items ORDER BY list_id, id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to export items")?;
+
+        let shared_users = sqlx::query_as::<_, DbSharedUser>(
+            "SELECT list_id, user_id FROM list_shared_users ORDER BY list_id, user_id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to export shared users")?;
+
+        let users = sqlx::query_as::<_, DbUser>(
+            "SELECT user_id, display_name, last_updated FROM users ORDER BY user_id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to export users")?;
+
+        let history = sqlx::query_as::<_, DbHistoryEntry>(
+            "SELECT id, list_id, list_name, change_type, user_id, created_at, payload, dedup_key FROM change_history ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to export history")?;
+
+        Ok(CacheDump {
+            version: CacheDump::VERSION,
+            exported_at: Self::current_timestamp(),
+            lists,
+            items,
+            shared_users,
+            users,
+            history,
+        })
+    }
+
+    /// Upsert a dump into the cache in a single transaction
+    /// History entries already present (same list, time and payload) are skipped,
+    /// so importing the same dump twice does not duplicate them.
+    pub async fn import(&self, dump: &CacheDump) -> Result<ImportStats> {
+        if dump.version > CacheDump::VERSION {
+            anyhow::bail!(
+                "Unsupported cache export version {} (this build reads up to {})",
+                dump.version,
+                CacheDump::VERSION
+            );
+        }
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin import transaction")?;
+
+        for list in &dump.lists {
+            sqlx::query(
+                r#"
+                INSERT INTO lists (id, name, last_updated)
+                VALUES (?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    last_updated = excluded.last_updated
+                "#,
+            )
+            .bind(&list.id)
+            .bind(&list.name)
+            .bind(list.last_updated)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to import list {}", list.id))?;
+        }
+
+        for item in &dump.items {
+            sqlx::query(
+                r#"
+                INSERT INTO items (id, list_id, name, details, quantity, category, is_checked, user_id, last_seen, first_seen)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    list_id = excluded.list_id,
+                    name = excluded.name,
+                    details = excluded.details,
+                    quantity = excluded.quantity,
+                    category = excluded.category,
+                    is_checked = excluded.is_checked,
+                    user_id = excluded.user_id,
+                    last_seen = excluded.last_seen,
+                    first_seen = excluded.first_seen
+                "#,
+            )
+            .bind(&item.id)
+            .bind(&item.list_id)
+            .bind(&item.name)
+            .bind(&item.details)
+            .bind(&item.quantity)
+            .bind(&item.category)
+            .bind(item.is_checked)
+            .bind(&item.user_id)
+            .bind(item.last_seen)
+            .bind(item.first_seen)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to import item {}", item.id))?;
+        }
+
+        for shared in &dump.shared_users {
+            sqlx::query(
+                "INSERT OR IGNORE INTO list_shared_users (list_id, user_id) VALUES (?, ?)",
+            )
+            .bind(&shared.list_id)
+            .bind(&shared.user_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to import shared user")?;
+        }
+
+        for user in &dump.users {
+            sqlx::query(
+                r#"
+                INSERT INTO users (user_id, display_name, last_updated)
+                VALUES (?, ?, ?)
+                ON CONFLICT(user_id) DO UPDATE SET
+                    display_name = excluded.display_name,
+                    last_updated = excluded.last_updated
+                "#,
+            )
+            .bind(&user.user_id)
+            .bind(&user.display_name)
+            .bind(user.last_updated)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to import user")?;
+        }
+
+        let mut history = 0;
+        for entry in &dump.history {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO change_history
+                    (list_id, list_name, change_type, user_id, created_at, payload, dedup_key)
+                SELECT ?, ?, ?, ?, ?, ?, ?
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM change_history
+                    WHERE list_id = ? AND created_at = ? AND payload = ?
+                )
+                "#,
+            )
+            .bind(&entry.list_id)
+            .bind(&entry.list_name)
+            .bind(&entry.change_type)
+            .bind(&entry.user_id)
+            .bind(entry.created_at)
+            .bind(&entry.payload)
+            .bind(&entry.dedup_key)
+            .bind(&entry.list_id)
+            .bind(entry.created_at)
+            .bind(&entry.payload)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to import history entry")?;
+            history += result.rows_affected() as usize;
+        }
+
+        tx.commit()
+            .await
+            .context("Failed to commit import transaction")?;
+
+        Ok(ImportStats {
+            lists: dump.lists.len(),
+            items: dump.items.len(),
+            users: dump.users.len(),
+            history,
+        })
+    }
+
+    /// Count recorded changes by local weekday and hour of day
+    /// Only non-empty buckets are returned, ordered by weekday then hour
+    pub async fn get_activity_buckets(&self) -> Result<Vec<ActivityBucket>> {
+        let buckets = sqlx::query_as::<_, ActivityBucket>(
+            r#"
+            SELECT
+                CAST(strftime('%w', created_at, 'unixepoch', 'localtime') AS INTEGER) AS weekday,
+                CAST(strftime('%H', created_at, 'unixepoch', 'localtime') AS INTEGER) AS hour,
+                COUNT(*) AS count
+            FROM change_history
+            GROUP BY weekday, hour
+            ORDER BY weekday, hour
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to aggregate history")?;
+
+        Ok(buckets)
+    }
+
+    /// Log a warning if an error was caused by the connection pool being exhausted
+    /// Returns whether the error was a pool acquire timeout
+    pub fn warn_if_pool_exhausted(&self, err: &anyhow::Error) -> bool {
+        let timed_out = err
+            .chain()
+            .any(|cause| matches!(cause.downcast_ref(), Some(sqlx::Error::PoolTimedOut)));
+
+        if timed_out {
+            warn!(
+                "Cache connection pool exhausted: no connection became free within {}s \
+                 (max_connections = {}). Consider raising cache.max_connections or \
+                 cache.acquire_timeout_secs",
+                self.acquire_timeout.as_secs(),
+                self.max_connections
+            );
+        }
+        timed_out
+    }
+
+    /// Get the current timestamp for marking items as seen
+    pub fn current_timestamp() -> i64 {
+        Utc::now().timestamp()
+    }
+
+    /// Timestamp `duration` before now, saturating instead of overflowing
+    pub fn timestamp_ago(duration: Duration) -> i64 {
+        let secs = i64::try_from(duration.as_secs()).unwrap_or(i64::MAX);
+        Self::current_timestamp().saturating_sub(secs)
+    }
+
+    /// Timestamp `duration` from now, saturating instead of overflowing
+    pub fn timestamp_in(duration: Duration) -> i64 {
+        let secs = i64::try_from(duration.as_secs()).unwrap_or(i64::MAX);
+        Self::current_timestamp().saturating_add(secs)
+    }
+
+    /// Get cache statistics
+    pub async fn get_stats(&self) -> Result<CacheStats> {
+        let total_lists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM lists")
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count lists")?;
+
+        let total_items: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items")
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to count items")?;
+
+        Ok(CacheStats {
+            total_lists: total_lists as usize,
+            total_items: total_items as usize,
+            last_successful_sync: self.last_successful_sync().await?,
+        })
+    }
+}
+
+/// Cache statistics
+pub struct CacheStats {
+    pub total_lists: usize,
+    pub total_items: usize,
+    pub last_successful_sync: Option<i64>, // Unix timestamp
+}
+
+/// Rows written by a cache import
+#[derive(Debug)]
+pub struct ImportStats {
+    pub lists: usize,
+    pub items: usize,
+    pub users: usize,
+    pub history: usize, // new entries only
+}
+
+/// `meta` key holding the time of the last fully successful sync pass
+const LAST_SUCCESSFUL_SYNC_KEY: &str = "last_successful_sync";
+
+/// `meta` key holding the time the last list change event arrived
+const LAST_EVENT_KEY: &str = "last_event";
+
+/// `meta` key holding the time up to which changes were included in a digest
+const LAST_DIGEST_KEY: &str = "last_digest";
+
+/// Version of the database schema this build creates; bump it whenever a
+/// migration changes the schema in a way older builds can't handle
+const SCHEMA_VERSION: i64 = 1;
+
+/// `meta` key holding the schema version of the build that last migrated the database
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Refuse a database written by a newer build unless explicitly allowed
+///
+/// Older builds don't know about the newer schema and could silently write
+/// data the newer build misreads.
+fn check_schema_version(stored: Option<i64>, allow_downgrade: bool) -> Result<()> {
+    let Some(stored) = stored.filter(|stored| *stored > SCHEMA_VERSION) else {
+        return Ok(());
+    };
+    if !allow_downgrade {
+        anyhow::bail!(
+            "Cache database schema version {} is newer than this build supports ({}); \
+             upgrade anylist_notify, or pass --allow-schema-downgrade to use it anyway",
+            stored,
+            SCHEMA_VERSION
+        );
+    }
+    warn!(
+        "Cache database schema version {} is newer than this build supports ({}); \
+         continuing because --allow-schema-downgrade was passed",
+        stored, SCHEMA_VERSION
+    );
+    Ok(())
+}
+
+/// List names are matched case-insensitively, like in the config
+fn mute_key(list_name: &str) -> String {
+    list_name.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamps_saturate() {
+        let now = SqliteCache::current_timestamp();
+        assert!(SqliteCache::timestamp_in(Duration::from_secs(60)) >= now + 60);
+        assert_eq!(SqliteCache::timestamp_in(Duration::MAX), i64::MAX);
+        assert!(SqliteCache::timestamp_ago(Duration::from_secs(60)) <= now - 60);
+        assert!(SqliteCache::timestamp_ago(Duration::MAX) < 0);
+    }
+
+    #[tokio::test]
+    async fn test_cache_operations() {
+        // Use in-memory database for testing
+        let cache = SqliteCache::new("sqlite::memory:")
+            .await
+            .expect("Failed to create cache");
+
+        // Test list operations
+        let list = DbList::new("test-list-1".to_string(), "Test List".to_string());
+        cache.upsert_list(&list).await.expect("Failed to upsert list");
+
+        let fetched = cache
+            .get_list("test-list-1")
+            .await
+            .expect("Failed to get list")
+            .expect("List not found");
+        assert_eq!(fetched.name, "Test List");
+
+        // Test item operations
         let item = DbItem::new(
             "item-1".to_string(),
             "test-list-1".to_string(),
@@ -332,5 +1285,534 @@ mod tests {
             .expect("Failed to get items");
         assert_eq!(items.len(), 1);
         assert_eq!(items[0].name, "Milk");
+
+        // Re-upserting keeps the original first_seen
+        let mut updated = item.clone();
+        updated.is_checked = true;
+        updated.last_seen = item.last_seen + 100;
+        updated.first_seen = item.last_seen + 100;
+        cache.upsert_item(&updated).await.expect("Failed to upsert item");
+
+        let items = cache
+            .get_items("test-list-1")
+            .await
+            .expect("Failed to get items");
+        assert!(items[0].is_checked);
+        assert_eq!(items[0].first_seen, item.first_seen);
+    }
+
+    #[tokio::test]
+    async fn test_delete_stale_items_in_same_second() {
+        let cache = SqliteCache::new("sqlite::memory:")
+            .await
+            .expect("Failed to create cache");
+
+        let list = DbList::new("test-list-1".to_string(), "Test List".to_string());
+        cache.upsert_list(&list).await.expect("Failed to upsert list");
+
+        // First sync: both items seen
+        let first_seen_at = cache
+            .next_seen_at("test-list-1")
+            .await
+            .expect("Failed to get sync timestamp");
+        for (id, name) in [("item-1", "Milk"), ("item-2", "Bread")] {
+            let mut item = DbItem::new(
+                id.to_string(),
+                "test-list-1".to_string(),
+                name.to_string(),
+                "".to_string(),
+                None,
+                None,
+                false,
+                None,
+            );
+            item.last_seen = first_seen_at;
+            cache.upsert_item(&item).await.expect("Failed to upsert item");
+        }
+
+        // Second sync, possibly within the same second: only Milk is still present
+        let second_seen_at = cache
+            .next_seen_at("test-list-1")
+            .await
+            .expect("Failed to get sync timestamp");
+        assert!(second_seen_at > first_seen_at);
+
+        let mut milk = cache
+            .get_items("test-list-1")
+            .await
+            .expect("Failed to get items")
+            .into_iter()
+            .find(|item| item.id == "item-1")
+            .expect("Milk not found");
+        milk.last_seen = second_seen_at;
+        cache
+            .upsert_item(&milk)
+            .await
+            .expect("Failed to upsert item");
+
+        let removed = cache
+            .delete_stale_items("test-list-1", second_seen_at)
+            .await
+            .expect("Failed to delete stale items");
+        assert_eq!(removed, 1);
+
+        let items = cache
+            .get_items("test-list-1")
+            .await
+            .expect("Failed to get items");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "Milk");
+    }
+
+    #[tokio::test]
+    async fn test_failed_sync_list_changes_nothing() {
+        use crate::sync::diff::test_support::{live_item, live_list};
+
+        let cache = SqliteCache::new("sqlite::memory:")
+            .await
+            .expect("Failed to create cache");
+        let list = live_list(vec![
+            live_item("item-1", "Milk", false),
+            live_item("item-2", "Eggs", false),
+        ]);
+        cache.sync_list(&list).await.expect("Failed to sync list");
+
+        // Checking off Milk and dropping Eggs, with an item the foreign key rejects
+        let mut orphan = live_item("item-3", "Bread", false);
+        orphan.list_id = "missing-list".to_string();
+        let list = live_list(vec![live_item("item-1", "Milk", true), orphan]);
+        assert!(cache.sync_list(&list).await.is_err());
+
+        let items = cache
+            .get_items("list-1")
+            .await
+            .expect("Failed to get items");
+        let mut names: Vec<_> = items.iter().map(|item| item.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["Eggs", "Milk"]);
+        assert!(items.iter().all(|item| !item.is_checked));
+    }
+
+    #[tokio::test]
+    async fn test_pool_exhaustion_is_reported() {
+        let cache = SqliteCache::from_config(&CacheConfig {
+            database_path: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            acquire_timeout_secs: 1,
+            ..CacheConfig::new("sqlite::memory:")
+        })
+        .await
+        .expect("Failed to create cache");
+
+        // Hold the only connection so the next query can't acquire one
+        let _conn = cache.pool.acquire().await.expect("Failed to acquire");
+
+        let err = cache
+            .get_all_lists()
+            .await
+            .expect_err("Query should time out");
+        assert!(cache.warn_if_pool_exhausted(&err));
+
+        let other = anyhow::anyhow!("unrelated");
+        assert!(!cache.warn_if_pool_exhausted(&other));
+    }
+
+    #[tokio::test]
+    async fn test_activity_buckets() {
+        let cache = SqliteCache::new("sqlite::memory:")
+            .await
+            .expect("Failed to create cache");
+
+        let entry = |created_at: i64| DbHistoryEntry {
+            id: 0,
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            change_type: "item_added".to_string(),
+            user_id: None,
+            created_at,
+            payload: "{}".to_string(),
+            dedup_key: None,
+        };
+        let base = 1_700_000_000;
+        cache
+            .record_history(&[entry(base), entry(base + 60), entry(base + 3 * 3600)])
+            .await
+            .expect("Failed to record history");
+
+        let buckets = cache
+            .get_activity_buckets()
+            .await
+            .expect("Failed to aggregate history");
+
+        // Two changes in the same hour, one three hours later
+        let counts: Vec<i64> = buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts.iter().sum::<i64>(), 3);
+        assert!(counts.contains(&2));
+        assert!(buckets
+            .iter()
+            .all(|b| (0..7).contains(&b.weekday) && (0..24).contains(&b.hour)));
+
+        let recent = cache.history_since(base).await.unwrap();
+        let times: Vec<i64> = recent.iter().map(|entry| entry.created_at).collect();
+        assert_eq!(times, vec![base + 60, base + 3 * 3600]);
+
+        let latest = cache.recent_history(2).await.unwrap();
+        let times: Vec<i64> = latest.iter().map(|entry| entry.created_at).collect();
+        assert_eq!(times, vec![base + 60, base + 3 * 3600]);
+    }
+
+    #[tokio::test]
+    async fn test_delete_list_and_orphans() {
+        let cache = SqliteCache::new("sqlite::memory:")
+            .await
+            .expect("Failed to create cache");
+
+        let list = DbList::new("test-list-1".to_string(), "Test List".to_string());
+        cache
+            .upsert_list(&list)
+            .await
+            .expect("Failed to upsert list");
+        let item = DbItem::new(
+            "item-1".to_string(),
+            "test-list-1".to_string(),
+            "Milk".to_string(),
+            "".to_string(),
+            None,
+            None,
+            false,
+            None,
+        );
+        cache
+            .upsert_item(&item)
+            .await
+            .expect("Failed to upsert item");
+
+        // Deleting a list cascades to its items
+        cache
+            .delete_list("test-list-1")
+            .await
+            .expect("Failed to delete list");
+        assert!(cache
+            .get_items("test-list-1")
+            .await
+            .expect("Failed to get items")
+            .is_empty());
+
+        // Simulate an item left behind by a database without foreign keys
+        let mut conn = cache.pool.acquire().await.expect("Failed to acquire");
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await
+            .expect("Failed to disable foreign keys");
+        sqlx::query(
+            "INSERT INTO items (id, list_id, name, details, is_checked, last_seen) VALUES ('item-2', 'gone', 'Bread', '', 0, 0)",
+        )
+        .execute(&mut *conn)
+        .await
+        .expect("Failed to insert orphan");
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *conn)
+            .await
+            .expect("Failed to enable foreign keys");
+        drop(conn);
+
+        assert_eq!(
+            cache.delete_orphans().await.expect("Failed to delete orphans"),
+            1
+        );
+        assert!(cache
+            .get_items("gone")
+            .await
+            .expect("Failed to get items")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_user_names() {
+        let cache = SqliteCache::new("sqlite::memory:")
+            .await
+            .expect("Failed to create cache");
+
+        cache
+            .upsert_user_name("user-1", "user1@example.com")
+            .await
+            .expect("Failed to upsert user name");
+        cache
+            .upsert_user_name("user-1", "Alice")
+            .await
+            .expect("Failed to update user name");
+        cache
+            .upsert_user_name("user-2", "Bob")
+            .await
+            .expect("Failed to upsert user name");
+
+        let names = cache.get_user_names().await.expect("Failed to get user names");
+        assert_eq!(names.len(), 2);
+        assert_eq!(names.get("user-1").map(String::as_str), Some("Alice"));
+        assert_eq!(names.get("user-2").map(String::as_str), Some("Bob"));
+    }
+
+    #[tokio::test]
+    async fn test_list_mutes() {
+        let cache = SqliteCache::new("sqlite::memory:").await.unwrap();
+
+        assert_eq!(cache.get_mute("Groceries").await.unwrap(), None);
+
+        cache.set_mute("Groceries", 1_000).await.unwrap();
+        assert_eq!(cache.get_mute("groceries ").await.unwrap(), Some(1_000));
+
+        cache.set_mute("GROCERIES", 2_000).await.unwrap();
+        assert_eq!(cache.get_mute("Groceries").await.unwrap(), Some(2_000));
+
+        assert!(cache.clear_mute("Groceries").await.unwrap());
+        assert!(!cache.clear_mute("Groceries").await.unwrap());
+        assert_eq!(cache.get_mute("Groceries").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_pending_reviews() {
+        let cache = SqliteCache::new("sqlite::memory:").await.unwrap();
+
+        let first = cache.add_pending_review("Groceries", "{}").await.unwrap();
+        let second = cache.add_pending_review("Groceries", "[]").await.unwrap();
+        assert_ne!(first, second);
+
+        assert_eq!(
+            cache.take_pending_review(second).await.unwrap(),
+            Some("[]".to_string())
+        );
+        // A review is only acted on once
+        assert_eq!(cache.take_pending_review(second).await.unwrap(), None);
+        assert_eq!(
+            cache.take_pending_review(first).await.unwrap(),
+            Some("{}".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prune_pending_reviews() {
+        let cache = SqliteCache::new("sqlite::memory:").await.unwrap();
+
+        let id = cache.add_pending_review("Groceries", "{}").await.unwrap();
+        let now = SqliteCache::current_timestamp();
+        assert_eq!(cache.prune_pending_reviews(now - 60).await.unwrap(), 0);
+        assert_eq!(cache.prune_pending_reviews(now + 60).await.unwrap(), 1);
+        assert_eq!(cache.take_pending_review(id).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_sent_markers() {
+        let cache = SqliteCache::new("sqlite::memory:").await.unwrap();
+
+        assert!(!cache.was_sent_since("abc", 0).await.unwrap());
+        cache.mark_sent("abc", 1_000).await.unwrap();
+        assert!(cache.was_sent_since("abc", 900).await.unwrap());
+        assert!(!cache.was_sent_since("abc", 1_001).await.unwrap());
+
+        // Sending again moves the marker forward
+        cache.mark_sent("abc", 2_000).await.unwrap();
+        cache.mark_sent("def", 500).await.unwrap();
+        assert_eq!(cache.prune_sent_markers(1_500).await.unwrap(), 1);
+        assert!(cache.was_sent_since("abc", 1_500).await.unwrap());
+        assert!(!cache.was_sent_since("def", 0).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_schema_version() {
+        let path = std::env::temp_dir().join(format!(
+            "anylist_notify_schema_{}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut config = CacheConfig::new(&format!("sqlite://{}", path.display()));
+
+        let cache = SqliteCache::from_config(&config).await.unwrap();
+        assert_eq!(cache.schema_version().await.unwrap(), Some(SCHEMA_VERSION));
+
+        // Simulate a newer build having migrated the database
+        cache.set_schema_version(SCHEMA_VERSION + 1).await.unwrap();
+        cache.pool.close().await;
+        let error = SqliteCache::from_config(&config).await.err().unwrap();
+        assert!(error.to_string().contains("--allow-schema-downgrade"));
+
+        // The newer version is kept so the newer build doesn't re-run its migrations
+        config.allow_schema_downgrade = true;
+        let cache = SqliteCache::from_config(&config).await.unwrap();
+        assert_eq!(
+            cache.schema_version().await.unwrap(),
+            Some(SCHEMA_VERSION + 1)
+        );
+
+        cache.pool.close().await;
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_open_read_only() {
+        let path = std::env::temp_dir().join(format!(
+            "anylist_notify_read_only_{}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let config = CacheConfig::new(&format!("sqlite://{}", path.display()));
+
+        // A missing database isn't created
+        assert!(SqliteCache::open_read_only(&config).await.is_err());
+        assert!(!path.exists());
+
+        let cache = SqliteCache::from_config(&config).await.unwrap();
+        cache
+            .upsert_list(&DbList::new("list-1".to_string(), "Groceries".to_string()))
+            .await
+            .unwrap();
+        cache.pool.close().await;
+
+        let cache = SqliteCache::open_read_only(&config).await.unwrap();
+        assert_eq!(cache.get_all_lists().await.unwrap().len(), 1);
+        assert!(cache.set_last_event(1_700_000_000).await.is_err());
+
+        cache.pool.close().await;
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_last_successful_sync() {
+        let cache = SqliteCache::new("sqlite::memory:").await.unwrap();
+        assert_eq!(cache.last_successful_sync().await.unwrap(), None);
+
+        cache.set_last_successful_sync(1_700_000_000).await.unwrap();
+        cache.set_last_successful_sync(1_700_000_060).await.unwrap();
+
+        assert_eq!(
+            cache.last_successful_sync().await.unwrap(),
+            Some(1_700_000_060)
+        );
+        assert_eq!(
+            cache.get_stats().await.unwrap().last_successful_sync,
+            Some(1_700_000_060)
+        );
+
+        // Stored separately from the last event time
+        assert_eq!(cache.last_event().await.unwrap(), None);
+        cache.set_last_event(1_700_000_030).await.unwrap();
+        assert_eq!(cache.last_event().await.unwrap(), Some(1_700_000_030));
+
+        assert_eq!(cache.last_digest().await.unwrap(), None);
+        cache.set_last_digest(1_700_000_090).await.unwrap();
+        assert_eq!(cache.last_digest().await.unwrap(), Some(1_700_000_090));
+    }
+
+    #[tokio::test]
+    async fn test_shared_users() {
+        let cache = SqliteCache::new("sqlite::memory:")
+            .await
+            .expect("Failed to create cache");
+
+        let list = DbList::new("test-list-1".to_string(), "Test List".to_string());
+        cache.upsert_list(&list).await.expect("Failed to upsert list");
+
+        cache
+            .set_shared_users("test-list-1", &["user-2".to_string(), "user-1".to_string()])
+            .await
+            .expect("Failed to set shared users");
+        let users = cache
+            .get_shared_users("test-list-1")
+            .await
+            .expect("Failed to get shared users");
+        assert_eq!(users, vec!["user-1", "user-2"]);
+
+        cache
+            .set_shared_users("test-list-1", &["user-1".to_string()])
+            .await
+            .expect("Failed to set shared users");
+        let users = cache
+            .get_shared_users("test-list-1")
+            .await
+            .expect("Failed to get shared users");
+        assert_eq!(users, vec!["user-1"]);
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let source = SqliteCache::new("sqlite::memory:")
+            .await
+            .expect("Failed to create cache");
+
+        let list = DbList::new("list-1".to_string(), "Groceries".to_string());
+        source.upsert_list(&list).await.expect("Failed to upsert list");
+        let mut item = DbItem::new(
+            "item-1".to_string(),
+            "list-1".to_string(),
+            "Milk".to_string(),
+            String::new(),
+            Some("2".to_string()),
+            None,
+            false,
+            Some("user-1".to_string()),
+        );
+        item.first_seen = 1_700_000_000;
+        source.upsert_item(&item).await.expect("Failed to upsert item");
+        source
+            .set_shared_users("list-1", &["user-1".to_string()])
+            .await
+            .expect("Failed to set shared users");
+        source
+            .upsert_user_name("user-1", "Alice")
+            .await
+            .expect("Failed to upsert user name");
+        source
+            .record_history(&[DbHistoryEntry {
+                id: 0,
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                change_type: "item_added".to_string(),
+                user_id: Some("user-1".to_string()),
+                created_at: 1_700_000_000,
+                payload: "{}".to_string(),
+                dedup_key: Some("abc".to_string()),
+            }])
+            .await
+            .expect("Failed to record history");
+
+        let dump = source.export().await.expect("Failed to export cache");
+        let json = serde_json::to_string(&dump).expect("Failed to serialize dump");
+        let dump: CacheDump = serde_json::from_str(&json).expect("Failed to parse dump");
+
+        let target = SqliteCache::new("sqlite::memory:")
+            .await
+            .expect("Failed to create cache");
+        let stats = target.import(&dump).await.expect("Failed to import cache");
+        assert_eq!((stats.lists, stats.items, stats.users, stats.history), (1, 1, 1, 1));
+
+        let items = target.get_items("list-1").await.expect("Failed to get items");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].quantity.as_deref(), Some("2"));
+        assert_eq!(items[0].first_seen, 1_700_000_000);
+        assert_eq!(
+            target.get_shared_users("list-1").await.unwrap(),
+            vec!["user-1"]
+        );
+        assert_eq!(
+            target.get_user_name("user-1").await.unwrap().as_deref(),
+            Some("Alice")
+        );
+
+        // Importing again upserts rows without duplicating history
+        let stats = target.import(&dump).await.expect("Failed to re-import cache");
+        assert_eq!(stats.history, 0);
+        let exported = target.export().await.expect("Failed to export cache");
+        assert_eq!(exported.history.len(), 1);
+        assert_eq!(exported.history[0].dedup_key.as_deref(), Some("abc"));
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_newer_version() {
+        let cache = SqliteCache::new("sqlite::memory:")
+            .await
+            .expect("Failed to create cache");
+        let mut dump = cache.export().await.expect("Failed to export cache");
+        dump.version = CacheDump::VERSION + 1;
+
+        assert!(cache.import(&dump).await.is_err());
     }
 }
@@ -0,0 +1,255 @@
+use crate::notify::commands::parse_duration;
+use crate::report::ReportFormat;
+use clap::{ArgAction, Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Monitor AnyList shopping lists and send notifications on changes
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Increase log verbosity (-v info, -vv debug, -vvv trace)
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Print how far the cache has drifted from the live lists and exit,
+    /// without updating the cache or sending notifications
+    #[arg(long)]
+    pub report_drift: bool,
+
+    /// Open a cache database created by a newer version instead of refusing to start
+    #[arg(long, global = true)]
+    pub allow_schema_downgrade: bool,
+
+    /// Send the most recent recorded changes to this backend once at startup,
+    /// e.g. to check a newly added backend's formatting
+    #[arg(long, value_name = "NAME")]
+    pub backfill_backend: Option<String>,
+
+    /// Number of recorded changes sent by --backfill-backend
+    #[arg(long, default_value_t = 10, requires = "backfill_backend")]
+    pub count: u32,
+
+    /// Run a one-off command instead of the notification service
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Export recorded changes counted by weekday and hour of day
+    ActivityReport {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Json)]
+        format: ReportFormat,
+        /// File to write the report to instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print how many changes each user made over a recent period
+    UserActivity {
+        /// How far back to count, e.g. 1d, 7d or 30d
+        #[arg(long, default_value = "7d", value_parser = parse_duration_arg)]
+        period: Duration,
+    },
+    /// Remove cached lists that no longer exist in the account, and their items
+    Prune,
+    /// Print cache statistics and the time of the last successful sync
+    Stats,
+    /// Write the cached lists, items, users and history as JSON
+    CacheExport {
+        /// File to write the export to instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Load a JSON export into the cache, updating existing rows
+    CacheImport {
+        /// Export file to read
+        #[arg(short, long = "in")]
+        input: PathBuf,
+    },
+    /// Silence a list's notifications for a while; changes still update the cache
+    Mute {
+        /// Name of the list to mute
+        list_name: String,
+        /// How long to mute it, e.g. 30m, 1h or 2d
+        #[arg(value_parser = parse_duration_arg)]
+        duration: Duration,
+    },
+    /// Lift a list's mute before it expires
+    Unmute {
+        /// Name of the list to unmute
+        list_name: String,
+    },
+}
+
+fn parse_duration_arg(text: &str) -> Result<Duration, String> {
+    parse_duration(text)
+        .ok_or_else(|| format!("invalid duration '{}', expected e.g. 30m, 1h or 2d", text))
+}
+
+impl Cli {
+    /// Whether the invocation prints a report and exits instead of running the service
+    pub fn is_one_off(&self) -> bool {
+        self.report_drift || self.command.is_some()
+    }
+
+    /// Log level requested on the command line, if any
+    pub fn log_level(&self) -> Option<&'static str> {
+        match self.verbose {
+            0 => None,
+            1 => Some("info"),
+            2 => Some("debug"),
+            _ => Some("trace"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbose_log_levels() {
+        let level = |args: &[&str]| Cli::try_parse_from(args).unwrap().log_level();
+
+        assert_eq!(level(&["anylist_notify"]), None);
+        assert_eq!(level(&["anylist_notify", "-v"]), Some("info"));
+        assert_eq!(level(&["anylist_notify", "-vv"]), Some("debug"));
+        assert_eq!(level(&["anylist_notify", "-vvv"]), Some("trace"));
+        assert_eq!(level(&["anylist_notify", "-vvvv"]), Some("trace"));
+        assert_eq!(level(&["anylist_notify", "--verbose"]), Some("info"));
+    }
+
+    #[test]
+    fn test_activity_report_command() {
+        let cli = Cli::try_parse_from(["anylist_notify", "activity-report", "--format", "csv"])
+            .unwrap();
+
+        match cli.command {
+            Some(Command::ActivityReport { format, output }) => {
+                assert_eq!(format, ReportFormat::Csv);
+                assert_eq!(output, None);
+            }
+            _ => panic!("Expected activity-report command"),
+        }
+
+        assert!(Cli::try_parse_from(["anylist_notify"]).unwrap().command.is_none());
+    }
+
+    #[test]
+    fn test_user_activity_command() {
+        let cli = Cli::try_parse_from(["anylist_notify", "user-activity"]).unwrap();
+        match cli.command {
+            Some(Command::UserActivity { period }) => {
+                assert_eq!(period, Duration::from_secs(7 * 86_400));
+            }
+            _ => panic!("Expected user-activity command"),
+        }
+
+        let cli = Cli::try_parse_from(["anylist_notify", "user-activity", "--period", "30d"])
+            .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::UserActivity { period }) if period == Duration::from_secs(30 * 86_400)
+        ));
+    }
+
+    #[test]
+    fn test_prune_command() {
+        let cli = Cli::try_parse_from(["anylist_notify", "prune"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Prune)));
+        assert!(cli.is_one_off());
+    }
+
+    #[test]
+    fn test_stats_command() {
+        let cli = Cli::try_parse_from(["anylist_notify", "stats"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Stats)));
+        assert!(cli.is_one_off());
+    }
+
+    #[test]
+    fn test_cache_export_import_commands() {
+        let cli = Cli::try_parse_from(["anylist_notify", "cache-export", "--out", "cache.json"])
+            .unwrap();
+        match cli.command {
+            Some(Command::CacheExport { out }) => {
+                assert_eq!(out, Some(PathBuf::from("cache.json")));
+            }
+            _ => panic!("Expected cache-export command"),
+        }
+
+        let cli = Cli::try_parse_from(["anylist_notify", "cache-import", "--in", "cache.json"])
+            .unwrap();
+        match cli.command {
+            Some(Command::CacheImport { input }) => {
+                assert_eq!(input, PathBuf::from("cache.json"));
+            }
+            _ => panic!("Expected cache-import command"),
+        }
+
+        assert!(Cli::try_parse_from(["anylist_notify", "cache-import"]).is_err());
+    }
+
+    #[test]
+    fn test_mute_commands() {
+        let cli = Cli::try_parse_from(["anylist_notify", "mute", "Groceries", "1h"]).unwrap();
+        match cli.command {
+            Some(Command::Mute {
+                list_name,
+                duration,
+            }) => {
+                assert_eq!(list_name, "Groceries");
+                assert_eq!(duration, Duration::from_secs(3600));
+            }
+            _ => panic!("Expected mute command"),
+        }
+
+        let cli = Cli::try_parse_from(["anylist_notify", "unmute", "Groceries"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Unmute { .. })));
+
+        assert!(Cli::try_parse_from(["anylist_notify", "mute", "Groceries", "soon"]).is_err());
+    }
+
+    #[test]
+    fn test_backfill_backend_flag() {
+        let cli = Cli::try_parse_from(["anylist_notify", "--backfill-backend", "slack"]).unwrap();
+        assert_eq!(cli.backfill_backend.as_deref(), Some("slack"));
+        assert_eq!(cli.count, 10);
+        assert!(!cli.is_one_off());
+
+        let cli = Cli::try_parse_from([
+            "anylist_notify",
+            "--backfill-backend",
+            "slack",
+            "--count",
+            "25",
+        ])
+        .unwrap();
+        assert_eq!(cli.count, 25);
+
+        assert!(Cli::try_parse_from(["anylist_notify", "--count", "25"]).is_err());
+    }
+
+    #[test]
+    fn test_report_drift_flag() {
+        let cli = Cli::try_parse_from(["anylist_notify", "--report-drift"]).unwrap();
+        assert!(cli.report_drift);
+        assert!(cli.is_one_off());
+
+        assert!(!Cli::try_parse_from(["anylist_notify"]).unwrap().is_one_off());
+    }
+
+    #[test]
+    fn test_allow_schema_downgrade_flag() {
+        let cli = Cli::try_parse_from(["anylist_notify", "--allow-schema-downgrade"]).unwrap();
+        assert!(cli.allow_schema_downgrade);
+        assert!(!cli.is_one_off());
+
+        // Also accepted after a subcommand that opens the cache
+        let cli = Cli::try_parse_from(["anylist_notify", "stats", "--allow-schema-downgrade"])
+            .unwrap();
+        assert!(cli.allow_schema_downgrade);
+    }
+}
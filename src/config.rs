@@ -1,5 +1,13 @@
+use crate::notify::display::NameDisplay;
+use crate::notify::i18n::Language;
+use crate::sync::diff::ThresholdDirection;
 use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::NaiveTime;
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
@@ -9,6 +17,38 @@ pub struct Config {
     pub logging: LoggingConfig,
     #[serde(default)]
     pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    #[serde(default)]
+    pub slack: SlackConfig,
+    #[serde(default)]
+    pub discord: DiscordConfig,
+    #[serde(default)]
+    pub unix_socket: UnixSocketConfig,
+    #[serde(default)]
+    pub change_log: ChangeLogConfig,
+    #[serde(default)]
+    pub heartbeat: HeartbeatConfig,
+    #[serde(default)]
+    pub digest: DigestConfig,
+    /// Unchecked-item thresholds for individual lists
+    #[serde(default)]
+    pub list_thresholds: Vec<ListThresholdConfig>,
+    /// Per-category unchecked-item thresholds for individual lists
+    #[serde(default)]
+    pub category_thresholds: Vec<CategoryThresholdConfig>,
+    /// Options for individual lists
+    #[serde(default)]
+    pub lists: Vec<ListConfig>,
+    /// ntfy topic, priority and tags for individual lists
+    #[serde(default)]
+    pub list_overrides: Vec<ListOverrideConfig>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -20,16 +60,194 @@ pub struct AnyListConfig {
 #[derive(Debug, Deserialize, Clone)]
 pub struct CacheConfig {
     pub database_path: String,
+    /// Maximum number of pooled SQLite connections
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
+    /// How long to wait for a free pooled connection before failing
+    #[serde(default = "default_acquire_timeout_secs")]
+    pub acquire_timeout_secs: u64,
+    /// How many more times lists that fail to seed at startup are retried
+    #[serde(default = "default_seed_retries")]
+    pub seed_retries: u32,
+    /// Delay before the first seeding retry, doubled for each further retry up
+    /// to five minutes
+    #[serde(default = "default_seed_retry_backoff_secs")]
+    pub seed_retry_backoff_secs: u64,
+    /// Open a database written by a newer build; set by `--allow-schema-downgrade`
+    #[serde(skip)]
+    pub allow_schema_downgrade: bool,
+}
+
+impl CacheConfig {
+    /// Cache settings for a database path with default pool settings
+    #[cfg(test)]
+    pub fn new(database_path: &str) -> Self {
+        Self {
+            database_path: database_path.to_string(),
+            max_connections: default_max_connections(),
+            acquire_timeout_secs: default_acquire_timeout_secs(),
+            seed_retries: default_seed_retries(),
+            seed_retry_backoff_secs: default_seed_retry_backoff_secs(),
+            allow_schema_downgrade: false,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct NtfyConfig {
+    /// Enable the ntfy backend; defaults to enabled when a topic is configured
+    #[serde(default)]
+    pub enabled: Option<bool>,
     pub base_url: String,
+    #[serde(default)]
     pub topic: String,
     #[serde(default)]
     pub priorities: NtfyPriorities,
     #[serde(default)]
     pub tags: NtfyTags,
+    /// Topic to read commands from, such as "list Groceries"; disabled when unset
+    #[serde(default)]
+    pub command_topic: Option<String>,
+    /// Topic that receives every detected change, including filtered ones; disabled when unset
+    #[serde(default)]
+    pub audit_topic: Option<String>,
+    /// Topic where changes wait for approval before they are sent anywhere; disabled when unset
+    #[serde(default)]
+    pub review_topic: Option<String>,
+    /// Token the review buttons send, which should only be allowed to write the command
+    /// topic; the buttons send no credentials when unset
+    #[serde(default)]
+    pub action_token: Option<String>,
+    /// Seconds a change waits for review before it is dropped unanswered
+    #[serde(default = "default_review_expiry_secs")]
+    pub review_expiry_secs: u64,
+    /// Access token sent with every publish, for protected topics and phone calls
+    #[serde(default)]
+    pub access_token: Option<String>,
+    /// Credentials for servers that require them: a token, or a username and password
+    #[serde(default)]
+    pub auth: Option<NtfyAuth>,
+    /// Topics for changes to items in a category, whatever their list; category names
+    /// are matched case-insensitively and other changes go to `topic`
+    #[serde(default)]
+    pub category_topics: HashMap<String, String>,
+    /// Times a publish is retried after a network error or 5xx response
+    #[serde(default = "default_ntfy_max_retries")]
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each one after it
+    #[serde(default = "default_ntfy_retry_backoff_secs")]
+    pub retry_backoff_secs: u64,
+    /// Log notifications instead of publishing them, while other backends send as usual
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl NtfyConfig {
+    /// Whether the ntfy backend should be constructed
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(!self.topic.is_empty())
+    }
+
+    /// Command topic, if one is configured
+    pub fn command_topic(&self) -> Option<&str> {
+        self.command_topic.as_deref().filter(|topic| !topic.is_empty())
+    }
+
+    /// Audit topic, if one is configured
+    pub fn audit_topic(&self) -> Option<&str> {
+        self.audit_topic.as_deref().filter(|topic| !topic.is_empty())
+    }
+
+    /// Review topic, if one is configured
+    pub fn review_topic(&self) -> Option<&str> {
+        self.review_topic.as_deref().filter(|topic| !topic.is_empty())
+    }
+
+    /// Topic for changes to items in a category, if one is configured
+    pub fn category_topic(&self, category: &str) -> Option<&str> {
+        self.category_topics
+            .iter()
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(category.trim()))
+            .map(|(_, topic)| topic.as_str())
+            .filter(|topic| !topic.is_empty())
+    }
+
+    /// Access token, if one is configured
+    pub fn access_token(&self) -> Option<&str> {
+        self.access_token.as_deref().filter(|token| !token.is_empty())
+    }
+
+    /// Review button token, if one is configured
+    pub fn action_token(&self) -> Option<&str> {
+        self.action_token
+            .as_deref()
+            .filter(|token| !token.is_empty())
+    }
+
+    /// Credentials sent with every request, from `auth` or the `access_token` shorthand
+    pub fn auth(&self) -> Option<NtfyAuth> {
+        self.auth.clone().or_else(|| {
+            self.access_token().map(|token| NtfyAuth::Token {
+                token: token.to_string(),
+            })
+        })
+    }
+}
+
+/// Credentials for a ntfy server
+#[derive(Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum NtfyAuth {
+    /// Access token, sent as a bearer token
+    Token { token: String },
+    /// Username and password, sent as basic auth
+    Basic { username: String, password: String },
+}
+
+/// Catch basic auth with only one half set, which would otherwise fail to
+/// deserialize as either kind of `NtfyAuth` with an unhelpful error
+fn check_basic_auth(config: &config::Config) -> Result<()> {
+    if config.get_string("ntfy.auth.token").is_ok() {
+        return Ok(());
+    }
+    let username = config.get_string("ntfy.auth.username").is_ok();
+    let password = config.get_string("ntfy.auth.password").is_ok();
+    match (username, password) {
+        (true, false) => anyhow::bail!(
+            "ntfy.auth has a username but no password; set both, e.g. NTFY_USERNAME and NTFY_PASSWORD"
+        ),
+        (false, true) => anyhow::bail!(
+            "ntfy.auth has a password but no username; set both, e.g. NTFY_USERNAME and NTFY_PASSWORD"
+        ),
+        _ => Ok(()),
+    }
+}
+
+impl NtfyAuth {
+    /// Value of the `Authorization` header carrying the credentials
+    pub fn header_value(&self) -> String {
+        match self {
+            NtfyAuth::Token { token } => format!("Bearer {}", token),
+            NtfyAuth::Basic { username, password } => {
+                let credentials = format!("{}:{}", username, password);
+                format!("Basic {}", BASE64.encode(credentials))
+            }
+        }
+    }
+}
+
+// Keeps credentials out of logs if the config is ever printed
+impl fmt::Debug for NtfyAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NtfyAuth::Token { .. } => f.write_str("Token { token: <redacted> }"),
+            NtfyAuth::Basic { username, .. } => write!(
+                f,
+                "Basic {{ username: {:?}, password: <redacted> }}",
+                username
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -44,6 +262,46 @@ pub struct NtfyPriorities {
     pub item_removed: String,
     #[serde(default = "default_priority")]
     pub item_modified: String,
+    #[serde(default = "high_priority")]
+    pub list_sharing_changed: String,
+    #[serde(default = "max_priority")]
+    pub list_became_shared: String,
+    #[serde(default = "max_priority")]
+    pub list_became_private: String,
+    #[serde(default = "default_priority")]
+    pub items_recategorized: String,
+    #[serde(default = "default_priority")]
+    pub changes_by_user: String,
+    #[serde(default = "high_priority")]
+    pub item_toggled_repeatedly: String,
+    #[serde(default = "default_priority")]
+    pub item_marked_maybe: String,
+    /// Modifications that clear an item's quantity or set it to zero
+    #[serde(default = "default_priority")]
+    pub quantity_depleted: String,
+    #[serde(default = "high_priority")]
+    pub cache_unavailable: String,
+    #[serde(default = "default_priority")]
+    pub list_snapshot: String,
+    #[serde(default = "low_priority")]
+    pub service_recovered: String,
+    #[serde(default = "default_priority")]
+    pub list_threshold_crossed: String,
+    #[serde(default = "default_priority")]
+    pub missed_changes: String,
+    #[serde(default = "low_priority")]
+    pub heartbeat: String,
+    #[serde(default = "high_priority")]
+    pub list_reseeded: String,
+    #[serde(default = "default_priority")]
+    pub digest: String,
+    #[serde(default = "low_priority")]
+    pub editing_started: String,
+    #[serde(default = "high_priority")]
+    pub watched_item_added: String,
+    /// Every change sent to the audit topic
+    #[serde(default = "low_priority")]
+    pub audit: String,
 }
 
 impl Default for NtfyPriorities {
@@ -54,6 +312,25 @@ impl Default for NtfyPriorities {
             item_unchecked: default_priority(),
             item_removed: default_priority(),
             item_modified: default_priority(),
+            list_sharing_changed: high_priority(),
+            list_became_shared: max_priority(),
+            list_became_private: max_priority(),
+            items_recategorized: default_priority(),
+            changes_by_user: default_priority(),
+            item_toggled_repeatedly: high_priority(),
+            item_marked_maybe: default_priority(),
+            quantity_depleted: default_priority(),
+            cache_unavailable: high_priority(),
+            list_snapshot: default_priority(),
+            service_recovered: low_priority(),
+            list_threshold_crossed: default_priority(),
+            missed_changes: default_priority(),
+            heartbeat: low_priority(),
+            list_reseeded: high_priority(),
+            digest: default_priority(),
+            editing_started: low_priority(),
+            watched_item_added: high_priority(),
+            audit: low_priority(),
         }
     }
 }
@@ -70,6 +347,50 @@ pub struct NtfyTags {
     pub item_removed: String,
     #[serde(default = "default_modified_tags")]
     pub item_modified: String,
+    #[serde(default = "default_sharing_tags")]
+    pub list_sharing_changed: String,
+    #[serde(default = "default_became_shared_tags")]
+    pub list_became_shared: String,
+    #[serde(default = "default_became_private_tags")]
+    pub list_became_private: String,
+    /// Extra tags added to modifications that change an item's category
+    #[serde(default = "default_category_changed_tags")]
+    pub category_changed: String,
+    #[serde(default = "default_recategorized_tags")]
+    pub items_recategorized: String,
+    #[serde(default = "default_changes_by_user_tags")]
+    pub changes_by_user: String,
+    #[serde(default = "default_toggled_tags")]
+    pub item_toggled_repeatedly: String,
+    #[serde(default = "default_maybe_tags")]
+    pub item_marked_maybe: String,
+    #[serde(default = "default_quantity_depleted_tags")]
+    pub quantity_depleted: String,
+    #[serde(default = "default_quantity_set_tags")]
+    pub quantity_set: String,
+    /// Extra tags added to changes escalated by `urgent_keywords`
+    #[serde(default = "default_urgent_tags")]
+    pub urgent: String,
+    #[serde(default = "default_cache_unavailable_tags")]
+    pub cache_unavailable: String,
+    #[serde(default = "default_list_snapshot_tags")]
+    pub list_snapshot: String,
+    #[serde(default = "default_service_recovered_tags")]
+    pub service_recovered: String,
+    #[serde(default = "default_threshold_tags")]
+    pub list_threshold_crossed: String,
+    #[serde(default = "default_missed_changes_tags")]
+    pub missed_changes: String,
+    #[serde(default = "default_heartbeat_tags")]
+    pub heartbeat: String,
+    #[serde(default = "default_list_reseeded_tags")]
+    pub list_reseeded: String,
+    #[serde(default = "default_digest_tags")]
+    pub digest: String,
+    #[serde(default = "default_editing_started_tags")]
+    pub editing_started: String,
+    #[serde(default = "default_watched_item_added_tags")]
+    pub watched_item_added: String,
 }
 
 impl Default for NtfyTags {
@@ -80,10 +401,65 @@ impl Default for NtfyTags {
             item_unchecked: default_unchecked_tags(),
             item_removed: default_removed_tags(),
             item_modified: default_modified_tags(),
+            list_sharing_changed: default_sharing_tags(),
+            list_became_shared: default_became_shared_tags(),
+            list_became_private: default_became_private_tags(),
+            category_changed: default_category_changed_tags(),
+            items_recategorized: default_recategorized_tags(),
+            changes_by_user: default_changes_by_user_tags(),
+            item_toggled_repeatedly: default_toggled_tags(),
+            item_marked_maybe: default_maybe_tags(),
+            quantity_depleted: default_quantity_depleted_tags(),
+            quantity_set: default_quantity_set_tags(),
+            urgent: default_urgent_tags(),
+            cache_unavailable: default_cache_unavailable_tags(),
+            list_snapshot: default_list_snapshot_tags(),
+            service_recovered: default_service_recovered_tags(),
+            list_threshold_crossed: default_threshold_tags(),
+            missed_changes: default_missed_changes_tags(),
+            heartbeat: default_heartbeat_tags(),
+            list_reseeded: default_list_reseeded_tags(),
+            digest: default_digest_tags(),
+            editing_started: default_editing_started_tags(),
+            watched_item_added: default_watched_item_added_tags(),
         }
     }
 }
 
+impl NtfyTags {
+    /// Every tag setting with its name
+    pub fn all(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("item_added", &self.item_added),
+            ("item_checked", &self.item_checked),
+            ("item_unchecked", &self.item_unchecked),
+            ("item_removed", &self.item_removed),
+            ("item_modified", &self.item_modified),
+            ("list_sharing_changed", &self.list_sharing_changed),
+            ("list_became_shared", &self.list_became_shared),
+            ("list_became_private", &self.list_became_private),
+            ("category_changed", &self.category_changed),
+            ("items_recategorized", &self.items_recategorized),
+            ("changes_by_user", &self.changes_by_user),
+            ("item_toggled_repeatedly", &self.item_toggled_repeatedly),
+            ("item_marked_maybe", &self.item_marked_maybe),
+            ("quantity_depleted", &self.quantity_depleted),
+            ("quantity_set", &self.quantity_set),
+            ("urgent", &self.urgent),
+            ("cache_unavailable", &self.cache_unavailable),
+            ("list_snapshot", &self.list_snapshot),
+            ("service_recovered", &self.service_recovered),
+            ("list_threshold_crossed", &self.list_threshold_crossed),
+            ("missed_changes", &self.missed_changes),
+            ("heartbeat", &self.heartbeat),
+            ("list_reseeded", &self.list_reseeded),
+            ("digest", &self.digest),
+            ("editing_started", &self.editing_started),
+            ("watched_item_added", &self.watched_item_added),
+        ]
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct LoggingConfig {
     #[serde(default = "default_log_level")]
@@ -95,125 +471,1506 @@ pub struct NotificationsConfig {
     /// Filter out changes made by the authenticated user (based on AnyList email)
     #[serde(default = "default_filter_own_changes")]
     pub filter_own_changes: bool,
+    /// Include the removed item's quantity, details and category in removal notifications
+    #[serde(default)]
+    pub include_details_in_removal: bool,
+    /// Include the item's quantity and category in check and uncheck notifications
+    #[serde(default)]
+    pub context_in_check: bool,
+    /// Append the IDs of the affected items to notifications, to help debug them
+    #[serde(default)]
+    pub include_item_id: bool,
+    /// Only notify about lists shared with someone besides the authenticated user
+    #[serde(default)]
+    pub shared_lists_only: bool,
+    /// Send one notification per user and list for a sync pass's item changes
+    #[serde(default)]
+    pub group_by_user: bool,
+    /// Send one notification for an item added to several lists in the same sync
+    #[serde(default)]
+    pub merge_cross_list_adds: bool,
+    /// Tag ntfy list notifications with the list name, e.g. `list-groceries`
+    #[serde(default)]
+    pub tag_list_name: bool,
+    /// Don't notify about modifications to items that are checked off
+    #[serde(default)]
+    pub ignore_changes_on_checked: bool,
+    /// Drop category-only modifications that AnyList assigns shortly after an item is added
+    #[serde(default)]
+    pub suppress_auto_category: bool,
+    /// How long after an item is first seen a category assignment counts as automatic
+    #[serde(default = "default_auto_category_window_secs")]
+    pub auto_category_window_secs: u64,
+    /// Text appended to every notification body; `{list_name}` is replaced with the list name
+    #[serde(default)]
+    pub footer: Option<String>,
+    /// Append the list's item and unchecked counts after the change to every notification
+    #[serde(default)]
+    pub include_list_totals: bool,
+    /// How category changes on items are reported
+    #[serde(default)]
+    pub category_change_mode: CategoryChangeMode,
+    /// Collapse several category-only changes on one list in a sync pass into a single summary
+    #[serde(default)]
+    pub summarize_recategorization: bool,
+    /// Replace emoji in notification text with ASCII markers and send no emoji tags
+    #[serde(default)]
+    pub ascii_only: bool,
+    /// Language of the fixed notification phrases, e.g. "en" or "de"
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// Changes to items whose name contains one of these (case-insensitive) are sent at max priority
+    #[serde(default)]
+    pub urgent_keywords: Vec<String>,
+    /// Item names (case-insensitive, `*` and `?` wildcards) whose addition to any
+    /// list sends a separate watched-item alert
+    #[serde(default)]
+    pub watch_items: Vec<String>,
+    /// Send only the watched-item alert for those adds, not the usual add notification
+    #[serde(default)]
+    pub watch_items_replace_add: bool,
+    /// Send bodies longer than `max_body_length` as a text attachment with a short
+    /// summary as the message
+    #[serde(default)]
+    pub overflow_to_attachment: bool,
+    /// Longest message body, in bytes, sent inline with `overflow_to_attachment`
+    #[serde(default = "default_max_body_length")]
+    pub max_body_length: usize,
+    /// Have ntfy phone this number ("+12223334444", or "yes" for the account's
+    /// first verified number) about urgent-keyword changes; disabled when unset
+    #[serde(default)]
+    pub call_on_urgent: Option<String>,
+    /// Grouped and summary notifications covering more changes than this are
+    /// sent one priority level higher, up to max; 0 disables
+    #[serde(default)]
+    pub escalate_batch_threshold: usize,
+    /// Lowest priority any notification is sent with
+    #[serde(default)]
+    pub min_priority: Option<Priority>,
+    /// Highest priority any notification is sent with
+    #[serde(default)]
+    pub max_priority: Option<Priority>,
+    /// Compare details made of `key: value` lines per key and report only changed keys
+    #[serde(default)]
+    pub details_as_kv: bool,
+    /// Report a quantity given to an item that had none as "quantity set", not a modification
+    #[serde(default)]
+    pub highlight_quantity_set: bool,
+    /// Open the first link in an item's details when the notification is tapped, and
+    /// show the first phone number at the top of the message
+    #[serde(default)]
+    pub extract_links: bool,
+    /// How long shutdown waits for changes still being processed to be notified
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
+    /// Replace check notifications for an item toggled many times with a single alert
+    #[serde(default)]
+    pub detect_toggling: bool,
+    /// Check-state changes within the window that trigger a toggling alert
+    #[serde(default = "default_toggle_threshold")]
+    pub toggle_threshold: usize,
+    /// Window in which check-state changes are counted
+    #[serde(default = "default_toggle_window_secs")]
+    pub toggle_window_secs: u64,
+    /// How long check notifications for an item stay muted after a toggling alert
+    #[serde(default = "default_toggle_cooldown_secs")]
+    pub toggle_cooldown_secs: u64,
+    /// Report an item checked and unchecked again within `maybe_window_secs` once as a
+    /// "maybe", then mute its check changes until it is left alone for the window
+    #[serde(default)]
+    pub maybe_toggles: bool,
+    #[serde(default = "default_maybe_window_secs")]
+    pub maybe_window_secs: u64,
+    /// Report a list gaining its first other user, or losing its last one, as the list
+    /// becoming shared or private instead of a routine sharing change
+    #[serde(default)]
+    pub sharing_transitions: bool,
+    /// Merge an add and a check-off of the same item within this many seconds into one
+    /// notification; adds are held back for the window (0 disables)
+    #[serde(default)]
+    pub coalesce_add_check_secs: u64,
+    /// Drop an uncheck and a recheck of the same item within this many seconds, with no
+    /// other change between them; unchecks are held back for the window (0 disables)
+    #[serde(default)]
+    pub suppress_uncheck_recheck_secs: u64,
+    /// Drop a removal and a re-add of the same item within this many seconds, matched
+    /// by ID or name; removals are held back for the window (0 disables)
+    #[serde(default)]
+    pub undo_remove_secs: u64,
+    /// Send an item's notifications at most once within this many seconds; later
+    /// changes are held and sent together once it passes (0 disables)
+    #[serde(default)]
+    pub per_item_min_interval_secs: u64,
+    /// Changes by one user to a list within `editing_session_window_secs` that start
+    /// an editing session, whose further changes are summarized once it ends (0 disables)
+    #[serde(default)]
+    pub editing_session_changes: usize,
+    /// Window in seconds for counting changes towards an editing session
+    #[serde(default = "default_editing_session_window_secs")]
+    pub editing_session_window_secs: u64,
+    /// Seconds without a change after which an editing session ends and is summarized
+    #[serde(default = "default_editing_session_quiet_secs")]
+    pub editing_session_quiet_secs: u64,
+    /// Send a notification when an editing session starts
+    #[serde(default)]
+    pub editing_session_ping: bool,
+    /// Hold item changes to a list for this many milliseconds after the first one and
+    /// send them as one summary (0 sends each change immediately)
+    #[serde(default)]
+    pub batch_window_ms: u64,
+    /// Send an alert when the service becomes degraded, e.g. the cache stops accepting writes
+    #[serde(default = "default_alert_on_degraded")]
+    pub alert_on_degraded: bool,
+    /// Send a low-priority notification when the service recovers from a degraded state
+    #[serde(default = "default_notify_on_recovery")]
+    pub notify_on_recovery: bool,
+    /// Cosmetic transforms for item names shown in notifications
+    #[serde(default)]
+    pub display: DisplayConfig,
+    /// Window in seconds within which identical changes share a dedup key
+    #[serde(default = "default_dedup_bucket_secs")]
+    pub dedup_bucket_secs: u64,
+    /// Seconds an identical change is skipped after one is sent, even across restarts (0 disables)
+    #[serde(default)]
+    pub sent_marker_secs: u64,
+    /// Whether changes are notified as they happen, in a scheduled digest, or both
+    #[serde(default)]
+    pub mode: NotificationMode,
+    /// Shell command run for every notified change, with the change as JSON on stdin
+    #[serde(default)]
+    pub exec_hook: Option<String>,
+    /// Seconds an exec hook may run before it is killed
+    #[serde(default = "default_exec_hook_timeout_secs")]
+    pub exec_hook_timeout_secs: u64,
+    /// Log the hook's input instead of running it
+    #[serde(default)]
+    pub exec_hook_dry_run: bool,
+    /// Names of the backends to start, such as "ntfy" and "discord"; every configured
+    /// backend when unset
+    #[serde(default)]
+    pub backends: Option<Vec<String>>,
+}
+
+/// Transforms applied to item names in notifications only; the cache keeps the real names
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct DisplayConfig {
+    /// Capitalize the first letter of each word and lowercase the rest
+    #[serde(default)]
+    pub title_case: bool,
+    /// Regular expression whose matches are removed, e.g. trailing store codes
+    #[serde(default)]
+    pub strip_pattern: Option<String>,
+    /// Shorten longer names to this many characters, ending with an ellipsis
+    #[serde(default)]
+    pub max_name_length: Option<usize>,
+}
+
+/// When changes are notified
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationMode {
+    /// Notify each change as it happens
+    #[default]
+    Realtime,
+    /// Only send the scheduled digest; no per-change notifications
+    Digest,
+    /// Notify each change and also send the digest
+    Both,
+}
+
+impl NotificationMode {
+    /// Whether individual changes are notified as they happen
+    pub fn sends_realtime(self) -> bool {
+        self != NotificationMode::Digest
+    }
+
+    /// Whether the scheduled digest is sent
+    pub fn sends_digest(self) -> bool {
+        self != NotificationMode::Realtime
+    }
+}
+
+/// ntfy message priority, ordered from least to most intrusive
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    Min,
+    Low,
+    Default,
+    High,
+    #[serde(alias = "urgent")]
+    Max,
+}
+
+impl Priority {
+    const LEVELS: [Priority; 5] = [
+        Priority::Min,
+        Priority::Low,
+        Priority::Default,
+        Priority::High,
+        Priority::Max,
+    ];
+
+    /// Parse a priority name or ntfy's 1-5 number
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "1" | "min" => Some(Priority::Min),
+            "2" | "low" => Some(Priority::Low),
+            "3" | "default" => Some(Priority::Default),
+            "4" | "high" => Some(Priority::High),
+            "5" | "max" | "urgent" => Some(Priority::Max),
+            _ => None,
+        }
+    }
+
+    /// ntfy's number for the priority, from 1 (min) to 5 (max)
+    pub fn level(self) -> u8 {
+        Self::LEVELS.iter().position(|p| *p == self).unwrap_or(2) as u8 + 1
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Priority::Min => "min",
+            Priority::Low => "low",
+            Priority::Default => "default",
+            Priority::High => "high",
+            Priority::Max => "max",
+        }
+    }
+
+    /// The next level up, staying at max
+    pub fn raise(self) -> Self {
+        let level = Self::LEVELS.iter().position(|p| *p == self).unwrap_or(0);
+        Self::LEVELS[(level + 1).min(Self::LEVELS.len() - 1)]
+    }
+}
+
+/// How category changes on items are reported
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CategoryChangeMode {
+    /// Report category changes like any other field change
+    #[default]
+    Notify,
+    /// Drop category changes entirely
+    Ignore,
+    /// Report category changes with a minimal body and a category tag
+    TagOnly,
 }
 
 impl Default for NotificationsConfig {
     fn default() -> Self {
         Self {
             filter_own_changes: default_filter_own_changes(),
+            include_details_in_removal: false,
+            context_in_check: false,
+            include_item_id: false,
+            shared_lists_only: false,
+            group_by_user: false,
+            merge_cross_list_adds: false,
+            tag_list_name: false,
+            ignore_changes_on_checked: false,
+            suppress_auto_category: false,
+            auto_category_window_secs: default_auto_category_window_secs(),
+            footer: None,
+            include_list_totals: false,
+            category_change_mode: CategoryChangeMode::default(),
+            summarize_recategorization: false,
+            ascii_only: false,
+            language: default_language(),
+            urgent_keywords: Vec::new(),
+            watch_items: Vec::new(),
+            watch_items_replace_add: false,
+            call_on_urgent: None,
+            overflow_to_attachment: false,
+            max_body_length: default_max_body_length(),
+            escalate_batch_threshold: 0,
+            min_priority: None,
+            max_priority: None,
+            details_as_kv: false,
+            highlight_quantity_set: false,
+            extract_links: false,
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+            detect_toggling: false,
+            toggle_threshold: default_toggle_threshold(),
+            toggle_window_secs: default_toggle_window_secs(),
+            toggle_cooldown_secs: default_toggle_cooldown_secs(),
+            maybe_toggles: false,
+            maybe_window_secs: default_maybe_window_secs(),
+            sharing_transitions: false,
+            coalesce_add_check_secs: 0,
+            suppress_uncheck_recheck_secs: 0,
+            undo_remove_secs: 0,
+            per_item_min_interval_secs: 0,
+            editing_session_changes: 0,
+            editing_session_window_secs: default_editing_session_window_secs(),
+            editing_session_quiet_secs: default_editing_session_quiet_secs(),
+            editing_session_ping: false,
+            batch_window_ms: 0,
+            alert_on_degraded: default_alert_on_degraded(),
+            notify_on_recovery: default_notify_on_recovery(),
+            display: DisplayConfig::default(),
+            dedup_bucket_secs: default_dedup_bucket_secs(),
+            sent_marker_secs: 0,
+            mode: NotificationMode::default(),
+            exec_hook: None,
+            exec_hook_timeout_secs: default_exec_hook_timeout_secs(),
+            exec_hook_dry_run: false,
+            backends: None,
         }
     }
 }
 
-// Default value functions
-fn default_filter_own_changes() -> bool {
-    true
+impl NotificationsConfig {
+    /// Number to call about urgent changes, if one is configured
+    pub fn call_on_urgent(&self) -> Option<&str> {
+        self.call_on_urgent
+            .as_deref()
+            .map(str::trim)
+            .filter(|number| !number.is_empty())
+    }
+
+    /// Command run for every change, if one is configured
+    pub fn exec_hook(&self) -> Option<&str> {
+        self.exec_hook
+            .as_deref()
+            .map(str::trim)
+            .filter(|command| !command.is_empty())
+    }
 }
-fn default_priority() -> String {
-    "default".to_string()
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AuthConfig {
+    /// Number of times to retry the initial AnyList login before giving up
+    #[serde(default = "default_max_startup_retries")]
+    pub max_startup_retries: u32,
+    /// Delay before the first login retry, doubled after each failed attempt up
+    /// to five minutes
+    #[serde(default = "default_startup_backoff_secs")]
+    pub startup_backoff_secs: u64,
 }
 
-fn low_priority() -> String {
-    "low".to_string()
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            max_startup_retries: default_max_startup_retries(),
+            startup_backoff_secs: default_startup_backoff_secs(),
+        }
+    }
 }
 
-fn default_added_tags() -> String {
-    "heavy_plus_sign,shopping_cart".to_string()
+/// Options that apply to a single list
+#[derive(Debug, Deserialize, Clone)]
+pub struct ListConfig {
+    /// Name of the list, matched case-insensitively
+    pub list_name: String,
+    /// Report check-offs as the item being done, with removal-style priority and
+    /// tags, and unchecks as the item being added back
+    #[serde(default)]
+    pub treat_checked_as_done: bool,
+    /// Names of the backends that receive this list's changes, such as "ntfy";
+    /// every enabled backend when unset
+    #[serde(default)]
+    pub backends: Option<Vec<String>>,
 }
 
-fn default_checked_tags() -> String {
-    "white_check_mark".to_string()
+/// ntfy settings that replace the global ones for a single list
+#[derive(Debug, Deserialize, Clone)]
+pub struct ListOverrideConfig {
+    /// Name of the list, matched case-insensitively
+    #[serde(default)]
+    pub list_name: Option<String>,
+    /// ID of the list, matched exactly; wins over a list_name match
+    #[serde(default)]
+    pub list_id: Option<String>,
+    /// Topic for the list's changes instead of `ntfy.topic`
+    #[serde(default)]
+    pub topic: Option<String>,
+    /// Priority for every change to the list instead of the per-change priority
+    #[serde(default)]
+    pub priority: Option<String>,
+    /// Comma-separated tags for every change to the list instead of the per-change tags
+    #[serde(default)]
+    pub tags: Option<String>,
 }
 
-fn default_unchecked_tags() -> String {
-    "arrow_backward".to_string()
+/// Notify when a list's unchecked-item count crosses a threshold
+#[derive(Debug, Deserialize, Clone)]
+pub struct ListThresholdConfig {
+    /// Name of the list, matched case-insensitively
+    pub list_name: String,
+    /// Unchecked-item count at which the list counts as full, e.g. 10 for "time to shop"
+    pub threshold: usize,
+    /// Which crossings to notify about
+    #[serde(default)]
+    pub notify_on: ThresholdNotify,
+    /// Send only threshold crossings for this list, not its individual changes
+    #[serde(default)]
+    pub only_crossings: bool,
 }
 
-fn default_removed_tags() -> String {
-    "x,shopping_cart".to_string()
+#[derive(Debug, Deserialize, Clone)]
+pub struct CategoryThresholdConfig {
+    /// Name of the list, matched case-insensitively
+    pub list_name: String,
+    /// Threshold for categories without their own entry; only listed categories
+    /// are watched when unset
+    #[serde(default)]
+    pub default_threshold: Option<usize>,
+    /// Thresholds for individual categories, matched case-insensitively
+    #[serde(default)]
+    pub categories: HashMap<String, usize>,
+    /// Which crossings to notify about
+    #[serde(default)]
+    pub notify_on: ThresholdNotify,
 }
 
-fn default_modified_tags() -> String {
-    "pencil2".to_string()
+impl CategoryThresholdConfig {
+    /// Unchecked-item count at which a category counts as full, if it is watched
+    pub fn threshold_for(&self, category: &str) -> Option<usize> {
+        self.categories
+            .iter()
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(category.trim()))
+            .map(|(_, threshold)| *threshold)
+            .or(self.default_threshold)
+    }
 }
 
-fn default_log_level() -> String {
-    "info".to_string()
+/// Which threshold crossings are notified
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdNotify {
+    /// Only when the count rises to the threshold
+    #[default]
+    Above,
+    /// Only when the count drops back below the threshold
+    Below,
+    /// Both directions
+    Both,
 }
 
-impl Config {
-    /// Load configuration from file and environment variables
-    /// Environment variables override config file values
-    pub fn load() -> Result<Self> {
-        // Load .env file if it exists (doesn't error if missing)
-        dotenvy::dotenv().ok();
+impl ThresholdNotify {
+    /// Whether a crossing in the given direction should be notified
+    pub fn includes(self, direction: ThresholdDirection) -> bool {
+        matches!(
+            (self, direction),
+            (ThresholdNotify::Both, _)
+                | (ThresholdNotify::Above, ThresholdDirection::Above)
+                | (ThresholdNotify::Below, ThresholdDirection::Below)
+        )
+    }
+}
 
-        let mut builder = config::Config::builder()
-            // Start with default values
-            .set_default("cache.database_path", "./anylist.db")?
-            .set_default("ntfy.base_url", "https://ntfy.sh")?
-            .set_default("logging.level", "info")?;
+/// What happens to notifications for a backend whose circuit is open
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerOpenMode {
+    /// Discard notifications until the backend recovers
+    #[default]
+    Drop,
+    /// Hold notifications and send them once the backend recovers
+    Queue,
+}
 
-        // Try to load config.toml if it exists
-        if let Ok(config_path) = std::env::current_dir() {
-            let config_file = config_path.join("config.toml");
-            if config_file.exists() {
-                builder = builder.add_source(config::File::from(config_file));
-            }
-        }
+#[derive(Debug, Deserialize, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures before a backend is paused; 0 disables the breaker
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// How long a paused backend is skipped before a trial notification is sent
+    #[serde(default = "default_breaker_cooldown_secs")]
+    pub cooldown_secs: u64,
+    #[serde(default)]
+    pub open_mode: BreakerOpenMode,
+    /// Maximum notifications held per backend in `queue` mode; the oldest are dropped first
+    #[serde(default = "default_max_queued")]
+    pub max_queued: usize,
+}
 
-        // Manually set values from environment variables
-        // This is more explicit and reliable than using Environment source
-        if let Ok(email) = std::env::var("ANYLIST_EMAIL") {
-            builder = builder.set_override("anylist.email", email)?;
-        }
-        if let Ok(password) = std::env::var("ANYLIST_PASSWORD") {
-            builder = builder.set_override("anylist.password", password)?;
-        }
-        if let Ok(url) = std::env::var("NTFY_URL") {
-            builder = builder.set_override("ntfy.base_url", url)?;
-        }
-        if let Ok(topic) = std::env::var("NTFY_TOPIC") {
-            builder = builder.set_override("ntfy.topic", topic)?;
-        }
-        if let Ok(db_path) = std::env::var("DATABASE_PATH") {
-            builder = builder.set_override("cache.database_path", db_path)?;
-        }
-        if let Ok(log_level) = std::env::var("RUST_LOG") {
-            builder = builder.set_override("logging.level", log_level)?;
-        }
-        if let Ok(filter_own) = std::env::var("FILTER_OWN_CHANGES") {
-            let filter_bool = filter_own.to_lowercase() == "true" || filter_own == "1";
-            builder = builder.set_override("notifications.filter_own_changes", filter_bool)?;
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_failure_threshold(),
+            cooldown_secs: default_breaker_cooldown_secs(),
+            open_mode: BreakerOpenMode::default(),
+            max_queued: default_max_queued(),
         }
+    }
+}
 
-        let config = builder
-            .build()
-            .context("Failed to build configuration")?;
+#[derive(Debug, Deserialize, Clone)]
+pub struct WatchdogConfig {
+    /// In-flight event tasks above which the service counts as overloaded; 0 disables the watchdog
+    #[serde(default = "default_high_water_mark")]
+    pub high_water_mark: usize,
+    /// How long the count must stay above the mark before the watchdog acts
+    #[serde(default = "default_sustain_secs")]
+    pub sustain_secs: u64,
+    /// Skip heartbeats and redundant list refreshes while overloaded, then run one catch-up sync
+    #[serde(default = "default_shed_load")]
+    pub shed_load: bool,
+    /// Also disconnect and resubscribe to the WebSocket when overloaded
+    #[serde(default)]
+    pub resubscribe: bool,
+}
 
-        config
-            .try_deserialize()
-            .context("Failed to deserialize configuration")
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            high_water_mark: default_high_water_mark(),
+            sustain_secs: default_sustain_secs(),
+            shed_load: default_shed_load(),
+            resubscribe: false,
+        }
     }
+}
 
-    /// Validate that required fields are present
-    pub fn validate(&self) -> Result<()> {
+#[derive(Debug, Deserialize, Clone)]
+pub struct SyncConfig {
+    /// What to do with changes found when catching up after missed events
+    #[serde(default)]
+    pub offline_change_policy: OfflineChangePolicy,
+    /// How long after startup seeds a list its category-only changes aren't notified
+    #[serde(default = "default_post_init_grace_secs")]
+    pub post_init_grace_secs: u64,
+    /// Re-seed a list instead of notifying when a pass finds more item changes than
+    /// this multiple of the list's size; 0 disables
+    #[serde(default)]
+    pub reseed_change_ratio: f64,
+    /// Collapse list-change events arriving within this many milliseconds into one
+    /// pass, delaying it by as much; 0 disables
+    #[serde(default = "default_event_debounce_ms")]
+    pub event_debounce_ms: u64,
+    /// Consecutive fetches a cached list must be missing from before it is
+    /// treated as deleted and removed from the cache
+    #[serde(default = "default_deleted_list_confirmations")]
+    pub deleted_list_confirmations: u32,
+    /// Ignore "resync" commands arriving within this many seconds of the last one
+    #[serde(default = "default_resync_min_interval_secs")]
+    pub resync_min_interval_secs: u64,
+    /// What to do with items AnyList returns without an ID
+    #[serde(default)]
+    pub empty_item_ids: EmptyItemIdPolicy,
+    /// Refetch lists to learn the name of a user a change references but who
+    /// isn't known yet, instead of showing their raw ID
+    #[serde(default = "default_refresh_unknown_users")]
+    pub refresh_unknown_users: bool,
+    /// Seconds without any WebSocket event, heartbeats included, after which the
+    /// connection is treated as lost; 0 disables
+    #[serde(default = "default_connection_timeout_secs")]
+    pub connection_timeout_secs: u64,
+    /// How often lists are polled for changes while the WebSocket is down
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// Longest wait between attempts to reconnect the WebSocket
+    #[serde(default = "default_reconnect_max_backoff_secs")]
+    pub reconnect_max_backoff_secs: u64,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            offline_change_policy: OfflineChangePolicy::default(),
+            post_init_grace_secs: default_post_init_grace_secs(),
+            reseed_change_ratio: 0.0,
+            event_debounce_ms: default_event_debounce_ms(),
+            deleted_list_confirmations: default_deleted_list_confirmations(),
+            resync_min_interval_secs: default_resync_min_interval_secs(),
+            empty_item_ids: EmptyItemIdPolicy::default(),
+            refresh_unknown_users: default_refresh_unknown_users(),
+            connection_timeout_secs: default_connection_timeout_secs(),
+            poll_interval_secs: default_poll_interval_secs(),
+            reconnect_max_backoff_secs: default_reconnect_max_backoff_secs(),
+        }
+    }
+}
+
+/// How changes made while the service wasn't receiving events are reported
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OfflineChangePolicy {
+    /// Update the cache without notifying
+    #[default]
+    Drop,
+    /// Notify each change as if it had just happened
+    QueueIndividual,
+    /// Send one summary per list with counts of the changes
+    Summarize,
+}
+
+/// How items without an ID are handled, since changes are tracked by item ID
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyItemIdPolicy {
+    /// Leave the items out of diffs and the cache
+    #[default]
+    Skip,
+    /// Track the items by their name instead
+    Name,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct SlackConfig {
+    /// Enable the Slack backend; defaults to enabled when a webhook URL is configured
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Incoming-webhook URL notifications are posted to
+    #[serde(default)]
+    pub webhook_url: String,
+    /// Log messages instead of posting them, while other backends send as usual
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl SlackConfig {
+    /// Whether the Slack backend should be constructed
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(!self.webhook_url.is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct DiscordConfig {
+    /// Enable the Discord backend; defaults to enabled when a webhook URL is configured
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Webhook URL notifications are posted to as embeds
+    #[serde(default)]
+    pub webhook_url: String,
+    /// Log messages instead of posting them, while other backends send as usual
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl DiscordConfig {
+    /// Whether the Discord backend should be constructed
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(!self.webhook_url.is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ChangeLogConfig {
+    /// File every notified change is appended to as a JSON line
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Rotate the file before it grows past this many megabytes (0 disables)
+    #[serde(default = "default_change_log_max_size_mb")]
+    pub max_size_mb: u64,
+    /// Rotate the file when a change is written on a new local day
+    #[serde(default)]
+    pub daily: bool,
+    /// Number of rotated files to keep
+    #[serde(default = "default_change_log_keep")]
+    pub keep: usize,
+    /// Log lines instead of appending them to the file
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl Default for ChangeLogConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            max_size_mb: default_change_log_max_size_mb(),
+            daily: false,
+            keep: default_change_log_keep(),
+            dry_run: false,
+        }
+    }
+}
+
+impl ChangeLogConfig {
+    /// The log path, treating an empty value as unset
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref().filter(|path| !path.is_empty())
+    }
+
+    /// The rotation size in bytes, or `None` if it doesn't fit in a `u64`
+    pub fn max_bytes(&self) -> Option<u64> {
+        self.max_size_mb.checked_mul(1024 * 1024)
+    }
+}
+
+fn default_change_log_max_size_mb() -> u64 {
+    10
+}
+
+fn default_change_log_keep() -> usize {
+    5
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct UnixSocketConfig {
+    /// Where to create the socket that streams changes as JSON lines
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Log lines instead of streaming them to clients
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl UnixSocketConfig {
+    /// The socket path, treating an empty value as unset
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref().filter(|path| !path.is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct HeartbeatConfig {
+    /// Local time of day to send a daily "still alive" notification, e.g. "09:00"
+    #[serde(default)]
+    pub at: Option<String>,
+}
+
+impl HeartbeatConfig {
+    /// The configured time of day, or None when heartbeats are disabled
+    pub fn time(&self) -> Result<Option<NaiveTime>> {
+        parse_time_of_day(self.at.as_deref(), "heartbeat")
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct DigestConfig {
+    /// Local time of day to send the digest of each list's changes, e.g. "18:00"
+    #[serde(default)]
+    pub at: Option<String>,
+}
+
+impl DigestConfig {
+    /// The configured time of day, or None when no time is set
+    pub fn time(&self) -> Result<Option<NaiveTime>> {
+        parse_time_of_day(self.at.as_deref(), "digest")
+    }
+}
+
+/// Parse an optional "HH:MM" time of day, treating an empty value as unset
+fn parse_time_of_day(at: Option<&str>, what: &str) -> Result<Option<NaiveTime>> {
+    at.filter(|at| !at.is_empty())
+        .map(|at| {
+            NaiveTime::parse_from_str(at, "%H:%M")
+                .with_context(|| format!("Invalid {} time '{}', expected HH:MM", what, at))
+        })
+        .transpose()
+}
+
+/// Most times a publish may be retried, so an outage can't hold up changes for long
+const MAX_NTFY_RETRIES: u32 = 10;
+
+/// Names of the notification backends, as used in per-list `backends`
+const BACKEND_NAMES: &[&str] = &[
+    "ntfy",
+    "slack",
+    "discord",
+    "unix_socket",
+    "exec_hook",
+    "change_log",
+];
+
+/// Whether ntfy accepts the value as a call target: "yes" or an E.164 phone number
+fn is_call_target(value: &str) -> bool {
+    match value.strip_prefix('+') {
+        Some(digits) => {
+            (2..=15).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit())
+        }
+        None => value == "yes",
+    }
+}
+
+// Default value functions
+fn default_high_water_mark() -> usize {
+    20
+}
+
+fn default_sustain_secs() -> u64 {
+    30
+}
+
+fn default_shed_load() -> bool {
+    true
+}
+
+fn default_failure_threshold() -> u32 {
+    5
+}
+
+fn default_breaker_cooldown_secs() -> u64 {
+    60
+}
+
+fn default_max_queued() -> usize {
+    100
+}
+
+fn default_max_connections() -> u32 {
+    5
+}
+
+fn default_acquire_timeout_secs() -> u64 {
+    30
+}
+
+fn default_seed_retries() -> u32 {
+    2
+}
+
+fn default_seed_retry_backoff_secs() -> u64 {
+    2
+}
+
+fn default_filter_own_changes() -> bool {
+    true
+}
+
+fn default_alert_on_degraded() -> bool {
+    true
+}
+
+fn default_notify_on_recovery() -> bool {
+    true
+}
+
+fn default_auto_category_window_secs() -> u64 {
+    60
+}
+
+fn default_dedup_bucket_secs() -> u64 {
+    60
+}
+
+fn default_toggle_threshold() -> usize {
+    5
+}
+
+fn default_toggle_window_secs() -> u64 {
+    60
+}
+
+fn default_toggle_cooldown_secs() -> u64 {
+    600
+}
+
+fn default_maybe_window_secs() -> u64 {
+    300
+}
+
+fn default_editing_session_window_secs() -> u64 {
+    60
+}
+
+fn default_editing_session_quiet_secs() -> u64 {
+    120
+}
+
+/// ntfy's default message size limit
+fn default_max_body_length() -> usize {
+    4096
+}
+
+fn default_exec_hook_timeout_secs() -> u64 {
+    10
+}
+
+fn default_post_init_grace_secs() -> u64 {
+    60
+}
+
+fn default_event_debounce_ms() -> u64 {
+    500
+}
+
+fn default_deleted_list_confirmations() -> u32 {
+    3
+}
+
+fn default_resync_min_interval_secs() -> u64 {
+    30
+}
+
+fn default_refresh_unknown_users() -> bool {
+    true
+}
+
+fn default_connection_timeout_secs() -> u64 {
+    120
+}
+
+fn default_poll_interval_secs() -> u64 {
+    60
+}
+
+fn default_reconnect_max_backoff_secs() -> u64 {
+    300
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    10
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
+
+fn default_max_startup_retries() -> u32 {
+    5
+}
+
+fn default_startup_backoff_secs() -> u64 {
+    2
+}
+
+fn default_review_expiry_secs() -> u64 {
+    7 * 24 * 60 * 60
+}
+
+fn default_ntfy_max_retries() -> u32 {
+    3
+}
+
+fn default_ntfy_retry_backoff_secs() -> u64 {
+    1
+}
+fn default_priority() -> String {
+    "default".to_string()
+}
+
+fn low_priority() -> String {
+    "low".to_string()
+}
+
+fn high_priority() -> String {
+    "high".to_string()
+}
+
+fn max_priority() -> String {
+    "max".to_string()
+}
+
+fn default_added_tags() -> String {
+    "heavy_plus_sign,shopping_cart".to_string()
+}
+
+fn default_checked_tags() -> String {
+    "white_check_mark".to_string()
+}
+
+fn default_unchecked_tags() -> String {
+    "arrow_backward".to_string()
+}
+
+fn default_removed_tags() -> String {
+    "x,shopping_cart".to_string()
+}
+
+fn default_modified_tags() -> String {
+    "pencil2".to_string()
+}
+
+fn default_sharing_tags() -> String {
+    "busts_in_silhouette".to_string()
+}
+
+fn default_became_shared_tags() -> String {
+    "unlock,busts_in_silhouette".to_string()
+}
+
+fn default_became_private_tags() -> String {
+    "lock".to_string()
+}
+
+fn default_category_changed_tags() -> String {
+    "label".to_string()
+}
+
+fn default_recategorized_tags() -> String {
+    "card_index_dividers".to_string()
+}
+
+fn default_changes_by_user_tags() -> String {
+    "bust_in_silhouette".to_string()
+}
+
+fn default_toggled_tags() -> String {
+    "warning,repeat".to_string()
+}
+
+fn default_maybe_tags() -> String {
+    "thinking".to_string()
+}
+
+fn default_quantity_depleted_tags() -> String {
+    "hourglass".to_string()
+}
+
+fn default_quantity_set_tags() -> String {
+    "package".to_string()
+}
+
+fn default_urgent_tags() -> String {
+    "rotating_light".to_string()
+}
+
+fn default_list_snapshot_tags() -> String {
+    "shopping_cart".to_string()
+}
+
+fn default_service_recovered_tags() -> String {
+    "arrows_counterclockwise".to_string()
+}
+
+fn default_threshold_tags() -> String {
+    "shopping".to_string()
+}
+
+fn default_missed_changes_tags() -> String {
+    "electric_plug".to_string()
+}
+
+fn default_heartbeat_tags() -> String {
+    "green_heart".to_string()
+}
+
+fn default_list_reseeded_tags() -> String {
+    "recycle".to_string()
+}
+
+fn default_digest_tags() -> String {
+    "newspaper".to_string()
+}
+
+fn default_editing_started_tags() -> String {
+    "writing_hand".to_string()
+}
+
+fn default_watched_item_added_tags() -> String {
+    "eyes".to_string()
+}
+
+fn default_cache_unavailable_tags() -> String {
+    "warning,floppy_disk".to_string()
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+impl Config {
+    /// Load configuration from file and environment variables
+    /// Environment variables override config file values
+    pub fn load() -> Result<Self> {
+        // Load .env file if it exists (doesn't error if missing)
+        dotenvy::dotenv().ok();
+
+        let mut builder = config::Config::builder()
+            // Start with default values
+            .set_default("cache.database_path", "./anylist.db")?
+            .set_default("ntfy.base_url", "https://ntfy.sh")?
+            .set_default("logging.level", "info")?;
+
+        // Try to load config.toml if it exists
+        if let Ok(config_path) = std::env::current_dir() {
+            let config_file = config_path.join("config.toml");
+            if config_file.exists() {
+                builder = builder.add_source(config::File::from(config_file));
+            }
+        }
+
+        // Manually set values from environment variables
+        // This is more explicit and reliable than using Environment source
+        if let Ok(email) = std::env::var("ANYLIST_EMAIL") {
+            builder = builder.set_override("anylist.email", email)?;
+        }
+        if let Ok(password) = std::env::var("ANYLIST_PASSWORD") {
+            builder = builder.set_override("anylist.password", password)?;
+        }
+        if let Ok(url) = std::env::var("NTFY_URL") {
+            builder = builder.set_override("ntfy.base_url", url)?;
+        }
+        if let Ok(topic) = std::env::var("NTFY_TOPIC") {
+            builder = builder.set_override("ntfy.topic", topic)?;
+        }
+        if let Ok(token) = std::env::var("NTFY_ACCESS_TOKEN") {
+            builder = builder.set_override("ntfy.access_token", token)?;
+        }
+        if let Ok(token) = std::env::var("NTFY_ACTION_TOKEN") {
+            builder = builder.set_override("ntfy.action_token", token)?;
+        }
+        if let Ok(username) = std::env::var("NTFY_USERNAME") {
+            builder = builder.set_override("ntfy.auth.username", username)?;
+        }
+        if let Ok(password) = std::env::var("NTFY_PASSWORD") {
+            builder = builder.set_override("ntfy.auth.password", password)?;
+        }
+        if let Ok(enabled) = std::env::var("NTFY_ENABLED") {
+            let enabled_bool = enabled.to_lowercase() == "true" || enabled == "1";
+            builder = builder.set_override("ntfy.enabled", enabled_bool)?;
+        }
+        if let Ok(url) = std::env::var("SLACK_WEBHOOK_URL") {
+            builder = builder.set_override("slack.webhook_url", url)?;
+        }
+        if let Ok(url) = std::env::var("DISCORD_WEBHOOK_URL") {
+            builder = builder.set_override("discord.webhook_url", url)?;
+        }
+        if let Ok(path) = std::env::var("CHANGE_LOG_PATH") {
+            builder = builder.set_override("change_log.path", path)?;
+        }
+        if let Ok(db_path) = std::env::var("DATABASE_PATH") {
+            builder = builder.set_override("cache.database_path", db_path)?;
+        }
+        if let Ok(log_level) = std::env::var("RUST_LOG") {
+            builder = builder.set_override("logging.level", log_level)?;
+        }
+        if let Ok(filter_own) = std::env::var("FILTER_OWN_CHANGES") {
+            let filter_bool = filter_own.to_lowercase() == "true" || filter_own == "1";
+            builder = builder.set_override("notifications.filter_own_changes", filter_bool)?;
+        }
+
+        let config = builder
+            .build()
+            .context("Failed to build configuration")?;
+        check_basic_auth(&config)?;
+
+        config
+            .try_deserialize()
+            .context("Failed to deserialize configuration")
+    }
+
+    /// Threshold settings for a list, if any
+    pub fn list_threshold(&self, list_name: &str) -> Option<&ListThresholdConfig> {
+        self.list_thresholds
+            .iter()
+            .find(|list| list.list_name.trim().eq_ignore_ascii_case(list_name))
+    }
+
+    /// Per-category thresholds for a list, if any
+    pub fn category_thresholds(&self, list_name: &str) -> Option<&CategoryThresholdConfig> {
+        self.category_thresholds
+            .iter()
+            .find(|list| list.list_name.trim().eq_ignore_ascii_case(list_name))
+    }
+
+    /// Options for a list, if any
+    pub fn list_options(&self, list_name: &str) -> Option<&ListConfig> {
+        self.lists
+            .iter()
+            .find(|list| list.list_name.trim().eq_ignore_ascii_case(list_name))
+    }
+
+    /// Whether a list's changes go to the named backend
+    pub fn routes_to(&self, list_name: &str, backend: &str) -> bool {
+        match self
+            .list_options(list_name)
+            .and_then(|list| list.backends.as_ref())
+        {
+            Some(backends) => backends
+                .iter()
+                .any(|name| name.trim().eq_ignore_ascii_case(backend)),
+            None => true,
+        }
+    }
+
+    /// Whether the named backend is configured and, with `notifications.backends`, listed
+    pub fn backend_enabled(&self, backend: &str) -> bool {
+        let configured = match backend {
+            "ntfy" => self.ntfy.is_enabled(),
+            "slack" => self.slack.is_enabled(),
+            "discord" => self.discord.is_enabled(),
+            "unix_socket" => self.unix_socket.path().is_some(),
+            "exec_hook" => self.notifications.exec_hook().is_some(),
+            "change_log" => self.change_log.path().is_some(),
+            _ => false,
+        };
+        configured
+            && match &self.notifications.backends {
+                Some(backends) => backends
+                    .iter()
+                    .any(|name| name.trim().eq_ignore_ascii_case(backend)),
+                None => true,
+            }
+    }
+
+    /// Validate that required fields are present
+    pub fn validate(&self) -> Result<()> {
         if self.anylist.email.is_empty() {
             anyhow::bail!("AnyList email is required");
         }
         if self.anylist.password.is_empty() {
             anyhow::bail!("AnyList password is required");
         }
-        if self.ntfy.topic.is_empty() {
-            anyhow::bail!("ntfy topic is required");
+        if self.ntfy.is_enabled() && self.ntfy.topic.is_empty() {
+            anyhow::bail!("ntfy is enabled but no topic is configured");
+        }
+        if self.slack.is_enabled() && self.slack.webhook_url.is_empty() {
+            anyhow::bail!("Slack is enabled but no webhook_url is configured");
+        }
+        if self.discord.is_enabled() && self.discord.webhook_url.is_empty() {
+            anyhow::bail!("Discord is enabled but no webhook_url is configured");
+        }
+        for backend in self.notifications.backends.iter().flatten() {
+            let name = backend.trim().to_lowercase();
+            if !BACKEND_NAMES.contains(&name.as_str()) {
+                anyhow::bail!(
+                    "Unknown backend '{}' in notifications.backends; expected one of: {}",
+                    backend,
+                    BACKEND_NAMES.join(", ")
+                );
+            }
+            if !self.backend_enabled(&name) {
+                anyhow::bail!(
+                    "notifications.backends lists '{}' but it isn't configured",
+                    backend
+                );
+            }
+        }
+        if !BACKEND_NAMES.iter().any(|name| self.backend_enabled(name)) {
+            anyhow::bail!("No notification backend is enabled");
+        }
+        if self.ntfy.command_topic.as_deref() == Some(self.ntfy.topic.as_str()) {
+            anyhow::bail!("ntfy command_topic must differ from the notification topic");
+        }
+        self.heartbeat.time()?;
+        let digest_time = self.digest.time()?;
+        if self.notifications.mode.sends_digest() && digest_time.is_none() {
+            anyhow::bail!("notifications.mode sends a digest but no digest.at time is configured");
+        }
+        let notifications = &self.notifications;
+        if notifications.editing_session_changes > 0 && notifications.editing_session_quiet_secs == 0
+        {
+            anyhow::bail!("notifications.editing_session_quiet_secs must be greater than 0");
+        }
+        if let (Some(min), Some(max)) = (notifications.min_priority, notifications.max_priority) {
+            if min > max {
+                anyhow::bail!(
+                    "notifications.min_priority ({}) is above max_priority ({})",
+                    min.as_str(),
+                    max.as_str()
+                );
+            }
+        }
+        if notifications.detect_toggling && notifications.toggle_threshold == 0 {
+            anyhow::bail!("notifications.toggle_threshold must be greater than 0");
+        }
+        if notifications.maybe_toggles {
+            if notifications.detect_toggling {
+                anyhow::bail!(
+                    "notifications.maybe_toggles and detect_toggling can't both be enabled"
+                );
+            }
+            if notifications.maybe_window_secs == 0 {
+                anyhow::bail!("notifications.maybe_window_secs must be greater than 0");
+            }
+        }
+        if notifications.exec_hook().is_some() && notifications.exec_hook_timeout_secs == 0 {
+            anyhow::bail!("notifications.exec_hook_timeout_secs must be greater than 0");
+        }
+        for list in &self.lists {
+            for backend in list.backends.iter().flatten() {
+                if !BACKEND_NAMES.contains(&backend.trim().to_lowercase().as_str()) {
+                    anyhow::bail!(
+                        "Unknown backend '{}' for list {}; expected one of: {}",
+                        backend,
+                        list.list_name,
+                        BACKEND_NAMES.join(", ")
+                    );
+                }
+            }
+        }
+        let ratio = self.sync.reseed_change_ratio;
+        if ratio.is_nan() || ratio < 0.0 {
+            anyhow::bail!("sync.reseed_change_ratio must be a non-negative number");
+        }
+        if self.sync.poll_interval_secs == 0 {
+            anyhow::bail!("sync.poll_interval_secs must be greater than 0");
+        }
+        if self.sync.reconnect_max_backoff_secs == 0 {
+            anyhow::bail!("sync.reconnect_max_backoff_secs must be greater than 0");
+        }
+        if self.sync.deleted_list_confirmations == 0 {
+            anyhow::bail!("sync.deleted_list_confirmations must be greater than 0");
+        }
+        match &self.ntfy.auth {
+            Some(_) if self.ntfy.access_token().is_some() => {
+                anyhow::bail!("ntfy.access_token and ntfy.auth can't both be set");
+            }
+            Some(NtfyAuth::Token { token }) if token.is_empty() => {
+                anyhow::bail!("ntfy.auth token must not be empty");
+            }
+            Some(NtfyAuth::Basic { username, .. }) if username.is_empty() => {
+                anyhow::bail!("ntfy.auth username must not be empty");
+            }
+            _ => {}
+        }
+        if self.ntfy.audit_topic() == Some(self.ntfy.topic.as_str()) {
+            anyhow::bail!("ntfy audit_topic must differ from the notification topic");
+        }
+        if let Some(review_topic) = self.ntfy.review_topic() {
+            if !self.backend_enabled("ntfy") {
+                anyhow::bail!("ntfy review_topic requires the ntfy backend to be enabled");
+            }
+            let Some(command_topic) = self.ntfy.command_topic() else {
+                anyhow::bail!("ntfy review_topic requires a command_topic to receive approvals");
+            };
+            if review_topic == self.ntfy.topic || review_topic == command_topic {
+                anyhow::bail!("ntfy review_topic must differ from the other ntfy topics");
+            }
+            if self.ntfy.review_expiry_secs == 0 {
+                anyhow::bail!("ntfy.review_expiry_secs must be greater than 0");
+            }
+        }
+        if self.change_log.max_bytes().is_none() {
+            anyhow::bail!(
+                "change_log.max_size_mb is too large: {}",
+                self.change_log.max_size_mb
+            );
+        }
+        if self.ntfy.max_retries > MAX_NTFY_RETRIES {
+            anyhow::bail!("ntfy.max_retries must be at most {}", MAX_NTFY_RETRIES);
+        }
+        // The action token is published in review messages, so it must not be the
+        // service's own credential
+        if let Some(action_token) = self.ntfy.action_token() {
+            if matches!(self.ntfy.auth(), Some(NtfyAuth::Token { token }) if token == action_token)
+            {
+                anyhow::bail!(
+                    "ntfy.action_token must be a separate token limited to writing the command topic"
+                );
+            }
+        }
+        let special_topics = [
+            self.ntfy.command_topic(),
+            self.ntfy.audit_topic(),
+            self.ntfy.review_topic(),
+        ];
+        if self
+            .ntfy
+            .category_topics
+            .values()
+            .any(|topic| special_topics.contains(&Some(topic.as_str())))
+        {
+            anyhow::bail!(
+                "ntfy category_topics must differ from the command, audit and review topics"
+            );
+        }
+        let mut categories = HashSet::new();
+        for category in self.ntfy.category_topics.keys() {
+            if !categories.insert(category.trim().to_lowercase()) {
+                anyhow::bail!(
+                    "ntfy category_topics has '{}' more than once; category names are matched case-insensitively",
+                    category.trim()
+                );
+            }
+        }
+        for list in &self.list_overrides {
+            let key = [&list.list_id, &list.list_name]
+                .into_iter()
+                .flatten()
+                .find(|key| !key.trim().is_empty());
+            let Some(key) = key else {
+                anyhow::bail!("Each list_overrides entry needs a list_name or list_id");
+            };
+            if let Some(topic) = &list.topic {
+                if topic.is_empty() || special_topics.contains(&Some(topic.as_str())) {
+                    anyhow::bail!(
+                        "list_overrides topic for {} must be non-empty and differ from the command, audit and review topics",
+                        key
+                    );
+                }
+            }
+            if let Some(priority) = &list.priority {
+                if Priority::parse(priority).is_none() {
+                    anyhow::bail!(
+                        "Invalid list_overrides priority '{}' for {}; expected min, low, default, high or max",
+                        priority,
+                        key
+                    );
+                }
+            }
+        }
+        if let Some(number) = self.notifications.call_on_urgent() {
+            if !is_call_target(number) {
+                anyhow::bail!(
+                    "notifications.call_on_urgent must be a phone number like +12223334444 or \"yes\""
+                );
+            }
+            if self.notifications.urgent_keywords.is_empty() {
+                anyhow::bail!("notifications.call_on_urgent requires urgent_keywords");
+            }
+            if self.ntfy.auth().is_none() {
+                anyhow::bail!(
+                    "notifications.call_on_urgent requires ntfy.access_token or ntfy.auth"
+                );
+            }
+        }
+        if Language::from_code(&self.notifications.language).is_none() {
+            anyhow::bail!(
+                "Unknown notification language '{}', supported languages: {}",
+                self.notifications.language,
+                Language::supported_codes().join(", ")
+            );
+        }
+        NameDisplay::from_config(&self.notifications.display)?;
+        if let Some(list) = self.list_thresholds.iter().find(|list| list.threshold == 0) {
+            anyhow::bail!(
+                "list_thresholds entry for '{}' needs a threshold above 0",
+                list.list_name
+            );
+        }
+        for list in &self.category_thresholds {
+            let mut thresholds = list.categories.values().chain(&list.default_threshold);
+            if thresholds.any(|threshold| *threshold == 0) {
+                anyhow::bail!(
+                    "category_thresholds entry for '{}' needs thresholds above 0",
+                    list.list_name
+                );
+            }
         }
         Ok(())
     }
 }
 
+/// Config fixtures shared by tests across the crate
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::Config;
+
+    /// The smallest config that passes validation; tests set the fields
+    /// they exercise
+    pub(crate) fn valid_config() -> Config {
+        toml_value(
+            r#"
+            [anylist]
+            email = "user@example.com"
+            password = "secret"
+
+            [cache]
+            database_path = "./anylist.db"
+
+            [ntfy]
+            base_url = "https://ntfy.sh"
+            topic = "anylist-updates"
+
+            [logging]
+            level = "info"
+            "#,
+        )
+    }
+
+    /// Deserialize a TOML snippet through the config crate
+    pub(crate) fn toml_value<T: serde::de::DeserializeOwned>(toml: &str) -> T {
+        config::Config::builder()
+            .add_source(config::File::from_str(toml, config::FileFormat::Toml))
+            .build()
+            .expect("Failed to build config")
+            .try_deserialize()
+            .expect("Failed to deserialize config")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_support::{toml_value, valid_config};
 
     #[test]
     fn test_default_priorities() {
@@ -222,6 +1979,463 @@ mod tests {
         assert_eq!(priorities.item_checked, "low");
     }
 
+    #[test]
+    fn test_ntfy_enabled_defaults_to_topic_presence() {
+        let mut ntfy = NtfyConfig {
+            enabled: None,
+            base_url: "https://ntfy.sh".to_string(),
+            topic: "anylist".to_string(),
+            priorities: NtfyPriorities::default(),
+            tags: NtfyTags::default(),
+            command_topic: None,
+            audit_topic: None,
+            review_topic: None,
+            action_token: None,
+            review_expiry_secs: default_review_expiry_secs(),
+            category_topics: HashMap::new(),
+            access_token: None,
+            auth: None,
+            max_retries: 0,
+            retry_backoff_secs: 0,
+            dry_run: false,
+        };
+        assert!(ntfy.is_enabled());
+
+        ntfy.topic.clear();
+        assert!(!ntfy.is_enabled());
+
+        ntfy.enabled = Some(true);
+        assert!(ntfy.is_enabled());
+    }
+
+    #[test]
+    fn test_slack_enabled_defaults_to_webhook_presence() {
+        let mut slack = SlackConfig::default();
+        assert!(!slack.is_enabled());
+
+        slack.webhook_url = "https://hooks.slack.com/services/T0/B0/x".to_string();
+        assert!(slack.is_enabled());
+
+        slack.enabled = Some(false);
+        assert!(!slack.is_enabled());
+    }
+
+    #[test]
+    fn test_is_call_target() {
+        assert!(is_call_target("+12223334444"));
+        assert!(is_call_target("yes"));
+        assert!(!is_call_target("12223334444"));
+        assert!(!is_call_target("+1 222 333 4444"));
+        assert!(!is_call_target("+"));
+    }
+
+    #[test]
+    fn test_heartbeat_time() {
+        let heartbeat = |at: Option<&str>| HeartbeatConfig {
+            at: at.map(str::to_string),
+        };
+
+        assert_eq!(heartbeat(None).time().unwrap(), None);
+        assert_eq!(heartbeat(Some("")).time().unwrap(), None);
+        assert_eq!(
+            heartbeat(Some("09:00")).time().unwrap(),
+            NaiveTime::from_hms_opt(9, 0, 0)
+        );
+        assert!(heartbeat(Some("9am")).time().is_err());
+        assert!(heartbeat(Some("25:00")).time().is_err());
+    }
+
+    #[test]
+    fn test_notification_mode() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            notifications: NotificationsConfig,
+        }
+
+        let mode = |toml: &str| toml_value::<Wrapper>(toml).notifications.mode;
+        assert_eq!(
+            mode("[notifications]\nascii_only = false"),
+            NotificationMode::Realtime
+        );
+        assert_eq!(
+            mode("[notifications]\nmode = \"digest\""),
+            NotificationMode::Digest
+        );
+
+        assert!(NotificationMode::Realtime.sends_realtime());
+        assert!(!NotificationMode::Realtime.sends_digest());
+        assert!(!NotificationMode::Digest.sends_realtime());
+        assert!(NotificationMode::Digest.sends_digest());
+        assert!(NotificationMode::Both.sends_realtime());
+        assert!(NotificationMode::Both.sends_digest());
+    }
+
+    #[test]
+    fn test_audit_topic_ignores_empty() {
+        let mut ntfy = NtfyConfig {
+            enabled: None,
+            base_url: "https://ntfy.sh".to_string(),
+            topic: "anylist".to_string(),
+            priorities: NtfyPriorities::default(),
+            tags: NtfyTags::default(),
+            command_topic: None,
+            audit_topic: Some(String::new()),
+            review_topic: None,
+            action_token: None,
+            review_expiry_secs: default_review_expiry_secs(),
+            category_topics: HashMap::new(),
+            access_token: None,
+            auth: None,
+            max_retries: 0,
+            retry_backoff_secs: 0,
+            dry_run: false,
+        };
+        assert_eq!(ntfy.audit_topic(), None);
+
+        ntfy.audit_topic = Some("anylist-audit".to_string());
+        assert_eq!(ntfy.audit_topic(), Some("anylist-audit"));
+        assert_eq!(ntfy.priorities.audit, "low");
+    }
+
+    #[test]
+    fn test_list_thresholds_deserialize() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            list_thresholds: Vec<ListThresholdConfig>,
+        }
+
+        let wrapper: Wrapper = toml_value(
+            r#"
+            [[list_thresholds]]
+            list_name = "Groceries"
+            threshold = 10
+
+            [[list_thresholds]]
+            list_name = "Hardware"
+            threshold = 5
+            notify_on = "both"
+            only_crossings = true
+            "#,
+        );
+        let thresholds = wrapper.list_thresholds;
+
+        assert_eq!(thresholds[0].notify_on, ThresholdNotify::Above);
+        assert!(!thresholds[0].only_crossings);
+        assert_eq!(thresholds[1].notify_on, ThresholdNotify::Both);
+        assert!(thresholds[1].only_crossings);
+
+        assert!(ThresholdNotify::Both.includes(ThresholdDirection::Below));
+        assert!(!ThresholdNotify::Above.includes(ThresholdDirection::Below));
+    }
+
+    #[test]
+    fn test_category_thresholds_deserialize() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            category_thresholds: Vec<CategoryThresholdConfig>,
+        }
+
+        let wrapper: Wrapper = toml_value(
+            r#"
+            [[category_thresholds]]
+            list_name = "Groceries"
+            default_threshold = 8
+
+            [category_thresholds.categories]
+            Produce = 5
+            "Frozen Foods" = 3
+
+            [[category_thresholds]]
+            list_name = "Hardware"
+            notify_on = "both"
+
+            [category_thresholds.categories]
+            Paint = 2
+            "#,
+        );
+        let groceries = &wrapper.category_thresholds[0];
+        assert_eq!(groceries.threshold_for("Produce"), Some(5));
+        assert_eq!(groceries.threshold_for("frozen foods"), Some(3));
+        assert_eq!(groceries.threshold_for("Dairy"), Some(8));
+        assert_eq!(groceries.notify_on, ThresholdNotify::Above);
+
+        // Without a default, only listed categories are watched
+        let hardware = &wrapper.category_thresholds[1];
+        assert_eq!(hardware.threshold_for("Paint"), Some(2));
+        assert_eq!(hardware.threshold_for("Tools"), None);
+        assert_eq!(hardware.notify_on, ThresholdNotify::Both);
+    }
+
+    #[test]
+    fn test_list_options_deserialize() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            lists: Vec<ListConfig>,
+        }
+
+        let wrapper: Wrapper = toml_value(
+            r#"
+            [[lists]]
+            list_name = "Groceries"
+            treat_checked_as_done = true
+
+            [[lists]]
+            list_name = "Hardware"
+            "#,
+        );
+
+        assert!(wrapper.lists[0].treat_checked_as_done);
+        assert!(!wrapper.lists[1].treat_checked_as_done);
+        assert!(wrapper.lists[1].backends.is_none());
+    }
+
+    #[test]
+    fn test_routes_to() {
+        let list = |name: &str, backends: Option<&[&str]>| ListConfig {
+            list_name: name.to_string(),
+            treat_checked_as_done: false,
+            backends: backends.map(|b| b.iter().map(|s| s.to_string()).collect()),
+        };
+        let mut config = valid_config();
+        config.lists = vec![
+            list("Groceries", Some(&["ntfy"])),
+            list("Work Tasks", Some(&["unix_socket"])),
+            list("Quiet", Some(&[])),
+        ];
+        assert!(config.validate().is_ok());
+
+        assert!(config.routes_to("groceries", "ntfy"));
+        assert!(!config.routes_to("Groceries", "unix_socket"));
+        assert!(config.routes_to("Work Tasks", "unix_socket"));
+        assert!(!config.routes_to("Quiet", "ntfy"));
+        // Lists without backends go to every backend
+        assert!(config.routes_to("Hardware", "ntfy"));
+        assert!(config.routes_to("Hardware", "unix_socket"));
+
+        config.lists[0].backends = Some(vec!["pager".to_string()]);
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("Unknown backend 'pager'"), "{}", err);
+    }
+
+    #[test]
+    fn test_list_overrides() {
+        let mut config = valid_config();
+        config.ntfy.command_topic = Some("anylist-commands".to_string());
+        config.list_overrides = vec![
+            ListOverrideConfig {
+                list_name: Some("Groceries".to_string()),
+                list_id: None,
+                topic: Some("anylist-groceries".to_string()),
+                priority: None,
+                tags: Some("shopping_cart".to_string()),
+            },
+            ListOverrideConfig {
+                list_name: None,
+                list_id: Some("b7f3c2".to_string()),
+                topic: None,
+                priority: Some("high".to_string()),
+                tags: None,
+            },
+        ];
+        assert!(config.validate().is_ok());
+
+        config.list_overrides[1].priority = Some("loud".to_string());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("Invalid list_overrides priority 'loud'"), "{}", err);
+
+        config.list_overrides[1].priority = None;
+        config.list_overrides[0].topic = Some("anylist-commands".to_string());
+        assert!(config.validate().is_err());
+
+        config.list_overrides[0].topic = None;
+        config.list_overrides[0].list_name = None;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("needs a list_name or list_id"), "{}", err);
+    }
+
+    #[test]
+    fn test_notifications_backends() {
+        let mut config = valid_config();
+        config.slack.webhook_url = "https://hooks.slack.com/services/T0/B0/x".to_string();
+        config.notifications.backends = Some(vec!["Slack".to_string()]);
+        assert!(config.validate().is_ok());
+        assert!(config.backend_enabled("slack"));
+        assert!(!config.backend_enabled("ntfy"));
+
+        config.notifications.backends = None;
+        assert!(config.backend_enabled("ntfy"));
+
+        config.notifications.backends = Some(vec!["discord".to_string()]);
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("isn't configured"), "{}", err);
+
+        config.notifications.backends = Some(vec!["pager".to_string()]);
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("Unknown backend 'pager'"), "{}", err);
+    }
+
+    #[test]
+    fn test_review_topic_requires_command_topic() {
+        let mut config = valid_config();
+        config.ntfy.review_topic = Some("anylist-review".to_string());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("requires a command_topic"), "{}", err);
+
+        config.ntfy.command_topic = Some("anylist-review".to_string());
+        assert!(config.validate().is_err());
+
+        config.ntfy.command_topic = Some("anylist-commands".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_ntfy_auth() {
+        let mut config = valid_config();
+        config.ntfy.auth = Some(toml_value(
+            r#"
+            username = "phil"
+            password = "hunter2"
+            "#,
+        ));
+        assert!(config.validate().is_ok());
+
+        let auth = config.ntfy.auth().unwrap();
+        assert_eq!(auth.header_value(), "Basic cGhpbDpodW50ZXIy");
+        assert!(!format!("{:?}", auth).contains("hunter2"));
+
+        config.ntfy.access_token = Some("tk_secret".to_string());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("can't both be set"), "{}", err);
+
+        // The access token is shorthand for a token in auth
+        config.ntfy.auth = None;
+        let auth = config.ntfy.auth().unwrap();
+        assert_eq!(auth.header_value(), "Bearer tk_secret");
+        assert!(!format!("{:?}", auth).contains("tk_secret"));
+    }
+
+    #[test]
+    fn test_change_log_max_bytes() {
+        let config = ChangeLogConfig::default();
+        assert_eq!(config.max_bytes(), Some(10 * 1024 * 1024));
+
+        let config = ChangeLogConfig {
+            max_size_mb: u64::MAX / 1024,
+            ..ChangeLogConfig::default()
+        };
+        assert_eq!(config.max_bytes(), None);
+    }
+
+    #[test]
+    fn test_ntfy_max_retries_is_bounded() {
+        let mut config = valid_config();
+        config.ntfy.max_retries = 10;
+        assert!(config.validate().is_ok());
+
+        config.ntfy.max_retries = 11;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("at most 10"), "{}", err);
+    }
+
+    #[test]
+    fn test_zero_toggle_threshold_is_rejected() {
+        let mut config = valid_config();
+        config.notifications.detect_toggling = true;
+        config.notifications.toggle_threshold = 0;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("toggle_threshold"), "{}", err);
+
+        // Unused without detect_toggling
+        config.notifications.detect_toggling = false;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_half_set_basic_auth() {
+        let build = |key: &str| {
+            config::Config::builder()
+                .set_override(key, "phil")
+                .unwrap()
+                .build()
+                .unwrap()
+        };
+
+        let err = check_basic_auth(&build("ntfy.auth.username"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("no password"), "{}", err);
+        let err = check_basic_auth(&build("ntfy.auth.password"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("no username"), "{}", err);
+        assert!(check_basic_auth(&build("ntfy.auth.token")).is_ok());
+        assert!(check_basic_auth(&build("ntfy.topic")).is_ok());
+    }
+
+    #[test]
+    fn test_category_topics() {
+        let mut config = valid_config();
+        config.ntfy.command_topic = Some("anylist-commands".to_string());
+        config.ntfy.category_topics = HashMap::from([
+            ("Produce".to_string(), "anylist-produce".to_string()),
+            ("Pharmacy".to_string(), "anylist-pharmacy".to_string()),
+        ]);
+        assert!(config.validate().is_ok());
+
+        assert_eq!(
+            config.ntfy.category_topic("produce"),
+            Some("anylist-produce")
+        );
+        assert_eq!(
+            config.ntfy.category_topic(" Pharmacy "),
+            Some("anylist-pharmacy")
+        );
+        assert_eq!(config.ntfy.category_topic("Dairy"), None);
+
+        config
+            .ntfy
+            .category_topics
+            .insert("Bakery".to_string(), "anylist-commands".to_string());
+        assert!(config.validate().is_err());
+
+        config.ntfy.category_topics.remove("Bakery");
+        config
+            .ntfy
+            .category_topics
+            .insert("produce ".to_string(), "anylist-veg".to_string());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("more than once"), "{}", err);
+    }
+
+    #[test]
+    fn test_category_change_mode_deserialize() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            mode: CategoryChangeMode,
+        }
+
+        let wrapper: Wrapper = toml_value("mode = \"tag_only\"");
+        assert_eq!(wrapper.mode, CategoryChangeMode::TagOnly);
+        assert_eq!(CategoryChangeMode::default(), CategoryChangeMode::Notify);
+    }
+
+    #[test]
+    fn test_priority_order() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            floor: Priority,
+            ceiling: Priority,
+        }
+
+        let wrapper: Wrapper = toml_value("floor = \"low\"\nceiling = \"urgent\"");
+        assert_eq!(wrapper.floor, Priority::Low);
+        assert_eq!(wrapper.ceiling, Priority::Max);
+        assert!(Priority::Min < Priority::Low && Priority::High < Priority::Max);
+        assert_eq!(Priority::High.raise(), Priority::Max);
+        assert_eq!(Priority::Max.raise(), Priority::Max);
+        assert_eq!(Priority::parse("3"), Some(Priority::Default));
+    }
+
     #[test]
     fn test_default_tags() {
         let tags = NtfyTags::default();
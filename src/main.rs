@@ -1,40 +1,97 @@
 mod cache;
+mod cli;
 mod config;
 mod notify;
+mod report;
 mod sync;
 
 use anyhow::{Context, Result};
-use anylist_rs::AnyListClient;
-use cache::SqliteCache;
+use anylist_rs::{AnyListClient, SyncEvent};
+use cache::{CacheDump, SqliteCache};
+use clap::Parser;
+use cli::{Cli, Command};
 use config::Config;
-use notify::NtfyClient;
-use sync::SyncHandler;
+use notify::format::{format_duration, Formatter};
+use notify::change_log::Rotation;
+use notify::{
+    BreakerNotifier, ChangeContext, ChangeLogNotifier, CommandListener, DiscordClient,
+    DryRunNotifier, ExecHookNotifier, Notifier, NtfyClient, SlackClient, UnixSocketNotifier,
+};
+use report::{render_drift, render_user_activity, ActivityReport, ReportFormat};
+use sync::connection::{ConnectionMonitor, PollLock, PollingFallback};
+use sync::diff::ListChange;
+use sync::{InFlight, SyncHandler, UserNames, Watchdog, WatchdogAction};
+use std::collections::HashSet;
+use std::path::Path;
 use std::sync::Arc;
-use tracing::{error, info};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
 use tracing_subscriber::EnvFilter;
 
+/// Longest wait between login attempts at startup
+const MAX_LOGIN_BACKOFF: Duration = Duration::from_secs(300);
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| EnvFilter::new("info"))
-        )
-        .init();
+    let cli = Cli::parse();
+
+    // Load configuration
+    let mut config = Config::load().context("Failed to load configuration")?;
+    config.cache.allow_schema_downgrade = cli.allow_schema_downgrade;
+
+    // Initialize logging: -v flags take precedence over RUST_LOG and the config file
+    let filter = match cli.log_level() {
+        Some(level) => EnvFilter::new(level),
+        None => EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| EnvFilter::new(&config.logging.level)),
+    };
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    if cli.is_one_off() {
+        // Keep stdout free for command output
+        subscriber.with_writer(std::io::stderr).init();
+    } else {
+        subscriber.init();
+    }
+
+    match &cli.command {
+        Some(Command::ActivityReport { format, output }) => {
+            return activity_report(&config, *format, output.as_deref()).await;
+        }
+        Some(Command::UserActivity { period }) => return user_activity(&config, *period).await,
+        Some(Command::Prune) => return prune(&config).await,
+        Some(Command::Stats) => return stats(&config).await,
+        Some(Command::CacheExport { out }) => return cache_export(&config, out.as_deref()).await,
+        Some(Command::CacheImport { input }) => return cache_import(&config, input).await,
+        Some(Command::Mute {
+            list_name,
+            duration,
+        }) => return mute(&config, list_name, *duration).await,
+        Some(Command::Unmute { list_name }) => return unmute(&config, list_name).await,
+        None => {}
+    }
+    if cli.report_drift {
+        return report_drift(&config).await;
+    }
 
     info!("Starting AnyList Notification Service");
 
-    // Load configuration
-    let config = Config::load().context("Failed to load configuration")?;
     config.validate().context("Invalid configuration")?;
 
     info!("Configuration loaded successfully");
-    info!("ntfy topic: {}", config.ntfy.topic);
+    if config.backend_enabled("ntfy") {
+        info!("ntfy topic: {}", config.ntfy.topic);
+        for (setting, tag) in notify::emoji::unknown_tags(&config.ntfy.tags) {
+            warn!(
+                "Tag '{}' in ntfy.tags.{} isn't on the built-in list of emoji shortcodes; \
+                 if ntfy doesn't know it either, it will show it as text",
+                tag, setting
+            );
+        }
+    }
     info!("Database path: {}", config.cache.database_path);
 
     // Initialize SQLite cache
-    let cache = SqliteCache::new(&config.cache.database_path)
+    let cache = SqliteCache::from_config(&config.cache)
         .await
         .context("Failed to initialize cache")?;
     let cache = Arc::new(cache);
@@ -43,7 +100,7 @@ async fn main() -> Result<()> {
 
     // Authenticate with AnyList
     info!("Authenticating with AnyList...");
-    let client = AnyListClient::login(&config.anylist.email, &config.anylist.password)
+    let client = login_with_retry(&config)
         .await
         .context("Failed to authenticate with AnyList")?;
     let client = Arc::new(client);
@@ -53,11 +110,104 @@ async fn main() -> Result<()> {
     // Create config Arc for sharing
     let config = Arc::new(config);
 
-    // Create shared user names map
-    let user_names = Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new()));
+    // Create shared user names map, pre-populated with persisted names
+    let persisted_names = cache
+        .get_user_names()
+        .await
+        .context("Failed to load user names from cache")?;
+    info!("Loaded {} persisted user names", persisted_names.len());
+    let user_names = Arc::new(UserNames::new(cache.clone(), persisted_names));
+
+    // Initialize enabled notification backends; those in dry-run mode only log
+    let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+    if config.backend_enabled("ntfy") {
+        let client = NtfyClient::new(
+            config.ntfy.clone(),
+            config.notifications.clone(),
+            config.list_overrides.clone(),
+        );
+        let notifier = BreakerNotifier::wrap(Arc::new(client), &config.circuit_breaker);
+        notifiers.push(DryRunNotifier::wrap(notifier, config.ntfy.dry_run));
+    }
+    // Slack and Discord word changes like ntfy but style them their own way
+    let formatter = Formatter::new(config.notifications.clone());
+    if config.backend_enabled("slack") {
+        let notifier = BreakerNotifier::wrap(
+            Arc::new(SlackClient::new(
+                config.slack.webhook_url.clone(),
+                formatter.clone(),
+            )),
+            &config.circuit_breaker,
+        );
+        notifiers.push(DryRunNotifier::wrap(notifier, config.slack.dry_run));
+    }
+    if config.backend_enabled("discord") {
+        let notifier = BreakerNotifier::wrap(
+            Arc::new(DiscordClient::new(
+                config.discord.webhook_url.clone(),
+                formatter.clone(),
+            )),
+            &config.circuit_breaker,
+        );
+        notifiers.push(DryRunNotifier::wrap(notifier, config.discord.dry_run));
+    }
+    if let Some(path) = config
+        .unix_socket
+        .path()
+        .filter(|_| config.backend_enabled("unix_socket"))
+    {
+        let notifier = UnixSocketNotifier::bind(Path::new(path))
+            .await
+            .context("Failed to create Unix socket")?;
+        notifiers.push(DryRunNotifier::wrap(
+            Arc::new(notifier),
+            config.unix_socket.dry_run,
+        ));
+    }
+    if let Some(command) = config
+        .notifications
+        .exec_hook()
+        .filter(|_| config.backend_enabled("exec_hook"))
+    {
+        let notifier = ExecHookNotifier::new(
+            command.to_string(),
+            Duration::from_secs(config.notifications.exec_hook_timeout_secs),
+        );
+        notifiers.push(DryRunNotifier::wrap(
+            Arc::new(notifier),
+            config.notifications.exec_hook_dry_run,
+        ));
+    }
+    if let Some(path) = config
+        .change_log
+        .path()
+        .filter(|_| config.backend_enabled("change_log"))
+    {
+        let rotation = Rotation {
+            // Oversized values are rejected by config validation
+            max_bytes: config.change_log.max_bytes().unwrap_or(u64::MAX),
+            daily: config.change_log.daily,
+            keep: config.change_log.keep,
+        };
+        let notifier = ChangeLogNotifier::new(Path::new(path), rotation)
+            .context("Failed to open change log")?;
+        notifiers.push(DryRunNotifier::wrap(
+            Arc::new(notifier),
+            config.change_log.dry_run,
+        ));
+    }
+    info!(
+        "Enabled notification backends: {}",
+        notifiers
+            .iter()
+            .map(|n| n.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
 
-    // Initialize ntfy client with user names map
-    let notifier = Arc::new(NtfyClient::new(config.ntfy.clone(), user_names.clone()));
+    if let Some(name) = &cli.backfill_backend {
+        backfill_backend(&cache, &user_names, &notifiers, name, cli.count).await?;
+    }
 
     // Log filtering settings
     if config.notifications.filter_own_changes {
@@ -70,7 +220,7 @@ async fn main() -> Result<()> {
     let handler = Arc::new(SyncHandler::new(
         client.clone(),
         cache.clone(),
-        notifier.clone(),
+        notifiers,
         config.clone(),
         user_names.clone(),
     ));
@@ -84,28 +234,319 @@ async fn main() -> Result<()> {
 
     info!("Cache initialized with current list state");
 
-    // Set up WebSocket event handler
-    let handler_clone = handler.clone();
-    let event_callback = move |event| {
-        let handler = handler_clone.clone();
-        tokio::spawn(async move {
-            if let Err(e) = handler.handle_event(event).await {
-                error!("Error handling event: {}", e);
+    // Set up WebSocket event handler, tracking in-flight events for shutdown
+    // and for the watchdog, and every event for noticing a dead connection
+    let in_flight = InFlight::new();
+    let watchdog = Arc::new(Watchdog::new(&config.watchdog));
+    let connection = Arc::new(ConnectionMonitor::new(&config.sync, Instant::now()));
+    let event_callback = || {
+        let handler = handler.clone();
+        let in_flight = in_flight.clone();
+        let watchdog = watchdog.clone();
+        let connection = connection.clone();
+        move |event: SyncEvent| {
+            connection.record_event(Instant::now());
+            if !watchdog.admit(&event) {
+                debug!("Overloaded, skipping event: {:?}", event);
+                return;
             }
+            spawn_event(&handler, &in_flight, event);
+        }
+    };
+
+    // Answer commands such as "list Groceries" published to the command topic
+    let command_listener = config
+        .ntfy
+        .command_topic()
+        .filter(|_| config.backend_enabled("ntfy"))
+        .map(|topic| {
+            info!("ntfy command topic: {}", topic);
+            let listener = CommandListener::new(&config.ntfy.base_url, topic, config.ntfy.auth());
+            tokio::spawn(listener.run(handler.clone()))
         });
+
+    // Send held adds, removals, unchecks, throttled changes and editing sessions
+    // once their window passes
+    let holds_changes = config.notifications.coalesce_add_check_secs > 0
+        || config.notifications.undo_remove_secs > 0
+        || config.notifications.suppress_uncheck_recheck_secs > 0
+        || config.notifications.per_item_min_interval_secs > 0
+        || config.notifications.editing_session_changes > 0;
+    let held_flusher = holds_changes.then(|| {
+        let handler = handler.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                handler.flush_held_changes().await;
+            }
+        })
+    });
+
+    // Send each list's batched changes once its batch window closes
+    let batch_flusher = (config.notifications.batch_window_ms > 0).then(|| {
+        let handler = handler.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(sync::batch::FLUSH_INTERVAL);
+            loop {
+                interval.tick().await;
+                handler.flush_batched_changes().await;
+            }
+        })
+    });
+
+    // Send a daily "still alive" notification at the configured time
+    let heartbeat = config.heartbeat.time()?.map(|at| {
+        info!("Heartbeat notifications enabled at {}", at.format("%H:%M"));
+        tokio::spawn(sync::heartbeat::run(handler.clone(), at))
+    });
+
+    // Send each list's digest daily at the configured time
+    let digest = match config.digest.time()? {
+        Some(at) if config.notifications.mode.sends_digest() => {
+            info!("Digest notifications enabled at {}", at.format("%H:%M"));
+            Some(tokio::spawn(sync::digest::run(handler.clone(), at)))
+        }
+        _ => None,
     };
 
-    // Start real-time sync
+    // Start real-time sync, polling instead until the WebSocket connects
     info!("Connecting to AnyList WebSocket...");
-    let mut sync = client
-        .start_realtime_sync(event_callback)
-        .await
-        .context("Failed to start real-time sync")?;
+    let mut sync = match client.start_realtime_sync(event_callback()).await {
+        Ok(sync) => {
+            info!("WebSocket connected - monitoring for changes");
+            Some(sync)
+        }
+        Err(e) => {
+            error!(
+                "Failed to start real-time sync, polling until it connects: {}",
+                e
+            );
+            None
+        }
+    };
+    let mut fallback: Option<PollingFallback> = None;
+    // Only a resubscribe skips events; a dropped connection is polled instead
+    let mut catch_up_on_reconnect = false;
+    let poll_lock = PollLock::new();
 
-    info!("WebSocket connected - monitoring for changes");
     info!("Press Ctrl+C to stop");
 
-    // Wait for Ctrl+C
+    // Wait for Ctrl+C or SIGTERM, checking for event tasks piling up and for
+    // the WebSocket going quiet meanwhile
+    let shutdown = wait_for_shutdown_signal();
+    tokio::pin!(shutdown);
+    let mut watchdog_interval = tokio::time::interval(sync::watchdog::CHECK_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = watchdog_interval.tick() => {}
+        }
+
+        match watchdog.check(in_flight.count(), Instant::now()) {
+            WatchdogAction::None => {}
+            WatchdogAction::Tripped { in_flight: count } => {
+                warn!(
+                    "{} event tasks still in flight after {}s above the high-water mark of {}{}",
+                    count,
+                    config.watchdog.sustain_secs,
+                    config.watchdog.high_water_mark,
+                    if config.watchdog.shed_load {
+                        "; skipping new events until they drain"
+                    } else {
+                        ""
+                    }
+                );
+                if config.watchdog.resubscribe {
+                    warn!("Resubscribing to AnyList WebSocket to reset state");
+                    catch_up_on_reconnect = true;
+                    if let Some(mut old) = sync.take() {
+                        if let Err(e) = old.disconnect().await {
+                            warn!("Failed to disconnect WebSocket: {}", e);
+                        }
+                    }
+                }
+            }
+            WatchdogAction::Recovered { skipped_refreshes } => {
+                info!(
+                    "Event backlog drained ({} refreshes skipped)",
+                    skipped_refreshes
+                );
+                // One pass fetches every list, covering all skipped refreshes
+                if skipped_refreshes > 0 {
+                    spawn_event(&handler, &in_flight, SyncEvent::ShoppingListsChanged);
+                }
+            }
+        }
+
+        // A connection without even heartbeats has died without telling us
+        if sync.is_some() && connection.is_stale(Instant::now()) {
+            warn!(
+                "No WebSocket events for {}s, falling back to polling every {}s",
+                config.sync.connection_timeout_secs, config.sync.poll_interval_secs
+            );
+            if let Some(mut old) = sync.take() {
+                if let Err(e) = old.disconnect().await {
+                    warn!("Failed to disconnect WebSocket: {}", e);
+                }
+            }
+        }
+
+        // While disconnected, poll for changes and reconnect with backoff.
+        // Once the WebSocket is back, a final poll reports changes since the
+        // last one as usual, while a resubscribe catches up on the events it
+        // skipped per the offline change policy
+        if sync.is_none() {
+            let now = Instant::now();
+            let polling = fallback.get_or_insert_with(|| PollingFallback::new(&config.sync, now));
+            if polling.reconnect_due(now) {
+                match client.start_realtime_sync(event_callback()).await {
+                    Ok(new_sync) => {
+                        info!("WebSocket reconnected - monitoring for changes");
+                        connection.record_event(Instant::now());
+                        sync = Some(new_sync);
+                        if std::mem::take(&mut catch_up_on_reconnect) {
+                            spawn_catch_up(&handler, &in_flight);
+                        } else {
+                            spawn_poll(&handler, &in_flight, &poll_lock);
+                        }
+                    }
+                    Err(e) => {
+                        let wait = polling.reconnect_failed(now);
+                        error!(
+                            "Failed to reconnect to WebSocket, retrying in {}s: {}",
+                            wait.as_secs(),
+                            e
+                        );
+                    }
+                }
+            }
+            if sync.is_none() && polling.poll_due(now) {
+                spawn_poll(&handler, &in_flight, &poll_lock);
+            }
+        } else {
+            fallback = None;
+        }
+    }
+
+    // Let changes that are still being processed finish notifying
+    let pending = in_flight.count();
+    if pending > 0 {
+        let timeout = Duration::from_secs(config.notifications.shutdown_timeout_secs);
+        info!(
+            "Waiting up to {}s for {} pending event(s) to finish",
+            timeout.as_secs(),
+            pending
+        );
+        if in_flight.wait_idle(timeout).await {
+            info!("Flushed {} pending event(s)", pending);
+        } else {
+            let remaining = in_flight.count();
+            warn!(
+                "Flushed {} pending event(s); {} still in progress after {}s, their notifications may be lost",
+                pending.saturating_sub(remaining),
+                remaining,
+                timeout.as_secs()
+            );
+        }
+    }
+
+    if let Some(command_listener) = command_listener {
+        command_listener.abort();
+    }
+    if let Some(digest) = digest {
+        digest.abort();
+    }
+    if let Some(heartbeat) = heartbeat {
+        heartbeat.abort();
+    }
+    if held_flusher.is_some() || batch_flusher.is_some() {
+        for flusher in held_flusher.into_iter().chain(batch_flusher) {
+            flusher.abort();
+        }
+        handler.flush_all_held_changes().await;
+    }
+    handler.shutdown_notifiers().await;
+
+    // Gracefully disconnect
+    if let Some(mut sync) = sync {
+        info!("Disconnecting...");
+        sync.disconnect()
+            .await
+            .context("Failed to disconnect gracefully")?;
+    }
+
+    info!("Service stopped");
+    Ok(())
+}
+
+/// Handle a WebSocket event in its own task, tracked as in flight until it finishes
+fn spawn_event(handler: &Arc<SyncHandler>, in_flight: &Arc<InFlight>, event: SyncEvent) {
+    let handler = handler.clone();
+    let guard = in_flight.start();
+    tokio::spawn(async move {
+        if let Err(e) = handler.handle_event(event).await {
+            error!("Error handling event: {}", e);
+        }
+        drop(guard);
+    });
+}
+
+/// Check every list for changes while disconnected, tracked like an event,
+/// unless the previous poll is still running
+fn spawn_poll(handler: &Arc<SyncHandler>, in_flight: &Arc<InFlight>, poll_lock: &Arc<PollLock>) {
+    let Some(poll_guard) = poll_lock.try_start() else {
+        debug!("Previous poll still running, skipping this one");
+        return;
+    };
+    let handler = handler.clone();
+    let guard = in_flight.start();
+    tokio::spawn(async move {
+        if let Err(e) = handler.poll().await {
+            error!("Error polling lists: {}", e);
+        }
+        drop(poll_guard);
+        drop(guard);
+    });
+}
+
+/// Catch up on changes missed while disconnected, tracked like an event
+fn spawn_catch_up(handler: &Arc<SyncHandler>, in_flight: &Arc<InFlight>) {
+    let handler = handler.clone();
+    let guard = in_flight.start();
+    tokio::spawn(async move {
+        if let Err(e) = handler.catch_up().await {
+            error!("Error catching up on missed changes: {}", e);
+        }
+        drop(guard);
+    });
+}
+
+/// Wait for Ctrl+C, or SIGTERM on Unix (sent by `docker stop` and systemd)
+async fn wait_for_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    result = tokio::signal::ctrl_c() => {
+                        if let Err(err) = result {
+                            error!("Unable to listen for shutdown signal: {}", err);
+                        }
+                    }
+                    _ = sigterm.recv() => {}
+                }
+                info!("Received shutdown signal");
+                return;
+            }
+            Err(err) => {
+                warn!("Unable to listen for SIGTERM: {}", err);
+            }
+        }
+    }
+
     match tokio::signal::ctrl_c().await {
         Ok(()) => {
             info!("Received shutdown signal");
@@ -114,13 +555,272 @@ async fn main() -> Result<()> {
             error!("Unable to listen for shutdown signal: {}", err);
         }
     }
+}
 
-    // Gracefully disconnect
-    info!("Disconnecting...");
-    sync.disconnect()
+/// Write the activity report from the change history
+async fn activity_report(config: &Config, format: ReportFormat, output: Option<&Path>) -> Result<()> {
+    let cache = SqliteCache::open_read_only(&config.cache)
         .await
-        .context("Failed to disconnect gracefully")?;
+        .context("Failed to open cache")?;
 
-    info!("Service stopped");
+    let buckets = cache.get_activity_buckets().await?;
+    let report = ActivityReport::from_buckets(&buckets).render(format)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, report)
+                .with_context(|| format!("Failed to write report to {}", path.display()))?;
+            info!("Activity report written to {}", path.display());
+        }
+        None => print!("{}", report),
+    }
+
+    Ok(())
+}
+
+/// Print each user's recorded changes over the last `period`
+async fn user_activity(config: &Config, period: Duration) -> Result<()> {
+    let cache = SqliteCache::open_read_only(&config.cache)
+        .await
+        .context("Failed to open cache")?;
+
+    let since = SqliteCache::timestamp_ago(period);
+    let entries = cache.history_since(since).await?;
+    let user_names = cache.get_user_names().await?;
+
+    let activity = report::user_activity(&entries, &user_names);
+    print!("{}", render_user_activity(&activity, period.as_secs()));
+    Ok(())
+}
+
+/// Send the `count` most recent recorded changes, oldest first, to one backend
+async fn backfill_backend(
+    cache: &SqliteCache,
+    user_names: &UserNames,
+    notifiers: &[Arc<dyn Notifier>],
+    name: &str,
+    count: u32,
+) -> Result<()> {
+    let Some(notifier) = notifiers.iter().find(|n| n.name() == name) else {
+        anyhow::bail!(
+            "Cannot backfill backend '{}': it is not enabled (enabled: {})",
+            name,
+            notifiers
+                .iter()
+                .map(|n| n.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    };
+
+    let entries = cache.recent_history(count).await?;
+    info!("Backfilling {} recorded change(s) to {}", entries.len(), name);
+    let mut sent = 0;
+    for entry in entries {
+        let change: ListChange = match serde_json::from_str(&entry.payload) {
+            Ok(change) => change,
+            Err(e) => {
+                warn!("Skipping history entry {} that can't be replayed: {}", entry.id, e);
+                continue;
+            }
+        };
+        let context = ChangeContext::resolve(&change, user_names).await;
+        match notifier.notify(&change, &context).await {
+            Ok(()) => sent += 1,
+            Err(e) => warn!("Failed to backfill change to {}: {}", name, e),
+        }
+    }
+    info!("Backfilled {} change(s) to {}", sent, name);
+    Ok(())
+}
+
+/// Print how the cache differs from the live lists without changing anything
+async fn report_drift(config: &Config) -> Result<()> {
+    let cache = SqliteCache::open_read_only(&config.cache)
+        .await
+        .context("Failed to open cache")?;
+
+    let client = login_with_retry(config)
+        .await
+        .context("Failed to authenticate with AnyList")?;
+    let mut lists = client.get_lists().await.context("Failed to fetch lists")?;
+    for list in &mut lists {
+        sync::diff::fix_empty_item_ids(&mut list.items, config.sync.empty_item_ids);
+    }
+
+    let drift = sync::drift::detect_drift(&cache, &lists).await?;
+    print!("{}", render_drift(&drift));
+
+    Ok(())
+}
+
+/// Remove cached lists that no longer exist in the account, plus orphaned items
+async fn prune(config: &Config) -> Result<()> {
+    let cache = SqliteCache::from_config(&config.cache)
+        .await
+        .context("Failed to open cache")?;
+
+    let client = login_with_retry(config)
+        .await
+        .context("Failed to authenticate with AnyList")?;
+    let lists = client.get_lists().await.context("Failed to fetch lists")?;
+    let live_ids: HashSet<&str> = lists.iter().map(|l| l.id.as_str()).collect();
+
+    let mut removed_lists = 0;
+    for cached_list in cache.get_all_lists().await? {
+        if live_ids.contains(cached_list.id.as_str()) {
+            continue;
+        }
+
+        let item_count = cache.get_items(&cached_list.id).await?.len();
+        cache.delete_list(&cached_list.id).await?;
+        println!(
+            "Removed list {} ({}) with {} items",
+            cached_list.name, cached_list.id, item_count
+        );
+        removed_lists += 1;
+    }
+
+    let orphaned_items = cache.delete_orphans().await?;
+    if orphaned_items > 0 {
+        println!("Removed {} orphaned items", orphaned_items);
+    }
+
+    println!(
+        "Pruned {} lists and {} orphaned items",
+        removed_lists, orphaned_items
+    );
     Ok(())
 }
+
+/// Print cache statistics, including how long ago the last successful sync was
+async fn stats(config: &Config) -> Result<()> {
+    let cache = SqliteCache::open_read_only(&config.cache)
+        .await
+        .context("Failed to open cache")?;
+    let stats = cache.get_stats().await?;
+
+    println!("Lists: {}", stats.total_lists);
+    println!("Items: {}", stats.total_items);
+    match stats.last_successful_sync {
+        Some(last_sync) => {
+            let ago = (SqliteCache::current_timestamp() - last_sync).max(0) as u64;
+            let at = chrono::DateTime::from_timestamp(last_sync, 0).unwrap_or_default();
+            println!(
+                "Last successful sync: {} ({} ago)",
+                at.to_rfc3339(),
+                format_duration(ago)
+            );
+        }
+        None => println!("Last successful sync: never"),
+    }
+
+    Ok(())
+}
+
+/// Write the exported cache tables to a JSON file or stdout
+async fn cache_export(config: &Config, out: Option<&Path>) -> Result<()> {
+    let cache = SqliteCache::open_read_only(&config.cache)
+        .await
+        .context("Failed to open cache")?;
+
+    let dump = cache.export().await?;
+    let json = serde_json::to_string_pretty(&dump).context("Failed to serialize cache")?;
+
+    match out {
+        Some(path) => {
+            std::fs::write(path, json)
+                .with_context(|| format!("Failed to write export to {}", path.display()))?;
+            info!(
+                "Exported {} lists and {} items to {}",
+                dump.lists.len(),
+                dump.items.len(),
+                path.display()
+            );
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+/// Load a JSON export into the cache, creating the database if needed
+async fn cache_import(config: &Config, input: &Path) -> Result<()> {
+    let json = std::fs::read_to_string(input)
+        .with_context(|| format!("Failed to read export from {}", input.display()))?;
+    let dump: CacheDump = serde_json::from_str(&json)
+        .with_context(|| format!("Failed to parse export {}", input.display()))?;
+
+    let cache = SqliteCache::from_config(&config.cache)
+        .await
+        .context("Failed to open cache")?;
+    let stats = cache.import(&dump).await?;
+
+    println!(
+        "Imported {} lists, {} items, {} users and {} new history entries",
+        stats.lists, stats.items, stats.users, stats.history
+    );
+    Ok(())
+}
+
+/// Mute a list in the cache; a running service picks it up on its next sync
+async fn mute(config: &Config, list_name: &str, duration: Duration) -> Result<()> {
+    let cache = SqliteCache::from_config(&config.cache)
+        .await
+        .context("Failed to open cache")?;
+
+    let muted_until = SqliteCache::timestamp_in(duration);
+    cache.set_mute(list_name, muted_until).await?;
+
+    match chrono::DateTime::from_timestamp(muted_until, 0) {
+        Some(until) => println!("Muted {} until {}", list_name, until.to_rfc3339()),
+        None => println!("Muted {} indefinitely", list_name),
+    }
+    Ok(())
+}
+
+/// Remove a list's mute from the cache
+async fn unmute(config: &Config, list_name: &str) -> Result<()> {
+    let cache = SqliteCache::from_config(&config.cache)
+        .await
+        .context("Failed to open cache")?;
+
+    if cache.clear_mute(list_name).await? {
+        println!("Unmuted {}", list_name);
+    } else {
+        println!("{} was not muted", list_name);
+    }
+    Ok(())
+}
+
+/// Log in to AnyList, retrying with exponential backoff on failure
+/// Transient network issues at container start-up resolve themselves this way
+async fn login_with_retry(config: &Config) -> Result<AnyListClient> {
+    let max_retries = config.auth.max_startup_retries;
+    let mut backoff = Duration::from_secs(config.auth.startup_backoff_secs);
+    let mut attempt = 0;
+
+    loop {
+        match AnyListClient::login(&config.anylist.email, &config.anylist.password).await {
+            Ok(client) => return Ok(client),
+            Err(e) if attempt < max_retries => {
+                attempt += 1;
+                warn!(
+                    "Login attempt {} of {} failed: {}. Retrying in {}s",
+                    attempt,
+                    max_retries + 1,
+                    e,
+                    backoff.as_secs()
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = backoff.saturating_mul(2).min(MAX_LOGIN_BACKOFF);
+            }
+            Err(e) => {
+                return Err(e).context(format!(
+                    "Giving up after {} login attempts",
+                    max_retries + 1
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+/// Replacements for the emoji and symbols used in notification text
+/// Sequences, including those with a variation selector, come first so they are replaced whole
+const REPLACEMENTS: &[(&str, &str)] = &[
+    ("🛒✅", "[+x]"),
+    ("◀️", "[ ]"),
+    ("✏️", "[~]"),
+    ("🗂️", "[#]"),
+    ("⚠️", "[!]"),
+    ("➕", "[+]"),
+    ("❌", "[-]"),
+    ("✅", "[x]"),
+    ("👥", "[@]"),
+    ("📉", "[0]"),
+    ("🛒", "[=]"),
+    ("❓", "[?]"),
+    ("🔄", "[*]"),
+    ("🛍️", "[>]"),
+    ("🧺", "[<]"),
+    ("📴", "[/]"),
+    ("👤", "[u]"),
+    ("💚", "[ok]"),
+    ("♻️", "[r]"),
+    ("📰", "[d]"),
+    ("✍️", "[e]"),
+    ("👀", "[w]"),
+    ("📦", "[q]"),
+    ("📞", "[tel]"),
+    ("🤔", "[m]"),
+    ("🔓", "[s]"),
+    ("🔒", "[p]"),
+    ("✓", "x"),
+    ("↩", "<"),
+    ("→", "->"),
+    ("…", "..."),
+];
+
+/// Replace the emoji and symbols used in notification text with ASCII
+/// Text from AnyList itself, such as item names, is left untouched
+pub fn to_ascii(text: &str) -> String {
+    REPLACEMENTS
+        .iter()
+        .fold(text.to_string(), |text, (from, to)| text.replace(from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_ascii() {
+        assert_eq!(
+            to_ascii("➕ Milk added to Groceries"),
+            "[+] Milk added to Groceries"
+        );
+        assert_eq!(
+            to_ascii("◀️ Milk unchecked in Groceries"),
+            "[ ] Milk unchecked in Groceries"
+        );
+        assert_eq!(to_ascii("Quantity: 1 → 2"), "Quantity: 1 -> 2");
+
+        for (from, _) in REPLACEMENTS {
+            assert!(to_ascii(from).is_ascii(), "{} was not replaced", from);
+        }
+    }
+}
@@ -0,0 +1,452 @@
+use super::{ChangeContext, Notifier, ServiceEvent};
+use crate::config::{BreakerOpenMode, CircuitBreakerConfig};
+use crate::sync::diff::ListChange;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// How often held notifications are retried in `queue` mode, so they go out
+/// once the cooldown ends even if nothing else is sent
+const QUEUE_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Circuit breaker state for a single backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BreakerState {
+    /// Notifications are sent; counts consecutive failures
+    Closed { failures: u32 },
+    /// Notifications are held back until the cooldown ends
+    Open { until: Instant },
+    /// One trial notification is allowed through to test recovery
+    HalfOpen { probing: bool },
+}
+
+/// Tracks consecutive failures and decides whether a backend may be called
+#[derive(Debug)]
+struct CircuitBreaker {
+    name: String,
+    threshold: u32,
+    cooldown: Duration,
+    state: BreakerState,
+}
+
+impl CircuitBreaker {
+    fn new(name: &str, threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            threshold,
+            cooldown,
+            state: BreakerState::Closed { failures: 0 },
+        }
+    }
+
+    /// Whether a notification may be sent now
+    fn allow(&mut self, now: Instant) -> bool {
+        match self.state {
+            BreakerState::Closed { .. } => true,
+            BreakerState::Open { until } if now >= until => {
+                info!("{} circuit half-open, testing recovery", self.name);
+                self.state = BreakerState::HalfOpen { probing: true };
+                true
+            }
+            BreakerState::Open { .. } => false,
+            BreakerState::HalfOpen { probing } => {
+                if probing {
+                    false
+                } else {
+                    self.state = BreakerState::HalfOpen { probing: true };
+                    true
+                }
+            }
+        }
+    }
+
+    fn record_success(&mut self) {
+        if matches!(self.state, BreakerState::HalfOpen { .. }) {
+            info!("{} circuit closed, backend recovered", self.name);
+        }
+        self.state = BreakerState::Closed { failures: 0 };
+    }
+
+    fn record_failure(&mut self, now: Instant) {
+        let failures = match self.state {
+            BreakerState::Closed { failures } => failures + 1,
+            // A failed probe reopens the circuit straight away
+            BreakerState::HalfOpen { .. } => self.threshold,
+            BreakerState::Open { .. } => return,
+        };
+
+        if failures >= self.threshold {
+            warn!(
+                "{} circuit open after {} consecutive failure(s), pausing for {}s",
+                self.name,
+                failures,
+                self.cooldown.as_secs()
+            );
+            self.state = BreakerState::Open {
+                until: now + self.cooldown,
+            };
+        } else {
+            self.state = BreakerState::Closed { failures };
+        }
+    }
+}
+
+/// A notification held back while the circuit is open
+#[derive(Debug, Clone)]
+enum Pending {
+    Change(Box<ListChange>, ChangeContext),
+    Service(ServiceEvent),
+}
+
+/// Wraps a backend with a circuit breaker so a persistently failing backend
+/// is skipped for a cooldown instead of being called for every change
+pub struct BreakerNotifier {
+    inner: Arc<dyn Notifier>,
+    breaker: Mutex<CircuitBreaker>,
+    mode: BreakerOpenMode,
+    max_queued: usize,
+    queue: tokio::sync::Mutex<VecDeque<Pending>>,
+}
+
+impl BreakerNotifier {
+    /// Wrap a backend, or return it unchanged if the breaker is disabled
+    pub fn wrap(inner: Arc<dyn Notifier>, config: &CircuitBreakerConfig) -> Arc<dyn Notifier> {
+        if config.failure_threshold == 0 {
+            return inner;
+        }
+        let notifier = Arc::new(Self::new(inner, config));
+        if notifier.mode == BreakerOpenMode::Queue {
+            let weak = Arc::downgrade(&notifier);
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(QUEUE_RETRY_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let Some(notifier) = weak.upgrade() else {
+                        break;
+                    };
+                    notifier.retry_queued().await;
+                }
+            });
+        }
+        notifier
+    }
+
+    fn new(inner: Arc<dyn Notifier>, config: &CircuitBreakerConfig) -> Self {
+        let breaker = CircuitBreaker::new(
+            inner.name(),
+            config.failure_threshold,
+            Duration::from_secs(config.cooldown_secs),
+        );
+        Self {
+            inner,
+            breaker: Mutex::new(breaker),
+            mode: config.open_mode,
+            max_queued: config.max_queued,
+            queue: tokio::sync::Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Send a notification through the breaker, holding it back while open
+    async fn send(&self, pending: Pending) -> Result<()> {
+        let allowed = self.breaker.lock().unwrap().allow(Instant::now());
+        if !allowed {
+            return self.hold(pending).await;
+        }
+
+        let result = self.deliver(&pending).await;
+        {
+            let mut breaker = self.breaker.lock().unwrap();
+            match &result {
+                Ok(()) => breaker.record_success(),
+                Err(_) => breaker.record_failure(Instant::now()),
+            }
+        }
+
+        if result.is_ok() {
+            self.flush().await;
+        }
+        result
+    }
+
+    async fn deliver(&self, pending: &Pending) -> Result<()> {
+        match pending {
+            Pending::Change(change, context) => self.inner.notify(change, context).await,
+            Pending::Service(event) => self.inner.notify_service(event).await,
+        }
+    }
+
+    /// Queue or drop a notification while the circuit is open
+    async fn hold(&self, pending: Pending) -> Result<()> {
+        match self.mode {
+            BreakerOpenMode::Drop => {
+                anyhow::bail!("{} circuit is open, notification dropped", self.name())
+            }
+            BreakerOpenMode::Queue => {
+                let mut queue = self.queue.lock().await;
+                if queue.len() >= self.max_queued {
+                    queue.pop_front();
+                    warn!(
+                        "{} queue is full, dropping the oldest held notification",
+                        self.name()
+                    );
+                }
+                queue.push_back(pending);
+                anyhow::bail!(
+                    "{} circuit is open, notification queued ({} held)",
+                    self.name(),
+                    queue.len()
+                )
+            }
+        }
+    }
+
+    /// Send the oldest held notification as the trial once the cooldown has
+    /// passed, and the rest if it gets through
+    async fn retry_queued(&self) {
+        let mut queue = self.queue.lock().await;
+        let Some(pending) = queue.front().cloned() else {
+            return;
+        };
+        let allowed = self.breaker.lock().unwrap().allow(Instant::now());
+        if !allowed {
+            return;
+        }
+
+        if let Err(e) = self.deliver(&pending).await {
+            warn!("Failed to send held {} notification: {}", self.name(), e);
+            self.breaker.lock().unwrap().record_failure(Instant::now());
+            return;
+        }
+        self.breaker.lock().unwrap().record_success();
+        queue.pop_front();
+        drop(queue);
+        self.flush().await;
+    }
+
+    /// Send queued notifications after the backend has recovered
+    async fn flush(&self) {
+        let mut queue = self.queue.lock().await;
+        if queue.is_empty() {
+            return;
+        }
+
+        info!(
+            "Sending {} held {} notification(s)",
+            queue.len(),
+            self.name()
+        );
+        while let Some(pending) = queue.pop_front() {
+            if let Err(e) = self.deliver(&pending).await {
+                warn!("Failed to send held {} notification: {}", self.name(), e);
+                self.breaker.lock().unwrap().record_failure(Instant::now());
+                queue.push_front(pending);
+                break;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for BreakerNotifier {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn is_push(&self) -> bool {
+        self.inner.is_push()
+    }
+
+    async fn notify(&self, change: &ListChange, context: &ChangeContext) -> Result<()> {
+        self.send(Pending::Change(Box::new(change.clone()), context.clone()))
+            .await
+    }
+
+    async fn notify_service(&self, event: &ServiceEvent) -> Result<()> {
+        self.send(Pending::Service(event.clone())).await
+    }
+
+    // The audit trail is best-effort and doesn't count towards the breaker
+    async fn audit(&self, change: &ListChange, context: &ChangeContext) -> Result<()> {
+        self.inner.audit(change, context).await
+    }
+
+    // Review requests go to the moderator's topic, not the one the breaker guards
+    async fn request_review(
+        &self,
+        review_id: i64,
+        change: &ListChange,
+        context: &ChangeContext,
+    ) -> Result<()> {
+        self.inner.request_review(review_id, change, context).await
+    }
+
+    async fn shutdown(&self) {
+        self.inner.shutdown().await
+    }
+
+    fn render(&self, change: &ListChange, context: &ChangeContext) -> Result<String> {
+        self.inner.render(change, context)
+    }
+
+    fn render_service(&self, event: &ServiceEvent) -> Result<Option<String>> {
+        self.inner.render_service(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// A backend whose failures can be switched on and off
+    #[derive(Default)]
+    struct FlakyNotifier {
+        failing: AtomicBool,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Notifier for FlakyNotifier {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn notify(&self, _change: &ListChange, _context: &ChangeContext) -> Result<()> {
+            self.notify_service(&ServiceEvent::CacheUnavailable {
+                error: String::new(),
+            })
+            .await
+        }
+
+        async fn notify_service(&self, _event: &ServiceEvent) -> Result<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.failing.load(Ordering::SeqCst) {
+                anyhow::bail!("backend down");
+            }
+            Ok(())
+        }
+    }
+
+    fn event() -> ServiceEvent {
+        ServiceEvent::CacheUnavailable {
+            error: "disk full".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_breaker_transitions() {
+        let mut breaker = CircuitBreaker::new("test", 2, Duration::from_secs(60));
+        let now = Instant::now();
+
+        breaker.record_failure(now);
+        assert!(breaker.allow(now));
+        breaker.record_failure(now);
+        assert!(!breaker.allow(now));
+
+        // After the cooldown a single probe is let through
+        let later = now + Duration::from_secs(60);
+        assert!(breaker.allow(later));
+        assert!(!breaker.allow(later));
+
+        // A failed probe reopens the circuit
+        breaker.record_failure(later);
+        assert!(!breaker.allow(later + Duration::from_secs(1)));
+
+        let recovered = later + Duration::from_secs(60);
+        assert!(breaker.allow(recovered));
+        breaker.record_success();
+        assert_eq!(breaker.state, BreakerState::Closed { failures: 0 });
+    }
+
+    #[tokio::test]
+    async fn test_queued_notifications_are_sent_after_recovery() {
+        let backend = Arc::new(FlakyNotifier::default());
+        backend.failing.store(true, Ordering::SeqCst);
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            cooldown_secs: 60,
+            open_mode: BreakerOpenMode::Queue,
+            max_queued: 10,
+        };
+        let notifier = BreakerNotifier::new(backend.clone(), &config);
+
+        // The first failure trips the breaker, later notifications are held
+        assert!(notifier.notify_service(&event()).await.is_err());
+        assert!(notifier.notify_service(&event()).await.is_err());
+        assert!(notifier.notify_service(&event()).await.is_err());
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(notifier.queue.lock().await.len(), 2);
+
+        // End the cooldown; the probe succeeds and the held notifications follow
+        notifier.breaker.lock().unwrap().state = BreakerState::Open {
+            until: Instant::now(),
+        };
+        backend.failing.store(false, Ordering::SeqCst);
+        assert!(notifier.notify_service(&event()).await.is_ok());
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 4);
+        assert!(notifier.queue.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_queue_is_retried_without_new_notifications() {
+        let backend = Arc::new(FlakyNotifier::default());
+        backend.failing.store(true, Ordering::SeqCst);
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            cooldown_secs: 60,
+            open_mode: BreakerOpenMode::Queue,
+            max_queued: 10,
+        };
+        let notifier = BreakerNotifier::new(backend.clone(), &config);
+
+        assert!(notifier.notify_service(&event()).await.is_err());
+        assert!(notifier.notify_service(&event()).await.is_err());
+        assert!(notifier.notify_service(&event()).await.is_err());
+
+        // Nothing is retried during the cooldown
+        notifier.retry_queued().await;
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 1);
+
+        // A failed trial keeps the notifications held and reopens the circuit
+        notifier.breaker.lock().unwrap().state = BreakerState::Open {
+            until: Instant::now(),
+        };
+        notifier.retry_queued().await;
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(notifier.queue.lock().await.len(), 2);
+        assert!(matches!(
+            notifier.breaker.lock().unwrap().state,
+            BreakerState::Open { .. }
+        ));
+
+        // Once the backend is back, the trial and the rest go out
+        notifier.breaker.lock().unwrap().state = BreakerState::Open {
+            until: Instant::now(),
+        };
+        backend.failing.store(false, Ordering::SeqCst);
+        notifier.retry_queued().await;
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 4);
+        assert!(notifier.queue.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_skips_backend() {
+        let backend = Arc::new(FlakyNotifier::default());
+        backend.failing.store(true, Ordering::SeqCst);
+        let config = CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown_secs: 60,
+            open_mode: BreakerOpenMode::Queue,
+            max_queued: 1,
+        };
+        let notifier = BreakerNotifier::wrap(backend.clone(), &config);
+
+        for _ in 0..5 {
+            assert!(notifier.notify_service(&event()).await.is_err());
+        }
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 2);
+    }
+}
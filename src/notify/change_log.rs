@@ -0,0 +1,238 @@
+use crate::notify::{ChangeContext, Notifier, ServiceEvent};
+use crate::sync::diff::ListChange;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::info;
+
+/// When the change log is moved aside and a new file started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotation {
+    /// Rotate before a write would grow the file past this many bytes (0 disables)
+    pub max_bytes: u64,
+    /// Rotate before the first write of a new local day
+    pub daily: bool,
+    /// Rotated files kept as `<path>.1` (newest) to `<path>.<keep>`
+    pub keep: usize,
+}
+
+/// Appends every change as a JSON line to a local file, rotating it by size or date
+///
+/// Unlike the tracing output, the file holds nothing but changes, each with
+/// the time it was written, so it can be archived and analyzed on its own.
+pub struct ChangeLogNotifier {
+    log: Arc<ChangeLog>,
+}
+
+/// The log file, written to from blocking tasks one line at a time
+struct ChangeLog {
+    path: PathBuf,
+    rotation: Rotation,
+    lock: Mutex<()>,
+}
+
+impl ChangeLogNotifier {
+    pub fn new(path: &Path, rotation: Rotation) -> Result<Self> {
+        // Fail at startup rather than on the first change if the file can't be written
+        open_append(path)?;
+        info!("Writing changes to {}", path.display());
+        Ok(Self {
+            log: Arc::new(ChangeLog {
+                path: path.to_path_buf(),
+                rotation,
+                lock: Mutex::new(()),
+            }),
+        })
+    }
+}
+
+impl ChangeLog {
+    /// Append a line, rotating the file first if it is due
+    fn append(&self, line: &str, now: DateTime<Local>) -> Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if needs_rotation(&self.path, &self.rotation, line.len() as u64, now) {
+            rotate(&self.path, self.rotation.keep)?;
+        }
+        open_append(&self.path)?
+            .write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write to {}", self.path.display()))
+    }
+}
+
+#[async_trait]
+impl Notifier for ChangeLogNotifier {
+    fn name(&self) -> &str {
+        "change_log"
+    }
+
+    fn is_push(&self) -> bool {
+        false
+    }
+
+    async fn notify(&self, change: &ListChange, _context: &ChangeContext) -> Result<()> {
+        let now = Local::now();
+        let mut line = format_line(change, now)?;
+        line.push('\n');
+        // File I/O blocks, so it runs off the async workers
+        let log = self.log.clone();
+        tokio::task::spawn_blocking(move || log.append(&line, now))
+            .await
+            .context("Change log write task failed")?
+    }
+
+    /// Only list changes are logged
+    async fn notify_service(&self, _event: &ServiceEvent) -> Result<()> {
+        Ok(())
+    }
+
+    fn render(&self, change: &ListChange, _context: &ChangeContext) -> Result<String> {
+        format_line(change, Local::now())
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open change log {}", path.display()))
+}
+
+/// Serialize a change with the time it was logged
+fn format_line(change: &ListChange, now: DateTime<Local>) -> Result<String> {
+    let mut value = serde_json::to_value(change).context("Failed to serialize change")?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert("logged_at".to_string(), now.to_rfc3339().into());
+    }
+    Ok(value.to_string())
+}
+
+/// Whether the log has to be rotated before writing `incoming` more bytes
+fn needs_rotation(path: &Path, rotation: &Rotation, incoming: u64, now: DateTime<Local>) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+    if metadata.len() == 0 {
+        return false;
+    }
+    if rotation.max_bytes > 0 && metadata.len() + incoming > rotation.max_bytes {
+        return true;
+    }
+    rotation.daily
+        && metadata
+            .modified()
+            .is_ok_and(|modified| DateTime::<Local>::from(modified).date_naive() < now.date_naive())
+}
+
+/// Shift `<path>.N` to `<path>.N+1`, dropping the oldest, and move the log to `<path>.1`
+fn rotate(path: &Path, keep: usize) -> Result<()> {
+    if keep == 0 {
+        return fs::remove_file(path)
+            .with_context(|| format!("Failed to remove change log {}", path.display()));
+    }
+    let _ = fs::remove_file(rotated_path(path, keep));
+    for n in (1..keep).rev() {
+        let from = rotated_path(path, n);
+        if from.exists() {
+            fs::rename(&from, rotated_path(path, n + 1))
+                .with_context(|| format!("Failed to rotate {}", from.display()))?;
+        }
+    }
+    fs::rename(path, rotated_path(path, 1))
+        .with_context(|| format!("Failed to rotate change log {}", path.display()))?;
+    info!("Rotated change log {}", path.display());
+    Ok(())
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::ItemInfo;
+
+    fn added(name: &str) -> ListChange {
+        ListChange::ItemAdded {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: ItemInfo {
+                id: format!("id-{}", name),
+                name: name.to_string(),
+                details: "".to_string(),
+                quantity: None,
+                category: None,
+                user_id: None,
+            },
+            user_id: None,
+        }
+    }
+
+    fn temp_log(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "anylist_notify_change_log_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("changes.jsonl")
+    }
+
+    #[tokio::test]
+    async fn test_changes_are_appended_as_json_lines() {
+        let path = temp_log("append");
+        let rotation = Rotation {
+            max_bytes: 0,
+            daily: false,
+            keep: 3,
+        };
+        let notifier = ChangeLogNotifier::new(&path, rotation).unwrap();
+        let context = ChangeContext::default();
+        // Still written to in digest mode
+        assert!(!notifier.is_push());
+
+        notifier.notify(&added("Milk"), &context).await.unwrap();
+        notifier.notify(&added("Eggs"), &context).await.unwrap();
+
+        let written = fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = written
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["item"]["name"], "Eggs");
+        assert!(lines[1]["logged_at"].is_string());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rotates_by_size_and_keeps_newest_files() {
+        let path = temp_log("size");
+        let rotation = Rotation {
+            max_bytes: 1,
+            daily: false,
+            keep: 2,
+        };
+        let notifier = ChangeLogNotifier::new(&path, rotation).unwrap();
+        let context = ChangeContext::default();
+
+        for name in ["Milk", "Eggs", "Bread", "Butter"] {
+            notifier.notify(&added(name), &context).await.unwrap();
+        }
+
+        let contains = |path: PathBuf, name: &str| fs::read_to_string(path).unwrap().contains(name);
+        assert!(contains(path.clone(), "Butter"));
+        assert!(contains(rotated_path(&path, 1), "Bread"));
+        assert!(contains(rotated_path(&path, 2), "Eggs"));
+        assert!(!rotated_path(&path, 3).exists());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,311 @@
+use super::ntfy::auth_failure;
+use crate::config::NtfyAuth;
+use crate::sync::SyncHandler;
+use anyhow::{Context, Result};
+use reqwest::header::AUTHORIZATION;
+use reqwest::Client;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// How long to wait before resubscribing after the command stream drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// A command published to the ntfy command topic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteCommand {
+    /// Send the unchecked items of a list, e.g. "list Groceries"
+    ShowList { list_name: String },
+    /// Silence a list's notifications for a while, e.g. "mute Groceries 1h"
+    Mute {
+        list_name: String,
+        duration: Duration,
+    },
+    /// Lift a list's mute early, e.g. "unmute Groceries"
+    Unmute { list_name: String },
+    /// Send a change held for review, e.g. "approve 12"
+    Approve { review_id: i64 },
+    /// Drop a change held for review, e.g. "reject 12"
+    Reject { review_id: i64 },
+    /// Check every list for changes right away, e.g. "resync"
+    Resync,
+}
+
+impl RemoteCommand {
+    /// Parse a command message; a leading slash and the verb's case are ignored
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches('/');
+        let (verb, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let list_name = rest.trim();
+
+        match verb.to_lowercase().as_str() {
+            "list" | "show" if !list_name.is_empty() => Some(RemoteCommand::ShowList {
+                list_name: list_name.to_string(),
+            }),
+            "mute" => {
+                let (list_name, duration) = list_name.rsplit_once(char::is_whitespace)?;
+                let list_name = list_name.trim();
+                if list_name.is_empty() {
+                    return None;
+                }
+                Some(RemoteCommand::Mute {
+                    list_name: list_name.to_string(),
+                    duration: parse_duration(duration)?,
+                })
+            }
+            "unmute" if !list_name.is_empty() => Some(RemoteCommand::Unmute {
+                list_name: list_name.to_string(),
+            }),
+            "approve" => Some(RemoteCommand::Approve {
+                review_id: list_name.parse().ok()?,
+            }),
+            "reject" => Some(RemoteCommand::Reject {
+                review_id: list_name.parse().ok()?,
+            }),
+            "resync" if list_name.is_empty() => Some(RemoteCommand::Resync),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a duration such as "30s", "90m", "1h" or "2d"
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = text.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+
+    let unit_secs = match unit.to_lowercase().as_str() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        _ => return None,
+    };
+    amount.checked_mul(unit_secs).map(Duration::from_secs)
+}
+
+/// A line of ntfy's JSON subscription stream
+#[derive(Debug, Deserialize)]
+struct NtfyEvent {
+    event: String,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Subscribes to the ntfy command topic and runs the commands published to it
+pub struct CommandListener {
+    client: Client,
+    url: String,
+    auth: Option<NtfyAuth>,
+}
+
+impl CommandListener {
+    pub fn new(base_url: &str, topic: &str, auth: Option<NtfyAuth>) -> Self {
+        Self {
+            client: Client::new(),
+            url: format!("{}/{}/json", base_url.trim_end_matches('/'), topic),
+            auth,
+        }
+    }
+
+    /// Listen for commands until the task is aborted, resubscribing when the stream drops
+    pub async fn run(self, handler: Arc<SyncHandler>) {
+        loop {
+            match self.listen(&handler).await {
+                Ok(()) => debug!("ntfy command stream closed, resubscribing"),
+                Err(e) => warn!("ntfy command stream failed: {:#}", e),
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    /// Read the subscription stream until it ends
+    async fn listen(&self, handler: &SyncHandler) -> Result<()> {
+        let mut request = self.client.get(&self.url);
+        if let Some(auth) = &self.auth {
+            request = request.header(AUTHORIZATION, auth.header_value());
+        }
+        let response = request
+            .send()
+            .await
+            .context("Failed to subscribe to ntfy command topic")?;
+        if let Some(reason) = auth_failure(response.status(), self.auth.is_some()) {
+            anyhow::bail!(reason);
+        }
+        let mut response = response
+            .error_for_status()
+            .context("ntfy rejected the command topic subscription")?;
+
+        info!("Listening for commands on {}", self.url);
+
+        let mut buffer = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .context("Failed to read ntfy command stream")?
+        {
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                if let Some(message) = parse_event_line(&line) {
+                    run_command(handler, &message).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Extract the message text from a stream line, ignoring keepalives and other events
+fn parse_event_line(line: &[u8]) -> Option<String> {
+    let event: NtfyEvent = match serde_json::from_slice(line) {
+        Ok(event) => event,
+        Err(e) => {
+            debug!("Ignoring unparseable ntfy stream line: {}", e);
+            return None;
+        }
+    };
+
+    if event.event == "message" {
+        event.message
+    } else {
+        None
+    }
+}
+
+async fn run_command(handler: &SyncHandler, message: &str) {
+    let Some(command) = RemoteCommand::parse(message) else {
+        warn!("Ignoring unknown command: {}", message);
+        return;
+    };
+
+    info!("Received command: {:?}", command);
+    match command {
+        RemoteCommand::ShowList { list_name } => {
+            if let Err(e) = handler.send_list_snapshot(&list_name).await {
+                error!("Failed to send snapshot of {}: {:#}", list_name, e);
+            }
+        }
+        RemoteCommand::Mute {
+            list_name,
+            duration,
+        } => {
+            if let Err(e) = handler.mute_list(&list_name, duration).await {
+                error!("Failed to mute {}: {:#}", list_name, e);
+            }
+        }
+        RemoteCommand::Unmute { list_name } => {
+            if let Err(e) = handler.unmute_list(&list_name).await {
+                error!("Failed to unmute {}: {:#}", list_name, e);
+            }
+        }
+        RemoteCommand::Approve { review_id } => {
+            if let Err(e) = handler.approve_review(review_id).await {
+                error!("Failed to approve review #{}: {:#}", review_id, e);
+            }
+        }
+        RemoteCommand::Reject { review_id } => {
+            if let Err(e) = handler.reject_review(review_id).await {
+                error!("Failed to reject review #{}: {:#}", review_id, e);
+            }
+        }
+        RemoteCommand::Resync => {
+            if let Err(e) = handler.resync().await {
+                error!("Failed to resync: {:#}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            RemoteCommand::parse("list Groceries"),
+            Some(RemoteCommand::ShowList {
+                list_name: "Groceries".to_string()
+            })
+        );
+        assert_eq!(
+            RemoteCommand::parse("  /Show  Hardware Store "),
+            Some(RemoteCommand::ShowList {
+                list_name: "Hardware Store".to_string()
+            })
+        );
+        assert_eq!(RemoteCommand::parse("list"), None);
+        assert_eq!(RemoteCommand::parse("delete Groceries"), None);
+    }
+
+    #[test]
+    fn test_parse_mute_commands() {
+        assert_eq!(
+            RemoteCommand::parse("mute Hardware Store 1h"),
+            Some(RemoteCommand::Mute {
+                list_name: "Hardware Store".to_string(),
+                duration: Duration::from_secs(3600),
+            })
+        );
+        assert_eq!(
+            RemoteCommand::parse("/unmute Groceries"),
+            Some(RemoteCommand::Unmute {
+                list_name: "Groceries".to_string()
+            })
+        );
+        assert_eq!(RemoteCommand::parse("mute Groceries"), None);
+        assert_eq!(RemoteCommand::parse("mute 1h"), None);
+        assert_eq!(RemoteCommand::parse("mute Groceries soon"), None);
+    }
+
+    #[test]
+    fn test_parse_review_commands() {
+        assert_eq!(
+            RemoteCommand::parse("approve 12"),
+            Some(RemoteCommand::Approve { review_id: 12 })
+        );
+        assert_eq!(
+            RemoteCommand::parse("/Reject  7 "),
+            Some(RemoteCommand::Reject { review_id: 7 })
+        );
+        assert_eq!(RemoteCommand::parse("approve"), None);
+        assert_eq!(RemoteCommand::parse("approve Groceries"), None);
+    }
+
+    #[test]
+    fn test_parse_resync() {
+        assert_eq!(RemoteCommand::parse("resync"), Some(RemoteCommand::Resync));
+        assert_eq!(RemoteCommand::parse(" /ReSync "), Some(RemoteCommand::Resync));
+        assert_eq!(RemoteCommand::parse("resync Groceries"), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("90m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1H"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("2d"), Some(Duration::from_secs(172_800)));
+        assert_eq!(parse_duration("15"), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("1w"), None);
+    }
+
+    #[test]
+    fn test_parse_event_line() {
+        assert_eq!(
+            parse_event_line(
+                br#"{"id":"a1","event":"message","topic":"cmd","message":"list Groceries"}"#
+            ),
+            Some("list Groceries".to_string())
+        );
+        assert_eq!(
+            parse_event_line(br#"{"id":"a2","event":"keepalive","topic":"cmd"}"#),
+            None
+        );
+        assert_eq!(parse_event_line(b"not json\n"), None);
+    }
+}
@@ -0,0 +1,205 @@
+use super::format::Formatter;
+use super::webhook::{self, truncate};
+use super::{ChangeContext, Notifier, ServiceEvent};
+use crate::sync::diff::ListChange;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Serialize;
+use tracing::debug;
+
+/// Longest embed title Discord accepts, in characters
+const MAX_TITLE_CHARS: usize = 256;
+
+/// Longest embed description Discord accepts, in characters
+const MAX_DESCRIPTION_CHARS: usize = 4096;
+
+/// Embed colors, as 0xRRGGBB
+const ADDED_COLOR: u32 = 0x2ecc71;
+const REMOVED_COLOR: u32 = 0xe74c3c;
+const CHECKED_COLOR: u32 = 0x95a5a6;
+const UNCHECKED_COLOR: u32 = 0xe67e22;
+const MODIFIED_COLOR: u32 = 0x3498db;
+const SHARING_COLOR: u32 = 0x9b59b6;
+const ALERT_COLOR: u32 = 0xf1c40f;
+const DEFAULT_COLOR: u32 = 0x5865f2;
+
+/// Posts notifications to a Discord webhook as embeds
+///
+/// The wording comes from the shared formatter, like Slack's, with the embed
+/// color showing the kind of change at a glance.
+pub struct DiscordClient {
+    client: Client,
+    webhook_url: String,
+    formatter: Formatter,
+}
+
+#[derive(Debug, Serialize)]
+struct DiscordMessage {
+    embeds: Vec<Embed>,
+}
+
+#[derive(Debug, Serialize)]
+struct Embed {
+    title: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    description: String,
+    color: u32,
+}
+
+impl DiscordMessage {
+    fn new(title: &str, message: &str, color: u32) -> Self {
+        Self {
+            embeds: vec![Embed {
+                title: truncate(title, MAX_TITLE_CHARS),
+                description: truncate(message, MAX_DESCRIPTION_CHARS),
+                color,
+            }],
+        }
+    }
+}
+
+impl DiscordClient {
+    pub fn new(webhook_url: String, formatter: Formatter) -> Self {
+        Self {
+            client: Client::new(),
+            webhook_url,
+            formatter,
+        }
+    }
+
+    fn change_message(&self, change: &ListChange, context: &ChangeContext) -> DiscordMessage {
+        let (title, message) = self.formatter.change(change, context);
+        DiscordMessage::new(&title, &message, embed_color(change))
+    }
+
+    fn service_message(&self, event: &ServiceEvent) -> DiscordMessage {
+        let (title, message) = self.formatter.service_event(event);
+        DiscordMessage::new(&title, &message, DEFAULT_COLOR)
+    }
+
+    async fn send_message(&self, message: &DiscordMessage) -> Result<()> {
+        webhook::post_json(&self.client, &self.webhook_url, message, "Discord").await
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordClient {
+    fn name(&self) -> &str {
+        "discord"
+    }
+
+    async fn notify(&self, change: &ListChange, context: &ChangeContext) -> Result<()> {
+        let message = self.change_message(change, context);
+        debug!(
+            "Sending notification to Discord: {}",
+            message.embeds[0].title
+        );
+        self.send_message(&message).await
+    }
+
+    async fn notify_service(&self, event: &ServiceEvent) -> Result<()> {
+        self.send_message(&self.service_message(event)).await
+    }
+
+    fn render(&self, change: &ListChange, context: &ChangeContext) -> Result<String> {
+        serde_json::to_string(&self.change_message(change, context))
+            .context("Failed to serialize Discord message")
+    }
+
+    fn render_service(&self, event: &ServiceEvent) -> Result<Option<String>> {
+        serde_json::to_string(&self.service_message(event))
+            .map(Some)
+            .context("Failed to serialize Discord message")
+    }
+}
+
+/// Embed color for the kind of change
+fn embed_color(change: &ListChange) -> u32 {
+    match change {
+        ListChange::ItemAdded { .. } | ListChange::ItemAddedToLists { .. } => ADDED_COLOR,
+        ListChange::ItemRemoved { .. } => REMOVED_COLOR,
+        ListChange::ItemChecked { .. }
+        | ListChange::ItemAddedAndChecked { .. }
+        | ListChange::ItemDone { .. } => CHECKED_COLOR,
+        ListChange::ItemUnchecked { .. } | ListChange::ItemMarkedMaybe { .. } => UNCHECKED_COLOR,
+        ListChange::ItemModified { .. } | ListChange::ItemsRecategorized { .. } => MODIFIED_COLOR,
+        ListChange::ListSharingChanged { .. }
+        | ListChange::ListBecameShared { .. }
+        | ListChange::ListBecamePrivate { .. } => SHARING_COLOR,
+        ListChange::ItemToggledRepeatedly { .. } | ListChange::ListThresholdCrossed { .. } => {
+            ALERT_COLOR
+        }
+        ListChange::ChangesByUser { .. } => DEFAULT_COLOR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::ItemInfo;
+    use serde_json::json;
+
+    fn item(name: &str) -> ItemInfo {
+        ItemInfo {
+            id: format!("id-{}", name),
+            name: name.to_string(),
+            details: "".to_string(),
+            quantity: None,
+            category: None,
+            user_id: None,
+        }
+    }
+
+    #[test]
+    fn test_message_embed() {
+        let message = DiscordMessage::new("➕ Milk added", "To Groceries", ADDED_COLOR);
+
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({
+                "embeds": [{
+                    "title": "➕ Milk added",
+                    "description": "To Groceries",
+                    "color": 0x2ecc71,
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_long_description_is_truncated() {
+        let message = DiscordMessage::new("Milk modified", &"a".repeat(5000), MODIFIED_COLOR);
+        let description = &message.embeds[0].description;
+
+        assert_eq!(description.chars().count(), MAX_DESCRIPTION_CHARS);
+        assert!(description.ends_with('…'));
+    }
+
+    #[test]
+    fn test_embed_color() {
+        let added = ListChange::ItemAdded {
+            list_id: "1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item("Milk"),
+            user_id: None,
+        };
+        assert_eq!(embed_color(&added), ADDED_COLOR);
+
+        let removed = ListChange::ItemRemoved {
+            list_id: "1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item("Milk"),
+            user_id: None,
+        };
+        assert_eq!(embed_color(&removed), REMOVED_COLOR);
+
+        let batch = ListChange::ChangesByUser {
+            list_id: "1".to_string(),
+            list_name: "Groceries".to_string(),
+            user_id: None,
+            changes: vec![added, removed],
+        };
+        assert_eq!(embed_color(&batch), DEFAULT_COLOR);
+    }
+}
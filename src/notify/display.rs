@@ -0,0 +1,139 @@
+use crate::config::DisplayConfig;
+use anyhow::{Context, Result};
+use regex::Regex;
+
+/// Marker appended to item names shortened by `max_name_length`
+const ELLIPSIS: char = '…';
+
+/// Cosmetic transforms applied to item names shown in notifications
+///
+/// Only the displayed text changes; the cache and diff keep the real names.
+#[derive(Debug, Clone, Default)]
+pub struct NameDisplay {
+    title_case: bool,
+    strip: Option<Regex>,
+    max_length: Option<usize>,
+}
+
+impl NameDisplay {
+    pub fn from_config(config: &DisplayConfig) -> Result<Self> {
+        let strip = config
+            .strip_pattern
+            .as_deref()
+            .filter(|pattern| !pattern.is_empty())
+            .map(Regex::new)
+            .transpose()
+            .context("Invalid notifications.display.strip_pattern")?;
+
+        Ok(Self {
+            title_case: config.title_case,
+            strip,
+            max_length: config.max_name_length.filter(|max| *max > 0),
+        })
+    }
+
+    /// Whether any transform is configured
+    pub fn is_identity(&self) -> bool {
+        !self.title_case && self.strip.is_none() && self.max_length.is_none()
+    }
+
+    /// Apply the configured transforms: strip, then title-case, then truncate
+    pub fn apply(&self, name: &str) -> String {
+        let mut name = match &self.strip {
+            Some(strip) => {
+                let stripped = strip.replace_all(name, "");
+                // Never strip a name down to nothing
+                match stripped.trim() {
+                    "" => name.to_string(),
+                    stripped => stripped.to_string(),
+                }
+            }
+            None => name.to_string(),
+        };
+
+        if self.title_case {
+            name = title_case(&name);
+        }
+
+        match self.max_length {
+            Some(max) if name.chars().count() > max => {
+                let kept: String = name.chars().take(max.saturating_sub(1)).collect();
+                format!("{}{}", kept.trim_end(), ELLIPSIS)
+            }
+            _ => name,
+        }
+    }
+}
+
+/// Capitalize the first letter of each word and lowercase the rest
+fn title_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut start_of_word = true;
+
+    for c in name.chars() {
+        if start_of_word {
+            result.extend(c.to_uppercase());
+        } else {
+            result.extend(c.to_lowercase());
+        }
+        start_of_word = c.is_whitespace() || c == '-';
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(title_case: bool, strip_pattern: Option<&str>, max: Option<usize>) -> NameDisplay {
+        NameDisplay::from_config(&DisplayConfig {
+            title_case,
+            strip_pattern: strip_pattern.map(str::to_string),
+            max_name_length: max,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_title_case() {
+        let display = display(true, None, None);
+        assert_eq!(display.apply("WHOLE MILK"), "Whole Milk");
+        assert_eq!(display.apply("gluten-free bread"), "Gluten-Free Bread");
+    }
+
+    #[test]
+    fn test_strip_pattern() {
+        let display = display(false, Some(r"\s*#\d+$"), None);
+        assert_eq!(display.apply("Milk #40213"), "Milk");
+        assert_eq!(display.apply("Bread"), "Bread");
+
+        // A name that would be stripped entirely is left alone
+        assert_eq!(display.apply("#123"), "#123");
+    }
+
+    #[test]
+    fn test_truncate() {
+        let display = display(false, None, Some(10));
+        assert_eq!(display.apply("Milk"), "Milk");
+        assert_eq!(display.apply("Extra virgin olive oil"), "Extra vir…");
+        assert_eq!(display.apply("Extra virgin olive oil").chars().count(), 10);
+    }
+
+    #[test]
+    fn test_transforms_combine() {
+        let display = display(true, Some(r"\s*\(\w+\)$"), Some(12));
+        assert!(!display.is_identity());
+        assert_eq!(display.apply("ORGANIC WHOLE MILK (SKU)"), "Organic Who…");
+        assert!(NameDisplay::default().is_identity());
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let config = DisplayConfig {
+            strip_pattern: Some("(".to_string()),
+            ..DisplayConfig::default()
+        };
+        assert!(NameDisplay::from_config(&config).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use super::{ChangeContext, Notifier, ServiceEvent};
+use crate::sync::diff::ListChange;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing::info;
+
+/// Logs what a backend would send instead of sending it
+///
+/// Lets a new backend's formatting be checked against real changes while
+/// the other backends keep delivering as usual.
+pub struct DryRunNotifier {
+    inner: Arc<dyn Notifier>,
+}
+
+impl DryRunNotifier {
+    /// Wrap a backend if its `dry_run` flag is set, otherwise return it unchanged
+    pub fn wrap(inner: Arc<dyn Notifier>, dry_run: bool) -> Arc<dyn Notifier> {
+        if !dry_run {
+            return inner;
+        }
+        info!(
+            "{} backend is in dry-run mode, notifications are only logged",
+            inner.name()
+        );
+        Arc::new(Self { inner })
+    }
+}
+
+#[async_trait]
+impl Notifier for DryRunNotifier {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn is_push(&self) -> bool {
+        self.inner.is_push()
+    }
+
+    async fn notify(&self, change: &ListChange, context: &ChangeContext) -> Result<()> {
+        let rendered = self.inner.render(change, context)?;
+        info!("[dry run] {} notification: {}", self.name(), rendered);
+        Ok(())
+    }
+
+    async fn notify_service(&self, event: &ServiceEvent) -> Result<()> {
+        if let Some(rendered) = self.inner.render_service(event)? {
+            info!("[dry run] {} notification: {}", self.name(), rendered);
+        }
+        Ok(())
+    }
+
+    // Audits and reviews are rendered like the change itself; only the
+    // destination differs
+    async fn audit(&self, change: &ListChange, context: &ChangeContext) -> Result<()> {
+        let rendered = self.inner.render(change, context)?;
+        info!("[dry run] {} audit: {}", self.name(), rendered);
+        Ok(())
+    }
+
+    async fn request_review(
+        &self,
+        review_id: i64,
+        change: &ListChange,
+        context: &ChangeContext,
+    ) -> Result<()> {
+        let rendered = self.inner.render(change, context)?;
+        info!(
+            "[dry run] {} review #{}: {}",
+            self.name(),
+            review_id,
+            rendered
+        );
+        Ok(())
+    }
+
+    fn render(&self, change: &ListChange, context: &ChangeContext) -> Result<String> {
+        self.inner.render(change, context)
+    }
+
+    fn render_service(&self, event: &ServiceEvent) -> Result<Option<String>> {
+        self.inner.render_service(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A backend that counts what it actually sends
+    #[derive(Default)]
+    struct CountingNotifier {
+        sent: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Notifier for CountingNotifier {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        async fn notify(&self, _change: &ListChange, _context: &ChangeContext) -> Result<()> {
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn notify_service(&self, _event: &ServiceEvent) -> Result<()> {
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_only_renders() {
+        let change = ListChange::ListSharingChanged {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            added_users: vec!["user-1".to_string()],
+            removed_users: vec![],
+        };
+        let context = ChangeContext::default();
+
+        let backend = Arc::new(CountingNotifier::default());
+        let live = DryRunNotifier::wrap(backend.clone(), false);
+        live.notify(&change, &context).await.unwrap();
+        assert_eq!(backend.sent.load(Ordering::SeqCst), 1);
+
+        let dry = DryRunNotifier::wrap(backend.clone(), true);
+        assert_eq!(dry.name(), "counting");
+        assert!(dry.is_push());
+        dry.notify(&change, &context).await.unwrap();
+        dry.notify_service(&ServiceEvent::Recovered).await.unwrap();
+        assert_eq!(backend.sent.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::config::NtfyTags;
+
+/// Emoji shortcodes ntfy renders in front of the title when sent as tags
+///
+/// A subset of ntfy's emoji list covering the defaults and the emoji that
+/// suit shopping lists; tags outside it are still sent, but ntfy shows any
+/// tag it doesn't recognize as plain text below the message.
+const SHORTCODES: &[&str] = &[
+    "+1",
+    "-1",
+    "alarm_clock",
+    "apple",
+    "arrow_backward",
+    "arrow_down",
+    "arrow_forward",
+    "arrow_up",
+    "arrows_clockwise",
+    "arrows_counterclockwise",
+    "avocado",
+    "baby_bottle",
+    "bacon",
+    "bagel",
+    "baguette_bread",
+    "balloon",
+    "banana",
+    "basket",
+    "bathtub",
+    "battery",
+    "beer",
+    "bell",
+    "bellhop_bell",
+    "birthday",
+    "bomb",
+    "book",
+    "bookmark",
+    "bread",
+    "broccoli",
+    "broom",
+    "bubbles",
+    "bulb",
+    "bust_in_silhouette",
+    "busts_in_silhouette",
+    "butter",
+    "cake",
+    "calendar",
+    "candle",
+    "canned_food",
+    "card_index_dividers",
+    "carrot",
+    "cat",
+    "champagne",
+    "cheese",
+    "cherries",
+    "chicken",
+    "chocolate_bar",
+    "christmas_tree",
+    "clipboard",
+    "clock1",
+    "coffee",
+    "cookie",
+    "corn",
+    "croissant",
+    "cucumber",
+    "cupcake",
+    "dog",
+    "doughnut",
+    "egg",
+    "electric_plug",
+    "envelope",
+    "eyes",
+    "fire",
+    "fish",
+    "floppy_disk",
+    "gift",
+    "grapes",
+    "green_apple",
+    "green_heart",
+    "hamburger",
+    "heart",
+    "heavy_check_mark",
+    "heavy_minus_sign",
+    "heavy_plus_sign",
+    "honey_pot",
+    "hourglass",
+    "hourglass_flowing_sand",
+    "house",
+    "ice_cream",
+    "information_source",
+    "key",
+    "label",
+    "lemon",
+    "lock",
+    "loudspeaker",
+    "mag",
+    "meat_on_bone",
+    "memo",
+    "milk_glass",
+    "money_with_wings",
+    "moneybag",
+    "mushroom",
+    "newspaper",
+    "no_entry",
+    "orange",
+    "package",
+    "pancakes",
+    "partying_face",
+    "peach",
+    "pear",
+    "pencil",
+    "pencil2",
+    "pill",
+    "pineapple",
+    "pizza",
+    "potato",
+    "poultry_leg",
+    "pushpin",
+    "question",
+    "recycle",
+    "red_circle",
+    "repeat",
+    "rice",
+    "roll_of_paper",
+    "rotating_light",
+    "salt",
+    "sandwich",
+    "scroll",
+    "shopping",
+    "shopping_cart",
+    "skull",
+    "soap",
+    "spaghetti",
+    "sparkles",
+    "sponge",
+    "star",
+    "stew",
+    "strawberry",
+    "taco",
+    "tada",
+    "tea",
+    "thinking",
+    "toilet",
+    "tomato",
+    "toothbrush",
+    "triangular_flag_on_post",
+    "unlock",
+    "warning",
+    "watermelon",
+    "white_check_mark",
+    "wine_glass",
+    "wrench",
+    "writing_hand",
+    "x",
+    "zap",
+];
+
+/// Whether a tag is on the built-in list of shortcodes ntfy renders as emoji
+pub fn is_shortcode(tag: &str) -> bool {
+    SHORTCODES.binary_search(&tag).is_ok()
+}
+
+/// Configured tags that aren't on the built-in shortcode list, with the setting
+/// they come from; ntfy may still know them, as the list is only a subset
+pub fn unknown_tags(tags: &NtfyTags) -> Vec<(&'static str, String)> {
+    tags.all()
+        .into_iter()
+        .flat_map(|(setting, value)| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty() && !is_shortcode(tag))
+                .map(move |tag| (setting, tag.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortcodes_are_sorted() {
+        assert!(SHORTCODES.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_unknown_tags() {
+        assert!(unknown_tags(&NtfyTags::default()).is_empty());
+
+        let tags = NtfyTags {
+            item_added: "heavy_plus_sign, groceries".to_string(),
+            item_checked: "".to_string(),
+            ..NtfyTags::default()
+        };
+        assert_eq!(
+            unknown_tags(&tags),
+            vec![("item_added", "groceries".to_string())]
+        );
+    }
+}
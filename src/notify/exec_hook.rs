@@ -0,0 +1,193 @@
+use crate::notify::{ChangeContext, Notifier, ServiceEvent};
+use crate::sync::diff::ListChange;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tracing::{debug, info, warn};
+
+/// Longest stderr excerpt included in a hook failure
+const MAX_STDERR_CHARS: usize = 500;
+
+/// Most hook invocations running at once
+const MAX_RUNNING_HOOKS: u32 = 4;
+
+/// Runs a local command for every change, with the change as JSON on stdin
+///
+/// Each invocation runs in the background, so a slow or failing hook only
+/// delays the other backends once `MAX_RUNNING_HOOKS` are already running;
+/// failures and timeouts are only logged.
+pub struct ExecHookNotifier {
+    command: String,
+    timeout: Duration,
+    running: Arc<Semaphore>,
+}
+
+impl ExecHookNotifier {
+    pub fn new(command: String, timeout: Duration) -> Self {
+        Self {
+            command,
+            timeout,
+            running: Arc::new(Semaphore::new(MAX_RUNNING_HOOKS as usize)),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for ExecHookNotifier {
+    fn name(&self) -> &str {
+        "exec_hook"
+    }
+
+    fn is_push(&self) -> bool {
+        false
+    }
+
+    async fn notify(&self, change: &ListChange, _context: &ChangeContext) -> Result<()> {
+        let input = serde_json::to_vec(change).context("Failed to serialize change")?;
+        let permit = self
+            .running
+            .clone()
+            .acquire_owned()
+            .await
+            .context("Exec hook is shut down")?;
+        let command = self.command.clone();
+        let timeout = self.timeout;
+        tokio::spawn(async move {
+            if let Err(e) = run_hook(&command, &input, timeout).await {
+                warn!("Exec hook failed: {:#}", e);
+            }
+            drop(permit);
+        });
+        Ok(())
+    }
+
+    /// Wait for running hooks, each bounded by its timeout, and run no more
+    async fn shutdown(&self) {
+        let running = MAX_RUNNING_HOOKS as usize - self.running.available_permits();
+        if running > 0 {
+            info!("Waiting for {} exec hook(s) to finish", running);
+        }
+        if let Ok(permits) = self.running.acquire_many(MAX_RUNNING_HOOKS).await {
+            permits.forget();
+        }
+        self.running.close();
+    }
+
+    /// Only list changes are passed to the hook
+    async fn notify_service(&self, _event: &ServiceEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Run the hook through `sh -c`, writing `input` to its stdin and killing it
+/// if it outlives `timeout`
+async fn run_hook(command: &str, input: &[u8], timeout: Duration) -> Result<()> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start exec hook '{}'", command))?;
+
+    let mut stdin = child.stdin.take().context("Exec hook stdin unavailable")?;
+    let run = async move {
+        // A hook that ignores its input may exit before reading it all
+        if let Err(e) = stdin.write_all(input).await {
+            debug!("Exec hook didn't read its input: {}", e);
+        }
+        drop(stdin);
+        child.wait_with_output().await
+    };
+
+    let output = tokio::time::timeout(timeout, run)
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out after {}s", timeout.as_secs()))?
+        .context("Failed to wait for exec hook")?;
+
+    if output.status.success() {
+        debug!("Exec hook finished");
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr: String = stderr.trim().chars().take(MAX_STDERR_CHARS).collect();
+        anyhow::bail!("exited with {}: {}", output.status, stderr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hook_receives_change_on_stdin() {
+        let path = std::env::temp_dir().join(format!(
+            "anylist_notify_hook_{}.json",
+            std::process::id()
+        ));
+        let command = format!("cat > '{}'", path.display());
+
+        run_hook(&command, br#"{"type":"item_added"}"#, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written, r#"{"type":"item_added"}"#);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_hook_failure_includes_stderr() {
+        let err = run_hook("echo broken >&2; exit 3", b"{}", Duration::from_secs(5))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("broken"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_running_hooks() {
+        let path = std::env::temp_dir().join(format!(
+            "anylist_notify_hook_shutdown_{}.json",
+            std::process::id()
+        ));
+        let command = format!("sleep 0.2; cat > '{}'", path.display());
+        let notifier = ExecHookNotifier::new(command, Duration::from_secs(5));
+        let change = ListChange::ListBecamePrivate {
+            list_id: "1".to_string(),
+            list_name: "Groceries".to_string(),
+            removed_users: Vec::new(),
+        };
+
+        notifier
+            .notify(&change, &ChangeContext::default())
+            .await
+            .unwrap();
+        notifier.shutdown().await;
+
+        assert!(path.exists());
+        std::fs::remove_file(&path).unwrap();
+
+        // No more hooks run once shut down
+        assert!(notifier
+            .notify(&change, &ChangeContext::default())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hook_timeout() {
+        let err = run_hook("sleep 5", b"{}", Duration::from_millis(100))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("timed out"), "{}", err);
+    }
+}
@@ -0,0 +1,1156 @@
+use super::ascii::to_ascii;
+use super::display::NameDisplay;
+use super::i18n::{Language, Phrase};
+use super::links::first_phone;
+use super::{ChangeContext, ServiceEvent};
+use crate::config::{CategoryChangeMode, NotificationsConfig};
+use crate::sync::diff::{ChangeCounts, FieldChange, ItemInfo, ListChange, ThresholdDirection};
+
+/// Number of item names listed in a recategorization summary
+const RECATEGORIZED_EXAMPLE_COUNT: usize = 3;
+
+/// Words changes and service events the same way for every backend
+///
+/// Only the title and body come from here, from the change and its context;
+/// each backend styles them itself, such as ntfy with its tags and priority
+/// or Discord with its embed color.
+#[derive(Clone)]
+pub struct Formatter {
+    notifications: NotificationsConfig,
+    language: Language,
+    display: NameDisplay,
+}
+
+/// How an item modification is presented
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifiedKind<'a> {
+    /// A category change in `tag_only` mode, reduced to the new category
+    CategoryTagOnly,
+    /// A first quantity for the item, with `highlight_quantity_set`
+    QuantitySet(&'a str),
+    /// A quantity cleared or dropped to zero
+    QuantityDepleted,
+    /// Any other modification
+    Edited,
+}
+
+impl Formatter {
+    pub fn new(notifications: NotificationsConfig) -> Self {
+        // Unknown languages are rejected by config validation
+        let language = Language::from_code(&notifications.language).unwrap_or_default();
+        // Invalid display settings are also rejected by config validation
+        let display = NameDisplay::from_config(&notifications.display).unwrap_or_default();
+
+        Self {
+            notifications,
+            language,
+            display,
+        }
+    }
+
+    pub fn language(&self) -> &Language {
+        &self.language
+    }
+
+    /// Whether emoji are replaced with ASCII, per `ascii_only`
+    pub fn ascii_only(&self) -> bool {
+        self.notifications.ascii_only
+    }
+
+    /// Title and body for a list change
+    pub fn change(&self, change: &ListChange, context: &ChangeContext) -> (String, String) {
+        let (title, mut message) = match self.display_change(change) {
+            Some(display_change) => self.format_change(&display_change, context),
+            None => self.format_change(change, context),
+        };
+
+        if let Some(totals) = context.list_totals {
+            message.push('\n');
+            message.push_str(&self.language.format(
+                Phrase::ListTotals,
+                &[
+                    ("list", change.list_name()),
+                    ("total", &totals.total.to_string()),
+                    ("unchecked", &totals.unchecked.to_string()),
+                ],
+            ));
+        }
+
+        if let Some(footer) = &self.notifications.footer {
+            message.push('\n');
+            message.push_str(&footer.replace("{list_name}", change.list_name()));
+        }
+
+        // A phone number in the details goes first, so it is visible in the collapsed notification
+        if self.notifications.extract_links {
+            if let Some(phone) = change.item_details().and_then(first_phone) {
+                message = format!("📞 {}\n{}", phone, message);
+            }
+        }
+
+        let item_ids = change.item_ids();
+        if self.notifications.include_item_id && !item_ids.is_empty() {
+            message.push_str(&format!(
+                "\n{}: {}",
+                self.language.get(Phrase::ItemId),
+                item_ids.join(", ")
+            ));
+        }
+
+        self.apply_ascii_only(title, message)
+    }
+
+    /// Title and body for a service event
+    pub fn service_event(&self, event: &ServiceEvent) -> (String, String) {
+        let (title, message) = self.format_service_event(event);
+        self.apply_ascii_only(title, message)
+    }
+
+    /// How a modification with these field changes is presented
+    pub fn modified_kind<'a>(&self, changes: &'a [FieldChange]) -> ModifiedKind<'a> {
+        let has_category_change = changes
+            .iter()
+            .any(|c| matches!(c, FieldChange::Category { .. }));
+        if has_category_change
+            && self.notifications.category_change_mode == CategoryChangeMode::TagOnly
+        {
+            return ModifiedKind::CategoryTagOnly;
+        }
+
+        // A first quantity clarifies how much to get rather than changing the item
+        if let [change @ FieldChange::Quantity {
+            new: Some(quantity),
+            ..
+        }] = changes
+        {
+            if self.notifications.highlight_quantity_set && change.is_quantity_first_set() {
+                return ModifiedKind::QuantitySet(quantity.trim());
+            }
+        }
+
+        // A cleared or zeroed quantity usually means the item ran out
+        if changes.iter().any(FieldChange::is_quantity_depleted) {
+            ModifiedKind::QuantityDepleted
+        } else {
+            ModifiedKind::Edited
+        }
+    }
+
+    /// Replace emoji with ASCII when `ascii_only` is enabled
+    fn apply_ascii_only(&self, title: String, message: String) -> (String, String) {
+        if !self.notifications.ascii_only {
+            return (title, message);
+        }
+        (to_ascii(&title), to_ascii(&message))
+    }
+
+    /// Copy of the change with display transforms applied to its item names,
+    /// or None when no transform is configured
+    fn display_change(&self, change: &ListChange) -> Option<ListChange> {
+        if self.display.is_identity() {
+            return None;
+        }
+
+        let mut display_change = change.clone();
+        display_change.map_item_names(|name| self.display.apply(name));
+        Some(display_change)
+    }
+
+    /// Item name for check notifications, with its quantity if `context_in_check` is set
+    fn check_label(&self, item: &ItemInfo) -> String {
+        match &item.quantity {
+            Some(quantity) if self.notifications.context_in_check => {
+                format!("{} ({})", item.name, quantity)
+            }
+            _ => item.name.clone(),
+        }
+    }
+
+    /// Append the item's category to a check notification if `context_in_check` is set
+    fn push_check_context(&self, message: &mut String, item: &ItemInfo) {
+        if !self.notifications.context_in_check {
+            return;
+        }
+        if let Some(category) = &item.category {
+            message.push_str(&format!(
+                "\n{}: {}",
+                self.language.get(Phrase::Category),
+                category
+            ));
+        }
+    }
+
+    /// "Changed by" line for a change, if the user is known
+    fn changed_by(&self, context: &ChangeContext, user_id: &Option<String>) -> Option<String> {
+        let uid = user_id.as_ref()?;
+        let user_name = context.user_name(uid);
+        Some(
+            self.language
+                .format(Phrase::ChangedBy, &[("user", &user_name)]),
+        )
+    }
+
+    /// Format a modification that includes a category change in `tag_only` mode
+    /// The category change is reduced to the new category plus a category tag,
+    /// while any other field changes are rendered as usual
+    fn format_category_tag_only(
+        &self,
+        context: &ChangeContext,
+        list_name: &str,
+        item_name: &str,
+        changes: &[FieldChange],
+        user_id: &Option<String>,
+    ) -> (String, String) {
+        let title = format!(
+            "✏️ {}",
+            self.language.format(
+                Phrase::ItemModifiedTitle,
+                &[("item", item_name), ("list", list_name)]
+            )
+        );
+
+        let other_changes: Vec<FieldChange> = changes
+            .iter()
+            .filter(|c| !matches!(c, FieldChange::Category { .. }))
+            .cloned()
+            .collect();
+        let none = self.language.get(Phrase::NoValue);
+        let mut message = if other_changes.is_empty() {
+            let new_category = changes.iter().find_map(|c| match c {
+                FieldChange::Category { new, .. } => Some(new.as_deref().unwrap_or(none)),
+                _ => None,
+            });
+            new_category.unwrap_or(none).to_string()
+        } else {
+            format_field_changes(
+                &other_changes,
+                &self.language,
+                self.notifications.details_as_kv,
+            )
+        };
+
+        if let Some(changed_by) = self.changed_by(context, user_id) {
+            message.push_str(&format!("\n{}", changed_by));
+        }
+
+        (title, message)
+    }
+
+    /// Word a service event
+    fn format_service_event(&self, event: &ServiceEvent) -> (String, String) {
+        match event {
+            ServiceEvent::CacheUnavailable { error } => {
+                let title = format!("⚠️ {}", self.language.get(Phrase::CacheUnavailableTitle));
+                let message = self
+                    .language
+                    .format(Phrase::CacheUnavailableMessage, &[("error", error)]);
+
+                (title, message)
+            }
+            ServiceEvent::Recovered => {
+                let title = format!("🔄 {}", self.language.get(Phrase::RecoveredTitle));
+                let message = self.language.get(Phrase::RecoveredMessage).to_string();
+
+                (title, message)
+            }
+            ServiceEvent::ListSnapshot { list_name, items } => {
+                let title = format!(
+                    "🛒 {}",
+                    self.language.format(
+                        Phrase::ListSnapshotTitle,
+                        &[("list", list_name), ("count", &items.len().to_string())]
+                    )
+                );
+                let message = if items.is_empty() {
+                    self.language.get(Phrase::NothingToGet).to_string()
+                } else {
+                    items.join("\n")
+                };
+
+                (title, message)
+            }
+            ServiceEvent::UnknownList {
+                list_name,
+                available,
+            } => {
+                let title = format!(
+                    "❓ {}",
+                    self.language
+                        .format(Phrase::UnknownListTitle, &[("list", list_name)])
+                );
+                let message = self
+                    .language
+                    .format(Phrase::AvailableLists, &[("lists", &available.join(", "))]);
+
+                (title, message)
+            }
+            ServiceEvent::MissedChanges {
+                list_name,
+                offline_secs,
+                counts,
+            } => {
+                let title = format!(
+                    "📴 {}",
+                    self.language
+                        .format(Phrase::MissedChangesTitle, &[("list", list_name)])
+                );
+                let message = self.language.format(
+                    Phrase::MissedChangesMessage,
+                    &[
+                        ("duration", &format_duration(*offline_secs)),
+                        ("list", list_name),
+                        ("counts", &format_counts(counts)),
+                    ],
+                );
+
+                (title, message)
+            }
+            ServiceEvent::ListReseeded { list_name, changes } => {
+                let title = format!(
+                    "♻️ {}",
+                    self.language
+                        .format(Phrase::ListReseededTitle, &[("list", list_name)])
+                );
+                let message = self.language.format(
+                    Phrase::ListReseededMessage,
+                    &[("count", &changes.to_string())],
+                );
+
+                (title, message)
+            }
+            ServiceEvent::Digest {
+                list_name,
+                period_secs,
+                counts,
+            } => {
+                let title = format!(
+                    "📰 {}",
+                    self.language.format(
+                        Phrase::ChangesTitle,
+                        &[("count", &counts.total().to_string()), ("list", list_name)]
+                    )
+                );
+                let message = self.language.format(
+                    Phrase::DigestMessage,
+                    &[
+                        ("duration", &format_duration(*period_secs)),
+                        ("counts", &format_counts(counts)),
+                    ],
+                );
+
+                (title, message)
+            }
+            ServiceEvent::WatchedItemAdded {
+                list_name,
+                item_name,
+                user_name,
+            } => {
+                let title = format!(
+                    "👀 {}",
+                    self.language
+                        .format(Phrase::WatchedItemAddedTitle, &[("item", item_name)])
+                );
+                let mut message = self
+                    .language
+                    .format(Phrase::AddedTo, &[("list", list_name)]);
+                if let Some(user_name) = user_name {
+                    message.push('\n');
+                    message.push_str(
+                        &self
+                            .language
+                            .format(Phrase::ChangedBy, &[("user", user_name)]),
+                    );
+                }
+
+                (title, message)
+            }
+            ServiceEvent::EditingStarted {
+                list_name,
+                user_name,
+            } => {
+                let title = format!(
+                    "✍️ {}",
+                    self.language.format(
+                        Phrase::EditingStartedTitle,
+                        &[("user", user_name), ("list", list_name)]
+                    )
+                );
+                let message = self.language.get(Phrase::EditingStartedMessage).to_string();
+
+                (title, message)
+            }
+            ServiceEvent::Heartbeat {
+                watched_lists,
+                last_event_secs,
+            } => {
+                let title = format!("💚 {}", self.language.get(Phrase::HeartbeatTitle));
+                let count = watched_lists.to_string();
+                let message = match last_event_secs {
+                    Some(secs) => self.language.format(
+                        Phrase::HeartbeatMessage,
+                        &[("count", &count), ("duration", &format_duration(*secs))],
+                    ),
+                    None => self
+                        .language
+                        .format(Phrase::HeartbeatNoEvents, &[("count", &count)]),
+                };
+
+                (title, message)
+            }
+        }
+    }
+
+    /// Word the change-specific parts of a notification
+    fn format_change(&self, change: &ListChange, context: &ChangeContext) -> (String, String) {
+        match change {
+            ListChange::ItemAddedToLists { item, user_id, .. } => {
+                let list_names = change.list_names();
+                let title = format!(
+                    "➕ {}",
+                    self.language.format(
+                        Phrase::ItemAddedToListsTitle,
+                        &[
+                            ("item", &item.name),
+                            ("count", &list_names.len().to_string())
+                        ]
+                    )
+                );
+                let mut message_parts = vec![self
+                    .language
+                    .format(Phrase::AddedTo, &[("list", &list_names.join(", "))])];
+                message_parts.extend(format_item_details(item, &self.language));
+                if let Some(changed_by) = self.changed_by(context, user_id) {
+                    message_parts.push(changed_by);
+                }
+
+                (title, message_parts.join("\n"))
+            }
+            ListChange::ItemAdded {
+                list_name,
+                item,
+                user_id,
+                ..
+            } => {
+                let title = format!(
+                    "➕ {}",
+                    self.language.format(
+                        Phrase::ItemAddedTitle,
+                        &[("item", &item.name), ("list", list_name)]
+                    )
+                );
+                let mut message_parts = format_item_details(item, &self.language);
+
+                if let Some(changed_by) = self.changed_by(context, user_id) {
+                    message_parts.push(changed_by);
+                }
+
+                let message = if message_parts.is_empty() {
+                    self.language
+                        .format(Phrase::AddedTo, &[("list", list_name)])
+                } else {
+                    message_parts.join("\n")
+                };
+
+                (title, message)
+            }
+
+            ListChange::ItemRemoved {
+                list_name,
+                item,
+                user_id,
+                ..
+            } => {
+                let title = format!(
+                    "❌ {}",
+                    self.language.format(
+                        Phrase::ItemRemovedTitle,
+                        &[("item", &item.name), ("list", list_name)]
+                    )
+                );
+                let mut message = self
+                    .language
+                    .format(Phrase::RemovedFrom, &[("list", list_name)]);
+                if self.notifications.include_details_in_removal {
+                    for line in format_item_details(item, &self.language) {
+                        message.push_str(&format!("\n{}", line));
+                    }
+                }
+                if let Some(changed_by) = self.changed_by(context, user_id) {
+                    message.push_str(&format!("\n{}", changed_by));
+                }
+
+                (title, message)
+            }
+
+            ListChange::ItemChecked {
+                list_name,
+                item,
+                user_id,
+                ..
+            } => {
+                let title = format!(
+                    "✅ {}",
+                    self.language.format(
+                        Phrase::ItemCheckedTitle,
+                        &[("item", &self.check_label(item)), ("list", list_name)]
+                    )
+                );
+                let mut message = self
+                    .language
+                    .format(Phrase::CheckedOffIn, &[("list", list_name)]);
+                self.push_check_context(&mut message, item);
+                if let Some(changed_by) = self.changed_by(context, user_id) {
+                    message.push_str(&format!("\n{}", changed_by));
+                }
+
+                (title, message)
+            }
+
+            ListChange::ItemUnchecked {
+                list_name,
+                item,
+                user_id,
+                ..
+            } => {
+                let title = format!(
+                    "◀️ {}",
+                    self.language.format(
+                        Phrase::ItemUncheckedTitle,
+                        &[("item", &self.check_label(item)), ("list", list_name)]
+                    )
+                );
+                let mut message = self
+                    .language
+                    .format(Phrase::UncheckedIn, &[("list", list_name)]);
+                self.push_check_context(&mut message, item);
+                if let Some(changed_by) = self.changed_by(context, user_id) {
+                    message.push_str(&format!("\n{}", changed_by));
+                }
+
+                (title, message)
+            }
+
+            ListChange::ItemAddedAndChecked {
+                list_name,
+                item,
+                user_id,
+                ..
+            } => {
+                let title = format!(
+                    "🛒✅ {}",
+                    self.language.format(
+                        Phrase::ItemAddedAndCheckedTitle,
+                        &[("item", &self.check_label(item)), ("list", list_name)]
+                    )
+                );
+                let mut message = self
+                    .language
+                    .format(Phrase::AddedAndCheckedIn, &[("list", list_name)]);
+                self.push_check_context(&mut message, item);
+                if let Some(changed_by) = self.changed_by(context, user_id) {
+                    message.push_str(&format!("\n{}", changed_by));
+                }
+
+                (title, message)
+            }
+
+            ListChange::ItemDone {
+                list_name,
+                item_name,
+                user_id,
+                ..
+            } => {
+                let title = format!(
+                    "❌ {}",
+                    self.language.format(
+                        Phrase::ItemDoneTitle,
+                        &[("item", item_name), ("list", list_name)]
+                    )
+                );
+                let mut message = self.language.format(Phrase::DoneIn, &[("list", list_name)]);
+                if let Some(changed_by) = self.changed_by(context, user_id) {
+                    message.push_str(&format!("\n{}", changed_by));
+                }
+
+                (title, message)
+            }
+
+            ListChange::ItemModified {
+                list_name,
+                item_name,
+                changes,
+                user_id,
+                ..
+            } => {
+                let (emoji, title_phrase) = match self.modified_kind(changes) {
+                    ModifiedKind::CategoryTagOnly => {
+                        return self.format_category_tag_only(
+                            context, list_name, item_name, changes, user_id,
+                        );
+                    }
+                    ModifiedKind::QuantitySet(quantity) => {
+                        let title = format!(
+                            "📦 {}",
+                            self.language.format(
+                                Phrase::QuantitySetTitle,
+                                &[("item", item_name), ("quantity", quantity)]
+                            )
+                        );
+                        let mut message = self
+                            .language
+                            .format(Phrase::QuantitySetIn, &[("list", list_name)]);
+                        if let Some(changed_by) = self.changed_by(context, user_id) {
+                            message.push_str(&format!("\n{}", changed_by));
+                        }
+                        return (title, message);
+                    }
+                    ModifiedKind::QuantityDepleted => ("📉", Phrase::ItemDepletedTitle),
+                    ModifiedKind::Edited => ("✏️", Phrase::ItemModifiedTitle),
+                };
+                let title = format!(
+                    "{} {}",
+                    emoji,
+                    self.language
+                        .format(title_phrase, &[("item", item_name), ("list", list_name)])
+                );
+                let mut message =
+                    format_field_changes(changes, &self.language, self.notifications.details_as_kv);
+                if let Some(changed_by) = self.changed_by(context, user_id) {
+                    message.push_str(&format!("\n{}", changed_by));
+                }
+
+                (title, message)
+            }
+
+            ListChange::ListSharingChanged {
+                list_name,
+                added_users,
+                removed_users,
+                ..
+            } => {
+                let title = format!(
+                    "👥 {}",
+                    self.language
+                        .format(Phrase::SharingChangedTitle, &[("list", list_name)])
+                );
+                let mut message_parts = Vec::new();
+
+                if !added_users.is_empty() {
+                    let names = user_names(context, added_users);
+                    message_parts.push(
+                        self.language
+                            .format(Phrase::UsersAdded, &[("users", &names)]),
+                    );
+                }
+                if !removed_users.is_empty() {
+                    let names = user_names(context, removed_users);
+                    message_parts.push(
+                        self.language
+                            .format(Phrase::UsersRemoved, &[("users", &names)]),
+                    );
+                }
+
+                let message = message_parts.join("\n");
+
+                (title, message)
+            }
+
+            ListChange::ListBecameShared {
+                list_name,
+                added_users,
+                ..
+            } => {
+                let title = format!(
+                    "🔓 {}",
+                    self.language
+                        .format(Phrase::ListSharedTitle, &[("list", list_name)])
+                );
+                let names = user_names(context, added_users);
+                let message = self
+                    .language
+                    .format(Phrase::ListSharedMessage, &[("users", &names)]);
+
+                (title, message)
+            }
+
+            ListChange::ListBecamePrivate {
+                list_name,
+                removed_users,
+                ..
+            } => {
+                let title = format!(
+                    "🔒 {}",
+                    self.language
+                        .format(Phrase::ListPrivateTitle, &[("list", list_name)])
+                );
+                let names = user_names(context, removed_users);
+                let message = self
+                    .language
+                    .format(Phrase::ListPrivateMessage, &[("users", &names)]);
+
+                (title, message)
+            }
+
+            ListChange::ItemsRecategorized {
+                list_name,
+                item_names,
+                user_id,
+                ..
+            } => {
+                let title = format!(
+                    "🗂️ {}",
+                    self.language.format(
+                        Phrase::ItemsRecategorizedTitle,
+                        &[
+                            ("count", &item_names.len().to_string()),
+                            ("list", list_name)
+                        ]
+                    )
+                );
+                let examples = item_names
+                    .iter()
+                    .take(RECATEGORIZED_EXAMPLE_COUNT)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let mut message = if item_names.len() > RECATEGORIZED_EXAMPLE_COUNT {
+                    let remaining = item_names.len() - RECATEGORIZED_EXAMPLE_COUNT;
+                    self.language.format(
+                        Phrase::AndMore,
+                        &[("items", &examples), ("count", &remaining.to_string())],
+                    )
+                } else {
+                    examples
+                };
+                if let Some(changed_by) = self.changed_by(context, user_id) {
+                    message.push_str(&format!("\n{}", changed_by));
+                }
+
+                (title, message)
+            }
+
+            ListChange::ChangesByUser {
+                list_name,
+                user_id,
+                changes,
+                ..
+            } => {
+                let count = changes.len().to_string();
+                let summary = match user_id {
+                    Some(uid) => {
+                        let user_name = context.user_name(uid);
+                        self.language.format(
+                            Phrase::ChangesByUserTitle,
+                            &[("user", &user_name), ("count", &count), ("list", list_name)],
+                        )
+                    }
+                    None => self.language.format(
+                        Phrase::ChangesTitle,
+                        &[("count", &count), ("list", list_name)],
+                    ),
+                };
+                let title = format!("👤 {}", summary);
+                let message = changes
+                    .iter()
+                    .map(|change| {
+                        format!("{}{}", change_mark(change), change.item_names().join(", "))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                (title, message)
+            }
+
+            ListChange::ListThresholdCrossed {
+                list_name,
+                category,
+                unchecked_count,
+                threshold,
+                direction,
+                ..
+            } => {
+                let (emoji, title_phrase, message_phrase) = match direction {
+                    ThresholdDirection::Above => (
+                        "🛍️",
+                        Phrase::ThresholdAboveTitle,
+                        Phrase::ThresholdAboveMessage,
+                    ),
+                    ThresholdDirection::Below => (
+                        "🧺",
+                        Phrase::ThresholdBelowTitle,
+                        Phrase::ThresholdBelowMessage,
+                    ),
+                };
+                let count = unchecked_count.to_string();
+                let title = match category {
+                    Some(category) => {
+                        let title_phrase = match direction {
+                            ThresholdDirection::Above => Phrase::CategoryThresholdAboveTitle,
+                            ThresholdDirection::Below => Phrase::CategoryThresholdBelowTitle,
+                        };
+                        self.language.format(
+                            title_phrase,
+                            &[
+                                ("list", list_name),
+                                ("count", &count),
+                                ("category", category),
+                            ],
+                        )
+                    }
+                    None => self
+                        .language
+                        .format(title_phrase, &[("list", list_name), ("count", &count)]),
+                };
+                let title = format!("{} {}", emoji, title);
+                let message = self
+                    .language
+                    .format(message_phrase, &[("threshold", &threshold.to_string())]);
+
+                (title, message)
+            }
+
+            ListChange::ItemToggledRepeatedly {
+                list_name,
+                item_name,
+                toggle_count,
+                user_id,
+                ..
+            } => {
+                let title = format!(
+                    "⚠️ {}",
+                    self.language.format(
+                        Phrase::ItemToggledTitle,
+                        &[("item", item_name), ("list", list_name)]
+                    )
+                );
+                let mut message = self.language.format(
+                    Phrase::ToggledTimes,
+                    &[("count", &toggle_count.to_string())],
+                );
+                if let Some(changed_by) = self.changed_by(context, user_id) {
+                    message.push_str(&format!("\n{}", changed_by));
+                }
+
+                (title, message)
+            }
+
+            ListChange::ItemMarkedMaybe {
+                list_name,
+                item,
+                user_id,
+                ..
+            } => {
+                let title = format!(
+                    "🤔 {}",
+                    self.language.format(
+                        Phrase::ItemMaybeTitle,
+                        &[("item", &item.name), ("list", list_name)]
+                    )
+                );
+                let mut message = self.language.get(Phrase::MaybeMessage).to_string();
+                if let Some(changed_by) = self.changed_by(context, user_id) {
+                    message.push_str(&format!("\n{}", changed_by));
+                }
+
+                (title, message)
+            }
+        }
+    }
+}
+
+/// Display names of users, comma-separated
+fn user_names(context: &ChangeContext, user_ids: &[String]) -> String {
+    user_ids
+        .iter()
+        .map(|user_id| context.user_name(user_id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Format an item's quantity, details and category as message lines
+fn format_item_details(item: &ItemInfo, language: &Language) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if let Some(quantity) = &item.quantity {
+        lines.push(format!("{}: {}", language.get(Phrase::Quantity), quantity));
+    }
+    if !item.details.is_empty() {
+        lines.push(format!(
+            "{}: {}",
+            language.get(Phrase::Details),
+            item.details
+        ));
+    }
+    if let Some(category) = &item.category {
+        lines.push(format!("{}: {}", language.get(Phrase::Category), category));
+    }
+
+    lines
+}
+
+/// Compact duration such as "45s", "12m", "3h 5m" or "2d 4h"
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{}s", secs),
+        (0, 0, m) => format!("{}m", m),
+        (0, h, 0) => format!("{}h", h),
+        (0, h, m) => format!("{}h {}m", h, m),
+        (d, 0, _) => format!("{}d", d),
+        (d, h, _) => format!("{}d {}h", d, h),
+    }
+}
+
+/// Marker for an item change in a per-user summary, e.g. "+" for an addition
+fn change_mark(change: &ListChange) -> &'static str {
+    match change {
+        ListChange::ItemAdded { .. } => "+",
+        ListChange::ItemRemoved { .. } => "-",
+        ListChange::ItemChecked { .. } | ListChange::ItemDone { .. } => "✓",
+        ListChange::ItemUnchecked { .. } => "↩",
+        ListChange::ItemAddedAndChecked { .. } => "+✓",
+        ListChange::ItemModified { .. } => "✏️",
+        _ => "",
+    }
+}
+
+/// Non-zero change counts, e.g. "+3, ✓2, -1"
+fn format_counts(counts: &ChangeCounts) -> String {
+    [
+        ("+", counts.added),
+        ("✓", counts.checked),
+        ("↩", counts.unchecked),
+        ("✏️", counts.modified),
+        ("-", counts.removed),
+    ]
+    .iter()
+    .filter(|(_, count)| *count > 0)
+    .map(|(symbol, count)| format!("{}{}", symbol, count))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// Format field changes into a readable message
+/// With `details_as_kv`, details made of `key: value` lines are compared per key
+fn format_field_changes(
+    changes: &[FieldChange],
+    language: &Language,
+    details_as_kv: bool,
+) -> String {
+    let mut parts = Vec::new();
+    let none = language.get(Phrase::NoValue);
+
+    for change in changes {
+        match change {
+            FieldChange::Name { old, new } => {
+                parts.push(format!("{}: {} → {}", language.get(Phrase::Name), old, new));
+            }
+            FieldChange::Details { old, new } => {
+                let kv_changes = if details_as_kv {
+                    format_details_kv_changes(old, new, language)
+                } else {
+                    None
+                };
+
+                if let Some(kv_changes) = kv_changes {
+                    parts.extend(kv_changes);
+                } else if old.is_empty() {
+                    parts.push(format!("{}: {}", language.get(Phrase::DetailsAdded), new));
+                } else if new.is_empty() {
+                    parts.push(format!("{}: {}", language.get(Phrase::DetailsRemoved), old));
+                } else {
+                    parts.push(format!(
+                        "{}: {} → {}",
+                        language.get(Phrase::Details),
+                        old,
+                        new
+                    ));
+                }
+            }
+            FieldChange::Quantity { old, new } if change.is_quantity_depleted() => {
+                let old_str = old.as_deref().unwrap_or(none);
+                match new.as_deref().filter(|q| !q.trim().is_empty()) {
+                    Some(new_str) => parts.push(language.format(
+                        Phrase::QuantityDepleted,
+                        &[("old", old_str), ("new", new_str)],
+                    )),
+                    None => {
+                        parts.push(language.format(Phrase::QuantityCleared, &[("old", old_str)]))
+                    }
+                }
+            }
+            FieldChange::Quantity { old, new } => {
+                let old_str = old.as_deref().unwrap_or(none);
+                let new_str = new.as_deref().unwrap_or(none);
+                parts.push(format!(
+                    "{}: {} → {}",
+                    language.get(Phrase::Quantity),
+                    old_str,
+                    new_str
+                ));
+            }
+            FieldChange::Category { old, new } => {
+                let old_str = old.as_deref().unwrap_or(none);
+                let new_str = new.as_deref().unwrap_or(none);
+                parts.push(format!(
+                    "{}: {} → {}",
+                    language.get(Phrase::Category),
+                    old_str,
+                    new_str
+                ));
+            }
+        }
+    }
+
+    parts.join("\n")
+}
+
+/// Parse details made of `key: value` lines, preserving line order
+/// Returns None if any non-empty line isn't a `key: value` pair or a key repeats
+fn parse_kv_details(details: &str) -> Option<Vec<(&str, &str)>> {
+    let mut pairs: Vec<(&str, &str)> = Vec::new();
+
+    for line in details.lines().filter(|line| !line.trim().is_empty()) {
+        let (key, value) = line.split_once(':')?;
+        let key = key.trim();
+        if key.is_empty() || pairs.iter().any(|(k, _)| *k == key) {
+            return None;
+        }
+        pairs.push((key, value.trim()));
+    }
+
+    Some(pairs)
+}
+
+/// Report only the keys that changed between two structured details values
+/// Returns None when either side isn't structured or no key changed, so the
+/// caller can fall back to the plain diff
+fn format_details_kv_changes(old: &str, new: &str, language: &Language) -> Option<Vec<String>> {
+    if old.trim().is_empty() || new.trim().is_empty() {
+        return None;
+    }
+    let old_pairs = parse_kv_details(old)?;
+    let new_pairs = parse_kv_details(new)?;
+
+    let mut keys: Vec<&str> = old_pairs.iter().map(|(k, _)| *k).collect();
+    for (key, _) in &new_pairs {
+        if !keys.contains(key) {
+            keys.push(key);
+        }
+    }
+
+    let none = language.get(Phrase::NoValue);
+    let prefix = language.get(Phrase::Details).to_lowercase();
+    let lines: Vec<String> = keys
+        .into_iter()
+        .filter_map(|key| {
+            let old_value = kv_value(&old_pairs, key);
+            let new_value = kv_value(&new_pairs, key);
+            (old_value != new_value).then(|| {
+                format!(
+                    "{}.{}: {} → {}",
+                    prefix,
+                    key,
+                    old_value.unwrap_or(none),
+                    new_value.unwrap_or(none)
+                )
+            })
+        })
+        .collect();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines)
+    }
+}
+
+/// Value for a key in parsed `key: value` details
+fn kv_value<'a>(pairs: &[(&str, &'a str)], key: &str) -> Option<&'a str> {
+    pairs.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_field_changes() {
+        let changes = vec![
+            FieldChange::Quantity {
+                old: Some("1".to_string()),
+                new: Some("2".to_string()),
+            },
+            FieldChange::Category {
+                old: None,
+                new: Some("Dairy".to_string()),
+            },
+        ];
+
+        let message = format_field_changes(&changes, &Language::default(), false);
+        assert!(message.contains("Quantity: 1 → 2"));
+        assert!(message.contains("Category: none → Dairy"));
+    }
+
+    #[test]
+    fn test_format_quantity_depleted() {
+        let quantity = |old: Option<&str>, new: Option<&str>| {
+            vec![FieldChange::Quantity {
+                old: old.map(str::to_string),
+                new: new.map(str::to_string),
+            }]
+        };
+        let language = Language::default();
+
+        assert_eq!(
+            format_field_changes(&quantity(None, Some("2")), &language, false),
+            "Quantity: none → 2"
+        );
+        assert_eq!(
+            format_field_changes(&quantity(Some("2"), None), &language, false),
+            "Quantity cleared (was 2)"
+        );
+        assert_eq!(
+            format_field_changes(&quantity(Some("2"), Some("0")), &language, false),
+            "Quantity: 2 → 0 (used up)"
+        );
+    }
+
+    #[test]
+    fn test_format_details_as_kv() {
+        let changes = vec![FieldChange::Details {
+            old: "brand: Kirkland\nsize: 1 gal".to_string(),
+            new: "brand: Horizon\nsize: 1 gal\norganic: yes".to_string(),
+        }];
+
+        let message = format_field_changes(&changes, &Language::default(), true);
+        assert_eq!(
+            message,
+            "details.brand: Kirkland → Horizon\ndetails.organic: none → yes"
+        );
+
+        // Without the option the plain diff is shown
+        let message = format_field_changes(&changes, &Language::default(), false);
+        assert!(message.starts_with("Details: brand: Kirkland"));
+    }
+
+    #[test]
+    fn test_format_details_as_kv_falls_back_for_free_text() {
+        let changes = vec![FieldChange::Details {
+            old: "the big one".to_string(),
+            new: "brand: Horizon".to_string(),
+        }];
+
+        let message = format_field_changes(&changes, &Language::default(), true);
+        assert_eq!(message, "Details: the big one → brand: Horizon");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(12 * 60), "12m");
+        assert_eq!(format_duration(3 * 3600), "3h");
+        assert_eq!(format_duration(3 * 3600 + 5 * 60), "3h 5m");
+        assert_eq!(format_duration(2 * 86_400 + 4 * 3600 + 59), "2d 4h");
+    }
+}
@@ -0,0 +1,737 @@
+/// Fixed phrases used in notifications
+/// Placeholders in braces, such as `{item}`, are filled in by `Language::format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phrase {
+    ItemAddedTitle,
+    AddedTo,
+    ItemRemovedTitle,
+    RemovedFrom,
+    ItemCheckedTitle,
+    CheckedOffIn,
+    ItemAddedAndCheckedTitle,
+    AddedAndCheckedIn,
+    ItemDoneTitle,
+    DoneIn,
+    ItemUncheckedTitle,
+    UncheckedIn,
+    ItemModifiedTitle,
+    ItemDepletedTitle,
+    SharingChangedTitle,
+    UsersAdded,
+    UsersRemoved,
+    ItemsRecategorizedTitle,
+    ChangesByUserTitle,
+    ChangesTitle,
+    ThresholdAboveTitle,
+    ThresholdAboveMessage,
+    ThresholdBelowTitle,
+    ThresholdBelowMessage,
+    CategoryThresholdAboveTitle,
+    CategoryThresholdBelowTitle,
+    AndMore,
+    ItemToggledTitle,
+    ToggledTimes,
+    ChangedBy,
+    ItemId,
+    Name,
+    Quantity,
+    QuantityCleared,
+    QuantityDepleted,
+    Details,
+    DetailsAdded,
+    DetailsRemoved,
+    Category,
+    NoValue,
+    CacheUnavailableTitle,
+    CacheUnavailableMessage,
+    RecoveredTitle,
+    RecoveredMessage,
+    ListSnapshotTitle,
+    NothingToGet,
+    UnknownListTitle,
+    AvailableLists,
+    MissedChangesTitle,
+    MissedChangesMessage,
+    HeartbeatTitle,
+    HeartbeatMessage,
+    HeartbeatNoEvents,
+    DigestMessage,
+    EditingStartedTitle,
+    EditingStartedMessage,
+    WatchedItemAddedTitle,
+    ItemAddedToListsTitle,
+    QuantitySetTitle,
+    QuantitySetIn,
+    ReviewTitle,
+    ReviewApprove,
+    ReviewReject,
+    ItemMaybeTitle,
+    MaybeMessage,
+    ListTotals,
+    ListSharedTitle,
+    ListSharedMessage,
+    ListPrivateTitle,
+    ListPrivateMessage,
+    ListReseededTitle,
+    ListReseededMessage,
+    FullDetailsAttached,
+}
+
+const ENGLISH: &[(Phrase, &str)] = &[
+    (Phrase::ItemAddedTitle, "{item} added to {list}"),
+    (Phrase::AddedTo, "Added to {list}"),
+    (Phrase::ItemRemovedTitle, "{item} removed from {list}"),
+    (Phrase::RemovedFrom, "Removed from {list}"),
+    (Phrase::ItemCheckedTitle, "{item} checked off in {list}"),
+    (Phrase::CheckedOffIn, "Checked off in {list}"),
+    (Phrase::ItemUncheckedTitle, "{item} unchecked in {list}"),
+    (Phrase::UncheckedIn, "Unchecked in {list}"),
+    (Phrase::ItemModifiedTitle, "{item} modified in {list}"),
+    (Phrase::ItemDepletedTitle, "{item} running out in {list}"),
+    (Phrase::SharingChangedTitle, "Sharing changed for {list}"),
+    (Phrase::UsersAdded, "Added: {users}"),
+    (Phrase::UsersRemoved, "Removed: {users}"),
+    (
+        Phrase::ItemsRecategorizedTitle,
+        "{count} items recategorized in {list}",
+    ),
+    (Phrase::AndMore, "{items} and {count} more"),
+    (
+        Phrase::ItemToggledTitle,
+        "{item} is being toggled repeatedly in {list}",
+    ),
+    (
+        Phrase::ToggledTimes,
+        "Checked or unchecked {count} times in quick succession",
+    ),
+    (Phrase::ChangedBy, "Changed by: {user}"),
+    (Phrase::Name, "Name"),
+    (Phrase::Quantity, "Quantity"),
+    (Phrase::QuantityCleared, "Quantity cleared (was {old})"),
+    (
+        Phrase::QuantityDepleted,
+        "Quantity: {old} → {new} (used up)",
+    ),
+    (Phrase::Details, "Details"),
+    (Phrase::DetailsAdded, "Details added"),
+    (Phrase::DetailsRemoved, "Details removed"),
+    (Phrase::Category, "Category"),
+    (Phrase::NoValue, "none"),
+    (
+        Phrase::CacheUnavailableTitle,
+        "AnyList notifier cache unavailable",
+    ),
+    (
+        Phrase::CacheUnavailableMessage,
+        "Changes can't be saved to the local cache: {error}\n\
+         Notifications continue, but repeated changes are held back until the cache recovers.",
+    ),
+    (Phrase::ListSnapshotTitle, "{list}: {count} items to get"),
+    (Phrase::NothingToGet, "Nothing left to get"),
+    (Phrase::UnknownListTitle, "No list named {list}"),
+    (Phrase::AvailableLists, "Available lists: {lists}"),
+    (Phrase::RecoveredTitle, "AnyList notifier recovered"),
+    (Phrase::RecoveredMessage, "Monitoring resumed"),
+    (
+        Phrase::ThresholdAboveTitle,
+        "{list} has {count} items to get",
+    ),
+    (
+        Phrase::ThresholdAboveMessage,
+        "Reached the threshold of {threshold} unchecked items",
+    ),
+    (
+        Phrase::ThresholdBelowTitle,
+        "{list} is down to {count} items",
+    ),
+    (
+        Phrase::ThresholdBelowMessage,
+        "Dropped below the threshold of {threshold} unchecked items",
+    ),
+    (Phrase::ItemDoneTitle, "{item} done in {list}"),
+    (Phrase::DoneIn, "Done in {list}"),
+    (Phrase::ItemId, "Item ID"),
+    (Phrase::MissedChangesTitle, "{list} changed while offline"),
+    (
+        Phrase::MissedChangesMessage,
+        "While offline for {duration}, {list} changed: {counts}",
+    ),
+    (
+        Phrase::ItemAddedAndCheckedTitle,
+        "Added and checked off {item} in {list}",
+    ),
+    (Phrase::AddedAndCheckedIn, "Added and checked off in {list}"),
+    (
+        Phrase::ChangesByUserTitle,
+        "{user} made {count} changes to {list}",
+    ),
+    (Phrase::ChangesTitle, "{count} changes to {list}"),
+    (Phrase::HeartbeatTitle, "AnyList notifier healthy"),
+    (
+        Phrase::HeartbeatMessage,
+        "Watching {count} lists, last event {duration} ago",
+    ),
+    (
+        Phrase::HeartbeatNoEvents,
+        "Watching {count} lists, no events received yet",
+    ),
+    (Phrase::ListReseededTitle, "{list} re-synced"),
+    (Phrase::ListReseededMessage, "{count} changes at once looked like a change on AnyList's side rather than edits, so they weren't notified"),
+    (Phrase::FullDetailsAttached, "full details attached"),
+    (Phrase::CategoryThresholdAboveTitle, "{list} has {count} {category} items to get"),
+    (Phrase::CategoryThresholdBelowTitle, "{list} is down to {count} {category} items"),
+    (Phrase::DigestMessage, "In the last {duration}: {counts}"),
+    (Phrase::EditingStartedTitle, "{user} is editing {list}…"),
+    (Phrase::EditingStartedMessage, "Changes are summarized once editing stops"),
+    (Phrase::WatchedItemAddedTitle, "Watched item added: {item}"),
+    (Phrase::ItemAddedToListsTitle, "{item} added to {count} lists"),
+    (Phrase::QuantitySetTitle, "Quantity set for {item}: {quantity}"),
+    (Phrase::QuantitySetIn, "In {list}"),
+    (Phrase::ReviewTitle, "Review #{id}: {title}"),
+    (Phrase::ReviewApprove, "Approve"),
+    (Phrase::ReviewReject, "Reject"),
+    (Phrase::ItemMaybeTitle, "{item} marked as maybe in {list}"),
+    (Phrase::MaybeMessage, "Checked and unchecked in quick succession; muted until it settles"),
+    (Phrase::ListTotals, "({list} now has {total} items, {unchecked} unchecked)"),
+    (Phrase::ListSharedTitle, "{list} is now shared"),
+    (Phrase::ListSharedMessage, "Shared with: {users}"),
+    (Phrase::ListPrivateTitle, "{list} is now private"),
+    (Phrase::ListPrivateMessage, "No longer shared with: {users}"),
+];
+
+const GERMAN: &[(Phrase, &str)] = &[
+    (Phrase::ItemAddedTitle, "{item} zu {list} hinzugefügt"),
+    (Phrase::AddedTo, "Zu {list} hinzugefügt"),
+    (Phrase::ItemRemovedTitle, "{item} aus {list} entfernt"),
+    (Phrase::RemovedFrom, "Aus {list} entfernt"),
+    (Phrase::ItemCheckedTitle, "{item} in {list} abgehakt"),
+    (Phrase::CheckedOffIn, "In {list} abgehakt"),
+    (
+        Phrase::ItemUncheckedTitle,
+        "{item} in {list} nicht mehr abgehakt",
+    ),
+    (Phrase::UncheckedIn, "In {list} nicht mehr abgehakt"),
+    (Phrase::ItemModifiedTitle, "{item} in {list} geändert"),
+    (Phrase::ItemDepletedTitle, "{item} in {list} geht zur Neige"),
+    (Phrase::SharingChangedTitle, "Freigabe für {list} geändert"),
+    (Phrase::UsersAdded, "Hinzugefügt: {users}"),
+    (Phrase::UsersRemoved, "Entfernt: {users}"),
+    (
+        Phrase::ItemsRecategorizedTitle,
+        "{count} Artikel in {list} neu kategorisiert",
+    ),
+    (Phrase::AndMore, "{items} und {count} weitere"),
+    (
+        Phrase::ItemToggledTitle,
+        "{item} wird in {list} wiederholt abgehakt",
+    ),
+    (
+        Phrase::ToggledTimes,
+        "{count}-mal kurz hintereinander abgehakt oder nicht mehr abgehakt",
+    ),
+    (Phrase::ChangedBy, "Geändert von: {user}"),
+    (Phrase::Name, "Name"),
+    (Phrase::Quantity, "Menge"),
+    (Phrase::QuantityCleared, "Menge entfernt (war {old})"),
+    (
+        Phrase::QuantityDepleted,
+        "Menge: {old} → {new} (aufgebraucht)",
+    ),
+    (Phrase::Details, "Details"),
+    (Phrase::DetailsAdded, "Details hinzugefügt"),
+    (Phrase::DetailsRemoved, "Details entfernt"),
+    (Phrase::Category, "Kategorie"),
+    (Phrase::NoValue, "keine"),
+    (
+        Phrase::CacheUnavailableTitle,
+        "Cache des AnyList-Benachrichtigers nicht verfügbar",
+    ),
+    (
+        Phrase::CacheUnavailableMessage,
+        "Änderungen können nicht im lokalen Cache gespeichert werden: {error}\n\
+         Benachrichtigungen laufen weiter, wiederholte Änderungen werden aber zurückgehalten, \
+         bis der Cache wieder verfügbar ist.",
+    ),
+    (
+        Phrase::ListSnapshotTitle,
+        "{list}: {count} Artikel zu besorgen",
+    ),
+    (Phrase::NothingToGet, "Nichts mehr zu besorgen"),
+    (Phrase::UnknownListTitle, "Keine Liste namens {list}"),
+    (Phrase::AvailableLists, "Verfügbare Listen: {lists}"),
+    (
+        Phrase::RecoveredTitle,
+        "AnyList-Benachrichtiger wiederhergestellt",
+    ),
+    (Phrase::RecoveredMessage, "Überwachung wird fortgesetzt"),
+    (
+        Phrase::ThresholdAboveTitle,
+        "{list} hat {count} offene Artikel",
+    ),
+    (
+        Phrase::ThresholdAboveMessage,
+        "Schwelle von {threshold} offenen Artikeln erreicht",
+    ),
+    (
+        Phrase::ThresholdBelowTitle,
+        "{list} hat nur noch {count} offene Artikel",
+    ),
+    (
+        Phrase::ThresholdBelowMessage,
+        "Unter die Schwelle von {threshold} offenen Artikeln gefallen",
+    ),
+    (Phrase::ItemDoneTitle, "{item} in {list} erledigt"),
+    (Phrase::DoneIn, "In {list} erledigt"),
+    (Phrase::ItemId, "Artikel-ID"),
+    (Phrase::MissedChangesTitle, "{list} wurde offline geändert"),
+    (
+        Phrase::MissedChangesMessage,
+        "Während {duration} offline wurde {list} geändert: {counts}",
+    ),
+    (
+        Phrase::ItemAddedAndCheckedTitle,
+        "{item} in {list} hinzugefügt und abgehakt",
+    ),
+    (
+        Phrase::AddedAndCheckedIn,
+        "In {list} hinzugefügt und abgehakt",
+    ),
+    (
+        Phrase::ChangesByUserTitle,
+        "{user} hat {count} Änderungen an {list} vorgenommen",
+    ),
+    (Phrase::ChangesTitle, "{count} Änderungen an {list}"),
+    (Phrase::HeartbeatTitle, "AnyList-Benachrichtigung läuft"),
+    (
+        Phrase::HeartbeatMessage,
+        "Überwacht {count} Listen, letztes Ereignis vor {duration}",
+    ),
+    (
+        Phrase::HeartbeatNoEvents,
+        "Überwacht {count} Listen, noch keine Ereignisse empfangen",
+    ),
+    (Phrase::ListReseededTitle, "{list} neu synchronisiert"),
+    (Phrase::ListReseededMessage, "{count} Änderungen auf einmal sahen nach einer Änderung bei AnyList statt nach Bearbeitungen aus und wurden nicht gemeldet"),
+    (Phrase::FullDetailsAttached, "vollständige Details im Anhang"),
+    (Phrase::CategoryThresholdAboveTitle, "{list} hat {count} offene Artikel in {category}"),
+    (Phrase::CategoryThresholdBelowTitle, "{list} hat nur noch {count} offene Artikel in {category}"),
+    (Phrase::DigestMessage, "In den letzten {duration}: {counts}"),
+    (Phrase::EditingStartedTitle, "{user} bearbeitet {list}…"),
+    (Phrase::EditingStartedMessage, "Änderungen werden zusammengefasst, sobald die Bearbeitung endet"),
+    (Phrase::WatchedItemAddedTitle, "Beobachteter Artikel hinzugefügt: {item}"),
+    (Phrase::ItemAddedToListsTitle, "{item} zu {count} Listen hinzugefügt"),
+    (Phrase::QuantitySetTitle, "Menge für {item} festgelegt: {quantity}"),
+    (Phrase::QuantitySetIn, "In {list}"),
+    (Phrase::ReviewTitle, "Prüfung #{id}: {title}"),
+    (Phrase::ReviewApprove, "Freigeben"),
+    (Phrase::ReviewReject, "Ablehnen"),
+    (Phrase::ItemMaybeTitle, "{item} in {list} als vielleicht markiert"),
+    (Phrase::MaybeMessage, "Kurz hintereinander abgehakt und nicht mehr abgehakt; stumm, bis es sich beruhigt"),
+    (Phrase::ListTotals, "({list} hat jetzt {total} Artikel, {unchecked} nicht abgehakt)"),
+    (Phrase::ListSharedTitle, "{list} ist jetzt geteilt"),
+    (Phrase::ListSharedMessage, "Geteilt mit: {users}"),
+    (Phrase::ListPrivateTitle, "{list} ist jetzt privat"),
+    (Phrase::ListPrivateMessage, "Nicht mehr geteilt mit: {users}"),
+];
+
+const FRENCH: &[(Phrase, &str)] = &[
+    (Phrase::ItemAddedTitle, "{item} ajouté à {list}"),
+    (Phrase::AddedTo, "Ajouté à {list}"),
+    (Phrase::ItemRemovedTitle, "{item} retiré de {list}"),
+    (Phrase::RemovedFrom, "Retiré de {list}"),
+    (Phrase::ItemCheckedTitle, "{item} coché dans {list}"),
+    (Phrase::CheckedOffIn, "Coché dans {list}"),
+    (Phrase::ItemUncheckedTitle, "{item} décoché dans {list}"),
+    (Phrase::UncheckedIn, "Décoché dans {list}"),
+    (Phrase::ItemModifiedTitle, "{item} modifié dans {list}"),
+    (
+        Phrase::ItemDepletedTitle,
+        "{item} bientôt épuisé dans {list}",
+    ),
+    (Phrase::SharingChangedTitle, "Partage modifié pour {list}"),
+    (Phrase::UsersAdded, "Ajoutés : {users}"),
+    (Phrase::UsersRemoved, "Retirés : {users}"),
+    (
+        Phrase::ItemsRecategorizedTitle,
+        "{count} articles recatégorisés dans {list}",
+    ),
+    (Phrase::AndMore, "{items} et {count} de plus"),
+    (
+        Phrase::ItemToggledTitle,
+        "{item} est coché et décoché à répétition dans {list}",
+    ),
+    (
+        Phrase::ToggledTimes,
+        "Coché ou décoché {count} fois en peu de temps",
+    ),
+    (Phrase::ChangedBy, "Modifié par : {user}"),
+    (Phrase::Name, "Nom"),
+    (Phrase::Quantity, "Quantité"),
+    (Phrase::QuantityCleared, "Quantité effacée (était {old})"),
+    (
+        Phrase::QuantityDepleted,
+        "Quantité : {old} → {new} (épuisé)",
+    ),
+    (Phrase::Details, "Détails"),
+    (Phrase::DetailsAdded, "Détails ajoutés"),
+    (Phrase::DetailsRemoved, "Détails retirés"),
+    (Phrase::Category, "Catégorie"),
+    (Phrase::NoValue, "aucune"),
+    (
+        Phrase::CacheUnavailableTitle,
+        "Cache du notificateur AnyList indisponible",
+    ),
+    (
+        Phrase::CacheUnavailableMessage,
+        "Impossible d'enregistrer les modifications dans le cache local : {error}\n\
+         Les notifications continuent, mais les modifications répétées sont retenues \
+         jusqu'au rétablissement du cache.",
+    ),
+    (
+        Phrase::ListSnapshotTitle,
+        "{list} : {count} articles à acheter",
+    ),
+    (Phrase::NothingToGet, "Plus rien à acheter"),
+    (Phrase::UnknownListTitle, "Aucune liste nommée {list}"),
+    (Phrase::AvailableLists, "Listes disponibles : {lists}"),
+    (Phrase::RecoveredTitle, "Notificateur AnyList rétabli"),
+    (Phrase::RecoveredMessage, "Surveillance reprise"),
+    (
+        Phrase::ThresholdAboveTitle,
+        "{list} a {count} articles à acheter",
+    ),
+    (
+        Phrase::ThresholdAboveMessage,
+        "Seuil de {threshold} articles non cochés atteint",
+    ),
+    (
+        Phrase::ThresholdBelowTitle,
+        "{list} n'a plus que {count} articles",
+    ),
+    (
+        Phrase::ThresholdBelowMessage,
+        "Repassé sous le seuil de {threshold} articles non cochés",
+    ),
+    (Phrase::ItemDoneTitle, "{item} terminé dans {list}"),
+    (Phrase::DoneIn, "Terminé dans {list}"),
+    (Phrase::ItemId, "ID de l'article"),
+    (Phrase::MissedChangesTitle, "{list} modifiée hors ligne"),
+    (
+        Phrase::MissedChangesMessage,
+        "Hors ligne pendant {duration}, {list} a changé : {counts}",
+    ),
+    (
+        Phrase::ItemAddedAndCheckedTitle,
+        "{item} ajouté et coché dans {list}",
+    ),
+    (Phrase::AddedAndCheckedIn, "Ajouté et coché dans {list}"),
+    (
+        Phrase::ChangesByUserTitle,
+        "{user} a fait {count} modifications dans {list}",
+    ),
+    (Phrase::ChangesTitle, "{count} modifications dans {list}"),
+    (Phrase::HeartbeatTitle, "Notificateur AnyList opérationnel"),
+    (
+        Phrase::HeartbeatMessage,
+        "Surveille {count} listes, dernier événement il y a {duration}",
+    ),
+    (
+        Phrase::HeartbeatNoEvents,
+        "Surveille {count} listes, aucun événement reçu pour l'instant",
+    ),
+    (Phrase::ListReseededTitle, "{list} resynchronisée"),
+    (Phrase::ListReseededMessage, "{count} modifications d'un coup ressemblaient à un changement côté AnyList plutôt qu'à des modifications, elles n'ont pas été notifiées"),
+    (Phrase::FullDetailsAttached, "détails complets en pièce jointe"),
+    (Phrase::CategoryThresholdAboveTitle, "{list} a {count} articles à acheter dans {category}"),
+    (Phrase::CategoryThresholdBelowTitle, "{list} n'a plus que {count} articles dans {category}"),
+    (Phrase::DigestMessage, "Depuis {duration} : {counts}"),
+    (Phrase::EditingStartedTitle, "{user} modifie {list}…"),
+    (Phrase::EditingStartedMessage, "Les modifications seront résumées à la fin de l'édition"),
+    (Phrase::WatchedItemAddedTitle, "Article surveillé ajouté : {item}"),
+    (Phrase::ItemAddedToListsTitle, "{item} ajouté à {count} listes"),
+    (Phrase::QuantitySetTitle, "Quantité définie pour {item} : {quantity}"),
+    (Phrase::QuantitySetIn, "Dans {list}"),
+    (Phrase::ReviewTitle, "Validation n° {id} : {title}"),
+    (Phrase::ReviewApprove, "Approuver"),
+    (Phrase::ReviewReject, "Rejeter"),
+    (Phrase::ItemMaybeTitle, "{item} marqué comme peut-être dans {list}"),
+    (Phrase::MaybeMessage, "Coché puis décoché en peu de temps ; en sourdine jusqu'à ce qu'il se stabilise"),
+    (Phrase::ListTotals, "({list} compte maintenant {total} articles, {unchecked} non cochés)"),
+    (Phrase::ListSharedTitle, "{list} est maintenant partagée"),
+    (Phrase::ListSharedMessage, "Partagée avec : {users}"),
+    (Phrase::ListPrivateTitle, "{list} est maintenant privée"),
+    (Phrase::ListPrivateMessage, "N'est plus partagée avec : {users}"),
+];
+
+const SPANISH: &[(Phrase, &str)] = &[
+    (Phrase::ItemAddedTitle, "{item} añadido a {list}"),
+    (Phrase::AddedTo, "Añadido a {list}"),
+    (Phrase::ItemRemovedTitle, "{item} eliminado de {list}"),
+    (Phrase::RemovedFrom, "Eliminado de {list}"),
+    (Phrase::ItemCheckedTitle, "{item} marcado en {list}"),
+    (Phrase::CheckedOffIn, "Marcado en {list}"),
+    (Phrase::ItemUncheckedTitle, "{item} desmarcado en {list}"),
+    (Phrase::UncheckedIn, "Desmarcado en {list}"),
+    (Phrase::ItemModifiedTitle, "{item} modificado en {list}"),
+    (
+        Phrase::ItemDepletedTitle,
+        "{item} se está agotando en {list}",
+    ),
+    (
+        Phrase::SharingChangedTitle,
+        "Uso compartido cambiado en {list}",
+    ),
+    (Phrase::UsersAdded, "Añadidos: {users}"),
+    (Phrase::UsersRemoved, "Eliminados: {users}"),
+    (
+        Phrase::ItemsRecategorizedTitle,
+        "{count} artículos recategorizados en {list}",
+    ),
+    (Phrase::AndMore, "{items} y {count} más"),
+    (
+        Phrase::ItemToggledTitle,
+        "{item} se marca y desmarca repetidamente en {list}",
+    ),
+    (
+        Phrase::ToggledTimes,
+        "Marcado o desmarcado {count} veces seguidas",
+    ),
+    (Phrase::ChangedBy, "Cambiado por: {user}"),
+    (Phrase::Name, "Nombre"),
+    (Phrase::Quantity, "Cantidad"),
+    (Phrase::QuantityCleared, "Cantidad borrada (era {old})"),
+    (
+        Phrase::QuantityDepleted,
+        "Cantidad: {old} → {new} (agotado)",
+    ),
+    (Phrase::Details, "Detalles"),
+    (Phrase::DetailsAdded, "Detalles añadidos"),
+    (Phrase::DetailsRemoved, "Detalles eliminados"),
+    (Phrase::Category, "Categoría"),
+    (Phrase::NoValue, "ninguna"),
+    (
+        Phrase::CacheUnavailableTitle,
+        "Caché del notificador de AnyList no disponible",
+    ),
+    (
+        Phrase::CacheUnavailableMessage,
+        "No se pueden guardar los cambios en la caché local: {error}\n\
+         Las notificaciones continúan, pero los cambios repetidos se retienen \
+         hasta que la caché se recupere.",
+    ),
+    (
+        Phrase::ListSnapshotTitle,
+        "{list}: {count} artículos por comprar",
+    ),
+    (Phrase::NothingToGet, "No queda nada por comprar"),
+    (
+        Phrase::UnknownListTitle,
+        "No hay ninguna lista llamada {list}",
+    ),
+    (Phrase::AvailableLists, "Listas disponibles: {lists}"),
+    (Phrase::RecoveredTitle, "Notificador de AnyList recuperado"),
+    (Phrase::RecoveredMessage, "Supervisión reanudada"),
+    (
+        Phrase::ThresholdAboveTitle,
+        "{list} tiene {count} artículos por comprar",
+    ),
+    (
+        Phrase::ThresholdAboveMessage,
+        "Se alcanzó el umbral de {threshold} artículos sin marcar",
+    ),
+    (
+        Phrase::ThresholdBelowTitle,
+        "{list} baja a {count} artículos",
+    ),
+    (
+        Phrase::ThresholdBelowMessage,
+        "Por debajo del umbral de {threshold} artículos sin marcar",
+    ),
+    (Phrase::ItemDoneTitle, "{item} listo en {list}"),
+    (Phrase::DoneIn, "Listo en {list}"),
+    (Phrase::ItemId, "ID del artículo"),
+    (Phrase::MissedChangesTitle, "{list} cambió sin conexión"),
+    (
+        Phrase::MissedChangesMessage,
+        "Sin conexión durante {duration}, {list} cambió: {counts}",
+    ),
+    (
+        Phrase::ItemAddedAndCheckedTitle,
+        "{item} añadido y marcado en {list}",
+    ),
+    (Phrase::AddedAndCheckedIn, "Añadido y marcado en {list}"),
+    (
+        Phrase::ChangesByUserTitle,
+        "{user} hizo {count} cambios en {list}",
+    ),
+    (Phrase::ChangesTitle, "{count} cambios en {list}"),
+    (Phrase::HeartbeatTitle, "Notificador de AnyList activo"),
+    (
+        Phrase::HeartbeatMessage,
+        "Vigilando {count} listas, último evento hace {duration}",
+    ),
+    (
+        Phrase::HeartbeatNoEvents,
+        "Vigilando {count} listas, aún no se recibieron eventos",
+    ),
+    (Phrase::ListReseededTitle, "{list} resincronizada"),
+    (Phrase::ListReseededMessage, "{count} cambios a la vez parecían un cambio de AnyList y no ediciones, así que no se notificaron"),
+    (Phrase::FullDetailsAttached, "detalles completos adjuntos"),
+    (Phrase::CategoryThresholdAboveTitle, "{list} tiene {count} artículos por comprar en {category}"),
+    (Phrase::CategoryThresholdBelowTitle, "{list} baja a {count} artículos en {category}"),
+    (Phrase::DigestMessage, "En las últimas {duration}: {counts}"),
+    (Phrase::EditingStartedTitle, "{user} está editando {list}…"),
+    (Phrase::EditingStartedMessage, "Los cambios se resumirán cuando termine la edición"),
+    (Phrase::WatchedItemAddedTitle, "Artículo vigilado añadido: {item}"),
+    (Phrase::ItemAddedToListsTitle, "{item} añadido a {count} listas"),
+    (Phrase::QuantitySetTitle, "Cantidad fijada para {item}: {quantity}"),
+    (Phrase::QuantitySetIn, "En {list}"),
+    (Phrase::ReviewTitle, "Revisión n.º {id}: {title}"),
+    (Phrase::ReviewApprove, "Aprobar"),
+    (Phrase::ReviewReject, "Rechazar"),
+    (Phrase::ItemMaybeTitle, "{item} marcado como quizás en {list}"),
+    (Phrase::MaybeMessage, "Marcado y desmarcado seguidamente; silenciado hasta que se estabilice"),
+    (Phrase::ListTotals, "({list} tiene ahora {total} artículos, {unchecked} sin marcar)"),
+    (Phrase::ListSharedTitle, "{list} ahora está compartida"),
+    (Phrase::ListSharedMessage, "Compartida con: {users}"),
+    (Phrase::ListPrivateTitle, "{list} ahora es privada"),
+    (Phrase::ListPrivateMessage, "Ya no se comparte con: {users}"),
+];
+
+/// Supported language codes and their phrase tables
+const LANGUAGES: &[(&str, &[(Phrase, &str)])] = &[
+    ("en", ENGLISH),
+    ("de", GERMAN),
+    ("fr", FRENCH),
+    ("es", SPANISH),
+];
+
+/// A built-in language pack for notification phrases
+#[derive(Debug, Clone, Copy)]
+pub struct Language {
+    phrases: &'static [(Phrase, &'static str)],
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Self { phrases: ENGLISH }
+    }
+}
+
+impl Language {
+    /// Look up a language pack by code, e.g. "de"
+    pub fn from_code(code: &str) -> Option<Self> {
+        LANGUAGES
+            .iter()
+            .find(|(c, _)| c.eq_ignore_ascii_case(code.trim()))
+            .map(|(_, phrases)| Self { phrases })
+    }
+
+    /// Codes of all built-in language packs
+    pub fn supported_codes() -> Vec<&'static str> {
+        LANGUAGES.iter().map(|(code, _)| *code).collect()
+    }
+
+    /// Get a phrase, falling back to English if the pack doesn't define it
+    pub fn get(&self, phrase: Phrase) -> &'static str {
+        lookup(self.phrases, phrase)
+            .or_else(|| lookup(ENGLISH, phrase))
+            .unwrap_or_default()
+    }
+
+    /// Get a phrase with its `{name}` placeholders replaced
+    ///
+    /// The phrase is scanned once, so braces inside substituted values, e.g.
+    /// an item named "{list}", are kept as they are. Unknown placeholders are
+    /// left in place.
+    pub fn format(&self, phrase: Phrase, args: &[(&str, &str)]) -> String {
+        let mut text = String::new();
+        let mut rest = self.get(phrase);
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let value = after.find('}').and_then(|end| {
+                let name = &after[..end];
+                args.iter()
+                    .find(|(arg, _)| *arg == name)
+                    .map(|(_, value)| (*value, end))
+            });
+            match value {
+                Some((value, end)) => {
+                    text.push_str(value);
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    text.push('{');
+                    rest = after;
+                }
+            }
+        }
+        text.push_str(rest);
+        text
+    }
+}
+
+fn lookup(phrases: &[(Phrase, &'static str)], phrase: Phrase) -> Option<&'static str> {
+    phrases
+        .iter()
+        .find(|(p, _)| *p == phrase)
+        .map(|(_, text)| *text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_code() {
+        assert!(Language::from_code("de").is_some());
+        assert!(Language::from_code("FR").is_some());
+        assert!(Language::from_code("xx").is_none());
+    }
+
+    #[test]
+    fn test_format() {
+        let german = Language::from_code("de").unwrap();
+        assert_eq!(
+            german.format(
+                Phrase::ItemAddedTitle,
+                &[("item", "Milch"), ("list", "Einkauf")]
+            ),
+            "Milch zu Einkauf hinzugefügt"
+        );
+
+        // Values are inserted as-is, even if they look like placeholders
+        assert_eq!(
+            german.format(
+                Phrase::ItemAddedTitle,
+                &[("item", "{list}"), ("list", "Einkauf")]
+            ),
+            "{list} zu Einkauf hinzugefügt"
+        );
+    }
+
+    #[test]
+    fn test_falls_back_to_english() {
+        let partial = Language {
+            phrases: &[(Phrase::Quantity, "Menge")],
+        };
+
+        assert_eq!(partial.get(Phrase::Quantity), "Menge");
+        assert_eq!(partial.get(Phrase::Category), "Category");
+    }
+
+    #[test]
+    fn test_built_in_packs_are_complete() {
+        for (code, phrases) in LANGUAGES {
+            for (phrase, _) in ENGLISH {
+                assert!(
+                    lookup(phrases, *phrase).is_some(),
+                    "{:?} missing in {}",
+                    phrase,
+                    code
+                );
+            }
+        }
+    }
+}
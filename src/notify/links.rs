@@ -0,0 +1,77 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Characters that end a sentence rather than the link it follows
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', ')', ']', '\''];
+
+fn url_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r#"https?://[^\s<>"]+"#).expect("valid URL pattern"))
+}
+
+/// North American numbers with or without separators, or any number written with a `+` prefix
+fn phone_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(\+\d[\d\s().-]{6,}\d|\(?\b\d{3}\)?[\s.-]?\d{3}[\s.-]?\d{4}\b)")
+            .expect("valid phone pattern")
+    })
+}
+
+/// The first web link in some text
+pub fn first_url(text: &str) -> Option<&str> {
+    let url = url_pattern()
+        .find(text)?
+        .as_str()
+        .trim_end_matches(TRAILING_PUNCTUATION);
+    // A bare scheme isn't a link
+    url.split_once("://")
+        .filter(|(_, rest)| !rest.is_empty())
+        .map(|_| url)
+}
+
+/// The first phone number in some text, ignoring digits that are part of a link
+pub fn first_phone(text: &str) -> Option<String> {
+    let without_urls = url_pattern().replace_all(text, " ");
+    phone_pattern()
+        .find(&without_urls)
+        .map(|phone| phone.as_str().trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_url() {
+        assert_eq!(
+            first_url("Order at https://example.com/milk?id=2. Or http://other.org"),
+            Some("https://example.com/milk?id=2")
+        );
+        assert_eq!(
+            first_url("(see https://example.com/a)"),
+            Some("https://example.com/a")
+        );
+        assert_eq!(first_url("https:// nothing"), None);
+        assert_eq!(first_url("2 gallons, brand: Horizon"), None);
+    }
+
+    #[test]
+    fn test_first_phone() {
+        assert_eq!(
+            first_phone("Pharmacy: (555) 123-4567, ask for Sam"),
+            Some("(555) 123-4567".to_string())
+        );
+        assert_eq!(
+            first_phone("call +44 20 7946 0958"),
+            Some("+44 20 7946 0958".to_string())
+        );
+        assert_eq!(
+            first_phone("555.123.4567"),
+            Some("555.123.4567".to_string())
+        );
+        assert_eq!(first_phone("best before 2024-01-15"), None);
+        assert_eq!(first_phone("2 gallons"), None);
+        assert_eq!(first_phone("https://example.com/5551234567"), None);
+    }
+}
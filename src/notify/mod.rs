@@ -1,3 +1,178 @@
+pub mod ascii;
+pub mod breaker;
+pub mod change_log;
+pub mod commands;
+pub mod discord;
+pub mod display;
+pub mod dry_run;
+pub mod emoji;
+pub mod exec_hook;
+pub mod format;
+pub mod i18n;
+pub mod links;
 pub mod ntfy;
+pub mod slack;
+pub mod unix_socket;
+pub mod webhook;
 
+pub use breaker::BreakerNotifier;
+pub use change_log::ChangeLogNotifier;
+pub use commands::CommandListener;
+pub use discord::DiscordClient;
+pub use dry_run::DryRunNotifier;
+pub use exec_hook::ExecHookNotifier;
 pub use ntfy::NtfyClient;
+pub use slack::SlackClient;
+pub use unix_socket::UnixSocketNotifier;
+
+use crate::sync::diff::{ChangeCounts, ListChange, ListTotals};
+use crate::sync::UserNames;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// An event about the service itself rather than a list
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceEvent {
+    /// Writes to the local cache are failing
+    CacheUnavailable { error: String },
+    /// The service recovered from a degraded state and is monitoring again
+    Recovered,
+    /// The unchecked items of a list, requested through the command topic
+    ListSnapshot {
+        list_name: String,
+        items: Vec<String>,
+    },
+    /// A command asked for a list that doesn't exist
+    UnknownList {
+        list_name: String,
+        available: Vec<String>,
+    },
+    /// A list changed while the service wasn't running
+    MissedChanges {
+        list_name: String,
+        offline_secs: u64,
+        counts: ChangeCounts,
+    },
+    /// A list's changes were too many to be real edits, so the cache was
+    /// re-seeded from the live list without notifying them
+    ListReseeded { list_name: String, changes: usize },
+    /// Scheduled summary of a list's changes since the previous digest
+    Digest {
+        list_name: String,
+        period_secs: u64,
+        counts: ChangeCounts,
+    },
+    /// An item from `watch_items` was added to a list
+    WatchedItemAdded {
+        list_name: String,
+        item_name: String,
+        user_name: Option<String>,
+    },
+    /// A user started making many changes to a list in quick succession
+    EditingStarted { list_name: String, user_name: String },
+    /// Scheduled "still alive" notification
+    Heartbeat {
+        watched_lists: usize,
+        /// Seconds since the last list change event, if one was ever received
+        last_event_secs: Option<u64>,
+    },
+}
+
+/// Details every backend needs to render a change, resolved once for all of them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChangeContext {
+    /// Display names of the users the change mentions, by user ID
+    user_names: HashMap<String, String>,
+    /// Size of the change's list after the change, with `include_list_totals`
+    ///
+    /// Only set on change notifications; audit and review notifications carry
+    /// no totals
+    pub list_totals: Option<ListTotals>,
+}
+
+impl ChangeContext {
+    /// Resolve the display names of every user the change mentions
+    pub async fn resolve(change: &ListChange, user_names: &UserNames) -> Self {
+        let mut names = HashMap::new();
+        for user_id in change.user_ids() {
+            if !names.contains_key(user_id) {
+                names.insert(user_id.to_string(), user_names.resolve(user_id).await);
+            }
+        }
+        Self {
+            user_names: names,
+            list_totals: None,
+        }
+    }
+
+    /// Context with the given user names and no list totals
+    #[cfg(test)]
+    pub fn from_names(user_names: HashMap<String, String>) -> Self {
+        Self {
+            user_names,
+            list_totals: None,
+        }
+    }
+
+    /// Display name of a user, falling back to the ID itself
+    pub fn user_name(&self, user_id: &str) -> String {
+        self.user_names
+            .get(user_id)
+            .cloned()
+            .unwrap_or_else(|| user_id.to_string())
+    }
+}
+
+/// A notification backend that delivers list changes
+///
+/// Each backend renders changes itself from the change and its context, so
+/// it can use whatever format suits the channel.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Short backend name used in logs and configuration
+    fn name(&self) -> &str;
+
+    /// Whether the backend pushes notifications to people, rather than
+    /// recording changes locally; only push backends are quiet in digest mode
+    fn is_push(&self) -> bool {
+        true
+    }
+
+    /// Send a notification for a list change
+    async fn notify(&self, change: &ListChange, context: &ChangeContext) -> Result<()>;
+
+    /// Send a notification about the service itself
+    async fn notify_service(&self, event: &ServiceEvent) -> Result<()>;
+
+    /// Record a change in the backend's audit trail, if it keeps one
+    /// Unlike `notify`, this receives every detected change, including filtered ones
+    async fn audit(&self, _change: &ListChange, _context: &ChangeContext) -> Result<()> {
+        Ok(())
+    }
+
+    /// Ask a moderator to approve or reject a held change, if the backend takes reviews
+    async fn request_review(
+        &self,
+        _review_id: i64,
+        _change: &ListChange,
+        _context: &ChangeContext,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Wait for work the backend still has running in the background, before
+    /// the service exits
+    async fn shutdown(&self) {}
+
+    /// Render a change the way `notify` would send it, for dry runs
+    fn render(&self, change: &ListChange, _context: &ChangeContext) -> Result<String> {
+        serde_json::to_string(change).context("Failed to serialize change")
+    }
+
+    /// Render a service event the way `notify_service` would send it, for dry
+    /// runs; `None` for backends that don't send service events
+    fn render_service(&self, _event: &ServiceEvent) -> Result<Option<String>> {
+        Ok(None)
+    }
+}
@@ -1,16 +1,36 @@
-use crate::config::NtfyConfig;
+use super::format::{Formatter, ModifiedKind};
+use super::i18n::{Language, Phrase};
+use super::links::first_url;
+use super::{ChangeContext, Notifier, ServiceEvent};
+use crate::config::{ListOverrideConfig, NotificationsConfig, NtfyConfig, Priority};
 use crate::sync::diff::{FieldChange, ListChange};
 use anyhow::{Context, Result};
-use reqwest::Client;
-use serde::Serialize;
+use async_trait::async_trait;
+use reqwest::header::AUTHORIZATION;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::{Serialize, Serializer};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tracing::{debug, error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// Longest start of an overflowing body kept in its summary, in characters
+const OVERFLOW_SUMMARY_CHARS: usize = 120;
+
+/// Name of the attachment holding an overflowing body
+const OVERFLOW_FILENAME: &str = "changes.txt";
+
+/// Longest wait between two retries of a publish
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
 
 pub struct NtfyClient {
     client: Client,
     config: NtfyConfig,
-    user_names: Arc<tokio::sync::RwLock<HashMap<String, String>>>,
+    notifications: NotificationsConfig,
+    list_overrides: Vec<ListOverrideConfig>,
+    formatter: Formatter,
+    /// Set once a publish has run out of retries, until one succeeds
+    failing: AtomicBool,
 }
 
 #[derive(Debug, Serialize)]
@@ -18,192 +38,604 @@ struct NtfyMessage {
     topic: String,
     title: String,
     message: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_priority"
+    )]
     priority: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tags: Option<Vec<String>>,
+    /// Phone number for ntfy to call, or "yes" for the account's default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call: Option<String>,
+    /// URL opened when the notification is tapped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    click: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actions: Option<Vec<NtfyAction>>,
+}
+
+/// A notification button that sends an HTTP request when tapped
+#[derive(Debug, Clone, Serialize)]
+struct NtfyAction {
+    action: &'static str,
+    label: String,
+    url: String,
+    method: &'static str,
+    body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers: Option<HashMap<String, String>>,
+    /// Dismiss the notification once the request succeeds
+    clear: bool,
+}
+
+impl NtfyAction {
+    /// The action in the one-line form ntfy reads from the `Actions` header
+    fn to_header(&self) -> String {
+        let mut header = format!(
+            "{}, {}, {}, method={}, body={}, clear={}",
+            self.action, self.label, self.url, self.method, self.body, self.clear
+        );
+        for (name, value) in self.headers.iter().flatten() {
+            header.push_str(&format!(", headers.{}={}", name, value));
+        }
+        header
+    }
 }
 
 impl NtfyClient {
     pub fn new(
         config: NtfyConfig,
-        user_names: Arc<tokio::sync::RwLock<HashMap<String, String>>>,
+        notifications: NotificationsConfig,
+        list_overrides: Vec<ListOverrideConfig>,
     ) -> Self {
         Self {
             client: Client::new(),
             config,
-            user_names,
+            formatter: Formatter::new(notifications.clone()),
+            notifications,
+            list_overrides,
+            failing: AtomicBool::new(false),
         }
     }
 
-    /// Send a notification for a list change
-    pub async fn notify(&self, change: &ListChange) -> Result<()> {
-        let (title, message, priority, tags) = self.format_notification(change).await;
+    /// Send the actual HTTP request to ntfy.sh
+    ///
+    /// Network errors and 5xx responses are retried up to `max_retries` times
+    /// with exponential backoff; other failures are returned right away. Once
+    /// a publish has run out of retries, later ones are tried only once until
+    /// one succeeds, so an outage, including the circuit breaker's recovery
+    /// probe, doesn't hold up every change for the whole backoff.
+    async fn send_message(&self, message: &NtfyMessage) -> Result<()> {
+        debug!("Sending notification to ntfy: {}", message.title);
 
-        let ntfy_msg = NtfyMessage {
+        let authenticated = self.config.auth().is_some();
+        let max_retries = if self.failing.load(Ordering::Acquire) {
+            0
+        } else {
+            self.config.max_retries
+        };
+        let mut backoff =
+            Duration::from_secs(self.config.retry_backoff_secs).min(MAX_RETRY_BACKOFF);
+        let mut attempt = 0;
+        loop {
+            let failure = match self.build_request(message).send().await {
+                Ok(response) if response.status().is_success() => {
+                    info!("Notification sent: {}", message.title);
+                    self.failing.store(false, Ordering::Release);
+                    return Ok(());
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    if let Some(reason) = auth_failure(status, authenticated) {
+                        error!("Failed to send notification: {}", reason);
+                        anyhow::bail!(reason);
+                    }
+                    let body = response.text().await.unwrap_or_default();
+                    if !status.is_server_error() {
+                        error!("Failed to send notification: {} - {}", status, body);
+                        anyhow::bail!("ntfy.sh returned error: {} - {}", status, body);
+                    }
+                    anyhow::anyhow!("ntfy.sh returned error: {} - {}", status, body)
+                }
+                Err(e) => anyhow::Error::new(e).context("Failed to send notification to ntfy.sh"),
+            };
+
+            if attempt >= max_retries {
+                self.failing.store(true, Ordering::Release);
+                return Err(failure);
+            }
+            attempt += 1;
+            warn!(
+                "Retrying notification in {}s (retry {}/{}): {:#}",
+                backoff.as_secs(),
+                attempt,
+                max_retries,
+                failure
+            );
+            tokio::time::sleep(backoff).await;
+            backoff = backoff.saturating_mul(2).min(MAX_RETRY_BACKOFF);
+        }
+    }
+
+    /// Build the publish request for a message
+    ///
+    /// Messages are published as JSON, which carries emoji and other non-ASCII
+    /// text safely; only attachments are uploaded with the fields as headers.
+    fn build_request(&self, message: &NtfyMessage) -> RequestBuilder {
+        // Upload an overlong body as an attachment, with a summary as the message
+        let overflow = self.notifications.overflow_to_attachment
+            && message.message.len() > self.notifications.max_body_length;
+        let mut request = if overflow {
+            debug!(
+                "Sending {}-byte body as an attachment",
+                message.message.len()
+            );
+            let url = format!("{}/{}", self.config.base_url, message.topic);
+            let summary = overflow_summary(&message.message, self.formatter.language());
+            let mut request = self
+                .client
+                .put(&url)
+                .header("Filename", OVERFLOW_FILENAME)
+                .header("Message", encode_header(&summary))
+                .header("Title", encode_header(&message.title))
+                .header("Priority", message.priority.as_deref().unwrap_or("default"))
+                .header(
+                    "Tags",
+                    message
+                        .tags
+                        .as_ref()
+                        .map(|t| t.join(","))
+                        .unwrap_or_default(),
+                );
+            if let Some(call) = &message.call {
+                request = request.header("Call", call);
+            }
+            if let Some(click) = &message.click {
+                request = request.header("Click", click);
+            }
+            if let Some(actions) = &message.actions {
+                let actions: Vec<String> = actions.iter().map(NtfyAction::to_header).collect();
+                request = request.header("Actions", encode_header(&actions.join("; ")));
+            }
+            request.body(message.message.clone())
+        } else {
+            self.client.post(&self.config.base_url).json(message)
+        };
+        if let Some(auth) = self.config.auth() {
+            request = request.header(AUTHORIZATION, auth.header_value());
+        }
+        request
+    }
+
+    /// Format a service event into notification components
+    fn format_service_notification(
+        &self,
+        event: &ServiceEvent,
+    ) -> (String, String, String, Vec<String>) {
+        let (title, message) = self.formatter.service_event(event);
+        let (priority, tags) = self.service_style(event);
+        let priority = self.limit_priority(priority);
+        (title, message, priority, self.apply_ascii_only(tags))
+    }
+
+    /// Build the message announcing a service event on the main topic
+    fn service_message(&self, event: &ServiceEvent) -> NtfyMessage {
+        let (title, message, priority, tags) = self.format_service_notification(event);
+        NtfyMessage {
             topic: self.config.topic.clone(),
             title,
             message,
             priority: Some(priority),
             tags: Some(tags),
-        };
+            call: None,
+            click: None,
+            actions: None,
+        }
+    }
 
-        self.send_message(&ntfy_msg).await
+    /// The message published for a list change
+    ///
+    /// Emoji in the title are kept as they are, while tags stay shortcodes for
+    /// ntfy to render.
+    fn change_message(&self, change: &ListChange, context: &ChangeContext) -> NtfyMessage {
+        let (title, message, priority, tags) = self.format_notification(change, context);
+        NtfyMessage {
+            topic: self.topic_for(change).to_string(),
+            title,
+            message,
+            priority: Some(priority),
+            tags: Some(self.with_list_name_tag(change, tags)),
+            call: self.urgent_call(change),
+            click: self.click_url(change),
+            actions: None,
+        }
     }
 
-    /// Send the actual HTTP request to ntfy.sh
-    async fn send_message(&self, message: &NtfyMessage) -> Result<()> {
-        let url = format!("{}/{}", self.config.base_url, message.topic);
+    /// Topic configured for the item's category, then for its list, or the main topic
+    fn topic_for(&self, change: &ListChange) -> &str {
+        change
+            .item_category()
+            .and_then(|category| self.config.category_topic(category))
+            .or_else(|| {
+                self.list_override(change)
+                    .and_then(|list| list.topic.as_deref())
+            })
+            .unwrap_or(&self.config.topic)
+    }
 
-        debug!("Sending notification to ntfy: {}", message.title);
+    /// Overrides configured for the change's list, matching its ID before its name
+    fn list_override(&self, change: &ListChange) -> Option<&ListOverrideConfig> {
+        self.list_overrides
+            .iter()
+            .find(|list| list.list_id.as_deref() == Some(change.list_id()))
+            .or_else(|| {
+                self.list_overrides.iter().find(|list| {
+                    list.list_name
+                        .as_deref()
+                        .is_some_and(|name| name.trim().eq_ignore_ascii_case(change.list_name()))
+                })
+            })
+    }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Title", &message.title)
-            .header("Priority", message.priority.as_deref().unwrap_or("default"))
-            .header(
-                "Tags",
-                message
-                    .tags
-                    .as_ref()
-                    .map(|t| t.join(","))
-                    .unwrap_or_default(),
-            )
-            .body(message.message.clone())
-            .send()
-            .await
-            .context("Failed to send notification to ntfy.sh")?;
-
-        if response.status().is_success() {
-            info!("Notification sent: {}", message.title);
-            Ok(())
-        } else {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            error!("Failed to send notification: {} - {}", status, body);
-            anyhow::bail!("ntfy.sh returned error: {} - {}", status, body)
+    /// The first link in the item's details, if `extract_links` is set
+    fn click_url(&self, change: &ListChange) -> Option<String> {
+        if !self.notifications.extract_links {
+            return None;
         }
+        first_url(change.item_details()?).map(str::to_string)
     }
 
-    /// Get the user name for a given user ID, falling back to the ID itself
-    async fn get_user_name(&self, user_id: &str) -> String {
-        let names = self.user_names.read().await;
-        names.get(user_id).cloned().unwrap_or_else(|| user_id.to_string())
+    /// Buttons that publish "approve <id>" or "reject <id>" to the command topic
+    ///
+    /// The buttons' requests are part of the published message, so they only
+    /// carry the dedicated action token, never the service's own credentials.
+    fn review_actions(&self, review_id: i64) -> Vec<NtfyAction> {
+        // Config validation requires a command topic alongside the review topic
+        let url = format!(
+            "{}/{}",
+            self.config.base_url.trim_end_matches('/'),
+            self.config.command_topic().unwrap_or_default()
+        );
+        let headers = self.config.action_token().map(|token| {
+            HashMap::from([("Authorization".to_string(), format!("Bearer {}", token))])
+        });
+        [
+            (Phrase::ReviewApprove, "approve"),
+            (Phrase::ReviewReject, "reject"),
+        ]
+        .into_iter()
+        .map(|(label, verb)| NtfyAction {
+            action: "http",
+            label: self.formatter.language().get(label).to_string(),
+            url: url.clone(),
+            method: "POST",
+            body: format!("{} {}", verb, review_id),
+            headers: headers.clone(),
+            clear: true,
+        })
+        .collect()
     }
 
     /// Format a list change into notification components
-    async fn format_notification(&self, change: &ListChange) -> (String, String, String, Vec<String>) {
-        match change {
-            ListChange::ItemAdded {
-                list_name,
-                item,
-                user_id,
-                ..
-            } => {
-                let title = format!("➕ {} added to {}", item.name, list_name);
-                let mut message_parts = Vec::new();
-
-                if let Some(quantity) = &item.quantity {
-                    message_parts.push(format!("Quantity: {}", quantity));
-                }
-                if !item.details.is_empty() {
-                    message_parts.push(format!("Details: {}", item.details));
-                }
-                if let Some(category) = &item.category {
-                    message_parts.push(format!("Category: {}", category));
-                }
-                if let Some(uid) = user_id {
-                    let user_name = self.get_user_name(uid).await;
-                    message_parts.push(format!("Changed by: {}", user_name));
-                }
+    fn format_notification(
+        &self,
+        change: &ListChange,
+        context: &ChangeContext,
+    ) -> (String, String, String, Vec<String>) {
+        let (title, message) = self.formatter.change(change, context);
+        let (priority, tags) = self.change_style(change);
+
+        // List overrides replace the per-change values; urgency still escalates
+        let (priority, tags) = match self.list_override(change) {
+            Some(list) => (
+                list.priority.clone().unwrap_or(priority),
+                list.tags.as_deref().map(parse_tags).unwrap_or(tags),
+            ),
+            None => (priority, tags),
+        };
 
-                let message = if message_parts.is_empty() {
-                    format!("Added to {}", list_name)
-                } else {
-                    message_parts.join("\n")
-                };
+        let priority = self.escalate_batch(priority, batch_size(change));
+        let (priority, tags) = self.apply_urgency(change, priority, tags);
+        let priority = self.limit_priority(priority);
 
-                let priority = self.config.priorities.item_added.clone();
-                let tags = parse_tags(&self.config.tags.item_added);
+        (title, message, priority, self.apply_ascii_only(tags))
+    }
 
-                (title, message, priority, tags)
+    /// Priority and tags configured for the kind of change
+    fn change_style(&self, change: &ListChange) -> (String, Vec<String>) {
+        let (priorities, tags) = (&self.config.priorities, &self.config.tags);
+        let (priority, tags) = match change {
+            ListChange::ItemAdded { .. } | ListChange::ItemAddedToLists { .. } => {
+                (&priorities.item_added, &tags.item_added)
+            }
+            // Done items are styled like removals
+            ListChange::ItemRemoved { .. } | ListChange::ItemDone { .. } => {
+                (&priorities.item_removed, &tags.item_removed)
+            }
+            // An add checked off right away is styled like a check-off, the
+            // state the item ended up in
+            ListChange::ItemChecked { .. } | ListChange::ItemAddedAndChecked { .. } => {
+                (&priorities.item_checked, &tags.item_checked)
+            }
+            ListChange::ItemUnchecked { .. } => (&priorities.item_unchecked, &tags.item_unchecked),
+            ListChange::ItemModified { changes, .. } => return self.modified_style(changes),
+            ListChange::ListSharingChanged { .. } => {
+                (&priorities.list_sharing_changed, &tags.list_sharing_changed)
             }
+            ListChange::ListBecameShared { .. } => {
+                (&priorities.list_became_shared, &tags.list_became_shared)
+            }
+            ListChange::ListBecamePrivate { .. } => {
+                (&priorities.list_became_private, &tags.list_became_private)
+            }
+            ListChange::ItemsRecategorized { .. } => {
+                (&priorities.items_recategorized, &tags.items_recategorized)
+            }
+            ListChange::ChangesByUser { .. } => {
+                (&priorities.changes_by_user, &tags.changes_by_user)
+            }
+            ListChange::ListThresholdCrossed { .. } => (
+                &priorities.list_threshold_crossed,
+                &tags.list_threshold_crossed,
+            ),
+            ListChange::ItemToggledRepeatedly { .. } => (
+                &priorities.item_toggled_repeatedly,
+                &tags.item_toggled_repeatedly,
+            ),
+            ListChange::ItemMarkedMaybe { .. } => {
+                (&priorities.item_marked_maybe, &tags.item_marked_maybe)
+            }
+        };
+        (priority.clone(), parse_tags(tags))
+    }
 
-            ListChange::ItemRemoved {
-                list_name,
-                item_name,
-                user_id,
-                ..
-            } => {
-                let title = format!("❌ {} removed from {}", item_name, list_name);
-                let mut message = format!("Removed from {}", list_name);
-                if let Some(uid) = user_id {
-                    let user_name = self.get_user_name(uid).await;
-                    message.push_str(&format!("\nChanged by: {}", user_name));
-                }
-                let priority = self.config.priorities.item_removed.clone();
-                let tags = parse_tags(&self.config.tags.item_removed);
+    /// Priority and tags for an item modification, by how it is presented
+    fn modified_style(&self, changes: &[FieldChange]) -> (String, Vec<String>) {
+        let (priorities, tags) = (&self.config.priorities, &self.config.tags);
+        match self.formatter.modified_kind(changes) {
+            ModifiedKind::CategoryTagOnly => {
+                let mut modified = parse_tags(&tags.item_modified);
+                modified.extend(parse_tags(&tags.category_changed));
+                (priorities.item_modified.clone(), modified)
+            }
+            ModifiedKind::QuantitySet(_) => (
+                priorities.item_modified.clone(),
+                parse_tags(&tags.quantity_set),
+            ),
+            ModifiedKind::QuantityDepleted => (
+                priorities.quantity_depleted.clone(),
+                parse_tags(&tags.quantity_depleted),
+            ),
+            ModifiedKind::Edited => (
+                priorities.item_modified.clone(),
+                parse_tags(&tags.item_modified),
+            ),
+        }
+    }
 
-                (title, message, priority, tags)
+    /// Priority and tags configured for the kind of service event
+    fn service_style(&self, event: &ServiceEvent) -> (String, Vec<String>) {
+        let (priorities, tags) = (&self.config.priorities, &self.config.tags);
+        let (priority, tags) = match event {
+            ServiceEvent::CacheUnavailable { .. } => {
+                (&priorities.cache_unavailable, &tags.cache_unavailable)
+            }
+            ServiceEvent::Recovered => (&priorities.service_recovered, &tags.service_recovered),
+            ServiceEvent::ListSnapshot { .. } | ServiceEvent::UnknownList { .. } => {
+                (&priorities.list_snapshot, &tags.list_snapshot)
             }
+            ServiceEvent::MissedChanges { counts, .. } => {
+                let priority =
+                    self.escalate_batch(priorities.missed_changes.clone(), Some(counts.total()));
+                return (priority, parse_tags(&tags.missed_changes));
+            }
+            ServiceEvent::ListReseeded { .. } => (&priorities.list_reseeded, &tags.list_reseeded),
+            ServiceEvent::Digest { .. } => (&priorities.digest, &tags.digest),
+            ServiceEvent::WatchedItemAdded { .. } => {
+                (&priorities.watched_item_added, &tags.watched_item_added)
+            }
+            ServiceEvent::EditingStarted { .. } => {
+                (&priorities.editing_started, &tags.editing_started)
+            }
+            ServiceEvent::Heartbeat { .. } => (&priorities.heartbeat, &tags.heartbeat),
+        };
+        (priority.clone(), parse_tags(tags))
+    }
 
-            ListChange::ItemChecked {
-                list_name,
-                item_name,
-                user_id,
-                ..
-            } => {
-                let title = format!("✅ {} checked off in {}", item_name, list_name);
-                let mut message = format!("Checked off in {}", list_name);
-                if let Some(uid) = user_id {
-                    let user_name = self.get_user_name(uid).await;
-                    message.push_str(&format!("\nChanged by: {}", user_name));
-                }
-                let priority = self.config.priorities.item_checked.clone();
-                let tags = parse_tags(&self.config.tags.item_checked);
+    /// Add the list-name tag with `tag_list_name`
+    ///
+    /// Added after the ASCII pass since it never renders as an emoji.
+    fn with_list_name_tag(&self, change: &ListChange, mut tags: Vec<String>) -> Vec<String> {
+        if self.notifications.tag_list_name {
+            tags.extend(list_name_tag(change.list_name()));
+        }
+        tags
+    }
 
-                (title, message, priority, tags)
-            }
+    /// Raise a grouped notification covering more than `escalate_batch_threshold`
+    /// changes by one priority level
+    fn escalate_batch(&self, priority: String, batch_size: Option<usize>) -> String {
+        let threshold = self.notifications.escalate_batch_threshold;
+        match batch_size {
+            Some(size) if threshold > 0 && size > threshold => raise_priority(&priority),
+            _ => priority,
+        }
+    }
 
-            ListChange::ItemUnchecked {
-                list_name,
-                item_name,
-                user_id,
-                ..
-            } => {
-                let title = format!("◀️ {} unchecked in {}", item_name, list_name);
-                let mut message = format!("Unchecked in {}", list_name);
-                if let Some(uid) = user_id {
-                    let user_name = self.get_user_name(uid).await;
-                    message.push_str(&format!("\nChanged by: {}", user_name));
-                }
-                let priority = self.config.priorities.item_unchecked.clone();
-                let tags = parse_tags(&self.config.tags.item_unchecked);
+    /// Keep a priority between `min_priority` and `max_priority`
+    fn limit_priority(&self, priority: String) -> String {
+        clamp_priority(
+            &priority,
+            self.notifications.min_priority,
+            self.notifications.max_priority,
+        )
+    }
 
-                (title, message, priority, tags)
-            }
+    /// Whether the change affects an item matching `urgent_keywords`
+    fn is_urgent(&self, change: &ListChange) -> bool {
+        change.item_names().iter().any(|name| {
+            let name = name.to_lowercase();
+            self.notifications
+                .urgent_keywords
+                .iter()
+                .filter(|keyword| !keyword.trim().is_empty())
+                .any(|keyword| name.contains(&keyword.trim().to_lowercase()))
+        })
+    }
 
-            ListChange::ItemModified {
-                list_name,
-                item_name,
-                changes,
-                user_id,
-                ..
-            } => {
-                let title = format!("✏️ {} modified in {}", item_name, list_name);
-                let mut message = format_field_changes(changes);
-                if let Some(uid) = user_id {
-                    let user_name = self.get_user_name(uid).await;
-                    message.push_str(&format!("\nChanged by: {}", user_name));
-                }
-                let priority = self.config.priorities.item_modified.clone();
-                let tags = parse_tags(&self.config.tags.item_modified);
+    /// Number to phone about an urgent change, when `call_on_urgent` is set
+    fn urgent_call(&self, change: &ListChange) -> Option<String> {
+        let number = self.notifications.call_on_urgent()?;
+        self.is_urgent(change).then(|| number.to_string())
+    }
 
-                (title, message, priority, tags)
+    /// Escalate changes to items matching `urgent_keywords` to max priority with the urgent tag
+    fn apply_urgency(
+        &self,
+        change: &ListChange,
+        priority: String,
+        mut tags: Vec<String>,
+    ) -> (String, Vec<String>) {
+        if !self.is_urgent(change) {
+            return (priority, tags);
+        }
+
+        for tag in parse_tags(&self.config.tags.urgent) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
             }
         }
+        ("max".to_string(), tags)
+    }
+
+    /// Drop tags, which ntfy renders as emoji, when `ascii_only` is enabled
+    fn apply_ascii_only(&self, tags: Vec<String>) -> Vec<String> {
+        if self.notifications.ascii_only {
+            return Vec::new();
+        }
+        tags
+    }
+}
+
+#[async_trait]
+impl Notifier for NtfyClient {
+    fn name(&self) -> &str {
+        "ntfy"
+    }
+
+    /// Send a notification for a list change
+    async fn notify(&self, change: &ListChange, context: &ChangeContext) -> Result<()> {
+        let ntfy_msg = self.change_message(change, context);
+        self.send_message(&ntfy_msg).await
+    }
+
+    /// Send every change to the audit topic at the audit priority, if configured
+    async fn audit(&self, change: &ListChange, context: &ChangeContext) -> Result<()> {
+        let Some(topic) = self.config.audit_topic() else {
+            return Ok(());
+        };
+        let (title, message, _, tags) = self.format_notification(change, context);
+
+        let ntfy_msg = NtfyMessage {
+            topic: topic.to_string(),
+            title,
+            message,
+            priority: Some(self.config.priorities.audit.clone()),
+            tags: Some(self.with_list_name_tag(change, tags)),
+            call: None,
+            click: None,
+            actions: None,
+        };
+
+        self.send_message(&ntfy_msg).await
+    }
+
+    /// Send a held change to the review topic with buttons to approve or reject it
+    async fn request_review(
+        &self,
+        review_id: i64,
+        change: &ListChange,
+        context: &ChangeContext,
+    ) -> Result<()> {
+        let Some(topic) = self.config.review_topic() else {
+            return Ok(());
+        };
+        let mut ntfy_msg = self.change_message(change, context);
+        ntfy_msg.topic = topic.to_string();
+        ntfy_msg.title = self.formatter.language().format(
+            Phrase::ReviewTitle,
+            &[("id", &review_id.to_string()), ("title", &ntfy_msg.title)],
+        );
+        ntfy_msg.call = None;
+        ntfy_msg.actions = Some(self.review_actions(review_id));
+
+        self.send_message(&ntfy_msg).await
+    }
+
+    /// Send a notification about the service itself
+    async fn notify_service(&self, event: &ServiceEvent) -> Result<()> {
+        self.send_message(&self.service_message(event)).await
+    }
+
+    fn render(&self, change: &ListChange, context: &ChangeContext) -> Result<String> {
+        serde_json::to_string(&self.change_message(change, context))
+            .context("Failed to serialize ntfy message")
+    }
+
+    fn render_service(&self, event: &ServiceEvent) -> Result<Option<String>> {
+        serde_json::to_string(&self.service_message(event))
+            .map(Some)
+            .context("Failed to serialize ntfy message")
+    }
+}
+
+/// Explain a response rejecting the request's credentials, or the lack of them
+pub(crate) fn auth_failure(status: StatusCode, authenticated: bool) -> Option<String> {
+    if status != StatusCode::UNAUTHORIZED && status != StatusCode::FORBIDDEN {
+        return None;
+    }
+    Some(if authenticated {
+        format!(
+            "ntfy rejected the configured credentials ({}); check ntfy.auth or ntfy.access_token",
+            status
+        )
+    } else {
+        format!(
+            "ntfy requires authentication ({}); set ntfy.auth or ntfy.access_token",
+            status
+        )
+    })
+}
+
+/// ntfy's JSON API only takes the priority as a number from 1 to 5
+fn serialize_priority<S: Serializer>(
+    priority: &Option<String>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    let level = priority
+        .as_deref()
+        .and_then(Priority::parse)
+        .unwrap_or(Priority::Default)
+        .level();
+    serializer.serialize_u8(level)
+}
+
+/// Encode a header value as an RFC 2047 word if it isn't plain ASCII, which ntfy decodes
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_string();
+    }
+    let mut encoded = String::from("=?UTF-8?Q?");
+    for byte in value.bytes() {
+        match byte {
+            b' ' => encoded.push('_'),
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("={:02X}", byte)),
+        }
     }
+    encoded.push_str("?=");
+    encoded
 }
 
 /// Parse comma-separated tags into a vector
@@ -214,44 +646,80 @@ fn parse_tags(tags: &str) -> Vec<String> {
         .collect()
 }
 
-/// Format field changes into a readable message
-fn format_field_changes(changes: &[FieldChange]) -> String {
-    let mut parts = Vec::new();
+/// One-line summary of an overlong body sent as an attachment
+///
+/// Keeps the start of the first line, which is a header value and can't
+/// hold line breaks.
+fn overflow_summary(body: &str, language: &Language) -> String {
+    let first_line = body.lines().next().unwrap_or_default().trim();
+    let mut summary: String = first_line.chars().take(OVERFLOW_SUMMARY_CHARS).collect();
+    if summary.len() < first_line.len() || body.trim().lines().nth(1).is_some() {
+        summary.push('…');
+    }
+    format!("{} ({})", summary, language.get(Phrase::FullDetailsAttached))
+}
 
-    for change in changes {
-        match change {
-            FieldChange::Name { old, new } => {
-                parts.push(format!("Name: {} → {}", old, new));
-            }
-            FieldChange::Details { old, new } => {
-                if old.is_empty() {
-                    parts.push(format!("Details added: {}", new));
-                } else if new.is_empty() {
-                    parts.push(format!("Details removed: {}", old));
-                } else {
-                    parts.push(format!("Details: {} → {}", old, new));
-                }
-            }
-            FieldChange::Quantity { old, new } => {
-                let old_str = old.as_deref().unwrap_or("none");
-                let new_str = new.as_deref().unwrap_or("none");
-                parts.push(format!("Quantity: {} → {}", old_str, new_str));
-            }
-            FieldChange::Category { old, new } => {
-                let old_str = old.as_deref().unwrap_or("none");
-                let new_str = new.as_deref().unwrap_or("none");
-                parts.push(format!("Category: {} → {}", old_str, new_str));
-            }
-        }
+/// Number of changes a grouped notification covers, or None for single changes
+fn batch_size(change: &ListChange) -> Option<usize> {
+    match change {
+        ListChange::ChangesByUser { changes, .. } => Some(changes.len()),
+        ListChange::ItemsRecategorized { item_ids, .. } => Some(item_ids.len()),
+        _ => None,
+    }
+}
+
+/// The ntfy priority one level above `priority`, clamped at max
+/// Unrecognized priorities are left as they are
+fn raise_priority(priority: &str) -> String {
+    match Priority::parse(priority) {
+        Some(level) => level.raise().as_str().to_string(),
+        None => priority.to_string(),
+    }
+}
+
+/// Raise `priority` to `min` and lower it to `max` where set
+/// Unrecognized priorities are left as they are
+fn clamp_priority(priority: &str, min: Option<Priority>, max: Option<Priority>) -> String {
+    let Some(mut level) = Priority::parse(priority) else {
+        return priority.to_string();
+    };
+    if let Some(min) = min {
+        level = level.max(min);
+    }
+    if let Some(max) = max {
+        level = level.min(max);
     }
+    level.as_str().to_string()
+}
+
+/// Tag identifying a list, e.g. `list-weekly-groceries` for "Weekly Groceries 🛒"
+///
+/// Anything but ASCII letters and digits separates words, so the tag is safe
+/// in the comma-separated tags header and can't match an emoji shortcode.
+/// Returns None when the name has no letters or digits.
+fn list_name_tag(list_name: &str) -> Option<String> {
+    let words: Vec<String> = list_name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect();
 
-    parts.join("\n")
+    if words.is_empty() {
+        return None;
+    }
+    Some(format!("list-{}", words.join("-")))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::sync::diff::ItemInfo;
+    use crate::config::{CategoryChangeMode, DisplayConfig, NtfyAuth, NtfyPriorities, NtfyTags};
+    use crate::sync::diff::{ChangeCounts, ItemInfo, ListTotals, ThresholdDirection};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
 
     #[test]
     fn test_parse_tags() {
@@ -265,36 +733,63 @@ mod tests {
         assert_eq!(tags.len(), 0);
     }
 
-    #[test]
-    fn test_format_field_changes() {
-        let changes = vec![
-            FieldChange::Quantity {
-                old: Some("1".to_string()),
-                new: Some("2".to_string()),
-            },
-            FieldChange::Category {
-                old: None,
-                new: Some("Dairy".to_string()),
-            },
-        ];
-
-        let message = format_field_changes(&changes);
-        assert!(message.contains("Quantity: 1 → 2"));
-        assert!(message.contains("Category: none → Dairy"));
-    }
-
-    #[test]
-    fn test_format_added_notification() {
-        use crate::config::{NtfyPriorities, NtfyTags};
-
+    fn test_client(notifications: NotificationsConfig) -> NtfyClient {
         let config = NtfyConfig {
+            enabled: None,
             base_url: "https://ntfy.sh".to_string(),
             topic: "test".to_string(),
             priorities: NtfyPriorities::default(),
             tags: NtfyTags::default(),
+            command_topic: None,
+            audit_topic: None,
+            review_topic: None,
+            action_token: None,
+            review_expiry_secs: 604_800,
+            access_token: None,
+            auth: None,
+            category_topics: HashMap::new(),
+            max_retries: 0,
+            retry_backoff_secs: 0,
+            dry_run: false,
         };
 
-        let client = NtfyClient::new(config);
+        NtfyClient::new(config, notifications, Vec::new())
+    }
+
+    fn context() -> ChangeContext {
+        ChangeContext::from_names(HashMap::from([("user-1".to_string(), "Alice".to_string())]))
+    }
+
+    fn item_info(name: &str) -> ItemInfo {
+        ItemInfo {
+            id: "item-1".to_string(),
+            name: name.to_string(),
+            details: "".to_string(),
+            quantity: None,
+            category: None,
+            user_id: None,
+        }
+    }
+
+    fn removed_change() -> ListChange {
+        ListChange::ItemRemoved {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: ItemInfo {
+                id: "item-1".to_string(),
+                name: "Milk".to_string(),
+                details: "".to_string(),
+                quantity: Some("2 gallons".to_string()),
+                category: Some("Dairy".to_string()),
+                user_id: Some("user-1".to_string()),
+            },
+            user_id: Some("user-1".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_format_added_notification() {
+        let client = test_client(NotificationsConfig::default());
 
         let change = ListChange::ItemAdded {
             list_id: "list-1".to_string(),
@@ -305,17 +800,1241 @@ mod tests {
                 details: "Whole milk".to_string(),
                 quantity: Some("1 gallon".to_string()),
                 category: Some("Dairy".to_string()),
+                user_id: Some("user-1".to_string()),
             },
+            user_id: Some("user-1".to_string()),
         };
 
-        let (title, message, priority, tags) = client.format_notification(&change);
+        let (title, message, priority, tags) = client.format_notification(&change, &context());
 
         assert!(title.contains("Milk"));
         assert!(title.contains("Groceries"));
         assert!(message.contains("Quantity: 1 gallon"));
         assert!(message.contains("Details: Whole milk"));
         assert!(message.contains("Category: Dairy"));
+        assert!(message.contains("Changed by: Alice"));
         assert_eq!(priority, "default");
         assert!(!tags.is_empty());
     }
+
+    #[test]
+    fn test_format_removed_notification() {
+        let client = test_client(NotificationsConfig::default());
+
+        let (title, message, _, _) = client.format_notification(&removed_change(), &context());
+
+        assert!(title.contains("Milk removed from Groceries"));
+        assert!(!message.contains("Quantity"));
+        assert!(message.contains("Changed by: Alice"));
+    }
+
+    #[test]
+    fn test_format_removed_notification_with_details() {
+        let client = test_client(NotificationsConfig {
+            include_details_in_removal: true,
+            ..NotificationsConfig::default()
+        });
+
+        let (_, message, _, _) = client.format_notification(&removed_change(), &context());
+
+        assert!(message.contains("Quantity: 2 gallons"));
+        assert!(message.contains("Category: Dairy"));
+        assert!(!message.contains("Details"));
+    }
+
+    #[test]
+    fn test_format_category_tag_only() {
+        let client = test_client(NotificationsConfig {
+            category_change_mode: CategoryChangeMode::TagOnly,
+            ..NotificationsConfig::default()
+        });
+
+        let change = ListChange::ItemModified {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item_id: "item-1".to_string(),
+            item_name: "Milk".to_string(),
+            changes: vec![FieldChange::Category {
+                old: None,
+                new: Some("Dairy".to_string()),
+            }],
+            user_id: None,
+        };
+
+        let (title, message, _, tags) = client.format_notification(&change, &context());
+
+        assert!(title.contains("Milk modified in Groceries"));
+        assert_eq!(message, "Dairy");
+        assert!(tags.contains(&"label".to_string()));
+        assert!(tags.contains(&"pencil2".to_string()));
+    }
+
+    #[test]
+    fn test_format_recategorized_summary() {
+        let client = test_client(NotificationsConfig::default());
+
+        let change = ListChange::ItemsRecategorized {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item_ids: (1..=5).map(|i| format!("item-{}", i)).collect(),
+            item_names: ["Milk", "Bread", "Eggs", "Butter", "Cheese"]
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            user_id: Some("user-1".to_string()),
+        };
+
+        let (title, message, _, tags) = client.format_notification(&change, &context());
+
+        assert_eq!(title, "🗂️ 5 items recategorized in Groceries");
+        assert_eq!(message, "Milk, Bread, Eggs and 2 more\nChanged by: Alice");
+        assert_eq!(tags, vec!["card_index_dividers"]);
+    }
+
+    #[test]
+    fn test_format_check_context() {
+        let checked = ListChange::ItemChecked {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: ItemInfo {
+                quantity: Some("2 gallons".to_string()),
+                category: Some("Dairy".to_string()),
+                ..item_info("Milk")
+            },
+            user_id: None,
+        };
+
+        let client = test_client(NotificationsConfig::default());
+        let (title, message, _, _) = client.format_notification(&checked, &context());
+        assert_eq!(title, "✅ Milk checked off in Groceries");
+        assert_eq!(message, "Checked off in Groceries");
+
+        let client = test_client(NotificationsConfig {
+            context_in_check: true,
+            ..NotificationsConfig::default()
+        });
+        let (title, message, _, _) = client.format_notification(&checked, &context());
+        assert_eq!(title, "✅ Milk (2 gallons) checked off in Groceries");
+        assert_eq!(message, "Checked off in Groceries\nCategory: Dairy");
+    }
+
+    #[test]
+    fn test_format_item_done() {
+        let client = test_client(NotificationsConfig::default());
+
+        let change = ListChange::ItemDone {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item_id: "item-1".to_string(),
+            item_name: "Milk".to_string(),
+            user_id: Some("user-1".to_string()),
+        };
+
+        let (title, message, priority, tags) = client.format_notification(&change, &context());
+
+        assert_eq!(title, "❌ Milk done in Groceries");
+        assert_eq!(message, "Done in Groceries\nChanged by: Alice");
+        assert_eq!(priority, NtfyPriorities::default().item_removed);
+        assert_eq!(tags, parse_tags(&NtfyTags::default().item_removed));
+    }
+
+    #[test]
+    fn test_format_item_added_and_checked() {
+        let client = test_client(NotificationsConfig::default());
+
+        let change = ListChange::ItemAddedAndChecked {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item_info("Milk"),
+            user_id: Some("user-1".to_string()),
+        };
+
+        let (title, message, priority, tags) = client.format_notification(&change, &context());
+
+        assert_eq!(title, "🛒✅ Added and checked off Milk in Groceries");
+        assert_eq!(
+            message,
+            "Added and checked off in Groceries\nChanged by: Alice"
+        );
+        assert_eq!(priority, NtfyPriorities::default().item_checked);
+        assert_eq!(tags, parse_tags(&NtfyTags::default().item_checked));
+    }
+
+    #[test]
+    fn test_format_changes_by_user() {
+        let client = test_client(NotificationsConfig::default());
+        let change = |item: ItemInfo| ListChange::ItemAdded {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item,
+            user_id: Some("user-1".to_string()),
+        };
+        let checked = ListChange::ItemChecked {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item_info("Eggs"),
+            user_id: Some("user-1".to_string()),
+        };
+
+        let grouped = ListChange::ChangesByUser {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            user_id: Some("user-1".to_string()),
+            changes: vec![change(item_info("Milk")), change(item_info("Bread")), checked],
+        };
+        let (title, message, priority, tags) = client.format_notification(&grouped, &context());
+
+        assert_eq!(title, "👤 Alice made 3 changes to Groceries");
+        assert_eq!(message, "+Milk, +Bread, ✓Eggs");
+        assert_eq!(priority, "default");
+        assert_eq!(tags, vec!["bust_in_silhouette"]);
+
+        let anonymous = ListChange::ChangesByUser {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            user_id: None,
+            changes: vec![change(item_info("Milk")), change(item_info("Bread"))],
+        };
+        let (title, _, _, _) = client.format_notification(&anonymous, &context());
+        assert_eq!(title, "👤 2 changes to Groceries");
+    }
+
+    #[test]
+    fn test_json_payload_keeps_emoji_and_shortcodes() {
+        let client = test_client(NotificationsConfig::default());
+        let change = ListChange::ItemAdded {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item_info("Café au lait"),
+            user_id: None,
+        };
+
+        let payload = serde_json::to_value(client.change_message(&change, &context())).unwrap();
+        assert_eq!(payload["topic"], "test");
+        assert!(payload["title"].as_str().unwrap().starts_with("➕ Café au lait"));
+        assert_eq!(
+            payload["tags"],
+            serde_json::json!(["heavy_plus_sign", "shopping_cart"])
+        );
+        assert_eq!(payload["priority"], 3);
+        assert!(payload.get("call").is_none());
+    }
+
+    #[test]
+    fn test_priority_serialized_as_number() {
+        let message = |priority: Option<&str>| NtfyMessage {
+            topic: "test".to_string(),
+            title: "✅ Milk".to_string(),
+            message: "".to_string(),
+            priority: priority.map(str::to_string),
+            tags: None,
+            call: None,
+            click: None,
+            actions: None,
+        };
+        let priority = |p| serde_json::to_value(message(p)).unwrap()["priority"].clone();
+
+        assert_eq!(priority(Some("urgent")), 5);
+        assert_eq!(priority(Some("2")), 2);
+        assert_eq!(priority(Some("unknown")), 3);
+        assert!(priority(None).is_null());
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let notifications = NotificationsConfig {
+            extract_links: true,
+            ..NotificationsConfig::default()
+        };
+        let client = test_client(notifications);
+        let mut item = item_info("Prescription");
+        item.details =
+            "Order at https://pharmacy.example/rx/42. Or call (555) 123-4567".to_string();
+        let change = ListChange::ItemAdded {
+            list_id: "list-1".to_string(),
+            list_name: "Errands".to_string(),
+            item,
+            user_id: None,
+        };
+
+        let message = client.change_message(&change, &context());
+        assert_eq!(
+            message.click.as_deref(),
+            Some("https://pharmacy.example/rx/42")
+        );
+        assert!(message.message.starts_with("📞 (555) 123-4567\n"));
+
+        // Without the option nothing is extracted
+        let client = test_client(NotificationsConfig::default());
+        let message = client.change_message(&change, &context());
+        assert_eq!(message.click, None);
+        assert!(!message.message.contains("📞"));
+    }
+
+    #[test]
+    fn test_category_topic() {
+        let mut client = test_client(NotificationsConfig::default());
+        client
+            .config
+            .category_topics
+            .insert("Dairy".to_string(), "anylist-dairy".to_string());
+
+        // removed_change's item is in Dairy
+        let message = client.change_message(&removed_change(), &context());
+        assert_eq!(message.topic, "anylist-dairy");
+
+        let uncategorized = ListChange::ItemChecked {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item_info("Bread"),
+            user_id: None,
+        };
+        let message = client.change_message(&uncategorized, &context());
+        assert_eq!(message.topic, "test");
+    }
+
+    #[test]
+    fn test_list_overrides() {
+        let mut client = test_client(NotificationsConfig::default());
+        client.list_overrides = vec![
+            ListOverrideConfig {
+                list_name: Some("groceries".to_string()),
+                list_id: None,
+                topic: Some("anylist-groceries".to_string()),
+                priority: Some("high".to_string()),
+                tags: Some("shopping_cart".to_string()),
+            },
+            ListOverrideConfig {
+                list_name: None,
+                list_id: Some("list-2".to_string()),
+                topic: Some("anylist-hardware".to_string()),
+                priority: None,
+                tags: None,
+            },
+        ];
+        let checked = |list_id: &str, list_name: &str| ListChange::ItemChecked {
+            list_id: list_id.to_string(),
+            list_name: list_name.to_string(),
+            item: item_info("Bread"),
+            user_id: None,
+        };
+
+        // Names match case-insensitively, and the override replaces priority and tags
+        let message = client.change_message(&checked("list-1", "Groceries"), &context());
+        assert_eq!(message.topic, "anylist-groceries");
+        assert_eq!(message.priority.as_deref(), Some("high"));
+        assert_eq!(message.tags, Some(vec!["shopping_cart".to_string()]));
+
+        // IDs match exactly, even when the list is renamed
+        let message = client.change_message(&checked("list-2", "Tools"), &context());
+        assert_eq!(message.topic, "anylist-hardware");
+        assert_eq!(message.priority.as_deref(), Some("low"));
+        let message = client.change_message(&checked("LIST-2", "Tools"), &context());
+        assert_eq!(message.topic, "test");
+    }
+
+    #[test]
+    fn test_review_actions() {
+        let mut client = test_client(NotificationsConfig::default());
+        client.config.command_topic = Some("anylist-commands".to_string());
+        client.config.access_token = Some("tk_secret".to_string());
+
+        // The service's own credentials are never published in the buttons
+        let actions = client.review_actions(12);
+        let payload = serde_json::to_value(&actions).unwrap();
+        assert_eq!(payload[0]["label"], "Approve");
+        assert_eq!(payload[0]["url"], "https://ntfy.sh/anylist-commands");
+        assert_eq!(payload[0]["body"], "approve 12");
+        assert_eq!(payload[1]["body"], "reject 12");
+        assert!(payload[1].get("headers").is_none());
+        assert_eq!(
+            actions[1].to_header(),
+            "http, Reject, https://ntfy.sh/anylist-commands, method=POST, body=reject 12, \
+             clear=true"
+        );
+
+        client.config.action_token = Some("tk_commands_only".to_string());
+        let actions = client.review_actions(12);
+        let payload = serde_json::to_value(&actions).unwrap();
+        assert_eq!(
+            payload[1]["headers"]["Authorization"],
+            "Bearer tk_commands_only"
+        );
+        assert!(actions[1]
+            .to_header()
+            .ends_with("headers.Authorization=Bearer tk_commands_only"));
+    }
+
+    #[test]
+    fn test_review_actions_omit_basic_auth() {
+        let mut client = test_client(NotificationsConfig::default());
+        client.config.command_topic = Some("anylist-commands".to_string());
+        client.config.auth = Some(NtfyAuth::Basic {
+            username: "phil".to_string(),
+            password: "hunter2".to_string(),
+        });
+
+        for action in client.review_actions(12) {
+            let payload = serde_json::to_value(&action).unwrap();
+            assert!(payload.get("headers").is_none());
+            assert!(!action.to_header().contains("Basic"));
+        }
+    }
+
+    #[test]
+    fn test_auth_failure() {
+        assert_eq!(auth_failure(StatusCode::BAD_REQUEST, true), None);
+        assert!(auth_failure(StatusCode::UNAUTHORIZED, true)
+            .unwrap()
+            .contains("rejected the configured credentials"));
+        assert!(auth_failure(StatusCode::FORBIDDEN, false)
+            .unwrap()
+            .contains("requires authentication"));
+    }
+
+    /// Answer one request per status in turn on a local port, counting the requests
+    async fn mock_ntfy(statuses: Vec<u16>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                read_request(&mut socket).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    /// Read a whole HTTP request, headers and body
+    async fn read_request(socket: &mut TcpStream) {
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        loop {
+            let read = socket.read(&mut buf).await.unwrap();
+            if read == 0 {
+                return;
+            }
+            request.extend_from_slice(&buf[..read]);
+            let text = String::from_utf8_lossy(&request);
+            let Some(end) = text.find("\r\n\r\n") else {
+                continue;
+            };
+            let length = text[..end]
+                .lines()
+                .find_map(|line| {
+                    let line = line.to_ascii_lowercase();
+                    line.strip_prefix("content-length:")
+                        .map(|length| length.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            if request.len() >= end + 4 + length {
+                return;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_retries_server_errors() {
+        let mut client = test_client(NotificationsConfig::default());
+        client.config.max_retries = 3;
+        let message = client.change_message(&removed_change(), &context());
+
+        let (url, requests) = mock_ntfy(vec![503, 503, 200]).await;
+        client.config.base_url = url;
+        client.send_message(&message).await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        // Client errors are returned without retrying
+        let (url, requests) = mock_ntfy(vec![400, 200]).await;
+        client.config.base_url = url;
+        assert!(client.send_message(&message).await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // The last error is returned once retries run out
+        client.config.max_retries = 1;
+        let (url, requests) = mock_ntfy(vec![503, 503, 200]).await;
+        client.config.base_url = url;
+        let err = client.send_message(&message).await.unwrap_err().to_string();
+        assert!(err.contains("503"), "{}", err);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // After that, publishes are tried once until one succeeds
+        let (url, requests) = mock_ntfy(vec![503, 200, 503, 200]).await;
+        client.config.base_url = url;
+        assert!(client.send_message(&message).await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        client.send_message(&message).await.unwrap();
+        client.send_message(&message).await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_encode_header() {
+        assert_eq!(encode_header("Milk added"), "Milk added");
+        assert_eq!(encode_header("✅ Café"), "=?UTF-8?Q?=E2=9C=85_Caf=C3=A9?=");
+    }
+
+    #[test]
+    fn test_overflow_summary() {
+        let language = Language::default();
+
+        assert_eq!(
+            overflow_summary("+Milk, +Bread\nChanged by: Alice", &language),
+            "+Milk, +Bread… (full details attached)"
+        );
+        assert_eq!(
+            overflow_summary("+Milk, +Bread", &language),
+            "+Milk, +Bread (full details attached)"
+        );
+
+        let long_line = "+Milk, ".repeat(50);
+        let summary = overflow_summary(&long_line, &language);
+        assert!(summary.starts_with("+Milk, +Milk"));
+        assert_eq!(
+            summary.chars().count(),
+            OVERFLOW_SUMMARY_CHARS + "… (full details attached)".chars().count()
+        );
+    }
+
+    #[test]
+    fn test_raise_priority() {
+        assert_eq!(raise_priority("low"), "default");
+        assert_eq!(raise_priority("default"), "high");
+        assert_eq!(raise_priority("4"), "max");
+        assert_eq!(raise_priority("max"), "max");
+        assert_eq!(raise_priority("urgent"), "max");
+        assert_eq!(raise_priority("loud"), "loud");
+    }
+
+    #[test]
+    fn test_clamp_priority() {
+        let floor = Some(Priority::Default);
+        let ceiling = Some(Priority::High);
+
+        assert_eq!(clamp_priority("low", floor, None), "default");
+        assert_eq!(clamp_priority("min", floor, ceiling), "default");
+        assert_eq!(clamp_priority("high", floor, ceiling), "high");
+        assert_eq!(clamp_priority("urgent", floor, ceiling), "high");
+        assert_eq!(clamp_priority("5", None, ceiling), "high");
+        assert_eq!(clamp_priority("low", None, None), "low");
+        assert_eq!(clamp_priority("loud", floor, ceiling), "loud");
+    }
+
+    #[test]
+    fn test_format_applies_priority_limits() {
+        let client = test_client(NotificationsConfig {
+            urgent_keywords: vec!["milk".to_string()],
+            max_priority: Some(Priority::High),
+            min_priority: Some(Priority::Default),
+            ..NotificationsConfig::default()
+        });
+
+        // Urgent changes are capped by the ceiling
+        let change = ListChange::ItemChecked {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item_info("Milk"),
+            user_id: None,
+        };
+        let (_, _, priority, _) = client.format_notification(&change, &context());
+        assert_eq!(priority, "high");
+
+        // Low-priority service events are raised to the floor
+        let (_, _, priority, _) =
+            client.format_service_notification(&ServiceEvent::EditingStarted {
+                list_name: "Groceries".to_string(),
+                user_name: "Alice".to_string(),
+            });
+        assert_eq!(priority, "default");
+    }
+
+    #[test]
+    fn test_format_escalates_big_batches() {
+        let client = test_client(NotificationsConfig {
+            escalate_batch_threshold: 2,
+            ..NotificationsConfig::default()
+        });
+        let grouped = |names: &[&str]| ListChange::ChangesByUser {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            user_id: Some("user-1".to_string()),
+            changes: names
+                .iter()
+                .map(|name| ListChange::ItemAdded {
+                    list_id: "list-1".to_string(),
+                    list_name: "Groceries".to_string(),
+                    item: item_info(name),
+                    user_id: Some("user-1".to_string()),
+                })
+                .collect(),
+        };
+
+        // At the threshold the configured priority is kept
+        let (_, _, priority, _) =
+            client.format_notification(&grouped(&["Milk", "Bread"]), &context());
+        assert_eq!(priority, "default");
+
+        let (_, _, priority, _) =
+            client.format_notification(&grouped(&["Milk", "Bread", "Eggs"]), &context());
+        assert_eq!(priority, "high");
+
+        // Single changes are never escalated
+        let (_, _, priority, _) = client.format_notification(&removed_change(), &context());
+        assert_eq!(priority, "default");
+
+        let (_, _, priority, _) =
+            client.format_service_notification(&ServiceEvent::MissedChanges {
+                list_name: "Groceries".to_string(),
+                offline_secs: 60,
+                counts: ChangeCounts {
+                    added: 3,
+                    ..ChangeCounts::default()
+                },
+            });
+        assert_eq!(priority, "high");
+    }
+
+    #[test]
+    fn test_format_item_id() {
+        let client = test_client(NotificationsConfig {
+            include_item_id: true,
+            footer: Some("Sent from {list_name}".to_string()),
+            ..NotificationsConfig::default()
+        });
+
+        let (_, message, _, _) = client.format_notification(&removed_change(), &context());
+        assert_eq!(
+            message,
+            "Removed from Groceries\nChanged by: Alice\nSent from Groceries\nItem ID: item-1"
+        );
+
+        // Changes without items are left alone
+        let (_, message, _, _) = client.format_notification(
+            &ListChange::ListSharingChanged {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                added_users: vec!["user-1".to_string()],
+                removed_users: Vec::new(),
+            },
+            &context(),
+        );
+        assert!(!message.contains("Item ID"));
+    }
+
+    #[test]
+    fn test_format_privacy_transitions() {
+        let client = test_client(NotificationsConfig::default());
+
+        let (title, message, priority, tags) = client.format_notification(
+            &ListChange::ListBecameShared {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                added_users: vec!["user-1".to_string()],
+            },
+            &context(),
+        );
+        assert_eq!(title, "🔓 Groceries is now shared");
+        assert_eq!(message, "Shared with: Alice");
+        assert_eq!(priority, "max");
+        assert_eq!(tags, vec!["unlock", "busts_in_silhouette"]);
+
+        let (title, message, _, tags) = client.format_notification(
+            &ListChange::ListBecamePrivate {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                removed_users: vec!["user-1".to_string(), "user-2".to_string()],
+            },
+            &context(),
+        );
+        assert_eq!(title, "🔒 Groceries is now private");
+        assert_eq!(message, "No longer shared with: Alice, user-2");
+        assert_eq!(tags, vec!["lock"]);
+    }
+
+    #[test]
+    fn test_format_missed_changes() {
+        let client = test_client(NotificationsConfig::default());
+
+        let (title, message, priority, tags) =
+            client.format_service_notification(&ServiceEvent::MissedChanges {
+                list_name: "Groceries".to_string(),
+                offline_secs: 12 * 60 + 30,
+                counts: ChangeCounts {
+                    added: 3,
+                    checked: 2,
+                    removed: 1,
+                    ..ChangeCounts::default()
+                },
+            });
+
+        assert_eq!(title, "📴 Groceries changed while offline");
+        assert_eq!(
+            message,
+            "While offline for 12m, Groceries changed: +3, ✓2, -1"
+        );
+        assert_eq!(priority, "default");
+        assert_eq!(tags, vec!["electric_plug"]);
+    }
+
+    #[test]
+    fn test_format_list_reseeded() {
+        let client = test_client(NotificationsConfig::default());
+
+        let (title, message, priority, tags) =
+            client.format_service_notification(&ServiceEvent::ListReseeded {
+                list_name: "Groceries".to_string(),
+                changes: 48,
+            });
+
+        assert_eq!(title, "♻️ Groceries re-synced");
+        assert_eq!(
+            message,
+            "48 changes at once looked like a change on AnyList's side rather than edits, so they weren't notified"
+        );
+        assert_eq!(priority, "high");
+        assert_eq!(tags, vec!["recycle"]);
+    }
+
+    #[test]
+    fn test_format_heartbeat() {
+        let client = test_client(NotificationsConfig::default());
+
+        let (title, message, priority, tags) =
+            client.format_service_notification(&ServiceEvent::Heartbeat {
+                watched_lists: 4,
+                last_event_secs: Some(3 * 3600 + 5 * 60),
+            });
+        assert_eq!(title, "💚 AnyList notifier healthy");
+        assert_eq!(message, "Watching 4 lists, last event 3h 5m ago");
+        assert_eq!(priority, "low");
+        assert_eq!(tags, vec!["green_heart"]);
+
+        let (_, message, _, _) = client.format_service_notification(&ServiceEvent::Heartbeat {
+            watched_lists: 4,
+            last_event_secs: None,
+        });
+        assert_eq!(message, "Watching 4 lists, no events received yet");
+    }
+
+    #[test]
+    fn test_format_digest() {
+        let client = test_client(NotificationsConfig::default());
+
+        let (title, message, priority, tags) =
+            client.format_service_notification(&ServiceEvent::Digest {
+                list_name: "Groceries".to_string(),
+                period_secs: 86_400,
+                counts: ChangeCounts {
+                    added: 4,
+                    checked: 3,
+                    ..ChangeCounts::default()
+                },
+            });
+        assert_eq!(title, "📰 7 changes to Groceries");
+        assert_eq!(message, "In the last 1d: +4, ✓3");
+        assert_eq!(priority, "default");
+        assert_eq!(tags, vec!["newspaper"]);
+    }
+
+    #[test]
+    fn test_format_editing_started() {
+        let client = test_client(NotificationsConfig::default());
+
+        let (title, message, priority, tags) =
+            client.format_service_notification(&ServiceEvent::EditingStarted {
+                list_name: "Groceries".to_string(),
+                user_name: "Alice".to_string(),
+            });
+        assert_eq!(title, "✍️ Alice is editing Groceries…");
+        assert_eq!(message, "Changes are summarized once editing stops");
+        assert_eq!(priority, "low");
+        assert_eq!(tags, vec!["writing_hand"]);
+    }
+
+    #[test]
+    fn test_format_item_added_to_lists() {
+        let client = test_client(NotificationsConfig::default());
+        let change = ListChange::ItemAddedToLists {
+            list_id: "list-1".to_string(),
+            list_name: "Costco".to_string(),
+            other_list_ids: vec!["list-2".to_string()],
+            other_list_names: vec!["Grocery Store".to_string()],
+            item: item_info("Milk"),
+            user_id: None,
+        };
+
+        let (title, message, priority, tags) = client.format_notification(&change, &context());
+        assert_eq!(title, "➕ Milk added to 2 lists");
+        assert_eq!(message, "Added to Costco, Grocery Store");
+        assert_eq!(priority, "default");
+        assert_eq!(tags, vec!["heavy_plus_sign", "shopping_cart"]);
+    }
+
+    #[test]
+    fn test_format_watched_item_added() {
+        let client = test_client(NotificationsConfig::default());
+
+        let (title, message, priority, tags) =
+            client.format_service_notification(&ServiceEvent::WatchedItemAdded {
+                list_name: "Pharmacy".to_string(),
+                item_name: "Prescription refill".to_string(),
+                user_name: Some("Alice".to_string()),
+            });
+        assert_eq!(title, "👀 Watched item added: Prescription refill");
+        assert_eq!(message, "Added to Pharmacy\nChanged by: Alice");
+        assert_eq!(priority, "high");
+        assert_eq!(tags, vec!["eyes"]);
+    }
+
+    #[test]
+    fn test_list_name_tag() {
+        assert_eq!(list_name_tag("Groceries"), Some("list-groceries".to_string()));
+        assert_eq!(
+            list_name_tag("  Weekly Groceries 🛒"),
+            Some("list-weekly-groceries".to_string())
+        );
+        assert_eq!(
+            list_name_tag("Mom's list, v2"),
+            Some("list-mom-s-list-v2".to_string())
+        );
+        assert_eq!(list_name_tag("Café"), Some("list-caf".to_string()));
+        assert_eq!(list_name_tag("🎄"), None);
+    }
+
+    #[test]
+    fn test_format_tag_list_name() {
+        let client = test_client(NotificationsConfig {
+            tag_list_name: true,
+            ascii_only: true,
+            ..NotificationsConfig::default()
+        });
+
+        let message = client.change_message(&removed_change(), &context());
+
+        // Kept with ascii_only, which drops the emoji tags
+        assert_eq!(message.tags, Some(vec!["list-groceries".to_string()]));
+
+        // The tag is left out of the wording shared with Slack
+        let (_, _, _, tags) = client.format_notification(&removed_change(), &context());
+        assert!(tags.is_empty());
+    }
+
+    #[test]
+    fn test_format_ascii_only() {
+        let client = test_client(NotificationsConfig {
+            ascii_only: true,
+            ..NotificationsConfig::default()
+        });
+
+        let item = ItemInfo {
+            id: "item-1".to_string(),
+            name: "Milk".to_string(),
+            details: "".to_string(),
+            quantity: None,
+            category: None,
+            user_id: None,
+        };
+        let changes = vec![
+            ListChange::ItemAdded {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                item: item.clone(),
+                user_id: None,
+            },
+            removed_change(),
+            ListChange::ItemChecked {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                item: item.clone(),
+                user_id: None,
+            },
+            ListChange::ItemUnchecked {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                item: item.clone(),
+                user_id: None,
+            },
+            ListChange::ItemDone {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                item_id: "item-1".to_string(),
+                item_name: "Milk".to_string(),
+                user_id: None,
+            },
+            ListChange::ItemModified {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                item_id: "item-1".to_string(),
+                item_name: "Milk".to_string(),
+                changes: vec![FieldChange::Quantity {
+                    old: Some("1".to_string()),
+                    new: Some("2".to_string()),
+                }],
+                user_id: None,
+            },
+            ListChange::ListSharingChanged {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                added_users: vec!["user-1".to_string()],
+                removed_users: Vec::new(),
+            },
+            ListChange::ListBecameShared {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                added_users: vec!["user-1".to_string()],
+            },
+            ListChange::ListBecamePrivate {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                removed_users: vec!["user-1".to_string()],
+            },
+            ListChange::ItemsRecategorized {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                item_ids: vec!["item-1".to_string(), "item-2".to_string()],
+                item_names: vec!["Milk".to_string(), "Bread".to_string()],
+                user_id: None,
+            },
+            ListChange::ItemToggledRepeatedly {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                item_id: "item-1".to_string(),
+                item_name: "Milk".to_string(),
+                toggle_count: 5,
+                user_id: None,
+            },
+            ListChange::ItemMarkedMaybe {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                item: item_info("Milk"),
+                user_id: None,
+            },
+            ListChange::ListThresholdCrossed {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                category: None,
+                unchecked_count: 10,
+                threshold: 10,
+                direction: ThresholdDirection::Above,
+            },
+            ListChange::ListThresholdCrossed {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                category: None,
+                unchecked_count: 9,
+                threshold: 10,
+                direction: ThresholdDirection::Below,
+            },
+        ];
+
+        for change in &changes {
+            let (title, message, _, tags) = client.format_notification(change, &context());
+            assert!(title.is_ascii(), "non-ASCII title: {}", title);
+            assert!(message.is_ascii(), "non-ASCII message: {}", message);
+            assert!(tags.is_empty());
+        }
+
+        let (title, message, _, tags) =
+            client.format_service_notification(&ServiceEvent::CacheUnavailable {
+                error: "disk full".to_string(),
+            });
+        assert_eq!(title, "[!] AnyList notifier cache unavailable");
+        assert!(message.is_ascii());
+        assert!(tags.is_empty());
+
+        let (title, _, _, _) = client.format_service_notification(&ServiceEvent::ListSnapshot {
+            list_name: "Groceries".to_string(),
+            items: vec!["Milk".to_string()],
+        });
+        assert_eq!(title, "[=] Groceries: 1 items to get");
+
+        let (title, _, priority, _) = client.format_service_notification(&ServiceEvent::Recovered);
+        assert_eq!(title, "[*] AnyList notifier recovered");
+        assert_eq!(priority, "low");
+    }
+
+    #[test]
+    fn test_format_with_language_pack() {
+        let client = test_client(NotificationsConfig {
+            language: "de".to_string(),
+            include_details_in_removal: true,
+            ..NotificationsConfig::default()
+        });
+
+        let (title, message, _, _) = client.format_notification(&removed_change(), &context());
+
+        assert_eq!(title, "❌ Milk aus Groceries entfernt");
+        assert_eq!(
+            message,
+            "Aus Groceries entfernt\nMenge: 2 gallons\nKategorie: Dairy\nGeändert von: Alice"
+        );
+    }
+
+    #[test]
+    fn test_urgent_keywords_escalate_priority() {
+        let client = test_client(NotificationsConfig {
+            urgent_keywords: vec!["insulin".to_string(), "EpiPen".to_string()],
+            ..NotificationsConfig::default()
+        });
+
+        let checked = |name: &str| ListChange::ItemChecked {
+            list_id: "list-1".to_string(),
+            list_name: "Pharmacy".to_string(),
+            item: item_info(name),
+            user_id: None,
+        };
+
+        let (_, _, priority, tags) =
+            client.format_notification(&checked("Insulin pens"), &context());
+        assert_eq!(priority, "max");
+        assert!(tags.contains(&"rotating_light".to_string()));
+        assert!(tags.contains(&"white_check_mark".to_string()));
+
+        let (_, _, priority, tags) = client.format_notification(&checked("Bandages"), &context());
+        assert_eq!(priority, "low");
+        assert!(!tags.contains(&"rotating_light".to_string()));
+
+        // No calls unless call_on_urgent is set
+        assert_eq!(client.urgent_call(&checked("Insulin pens")), None);
+    }
+
+    #[test]
+    fn test_call_on_urgent() {
+        let client = test_client(NotificationsConfig {
+            urgent_keywords: vec!["insulin".to_string()],
+            call_on_urgent: Some("+12223334444".to_string()),
+            ..NotificationsConfig::default()
+        });
+
+        let checked = |name: &str| ListChange::ItemChecked {
+            list_id: "list-1".to_string(),
+            list_name: "Pharmacy".to_string(),
+            item: item_info(name),
+            user_id: None,
+        };
+
+        assert_eq!(
+            client.urgent_call(&checked("Insulin pens")),
+            Some("+12223334444".to_string())
+        );
+        assert_eq!(client.urgent_call(&checked("Bandages")), None);
+    }
+
+    #[test]
+    fn test_format_quantity_depleted_notification() {
+        let client = test_client(NotificationsConfig::default());
+        let modified = |old: Option<&str>, new: Option<&str>| ListChange::ItemModified {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item_id: "item-1".to_string(),
+            item_name: "Milk".to_string(),
+            changes: vec![FieldChange::Quantity {
+                old: old.map(str::to_string),
+                new: new.map(str::to_string),
+            }],
+            user_id: None,
+        };
+
+        let (title, _, _, tags) =
+            client.format_notification(&modified(None, Some("2")), &context());
+        assert_eq!(title, "✏️ Milk modified in Groceries");
+        assert_eq!(tags, vec!["pencil2"]);
+
+        for change in [
+            modified(Some("2"), None),
+            modified(Some("2 gallons"), Some("0")),
+        ] {
+            let (title, _, priority, tags) = client.format_notification(&change, &context());
+            assert_eq!(title, "📉 Milk running out in Groceries");
+            assert_eq!(priority, "default");
+            assert_eq!(tags, vec!["hourglass"]);
+        }
+    }
+
+    #[test]
+    fn test_format_quantity_set_notification() {
+        let notifications = NotificationsConfig {
+            highlight_quantity_set: true,
+            ..NotificationsConfig::default()
+        };
+        let client = test_client(notifications);
+        let modified = |old: Option<&str>, new: &str| ListChange::ItemModified {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item_id: "item-1".to_string(),
+            item_name: "Milk".to_string(),
+            changes: vec![FieldChange::Quantity {
+                old: old.map(str::to_string),
+                new: Some(new.to_string()),
+            }],
+            user_id: Some("user-1".to_string()),
+        };
+
+        let (title, message, priority, tags) =
+            client.format_notification(&modified(None, "2 gallons"), &context());
+        assert_eq!(title, "📦 Quantity set for Milk: 2 gallons");
+        assert_eq!(message, "In Groceries\nChanged by: Alice");
+        assert_eq!(priority, "default");
+        assert_eq!(tags, vec!["package"]);
+
+        // Later edits to the quantity stay generic modifications
+        let (title, message, _, tags) =
+            client.format_notification(&modified(Some("2 gallons"), "3 gallons"), &context());
+        assert_eq!(title, "✏️ Milk modified in Groceries");
+        assert!(message.starts_with("Quantity: 2 gallons → 3 gallons"));
+        assert_eq!(tags, vec!["pencil2"]);
+
+        // Without the option a first quantity is a modification too
+        let client = test_client(NotificationsConfig::default());
+        let (title, _, _, _) = client.format_notification(&modified(None, "2 gallons"), &context());
+        assert_eq!(title, "✏️ Milk modified in Groceries");
+    }
+
+    #[test]
+    fn test_format_list_snapshot() {
+        let client = test_client(NotificationsConfig::default());
+
+        let (title, message, _, tags) =
+            client.format_service_notification(&ServiceEvent::ListSnapshot {
+                list_name: "Groceries".to_string(),
+                items: vec!["Milk".to_string(), "Bread".to_string()],
+            });
+        assert_eq!(title, "🛒 Groceries: 2 items to get");
+        assert_eq!(message, "Milk\nBread");
+        assert_eq!(tags, vec!["shopping_cart"]);
+
+        let (_, message, _, _) = client.format_service_notification(&ServiceEvent::ListSnapshot {
+            list_name: "Groceries".to_string(),
+            items: Vec::new(),
+        });
+        assert_eq!(message, "Nothing left to get");
+
+        let (title, message, _, _) =
+            client.format_service_notification(&ServiceEvent::UnknownList {
+                list_name: "Grocries".to_string(),
+                available: vec!["Groceries".to_string(), "Hardware".to_string()],
+            });
+        assert_eq!(title, "❓ No list named Grocries");
+        assert_eq!(message, "Available lists: Groceries, Hardware");
+    }
+
+    #[test]
+    fn test_display_transforms_only_affect_notification_text() {
+        let client = test_client(NotificationsConfig {
+            display: DisplayConfig {
+                title_case: true,
+                strip_pattern: Some(r"\s*#\d+$".to_string()),
+                max_name_length: None,
+            },
+            urgent_keywords: vec!["#4021".to_string()],
+            ..NotificationsConfig::default()
+        });
+
+        let change = ListChange::ItemChecked {
+            list_id: "list-1".to_string(),
+            list_name: "GROCERIES".to_string(),
+            item: item_info("WHOLE MILK #4021"),
+            user_id: None,
+        };
+
+        let (title, _, priority, _) = client.format_notification(&change, &context());
+        assert_eq!(title, "✅ Whole Milk checked off in GROCERIES");
+        // Urgency matches the real name
+        assert_eq!(priority, "max");
+        // The change itself keeps the real name used by the cache and diff
+        assert_eq!(change.item_names(), vec!["WHOLE MILK #4021"]);
+    }
+
+    #[test]
+    fn test_format_threshold_crossed() {
+        let client = test_client(NotificationsConfig::default());
+
+        let (title, message, _, tags) = client.format_notification(
+            &ListChange::ListThresholdCrossed {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                category: None,
+                unchecked_count: 10,
+                threshold: 10,
+                direction: ThresholdDirection::Above,
+            },
+            &context(),
+        );
+
+        assert_eq!(title, "🛍️ Groceries has 10 items to get");
+        assert_eq!(message, "Reached the threshold of 10 unchecked items");
+        assert_eq!(tags, vec!["shopping"]);
+
+        let (title, _, _, _) = client.format_notification(
+            &ListChange::ListThresholdCrossed {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                category: Some("Produce".to_string()),
+                unchecked_count: 7,
+                threshold: 8,
+                direction: ThresholdDirection::Below,
+            },
+            &context(),
+        );
+        assert_eq!(title, "🧺 Groceries is down to 7 Produce items");
+    }
+
+    #[test]
+    fn test_format_list_totals() {
+        let client = test_client(NotificationsConfig::default());
+        let with_totals = ChangeContext {
+            list_totals: Some(ListTotals {
+                total: 14,
+                unchecked: 9,
+            }),
+            ..context()
+        };
+
+        let (_, message, _, _) = client.format_notification(&removed_change(), &with_totals);
+        assert!(message.ends_with("\n(Groceries now has 14 items, 9 unchecked)"));
+
+        let (_, message, _, _) = client.format_notification(&removed_change(), &context());
+        assert!(!message.contains("now has"));
+    }
+
+    #[test]
+    fn test_format_marked_maybe() {
+        let client = test_client(NotificationsConfig::default());
+        let change = ListChange::ItemMarkedMaybe {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item_info("Milk"),
+            user_id: Some("user-1".to_string()),
+        };
+
+        let (title, message, priority, tags) = client.format_notification(&change, &context());
+        assert_eq!(title, "🤔 Milk marked as maybe in Groceries");
+        assert_eq!(
+            message,
+            "Checked and unchecked in quick succession; muted until it settles\nChanged by: Alice"
+        );
+        assert_eq!(priority, "default");
+        assert_eq!(tags, vec!["thinking"]);
+    }
+
+    #[test]
+    fn test_format_notification_footer() {
+        let client = test_client(NotificationsConfig {
+            footer: Some("Open {list_name} in AnyList".to_string()),
+            ..NotificationsConfig::default()
+        });
+
+        let (title, message, _, _) = client.format_notification(&removed_change(), &context());
+
+        assert!(!title.contains("Open"));
+        assert!(message.ends_with("Changed by: Alice\nOpen Groceries in AnyList"));
+    }
 }
@@ -0,0 +1,238 @@
+use super::format::Formatter;
+use super::webhook::{self, truncate};
+use super::{ChangeContext, Notifier, ServiceEvent};
+use crate::sync::diff::ListChange;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tracing::debug;
+
+/// Longest text Slack accepts in a section block, in characters
+const MAX_SECTION_CHARS: usize = 3000;
+
+/// Posts notifications to a Slack incoming webhook
+///
+/// The wording comes from the shared formatter, so every backend describes a
+/// change the same way, but it is laid out as a Block Kit section led by a
+/// Slack emoji for the kind of change, with the plain title as the push
+/// notification text.
+pub struct SlackClient {
+    client: Client,
+    webhook_url: String,
+    formatter: Formatter,
+}
+
+#[derive(Debug, Serialize)]
+struct SlackMessage {
+    /// Shown in push notifications and by clients that can't render blocks
+    text: String,
+    blocks: Vec<Value>,
+}
+
+impl SlackMessage {
+    fn new(title: &str, message: &str, emoji: Option<&str>) -> Self {
+        let section = truncate(&format_text(title, message, emoji), MAX_SECTION_CHARS);
+        Self {
+            text: escape(title),
+            blocks: vec![json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": section },
+            })],
+        }
+    }
+}
+
+impl SlackClient {
+    pub fn new(webhook_url: String, formatter: Formatter) -> Self {
+        Self {
+            client: Client::new(),
+            webhook_url,
+            formatter,
+        }
+    }
+
+    fn change_message(&self, change: &ListChange, context: &ChangeContext) -> SlackMessage {
+        let (title, message) = self.formatter.change(change, context);
+        SlackMessage::new(&title, &message, self.emoji(change_emoji(change)))
+    }
+
+    fn service_message(&self, event: &ServiceEvent) -> SlackMessage {
+        let (title, message) = self.formatter.service_event(event);
+        SlackMessage::new(&title, &message, self.emoji(service_emoji(event)))
+    }
+
+    /// The emoji to lead a message with, none with `ascii_only`
+    fn emoji(&self, emoji: &'static str) -> Option<&'static str> {
+        (!self.formatter.ascii_only()).then_some(emoji)
+    }
+
+    async fn send_message(&self, message: &SlackMessage) -> Result<()> {
+        webhook::post_json(&self.client, &self.webhook_url, message, "Slack").await
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackClient {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn notify(&self, change: &ListChange, context: &ChangeContext) -> Result<()> {
+        let message = self.change_message(change, context);
+        debug!("Sending notification to Slack: {}", message.text);
+        self.send_message(&message).await
+    }
+
+    async fn notify_service(&self, event: &ServiceEvent) -> Result<()> {
+        self.send_message(&self.service_message(event)).await
+    }
+
+    fn render(&self, change: &ListChange, context: &ChangeContext) -> Result<String> {
+        serde_json::to_string(&self.change_message(change, context))
+            .context("Failed to serialize Slack message")
+    }
+
+    fn render_service(&self, event: &ServiceEvent) -> Result<Option<String>> {
+        serde_json::to_string(&self.service_message(event))
+            .map(Some)
+            .context("Failed to serialize Slack message")
+    }
+}
+
+/// Slack emoji shortcode for the kind of change
+fn change_emoji(change: &ListChange) -> &'static str {
+    match change {
+        ListChange::ItemAdded { .. } | ListChange::ItemAddedToLists { .. } => "heavy_plus_sign",
+        ListChange::ItemRemoved { .. } | ListChange::ItemDone { .. } => "x",
+        ListChange::ItemChecked { .. } | ListChange::ItemAddedAndChecked { .. } => {
+            "white_check_mark"
+        }
+        ListChange::ItemUnchecked { .. } => "arrow_backward",
+        ListChange::ItemModified { .. } => "pencil2",
+        ListChange::ListSharingChanged { .. } => "busts_in_silhouette",
+        ListChange::ListBecameShared { .. } => "unlock",
+        ListChange::ListBecamePrivate { .. } => "lock",
+        ListChange::ItemsRecategorized { .. } => "card_index_dividers",
+        ListChange::ChangesByUser { .. } => "bust_in_silhouette",
+        ListChange::ListThresholdCrossed { .. } => "shopping_bags",
+        ListChange::ItemToggledRepeatedly { .. } => "repeat",
+        ListChange::ItemMarkedMaybe { .. } => "thinking_face",
+    }
+}
+
+/// Slack emoji shortcode for the kind of service event
+fn service_emoji(event: &ServiceEvent) -> &'static str {
+    match event {
+        ServiceEvent::CacheUnavailable { .. } => "warning",
+        ServiceEvent::Recovered => "arrows_counterclockwise",
+        ServiceEvent::ListSnapshot { .. } => "shopping_trolley",
+        ServiceEvent::UnknownList { .. } => "question",
+        ServiceEvent::MissedChanges { .. } => "electric_plug",
+        ServiceEvent::ListReseeded { .. } => "recycle",
+        ServiceEvent::Digest { .. } => "newspaper",
+        ServiceEvent::WatchedItemAdded { .. } => "eyes",
+        ServiceEvent::EditingStarted { .. } => "writing_hand",
+        ServiceEvent::Heartbeat { .. } => "green_heart",
+    }
+}
+
+/// Render a notification as Slack mrkdwn: an emoji shortcode, a bold title and the body
+fn format_text(title: &str, message: &str, emoji: Option<&str>) -> String {
+    let mut text = String::new();
+    if let Some(emoji) = emoji {
+        text.push_str(&format!(":{}: ", emoji));
+    }
+    text.push_str(&format!("*{}*", escape(title)));
+    if !message.is_empty() {
+        text.push('\n');
+        text.push_str(&escape(message));
+    }
+    text
+}
+
+/// Escape the characters Slack treats as control sequences in message text
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NotificationsConfig;
+    use crate::sync::diff::test_support::{added, checked};
+
+    fn client(notifications: NotificationsConfig) -> SlackClient {
+        SlackClient::new(
+            "https://hooks.slack.com/services/test".to_string(),
+            Formatter::new(notifications),
+        )
+    }
+
+    fn section_text(message: &SlackMessage) -> &str {
+        message.blocks[0]["text"]["text"].as_str().unwrap()
+    }
+
+    #[test]
+    fn test_format_text() {
+        assert_eq!(
+            format_text("Milk added", "To Groceries", Some("heavy_plus_sign")),
+            ":heavy_plus_sign: *Milk added*\nTo Groceries"
+        );
+        assert_eq!(format_text("Milk added", "", None), "*Milk added*");
+    }
+
+    #[test]
+    fn test_format_text_escapes_control_characters() {
+        assert_eq!(
+            format_text("<Milk> & Bread", "a > b", None),
+            "*&lt;Milk&gt; &amp; Bread*\na &gt; b"
+        );
+    }
+
+    #[test]
+    fn test_message_blocks() {
+        let message =
+            SlackMessage::new("Milk & eggs added", "To Groceries", Some("heavy_plus_sign"));
+
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({
+                "text": "Milk &amp; eggs added",
+                "blocks": [{
+                    "type": "section",
+                    "text": {
+                        "type": "mrkdwn",
+                        "text": ":heavy_plus_sign: *Milk &amp; eggs added*\nTo Groceries",
+                    },
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_emoji_follows_the_change_not_ntfy_tags() {
+        let client = client(NotificationsConfig::default());
+        let context = ChangeContext::default();
+
+        let message = client.change_message(&added("1", "Milk"), &context);
+        assert!(section_text(&message).starts_with(":heavy_plus_sign: *"));
+
+        let message = client.change_message(&checked("1", "Milk"), &context);
+        assert!(section_text(&message).starts_with(":white_check_mark: *"));
+    }
+
+    #[test]
+    fn test_ascii_only_drops_the_emoji() {
+        let client = client(NotificationsConfig {
+            ascii_only: true,
+            ..NotificationsConfig::default()
+        });
+
+        let message = client.change_message(&added("1", "Milk"), &ChangeContext::default());
+        assert!(section_text(&message).starts_with('*'));
+    }
+}
@@ -0,0 +1,204 @@
+use crate::notify::{ChangeContext, Notifier, ServiceEvent};
+use crate::sync::diff::ListChange;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// How long a write to one client may take before that client is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Streams list changes as newline-delimited JSON to clients of a Unix socket
+pub struct UnixSocketNotifier {
+    path: PathBuf,
+    clients: Arc<Mutex<Vec<UnixStream>>>,
+    accept_task: JoinHandle<()>,
+}
+
+impl UnixSocketNotifier {
+    /// Bind the socket, replacing one left behind by a previous run
+    pub async fn bind(path: &Path) -> Result<Self> {
+        remove_stale_socket(path)?;
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to bind Unix socket {}", path.display()))?;
+        info!("Streaming changes to Unix socket {}", path.display());
+
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let accept_task = tokio::spawn(accept_clients(listener, clients.clone()));
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            clients,
+            accept_task,
+        })
+    }
+
+    /// Write a line to every client, dropping those that disconnected or stalled
+    async fn broadcast(&self, line: &str) {
+        let mut clients = self.clients.lock().await;
+        let mut connected = Vec::with_capacity(clients.len());
+        for mut client in clients.drain(..) {
+            match tokio::time::timeout(WRITE_TIMEOUT, client.write_all(line.as_bytes())).await {
+                Ok(Ok(())) => connected.push(client),
+                Ok(Err(e)) => debug!("Unix socket client disconnected: {}", e),
+                Err(_) => warn!("Dropping Unix socket client that stopped reading"),
+            }
+        }
+        *clients = connected;
+    }
+}
+
+impl Drop for UnixSocketNotifier {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[async_trait]
+impl Notifier for UnixSocketNotifier {
+    fn name(&self) -> &str {
+        "unix_socket"
+    }
+
+    fn is_push(&self) -> bool {
+        false
+    }
+
+    async fn notify(&self, change: &ListChange, _context: &ChangeContext) -> Result<()> {
+        let mut line = serde_json::to_string(change).context("Failed to serialize change")?;
+        line.push('\n');
+        self.broadcast(&line).await;
+        Ok(())
+    }
+
+    /// Only list changes are streamed
+    async fn notify_service(&self, _event: &ServiceEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+async fn accept_clients(listener: UnixListener, clients: Arc<Mutex<Vec<UnixStream>>>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                debug!("Unix socket client connected");
+                clients.lock().await.push(stream);
+            }
+            Err(e) => {
+                warn!("Failed to accept Unix socket client: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+/// Remove a socket file left by an earlier run, refusing to touch other files
+fn remove_stale_socket(path: &Path) -> Result<()> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display())),
+        Ok(_) => anyhow::bail!(
+            "{} exists and is not a socket, refusing to replace it",
+            path.display()
+        ),
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::ItemInfo;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "anylist_notify_{}_{}.sock",
+            name,
+            std::process::id()
+        ))
+    }
+
+    fn added_change() -> ListChange {
+        ListChange::ItemAdded {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: ItemInfo {
+                id: "item-1".to_string(),
+                name: "Milk".to_string(),
+                details: "".to_string(),
+                quantity: None,
+                category: None,
+                user_id: None,
+            },
+            user_id: None,
+        }
+    }
+
+    /// Wait for the accept loop to register the expected number of clients
+    async fn wait_for_clients(notifier: &UnixSocketNotifier, count: usize) {
+        for _ in 0..100 {
+            if notifier.clients.lock().await.len() == count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Expected {} Unix socket clients", count);
+    }
+
+    #[tokio::test]
+    async fn test_streams_changes_as_json_lines() {
+        let path = socket_path("stream");
+        let notifier = UnixSocketNotifier::bind(&path).await.unwrap();
+
+        let client = UnixStream::connect(&path).await.unwrap();
+        let context = ChangeContext::default();
+        wait_for_clients(&notifier, 1).await;
+
+        notifier.notify(&added_change(), &context).await.unwrap();
+
+        let mut lines = BufReader::new(client).lines();
+        let line = lines.next_line().await.unwrap().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["type"], "item_added");
+        assert_eq!(json["item"]["name"], "Milk");
+
+        // A disconnected client is dropped without failing the notification
+        drop(lines);
+        notifier.notify(&added_change(), &context).await.unwrap();
+        notifier.notify(&added_change(), &context).await.unwrap();
+        wait_for_clients(&notifier, 0).await;
+
+        drop(notifier);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_replaces_stale_socket() {
+        let path = socket_path("stale");
+        let first = UnixSocketNotifier::bind(&path).await.unwrap();
+        // Simulate a crash that left the socket file behind
+        std::mem::forget(first);
+
+        let second = UnixSocketNotifier::bind(&path).await.unwrap();
+        assert!(UnixStream::connect(&path).await.is_ok());
+        drop(second);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_to_replace_regular_file() {
+        let path = socket_path("file");
+        std::fs::write(&path, "not a socket").unwrap();
+
+        assert!(UnixSocketNotifier::bind(&path).await.is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Times a rate-limited message is retried before the send counts as failed
+const RATE_LIMIT_RETRIES: u32 = 2;
+
+/// Longest `Retry-After` wait honored before giving up on a message
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Wait used when a rate-limit response has no usable `Retry-After` header
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Post a JSON message to a chat webhook, waiting out the service's rate
+/// limits a couple of times
+pub async fn post_json<T: Serialize + ?Sized>(
+    client: &Client,
+    url: &str,
+    message: &T,
+    service: &str,
+) -> Result<()> {
+    let mut attempt = 0;
+    loop {
+        let response = client
+            .post(url)
+            .json(message)
+            .send()
+            .await
+            .with_context(|| format!("Failed to send notification to {}", service))?;
+
+        let status = response.status();
+        if status.is_success() {
+            info!("{} notification sent", service);
+            return Ok(());
+        }
+        if status == StatusCode::TOO_MANY_REQUESTS && attempt < RATE_LIMIT_RETRIES {
+            let wait = retry_after(response.headers());
+            if wait <= MAX_RETRY_AFTER {
+                attempt += 1;
+                warn!(
+                    "Rate limited by {}, retrying in {}s",
+                    service,
+                    wait.as_secs()
+                );
+                tokio::time::sleep(wait).await;
+                continue;
+            }
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("{} returned error: {} - {}", service, status, body)
+    }
+}
+
+/// Cut text down to `max` characters, marking the cut with an ellipsis
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// How long the service asked us to wait before retrying
+fn retry_after(headers: &HeaderMap) -> Duration {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_RETRY_AFTER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("Milk", 4), "Milk");
+        assert_eq!(truncate("Oat milk", 4), "Oat…");
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), DEFAULT_RETRY_AFTER);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("5"));
+        assert_eq!(retry_after(&headers), Duration::from_secs(5));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(retry_after(&headers), DEFAULT_RETRY_AFTER);
+    }
+}
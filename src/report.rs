@@ -0,0 +1,392 @@
+use crate::cache::{ActivityBucket, DbHistoryEntry};
+use crate::notify::format::format_duration;
+use crate::sync::diff::ChangeCounts;
+use crate::sync::digest::count_entry;
+use crate::sync::drift::ListDrift;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Label for recorded changes whose author is unknown
+const UNKNOWN_USER: &str = "Unknown user";
+
+/// Weekday names indexed by SQLite's `%w` (0 = Sunday)
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// Output format for reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+/// Change counts for every weekday and hour of the day
+#[derive(Debug, Serialize)]
+pub struct ActivityReport {
+    pub total: i64,
+    pub buckets: Vec<ActivityCell>,
+}
+
+/// Change count for a single weekday and hour
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ActivityCell {
+    pub weekday: &'static str,
+    pub hour: i64,
+    pub count: i64,
+}
+
+impl ActivityReport {
+    /// Build a full 7x24 grid, filling hours without activity with zero
+    pub fn from_buckets(buckets: &[ActivityBucket]) -> Self {
+        let mut counts = [[0i64; 24]; 7];
+        for bucket in buckets {
+            if (0..7).contains(&bucket.weekday) && (0..24).contains(&bucket.hour) {
+                counts[bucket.weekday as usize][bucket.hour as usize] += bucket.count;
+            }
+        }
+
+        let buckets = counts
+            .iter()
+            .enumerate()
+            .flat_map(|(weekday, hours)| {
+                hours
+                    .iter()
+                    .enumerate()
+                    .map(move |(hour, count)| ActivityCell {
+                        weekday: WEEKDAYS[weekday],
+                        hour: hour as i64,
+                        count: *count,
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            total: buckets.iter().map(|cell| cell.count).sum(),
+            buckets,
+        }
+    }
+
+    /// Render the report in the given format
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Json => {
+                serde_json::to_string_pretty(self).context("Failed to serialize activity report")
+            }
+            ReportFormat::Csv => {
+                let mut csv = String::from("weekday,hour,count\n");
+                for cell in &self.buckets {
+                    csv.push_str(&format!("{},{},{}\n", cell.weekday, cell.hour, cell.count));
+                }
+                Ok(csv)
+            }
+        }
+    }
+}
+
+/// Render a human-readable summary of cache drift, one section per list
+pub fn render_drift(drift: &[ListDrift]) -> String {
+    let mut out = String::new();
+    let mut total_changes = 0;
+    let mut drifted_lists = 0;
+
+    for list in drift {
+        match list {
+            ListDrift::New {
+                list_name,
+                item_count,
+            } => {
+                drifted_lists += 1;
+                out.push_str(&format!(
+                    "{}: new list with {} items\n",
+                    list_name, item_count
+                ));
+            }
+            ListDrift::Deleted { list_name } => {
+                drifted_lists += 1;
+                out.push_str(&format!("{}: deleted\n", list_name));
+            }
+            ListDrift::Changed { list_name, changes } if changes.is_empty() => {
+                out.push_str(&format!("{}: no changes\n", list_name));
+            }
+            ListDrift::Changed { list_name, changes } => {
+                drifted_lists += 1;
+                total_changes += changes.len();
+
+                let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+                for change in changes {
+                    *counts.entry(change.change_type()).or_default() += 1;
+                }
+                let counts = counts
+                    .iter()
+                    .map(|(change_type, count)| format!("{} {}", count, change_type))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                out.push_str(&format!(
+                    "{}: {} changes ({})\n",
+                    list_name,
+                    changes.len(),
+                    counts
+                ));
+
+                for change in changes {
+                    out.push_str(&format!(
+                        "  {}: {}\n",
+                        change.change_type(),
+                        change.item_names().join(", ")
+                    ));
+                }
+            }
+        }
+    }
+
+    out.push_str(&format!(
+        "Drift: {} changes across {} of {} lists\n",
+        total_changes,
+        drifted_lists,
+        drift.len()
+    ));
+    out
+}
+
+/// Count recorded changes per user, most active first
+///
+/// Users are labelled with their display name from `user_names`, or their ID
+/// when no name is known; changes without an author count as one unknown user.
+pub fn user_activity(
+    entries: &[DbHistoryEntry],
+    user_names: &HashMap<String, String>,
+) -> Vec<(String, ChangeCounts)> {
+    let mut users: HashMap<&str, ChangeCounts> = HashMap::new();
+    for entry in entries {
+        let user = match entry.user_id.as_deref() {
+            Some(user_id) => user_names
+                .get(user_id)
+                .map(String::as_str)
+                .unwrap_or(user_id),
+            None => UNKNOWN_USER,
+        };
+        count_entry(users.entry(user).or_default(), entry);
+    }
+
+    let mut activity: Vec<(String, ChangeCounts)> = users
+        .into_iter()
+        .filter(|(_, counts)| !counts.is_empty())
+        .map(|(user, counts)| (user.to_string(), counts))
+        .collect();
+    activity.sort_by(|(a_user, a), (b_user, b)| {
+        b.total().cmp(&a.total()).then_with(|| a_user.cmp(b_user))
+    });
+    activity
+}
+
+/// Render per-user change counts over the last `period_secs`, one line per user
+pub fn render_user_activity(activity: &[(String, ChangeCounts)], period_secs: u64) -> String {
+    let total: usize = activity.iter().map(|(_, counts)| counts.total()).sum();
+    let mut out = format!(
+        "Last {}: {} changes by {} users\n",
+        format_duration(period_secs),
+        total,
+        activity.len()
+    );
+    for (user, counts) in activity {
+        let kinds = [
+            ("added", counts.added),
+            ("checked off", counts.checked),
+            ("unchecked", counts.unchecked),
+            ("modified", counts.modified),
+            ("removed", counts.removed),
+        ];
+        let kinds = kinds
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(kind, count)| format!("{} {}", kind, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!("{}: {}\n", user, kinds));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::{ItemInfo, ListChange};
+
+    fn history(change_type: &str, user_id: Option<&str>) -> DbHistoryEntry {
+        DbHistoryEntry {
+            id: 0,
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            change_type: change_type.to_string(),
+            user_id: user_id.map(str::to_string),
+            created_at: 0,
+            payload: "{}".to_string(),
+            dedup_key: None,
+        }
+    }
+
+    fn buckets() -> Vec<ActivityBucket> {
+        vec![
+            ActivityBucket {
+                weekday: 1,
+                hour: 9,
+                count: 3,
+            },
+            ActivityBucket {
+                weekday: 6,
+                hour: 18,
+                count: 2,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_report_fills_full_grid() {
+        let report = ActivityReport::from_buckets(&buckets());
+
+        assert_eq!(report.buckets.len(), 7 * 24);
+        assert_eq!(report.total, 5);
+        assert_eq!(
+            report.buckets[24 + 9],
+            ActivityCell {
+                weekday: "Monday",
+                hour: 9,
+                count: 3
+            }
+        );
+        assert_eq!(report.buckets[0].count, 0);
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let csv = ActivityReport::from_buckets(&buckets())
+            .render(ReportFormat::Csv)
+            .unwrap();
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "weekday,hour,count");
+        assert_eq!(lines.len(), 1 + 7 * 24);
+        assert!(lines.contains(&"Saturday,18,2"));
+    }
+
+    #[test]
+    fn test_render_drift() {
+        let checked = |name: &str| ListChange::ItemChecked {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: ItemInfo {
+                id: "item-1".to_string(),
+                name: name.to_string(),
+                details: "".to_string(),
+                quantity: None,
+                category: None,
+                user_id: None,
+            },
+            user_id: None,
+        };
+        let added = ListChange::ItemAdded {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: ItemInfo {
+                id: "item-3".to_string(),
+                name: "Eggs".to_string(),
+                details: "".to_string(),
+                quantity: None,
+                category: None,
+                user_id: None,
+            },
+            user_id: None,
+        };
+
+        let drift = vec![
+            ListDrift::Changed {
+                list_name: "Groceries".to_string(),
+                changes: vec![checked("Milk"), added, checked("Bread")],
+            },
+            ListDrift::Changed {
+                list_name: "Hardware".to_string(),
+                changes: Vec::new(),
+            },
+            ListDrift::New {
+                list_name: "Party".to_string(),
+                item_count: 12,
+            },
+            ListDrift::Deleted {
+                list_name: "Old".to_string(),
+            },
+        ];
+
+        let output = render_drift(&drift);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Groceries: 3 changes (1 item_added, 2 item_checked)",
+                "  item_checked: Milk",
+                "  item_added: Eggs",
+                "  item_checked: Bread",
+                "Hardware: no changes",
+                "Party: new list with 12 items",
+                "Old: deleted",
+                "Drift: 3 changes across 3 of 4 lists",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json = ActivityReport::from_buckets(&buckets())
+            .render(ReportFormat::Json)
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["total"], 5);
+        assert_eq!(value["buckets"][24 + 9]["weekday"], "Monday");
+        assert_eq!(value["buckets"][24 + 9]["count"], 3);
+    }
+
+    #[test]
+    fn test_user_activity() {
+        let entries = vec![
+            history("item_added", Some("alice")),
+            history("item_added", Some("alice")),
+            history("item_checked", Some("bob")),
+            history("item_checked", Some("bob")),
+            history("item_removed", Some("bob")),
+            history("item_added", Some("carol")),
+            history("list_sharing_changed", Some("dave")),
+            history("item_modified", None),
+        ];
+        let user_names = HashMap::from([
+            ("alice".to_string(), "Alice".to_string()),
+            ("bob".to_string(), "Bob".to_string()),
+        ]);
+
+        let activity = user_activity(&entries, &user_names);
+        let users: Vec<&str> = activity.iter().map(|(user, _)| user.as_str()).collect();
+        // Users without a known name keep their ID; list-level changes aren't counted
+        assert_eq!(users, vec!["Bob", "Alice", "Unknown user", "carol"]);
+
+        let output = render_user_activity(&activity, 7 * 86_400);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Last 7d: 7 changes by 4 users",
+                "Bob: checked off 2, removed 1",
+                "Alice: added 2",
+                "Unknown user: modified 1",
+                "carol: added 1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::{HashMap, HashSet};
+
+/// Counts consecutive fetches each cached list was missing from
+///
+/// A fetch that comes back without a list may just be truncated, so a list
+/// only counts as deleted once it has been absent from `required` fetches in
+/// a row.
+#[derive(Debug)]
+pub struct AbsentLists {
+    required: u32,
+    absent: HashMap<String, u32>,
+}
+
+impl AbsentLists {
+    pub fn new(required: u32) -> Self {
+        Self {
+            required: required.max(1),
+            absent: HashMap::new(),
+        }
+    }
+
+    /// Record a successful fetch, returning the cached lists now considered deleted
+    pub fn observe<'a>(
+        &mut self,
+        cached_ids: impl IntoIterator<Item = &'a str>,
+        current_ids: &HashSet<&str>,
+    ) -> Vec<String> {
+        let mut missing = HashMap::new();
+        let mut deleted = Vec::new();
+        for id in cached_ids {
+            if current_ids.contains(id) {
+                continue;
+            }
+            let count = self.absent.get(id).copied().unwrap_or(0) + 1;
+            if count >= self.required {
+                deleted.push(id.to_string());
+            } else {
+                missing.insert(id.to_string(), count);
+            }
+        }
+        // Lists that reappeared start counting from zero again
+        self.absent = missing;
+        deleted
+    }
+
+    /// How many fetches in a row a list has been missing from
+    pub fn count(&self, list_id: &str) -> u32 {
+        self.absent.get(list_id).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_deleted_after_consecutive_absences() {
+        let mut absent = AbsentLists::new(3);
+        let cached = ["groceries", "hardware"];
+        let only_groceries = HashSet::from(["groceries"]);
+        let both = HashSet::from(["groceries", "hardware"]);
+
+        assert!(absent.observe(cached, &only_groceries).is_empty());
+        assert!(absent.observe(cached, &only_groceries).is_empty());
+        assert_eq!(absent.count("hardware"), 2);
+
+        // A fetch that includes the list again resets its count
+        assert!(absent.observe(cached, &both).is_empty());
+        assert_eq!(absent.count("hardware"), 0);
+
+        assert!(absent.observe(cached, &only_groceries).is_empty());
+        assert!(absent.observe(cached, &only_groceries).is_empty());
+        assert_eq!(
+            absent.observe(cached, &only_groceries),
+            vec!["hardware".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_single_confirmation_deletes_immediately() {
+        let mut absent = AbsentLists::new(1);
+        assert_eq!(
+            absent.observe(["groceries"], &HashSet::new()),
+            vec!["groceries".to_string()]
+        );
+    }
+}
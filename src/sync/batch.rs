@@ -0,0 +1,190 @@
+use crate::sync::diff::{is_user_action, ListChange};
+use crate::sync::held::HoldsChanges;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// How often batches are checked for a closed window
+pub const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Collects rapid changes to a list into one notification
+///
+/// The first item change to a list opens a batch; it and every further change
+/// to that list are held until `window` has passed since it opened, so a
+/// steady stream of changes can't delay the batch forever. Other kinds of
+/// change are never held.
+#[derive(Debug)]
+pub struct ChangeBatcher {
+    window: Duration,
+    batches: HashMap<String, Batch>,
+}
+
+#[derive(Debug)]
+struct Batch {
+    list_name: String,
+    opened: Instant,
+    held: Vec<ListChange>,
+}
+
+impl ChangeBatcher {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            batches: HashMap::new(),
+        }
+    }
+
+    /// Hold back a pass's item changes, returning the ones to notify now
+    pub fn hold(&mut self, changes: Vec<ListChange>, now: Instant) -> Vec<ListChange> {
+        let mut ready = Vec::new();
+        for change in changes {
+            if !is_user_action(&change) {
+                ready.push(change);
+                continue;
+            }
+            self.batches
+                .entry(change.list_id().to_string())
+                .or_insert_with(|| Batch {
+                    list_name: change.list_name().to_string(),
+                    opened: now,
+                    held: Vec::new(),
+                })
+                .held
+                .push(change);
+        }
+        ready
+    }
+}
+
+impl HoldsChanges for ChangeBatcher {
+    /// Summarize the batches whose window has closed
+    fn take_expired(&mut self, now: Instant) -> Vec<ListChange> {
+        let window = self.window;
+        let closed: Vec<String> = self
+            .batches
+            .iter()
+            .filter(|(_, batch)| now.duration_since(batch.opened) >= window)
+            .map(|(list_id, _)| list_id.clone())
+            .collect();
+        closed
+            .into_iter()
+            .filter_map(|list_id| {
+                let batch = self.batches.remove(&list_id)?;
+                Some(summarize(list_id, batch))
+            })
+            .collect()
+    }
+
+    fn take_all(&mut self) -> Vec<ListChange> {
+        self.batches
+            .drain()
+            .map(|(list_id, batch)| summarize(list_id, batch))
+            .collect()
+    }
+}
+
+/// The change itself for a batch of one, otherwise a summary attributed to
+/// the batch's user if every change was made by the same one
+fn summarize(list_id: String, mut batch: Batch) -> ListChange {
+    if batch.held.len() == 1 {
+        return batch.held.remove(0);
+    }
+    debug!(
+        "Batched {} change(s) to {}",
+        batch.held.len(),
+        batch.list_name
+    );
+    let first_user = batch.held[0].user_id();
+    let user_id = batch
+        .held
+        .iter()
+        .all(|change| change.user_id() == first_user)
+        .then(|| first_user.map(str::to_string))
+        .flatten();
+    ListChange::ChangesByUser {
+        list_id,
+        list_name: batch.list_name,
+        user_id,
+        changes: batch.held,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::test_support::{added, by_user, on_list};
+
+    fn added_to(list_id: &str, name: &str, user_id: &str) -> ListChange {
+        let change = added(&format!("id-{}", name), name);
+        by_user(
+            on_list(change, list_id, &format!("List {}", list_id)),
+            user_id,
+        )
+    }
+
+    #[test]
+    fn test_changes_within_window_are_summarized() {
+        let mut batcher = ChangeBatcher::new(Duration::from_millis(500));
+        let start = Instant::now();
+
+        let ready = batcher.hold(
+            vec![
+                added_to("1", "Milk", "alice"),
+                added_to("1", "Bread", "alice"),
+            ],
+            start,
+        );
+        assert!(ready.is_empty());
+        let ready = batcher.hold(
+            vec![
+                added_to("1", "Eggs", "alice"),
+                added_to("2", "Nails", "bob"),
+            ],
+            start + Duration::from_millis(300),
+        );
+        assert!(ready.is_empty());
+
+        // The window runs from the first change, not the latest
+        assert!(batcher
+            .take_expired(start + Duration::from_millis(499))
+            .is_empty());
+        let released = batcher.take_expired(start + Duration::from_millis(500));
+        assert_eq!(released.len(), 1);
+        match &released[0] {
+            ListChange::ChangesByUser {
+                list_name,
+                user_id,
+                changes,
+                ..
+            } => {
+                assert_eq!(list_name, "List 1");
+                assert_eq!(user_id.as_deref(), Some("alice"));
+                assert_eq!(changes.len(), 3);
+            }
+            other => panic!("expected a summary, got {:?}", other),
+        }
+
+        // A batch of one is released as the change itself
+        let released = batcher.take_all();
+        assert!(matches!(&released[..], [ListChange::ItemAdded { .. }]));
+    }
+
+    #[test]
+    fn test_mixed_users_are_not_attributed() {
+        let mut batcher = ChangeBatcher::new(Duration::from_millis(500));
+        let start = Instant::now();
+
+        batcher.hold(
+            vec![
+                added_to("1", "Milk", "alice"),
+                added_to("1", "Bread", "bob"),
+            ],
+            start,
+        );
+        let released = batcher.take_all();
+        assert!(matches!(
+            &released[..],
+            [ListChange::ChangesByUser { user_id: None, .. }]
+        ));
+    }
+}
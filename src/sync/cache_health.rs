@@ -0,0 +1,170 @@
+use crate::cache::DbItem;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Consecutive write failures before the cache is reported as unavailable
+const ALERT_THRESHOLD: u32 = 3;
+
+/// Initial delay between cache write attempts once writes start failing
+const BASE_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Upper bound for the delay between cache write attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// State of a list that was notified but could not be written to the cache
+#[derive(Debug, Clone)]
+pub struct ShadowList {
+    pub items: Vec<DbItem>,
+    pub shared_users: Vec<String>,
+}
+
+impl ShadowList {
+    /// Snapshot a live list, keeping `first_seen` from the previous baseline
+    pub fn from_list(list: &anylist_rs::List, baseline: &[DbItem]) -> Self {
+        let first_seen: HashMap<&str, i64> = baseline
+            .iter()
+            .map(|item| (item.id.as_str(), item.first_seen))
+            .collect();
+
+        let items = list
+            .items
+            .iter()
+            .map(|item| {
+                let mut db_item = DbItem::from(item);
+                if let Some(seen) = first_seen.get(item.id.as_str()) {
+                    db_item.first_seen = *seen;
+                }
+                db_item
+            })
+            .collect();
+
+        Self {
+            items,
+            shared_users: list.shared_users.iter().map(|u| u.user_id.clone()).collect(),
+        }
+    }
+}
+
+/// Tracks cache write health so a broken cache doesn't cause a notification flood
+///
+/// While writes fail, the last notified state of each affected list is kept in
+/// memory and used as the diff baseline instead of the stale cache, and write
+/// attempts back off exponentially.
+#[derive(Debug, Default)]
+pub struct CacheHealth {
+    consecutive_failures: u32,
+    retry_at: Option<Instant>,
+    alerted: bool,
+    shadows: HashMap<String, ShadowList>,
+}
+
+impl CacheHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// In-memory baseline for a list whose cache write failed
+    pub fn shadow(&self, list_id: &str) -> Option<&ShadowList> {
+        self.shadows.get(list_id)
+    }
+
+    /// Whether a cache write should be attempted now
+    pub fn should_attempt_write(&self, now: Instant) -> bool {
+        !matches!(self.retry_at, Some(retry_at) if now < retry_at)
+    }
+
+    /// Keep a list's state in memory without attempting a write
+    pub fn defer(&mut self, list_id: &str, shadow: ShadowList) {
+        self.shadows.insert(list_id.to_string(), shadow);
+    }
+
+    /// Record a failed write; returns true when an alert should be sent
+    pub fn record_failure(&mut self, list_id: &str, shadow: ShadowList, now: Instant) -> bool {
+        self.shadows.insert(list_id.to_string(), shadow);
+        self.consecutive_failures += 1;
+        self.retry_at = Some(now + backoff(self.consecutive_failures));
+
+        if self.consecutive_failures >= ALERT_THRESHOLD && !self.alerted {
+            self.alerted = true;
+            return true;
+        }
+        false
+    }
+
+    /// Record a successful write; returns true when the cache has just recovered
+    pub fn record_success(&mut self, list_id: &str) -> bool {
+        self.shadows.remove(list_id);
+
+        if self.consecutive_failures > 0 && self.shadows.is_empty() {
+            self.consecutive_failures = 0;
+            self.retry_at = None;
+            self.alerted = false;
+            return true;
+        }
+        false
+    }
+}
+
+/// Delay before the next write attempt after the given number of failures
+fn backoff(failures: u32) -> Duration {
+    let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+    BASE_BACKOFF.saturating_mul(factor).min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shadow() -> ShadowList {
+        ShadowList {
+            items: Vec::new(),
+            shared_users: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_alerts_once_after_threshold() {
+        let mut health = CacheHealth::new();
+        let now = Instant::now();
+
+        assert!(!health.record_failure("list-1", shadow(), now));
+        assert!(!health.record_failure("list-1", shadow(), now));
+        assert!(health.record_failure("list-1", shadow(), now));
+        assert!(!health.record_failure("list-1", shadow(), now));
+        assert!(health.shadow("list-1").is_some());
+    }
+
+    #[test]
+    fn test_backs_off_between_attempts() {
+        let mut health = CacheHealth::new();
+        let now = Instant::now();
+        assert!(health.should_attempt_write(now));
+
+        health.record_failure("list-1", shadow(), now);
+        assert!(!health.should_attempt_write(now));
+        assert!(health.should_attempt_write(now + BASE_BACKOFF));
+
+        health.record_failure("list-1", shadow(), now);
+        assert!(!health.should_attempt_write(now + BASE_BACKOFF));
+        assert!(health.should_attempt_write(now + BASE_BACKOFF * 2));
+
+        assert_eq!(backoff(20), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_recovers_when_all_lists_written() {
+        let mut health = CacheHealth::new();
+        let now = Instant::now();
+
+        health.record_failure("list-1", shadow(), now);
+        health.defer("list-2", shadow());
+
+        assert!(!health.record_success("list-1"));
+        assert!(health.shadow("list-1").is_none());
+        assert!(health.record_success("list-2"));
+        assert!(health.should_attempt_write(now));
+
+        // Healthy writes don't report a recovery
+        assert!(!health.record_success("list-1"));
+    }
+}
@@ -0,0 +1,171 @@
+use crate::sync::diff::ListChange;
+use crate::sync::held::{HeldWindow, HoldsChanges};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Merges an item's addition with a check-off that follows shortly after
+///
+/// Adds are held back for the window so a check-off in a later sync pass can
+/// still be merged; adds that aren't checked off are released once it passes.
+#[derive(Debug)]
+pub struct AddCheckCoalescer {
+    held: HeldWindow,
+}
+
+impl AddCheckCoalescer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            held: HeldWindow::new(window),
+        }
+    }
+
+    /// Merge or hold back a pass's changes, returning the ones to notify now
+    ///
+    /// `checked_ids` are the items currently checked off in the list, so an
+    /// item added and checked off between two passes is merged right away.
+    pub fn coalesce(
+        &mut self,
+        changes: Vec<ListChange>,
+        checked_ids: &HashSet<&str>,
+        now: Instant,
+    ) -> Vec<ListChange> {
+        let mut ready = Vec::new();
+        for change in changes {
+            match change {
+                ListChange::ItemAdded {
+                    list_id,
+                    list_name,
+                    item,
+                    user_id,
+                } if checked_ids.contains(item.id.as_str()) => {
+                    ready.push(ListChange::ItemAddedAndChecked {
+                        list_id,
+                        list_name,
+                        item,
+                        user_id,
+                    });
+                }
+                change @ ListChange::ItemAdded { .. } => self.held.hold(change, now),
+                ListChange::ItemChecked {
+                    list_id,
+                    list_name,
+                    item,
+                    user_id,
+                } => {
+                    let merged = self.held.take_item(&item.id).is_some();
+                    ready.push(if merged {
+                        ListChange::ItemAddedAndChecked {
+                            list_id,
+                            list_name,
+                            item,
+                            user_id,
+                        }
+                    } else {
+                        ListChange::ItemChecked {
+                            list_id,
+                            list_name,
+                            item,
+                            user_id,
+                        }
+                    });
+                }
+                change => {
+                    // Keep a held add ahead of any other change to the same item
+                    let item_ids: Vec<String> =
+                        change.item_ids().into_iter().map(str::to_string).collect();
+                    ready.extend(item_ids.iter().filter_map(|id| self.held.take_item(id)));
+                    ready.push(change);
+                }
+            }
+        }
+        ready
+    }
+}
+
+impl HoldsChanges for AddCheckCoalescer {
+    /// Release the held adds whose window passed without a check-off
+    fn take_expired(&mut self, now: Instant) -> Vec<ListChange> {
+        self.held.take_expired(now)
+    }
+
+    fn take_all(&mut self) -> Vec<ListChange> {
+        self.held.take_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::test_support::{added, added_and_checked, checked, item};
+
+    #[test]
+    fn test_merges_add_already_checked() {
+        let mut coalescer = AddCheckCoalescer::new(Duration::from_secs(60));
+        let checked_ids = HashSet::from(["item-1"]);
+
+        let ready = coalescer.coalesce(vec![added("item-1", "Milk")], &checked_ids, Instant::now());
+
+        assert_eq!(ready, vec![added_and_checked("item-1", "Milk")]);
+        assert!(coalescer.take_all().is_empty());
+    }
+
+    #[test]
+    fn test_merges_check_in_later_pass() {
+        let mut coalescer = AddCheckCoalescer::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        let ready = coalescer.coalesce(
+            vec![added("item-1", "Milk"), added("item-2", "Eggs")],
+            &HashSet::new(),
+            start,
+        );
+        assert!(ready.is_empty());
+
+        let ready = coalescer.coalesce(
+            vec![checked("item-1", "Milk")],
+            &HashSet::from(["item-1"]),
+            start + Duration::from_secs(10),
+        );
+        assert_eq!(ready, vec![added_and_checked("item-1", "Milk")]);
+
+        // Eggs wasn't checked off, so it is released once the window passes
+        assert!(coalescer
+            .take_expired(start + Duration::from_secs(30))
+            .is_empty());
+        assert_eq!(
+            coalescer.take_expired(start + Duration::from_secs(60)),
+            vec![added("item-2", "Eggs")]
+        );
+    }
+
+    #[test]
+    fn test_releases_add_before_other_changes() {
+        let mut coalescer = AddCheckCoalescer::new(Duration::from_secs(60));
+        let start = Instant::now();
+        coalescer.coalesce(vec![added("item-1", "Milk")], &HashSet::new(), start);
+
+        let removed = ListChange::ItemRemoved {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item("item-1", "Milk"),
+            user_id: None,
+        };
+        let ready = coalescer.coalesce(vec![removed.clone()], &HashSet::new(), start);
+
+        assert_eq!(ready, vec![added("item-1", "Milk"), removed]);
+        assert!(coalescer.take_all().is_empty());
+    }
+
+    #[test]
+    fn test_unrelated_check_passes_through() {
+        let mut coalescer = AddCheckCoalescer::new(Duration::from_secs(60));
+
+        let ready = coalescer.coalesce(
+            vec![checked("item-1", "Milk")],
+            &HashSet::from(["item-1"]),
+            Instant::now(),
+        );
+
+        assert_eq!(ready, vec![checked("item-1", "Milk")]);
+    }
+}
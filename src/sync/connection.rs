@@ -0,0 +1,199 @@
+use crate::config::SyncConfig;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Wait before the second reconnect attempt, doubled for each one after it
+const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Notices a WebSocket that has silently died
+///
+/// The connection gives no signal when it drops, e.g. after a router reboot,
+/// but it delivers heartbeats while it is alive. Going without any event for
+/// the timeout means it is gone.
+#[derive(Debug)]
+pub struct ConnectionMonitor {
+    timeout: Duration,
+    last_event: Mutex<Instant>,
+}
+
+impl ConnectionMonitor {
+    pub fn new(config: &SyncConfig, now: Instant) -> Self {
+        Self {
+            timeout: Duration::from_secs(config.connection_timeout_secs),
+            last_event: Mutex::new(now),
+        }
+    }
+
+    /// Note that the connection delivered an event or was just established
+    pub fn record_event(&self, now: Instant) {
+        *self.last_event.lock().unwrap() = now;
+    }
+
+    /// Whether the connection has gone quiet for longer than the timeout
+    pub fn is_stale(&self, now: Instant) -> bool {
+        !self.timeout.is_zero()
+            && now.duration_since(*self.last_event.lock().unwrap()) >= self.timeout
+    }
+}
+
+/// Paces polling and reconnect attempts while the WebSocket is down
+///
+/// Both a poll and a reconnect attempt are due right away; after that, polls
+/// follow the poll interval and failed reconnects back off exponentially up
+/// to the configured maximum.
+#[derive(Debug)]
+pub struct PollingFallback {
+    poll_interval: Duration,
+    max_backoff: Duration,
+    backoff: Duration,
+    next_poll: Instant,
+    next_reconnect: Instant,
+}
+
+impl PollingFallback {
+    pub fn new(config: &SyncConfig, now: Instant) -> Self {
+        let max_backoff = Duration::from_secs(config.reconnect_max_backoff_secs);
+        Self {
+            poll_interval: Duration::from_secs(config.poll_interval_secs),
+            max_backoff,
+            backoff: INITIAL_RECONNECT_BACKOFF.min(max_backoff),
+            next_poll: now,
+            next_reconnect: now,
+        }
+    }
+
+    /// Whether it is time to try the WebSocket again
+    pub fn reconnect_due(&self, now: Instant) -> bool {
+        now >= self.next_reconnect
+    }
+
+    /// Schedule the next reconnect attempt, returning how long it is away
+    pub fn reconnect_failed(&mut self, now: Instant) -> Duration {
+        let wait = self.backoff;
+        self.next_reconnect = now + wait;
+        self.backoff = (self.backoff * 2).min(self.max_backoff);
+        wait
+    }
+
+    /// Whether a poll is due, scheduling the next one if so
+    pub fn poll_due(&mut self, now: Instant) -> bool {
+        if now < self.next_poll {
+            return false;
+        }
+        self.next_poll = now + self.poll_interval;
+        true
+    }
+}
+
+/// Keeps fallback polls from overlapping when one outlasts the poll interval
+#[derive(Debug, Default)]
+pub struct PollLock {
+    running: AtomicBool,
+}
+
+/// Holds the poll lock until dropped, so a poll that panics still releases it
+pub struct PollGuard {
+    lock: Arc<PollLock>,
+}
+
+impl PollLock {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Take the lock for a poll, or None while the previous poll is still running
+    pub fn try_start(self: &Arc<Self>) -> Option<PollGuard> {
+        if self.running.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        Some(PollGuard { lock: self.clone() })
+    }
+}
+
+impl Drop for PollGuard {
+    fn drop(&mut self) {
+        self.lock.running.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SyncConfig {
+        SyncConfig {
+            connection_timeout_secs: 60,
+            poll_interval_secs: 30,
+            reconnect_max_backoff_secs: 5,
+            ..SyncConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_quiet_connection_is_stale() {
+        let start = Instant::now();
+        let monitor = ConnectionMonitor::new(&config(), start);
+
+        assert!(!monitor.is_stale(start + Duration::from_secs(59)));
+        monitor.record_event(start + Duration::from_secs(50));
+        assert!(!monitor.is_stale(start + Duration::from_secs(100)));
+        assert!(monitor.is_stale(start + Duration::from_secs(110)));
+
+        // A zero timeout never treats the connection as lost
+        let config = SyncConfig {
+            connection_timeout_secs: 0,
+            ..config()
+        };
+        let monitor = ConnectionMonitor::new(&config, start);
+        assert!(!monitor.is_stale(start + Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_reconnect_backs_off_to_the_maximum() {
+        let start = Instant::now();
+        let mut fallback = PollingFallback::new(&config(), start);
+
+        assert!(fallback.reconnect_due(start));
+        let waits: Vec<u64> = (0..5)
+            .map(|_| fallback.reconnect_failed(start).as_secs())
+            .collect();
+        assert_eq!(waits, vec![1, 2, 4, 5, 5]);
+        assert!(!fallback.reconnect_due(start + Duration::from_secs(4)));
+        assert!(fallback.reconnect_due(start + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_polls_follow_the_interval() {
+        let start = Instant::now();
+        let mut fallback = PollingFallback::new(&config(), start);
+
+        assert!(fallback.poll_due(start));
+        assert!(!fallback.poll_due(start + Duration::from_secs(29)));
+        assert!(fallback.poll_due(start + Duration::from_secs(30)));
+        assert!(!fallback.poll_due(start + Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn test_poll_lock_allows_one_poll_at_a_time() {
+        let lock = PollLock::new();
+        let guard = lock.try_start().expect("Lock should be free");
+        assert!(lock.try_start().is_none());
+
+        drop(guard);
+        assert!(lock.try_start().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_panicking_poll_releases_the_lock() {
+        let lock = PollLock::new();
+        let guard = lock.try_start().expect("Lock should be free");
+
+        let poll = tokio::spawn(async move {
+            let _guard = guard;
+            panic!("poll failed");
+        });
+        assert!(poll.await.is_err());
+        assert!(lock.try_start().is_some());
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Collapses events that arrive in quick succession into one processing pass
+///
+/// The first event waits out the window before it is processed; events
+/// arriving meanwhile are dropped, since the pending pass hasn't fetched the
+/// lists yet and will see their changes too. Nothing is lost this way, unlike
+/// skipping events that arrive shortly after a pass started.
+#[derive(Debug)]
+pub struct EventDebouncer {
+    window: Duration,
+    pending: AtomicBool,
+}
+
+impl EventDebouncer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: AtomicBool::new(false),
+        }
+    }
+
+    /// Wait out the window and return true if this event should be processed,
+    /// or return false right away if a pass is already pending
+    pub async fn enter(&self) -> bool {
+        if self.window.is_zero() {
+            return true;
+        }
+        if self.pending.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        tokio::time::sleep(self.window).await;
+        // Events from here on need another pass, as this one may fetch too early for them
+        self.pending.store(false, Ordering::SeqCst);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_events_within_window_collapse() {
+        let debouncer = Arc::new(EventDebouncer::new(Duration::from_millis(50)));
+
+        let first = tokio::spawn({
+            let debouncer = debouncer.clone();
+            async move { debouncer.enter().await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!debouncer.enter().await);
+        assert!(!debouncer.enter().await);
+        assert!(first.await.unwrap());
+
+        // Once the pending pass starts, a new event gets a pass of its own
+        assert!(debouncer.enter().await);
+    }
+
+    #[tokio::test]
+    async fn test_zero_window_processes_every_event() {
+        let debouncer = EventDebouncer::new(Duration::ZERO);
+        assert!(debouncer.enter().await);
+        assert!(debouncer.enter().await);
+    }
+}
@@ -1,9 +1,13 @@
 use crate::cache::DbItem;
+use crate::config::EmptyItemIdPolicy;
+use anyhow::{Context, Result};
 use anylist_rs::ListItem;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 /// Represents a change detected between cached and current list state
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum ListChange {
     /// An item was added to the list
     ItemAdded {
@@ -12,24 +16,49 @@ pub enum ListChange {
         item: ItemInfo,
         user_id: Option<String>,
     },
+    /// An item with the same name was added to several lists in one sync pass;
+    /// `list_id` and `list_name` are the first of them
+    ItemAddedToLists {
+        list_id: String,
+        list_name: String,
+        other_list_ids: Vec<String>,
+        other_list_names: Vec<String>,
+        item: ItemInfo,
+        user_id: Option<String>,
+    },
     /// An item was removed from the list
     ItemRemoved {
         list_id: String,
         list_name: String,
-        item_name: String,
+        item: ItemInfo,
         user_id: Option<String>,
     },
     /// An item was checked off
     ItemChecked {
         list_id: String,
         list_name: String,
-        item_name: String,
+        item: ItemInfo,
         user_id: Option<String>,
     },
     /// An item was unchecked
     ItemUnchecked {
         list_id: String,
         list_name: String,
+        item: ItemInfo,
+        user_id: Option<String>,
+    },
+    /// An item was added and checked off shortly after, reported as one change
+    ItemAddedAndChecked {
+        list_id: String,
+        list_name: String,
+        item: ItemInfo,
+        user_id: Option<String>,
+    },
+    /// An item was checked off in a list configured to treat checked items as done
+    ItemDone {
+        list_id: String,
+        list_name: String,
+        item_id: String,
         item_name: String,
         user_id: Option<String>,
     },
@@ -37,14 +66,86 @@ pub enum ListChange {
     ItemModified {
         list_id: String,
         list_name: String,
+        item_id: String,
         item_name: String,
         changes: Vec<FieldChange>,
         user_id: Option<String>,
     },
+    /// Users were added to or removed from a list's sharing
+    ListSharingChanged {
+        list_id: String,
+        list_name: String,
+        added_users: Vec<String>,
+        removed_users: Vec<String>,
+    },
+    /// A private list was shared with its first user besides the account owner
+    ListBecameShared {
+        list_id: String,
+        list_name: String,
+        added_users: Vec<String>,
+    },
+    /// The last user besides the account owner was removed from a list's sharing
+    ListBecamePrivate {
+        list_id: String,
+        list_name: String,
+        removed_users: Vec<String>,
+    },
+    /// An item's check state was toggled many times in a short period
+    ItemToggledRepeatedly {
+        list_id: String,
+        list_name: String,
+        item_id: String,
+        item_name: String,
+        toggle_count: usize,
+        user_id: Option<String>,
+    },
+    /// An item's check state flipped back soon after changing, read as "maybe buy this"
+    ItemMarkedMaybe {
+        list_id: String,
+        list_name: String,
+        item: ItemInfo,
+        user_id: Option<String>,
+    },
+    /// Several items had only their category changed in a single sync pass
+    ItemsRecategorized {
+        list_id: String,
+        list_name: String,
+        item_ids: Vec<String>,
+        item_names: Vec<String>,
+        user_id: Option<String>,
+    },
+    /// Several item changes made to a list by one user in a single sync pass
+    ChangesByUser {
+        list_id: String,
+        list_name: String,
+        user_id: Option<String>,
+        changes: Vec<ListChange>,
+    },
+    /// A list's unchecked-item count crossed its configured threshold
+    ListThresholdCrossed {
+        list_id: String,
+        list_name: String,
+        /// Set when only the unchecked items in this category were counted
+        #[serde(skip_serializing_if = "Option::is_none")]
+        category: Option<String>,
+        unchecked_count: usize,
+        threshold: usize,
+        direction: ThresholdDirection,
+    },
+}
+
+/// Which way a list's unchecked-item count crossed its threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdDirection {
+    /// The count rose to or above the threshold
+    Above,
+    /// The count fell back below the threshold
+    Below,
 }
 
 /// Information about a list item
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ItemInfo {
     pub id: String,
     pub name: String,
@@ -54,8 +155,139 @@ pub struct ItemInfo {
     pub user_id: Option<String>,
 }
 
+/// Fixtures shared by the sync modules' tests. Items are made by `user-1`
+/// on the `list-1` "Groceries" list unless moved with `on_list`/`by_user`.
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::{ItemInfo, ListChange};
+    use anylist_rs::ListItem;
+
+    pub(crate) fn item(id: &str, name: &str) -> ItemInfo {
+        ItemInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+            details: "".to_string(),
+            quantity: None,
+            category: None,
+            user_id: Some("user-1".to_string()),
+        }
+    }
+
+    /// The same item as AnyList returns it
+    pub(crate) fn live_item(id: &str, name: &str, is_checked: bool) -> ListItem {
+        ListItem {
+            id: id.to_string(),
+            list_id: "list-1".to_string(),
+            name: name.to_string(),
+            details: "".to_string(),
+            quantity: None,
+            category: None,
+            is_checked,
+            user_id: Some("user-1".to_string()),
+        }
+    }
+
+    /// The `list-1` "Groceries" list as AnyList returns it, shared with no one
+    pub(crate) fn live_list(items: Vec<ListItem>) -> anylist_rs::List {
+        anylist_rs::List {
+            id: "list-1".to_string(),
+            name: "Groceries".to_string(),
+            items,
+            shared_users: Vec::new(),
+        }
+    }
+
+    pub(crate) fn added(id: &str, name: &str) -> ListChange {
+        ListChange::ItemAdded {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item(id, name),
+            user_id: Some("user-1".to_string()),
+        }
+    }
+
+    pub(crate) fn removed(id: &str, name: &str) -> ListChange {
+        ListChange::ItemRemoved {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item(id, name),
+            user_id: Some("user-1".to_string()),
+        }
+    }
+
+    pub(crate) fn checked(id: &str, name: &str) -> ListChange {
+        ListChange::ItemChecked {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item(id, name),
+            user_id: Some("user-1".to_string()),
+        }
+    }
+
+    pub(crate) fn unchecked(id: &str, name: &str) -> ListChange {
+        ListChange::ItemUnchecked {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item(id, name),
+            user_id: Some("user-1".to_string()),
+        }
+    }
+
+    pub(crate) fn added_and_checked(id: &str, name: &str) -> ListChange {
+        ListChange::ItemAddedAndChecked {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: item(id, name),
+            user_id: Some("user-1".to_string()),
+        }
+    }
+
+    /// Move an item change to another list
+    pub(crate) fn on_list(mut change: ListChange, to_id: &str, to_name: &str) -> ListChange {
+        match &mut change {
+            ListChange::ItemAdded {
+                list_id, list_name, ..
+            }
+            | ListChange::ItemRemoved {
+                list_id, list_name, ..
+            }
+            | ListChange::ItemChecked {
+                list_id, list_name, ..
+            }
+            | ListChange::ItemUnchecked {
+                list_id, list_name, ..
+            }
+            | ListChange::ItemAddedAndChecked {
+                list_id, list_name, ..
+            } => {
+                *list_id = to_id.to_string();
+                *list_name = to_name.to_string();
+            }
+            other => panic!("not an item change: {:?}", other),
+        }
+        change
+    }
+
+    /// Attribute an item change to another user
+    pub(crate) fn by_user(mut change: ListChange, by: &str) -> ListChange {
+        match &mut change {
+            ListChange::ItemAdded { item, user_id, .. }
+            | ListChange::ItemRemoved { item, user_id, .. }
+            | ListChange::ItemChecked { item, user_id, .. }
+            | ListChange::ItemUnchecked { item, user_id, .. }
+            | ListChange::ItemAddedAndChecked { item, user_id, .. } => {
+                item.user_id = Some(by.to_string());
+                *user_id = Some(by.to_string());
+            }
+            other => panic!("not an item change: {:?}", other),
+        }
+        change
+    }
+}
+
 /// Represents a change to a specific field
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "field", rename_all = "snake_case")]
 pub enum FieldChange {
     Name { old: String, new: String },
     Details { old: String, new: String },
@@ -63,6 +295,411 @@ pub enum FieldChange {
     Category { old: Option<String>, new: Option<String> },
 }
 
+impl ListChange {
+    /// Name of the list the change happened in
+    pub fn list_name(&self) -> &str {
+        match self {
+            ListChange::ItemAdded { list_name, .. }
+            | ListChange::ItemAddedToLists { list_name, .. }
+            | ListChange::ItemRemoved { list_name, .. }
+            | ListChange::ItemChecked { list_name, .. }
+            | ListChange::ItemUnchecked { list_name, .. }
+            | ListChange::ItemAddedAndChecked { list_name, .. }
+            | ListChange::ItemDone { list_name, .. }
+            | ListChange::ItemModified { list_name, .. }
+            | ListChange::ListSharingChanged { list_name, .. }
+            | ListChange::ListBecameShared { list_name, .. }
+            | ListChange::ListBecamePrivate { list_name, .. }
+            | ListChange::ItemToggledRepeatedly { list_name, .. }
+            | ListChange::ItemMarkedMaybe { list_name, .. }
+            | ListChange::ItemsRecategorized { list_name, .. }
+            | ListChange::ChangesByUser { list_name, .. }
+            | ListChange::ListThresholdCrossed { list_name, .. } => list_name,
+        }
+    }
+
+    /// ID of the list the change happened in
+    pub fn list_id(&self) -> &str {
+        match self {
+            ListChange::ItemAdded { list_id, .. }
+            | ListChange::ItemAddedToLists { list_id, .. }
+            | ListChange::ItemRemoved { list_id, .. }
+            | ListChange::ItemChecked { list_id, .. }
+            | ListChange::ItemUnchecked { list_id, .. }
+            | ListChange::ItemAddedAndChecked { list_id, .. }
+            | ListChange::ItemDone { list_id, .. }
+            | ListChange::ItemModified { list_id, .. }
+            | ListChange::ListSharingChanged { list_id, .. }
+            | ListChange::ListBecameShared { list_id, .. }
+            | ListChange::ListBecamePrivate { list_id, .. }
+            | ListChange::ItemToggledRepeatedly { list_id, .. }
+            | ListChange::ItemMarkedMaybe { list_id, .. }
+            | ListChange::ItemsRecategorized { list_id, .. }
+            | ListChange::ChangesByUser { list_id, .. }
+            | ListChange::ListThresholdCrossed { list_id, .. } => list_id,
+        }
+    }
+
+    /// Names of every list the change happened in
+    pub fn list_names(&self) -> Vec<&str> {
+        match self {
+            ListChange::ItemAddedToLists {
+                list_name,
+                other_list_names,
+                ..
+            } => std::iter::once(list_name)
+                .chain(other_list_names)
+                .map(String::as_str)
+                .collect(),
+            _ => vec![self.list_name()],
+        }
+    }
+
+    /// User who made the change, if known
+    pub fn user_id(&self) -> Option<&str> {
+        match self {
+            ListChange::ItemAdded { user_id, .. }
+            | ListChange::ItemAddedToLists { user_id, .. }
+            | ListChange::ItemRemoved { user_id, .. }
+            | ListChange::ItemChecked { user_id, .. }
+            | ListChange::ItemUnchecked { user_id, .. }
+            | ListChange::ItemAddedAndChecked { user_id, .. }
+            | ListChange::ItemDone { user_id, .. }
+            | ListChange::ItemModified { user_id, .. }
+            | ListChange::ItemToggledRepeatedly { user_id, .. }
+            | ListChange::ItemMarkedMaybe { user_id, .. }
+            | ListChange::ItemsRecategorized { user_id, .. }
+            | ListChange::ChangesByUser { user_id, .. } => user_id.as_deref(),
+            ListChange::ListSharingChanged { .. }
+            | ListChange::ListBecameShared { .. }
+            | ListChange::ListBecamePrivate { .. }
+            | ListChange::ListThresholdCrossed { .. } => None,
+        }
+    }
+
+    /// Every user the change mentions: who made it, and who a sharing change added or removed
+    pub fn user_ids(&self) -> Vec<&str> {
+        match self {
+            ListChange::ListSharingChanged {
+                added_users,
+                removed_users,
+                ..
+            } => added_users
+                .iter()
+                .chain(removed_users)
+                .map(String::as_str)
+                .collect(),
+            ListChange::ListBecameShared { added_users, .. } => {
+                added_users.iter().map(String::as_str).collect()
+            }
+            ListChange::ListBecamePrivate { removed_users, .. } => {
+                removed_users.iter().map(String::as_str).collect()
+            }
+            ListChange::ChangesByUser { changes, .. } => self
+                .user_id()
+                .into_iter()
+                .chain(changes.iter().flat_map(ListChange::user_ids))
+                .collect(),
+            _ => self.user_id().into_iter().collect(),
+        }
+    }
+
+    /// Category of the single item the change affects, as it is after the change
+    pub fn item_category(&self) -> Option<&str> {
+        let category = match self {
+            ListChange::ItemAdded { item, .. }
+            | ListChange::ItemAddedToLists { item, .. }
+            | ListChange::ItemRemoved { item, .. }
+            | ListChange::ItemChecked { item, .. }
+            | ListChange::ItemUnchecked { item, .. }
+            | ListChange::ItemAddedAndChecked { item, .. }
+            | ListChange::ItemMarkedMaybe { item, .. } => item.category.as_deref()?,
+            ListChange::ItemModified { changes, .. } => changes.iter().find_map(|c| match c {
+                FieldChange::Category { new, .. } => new.as_deref(),
+                _ => None,
+            })?,
+            _ => return None,
+        };
+        Some(category).filter(|category| !category.trim().is_empty())
+    }
+
+    /// Names of the items the change affects
+    pub fn item_names(&self) -> Vec<&str> {
+        match self {
+            ListChange::ItemAdded { item, .. }
+            | ListChange::ItemAddedToLists { item, .. }
+            | ListChange::ItemRemoved { item, .. }
+            | ListChange::ItemChecked { item, .. }
+            | ListChange::ItemUnchecked { item, .. }
+            | ListChange::ItemAddedAndChecked { item, .. }
+            | ListChange::ItemMarkedMaybe { item, .. } => vec![item.name.as_str()],
+            ListChange::ItemDone { item_name, .. }
+            | ListChange::ItemModified { item_name, .. }
+            | ListChange::ItemToggledRepeatedly { item_name, .. } => vec![item_name.as_str()],
+            ListChange::ItemsRecategorized { item_names, .. } => {
+                item_names.iter().map(String::as_str).collect()
+            }
+            ListChange::ChangesByUser { changes, .. } => {
+                changes.iter().flat_map(ListChange::item_names).collect()
+            }
+            ListChange::ListSharingChanged { .. }
+            | ListChange::ListBecameShared { .. }
+            | ListChange::ListBecamePrivate { .. }
+            | ListChange::ListThresholdCrossed { .. } => Vec::new(),
+        }
+    }
+
+    /// Details of the single item the change affects, as they are after the change
+    pub fn item_details(&self) -> Option<&str> {
+        let details = match self {
+            ListChange::ItemAdded { item, .. }
+            | ListChange::ItemAddedToLists { item, .. }
+            | ListChange::ItemRemoved { item, .. }
+            | ListChange::ItemChecked { item, .. }
+            | ListChange::ItemUnchecked { item, .. }
+            | ListChange::ItemAddedAndChecked { item, .. }
+            | ListChange::ItemMarkedMaybe { item, .. } => item.details.as_str(),
+            ListChange::ItemModified { changes, .. } => changes.iter().find_map(|c| match c {
+                FieldChange::Details { new, .. } => Some(new.as_str()),
+                _ => None,
+            })?,
+            _ => return None,
+        };
+        Some(details).filter(|details| !details.trim().is_empty())
+    }
+
+    /// IDs of the items the change affects
+    pub fn item_ids(&self) -> Vec<&str> {
+        match self {
+            ListChange::ItemAdded { item, .. }
+            | ListChange::ItemAddedToLists { item, .. }
+            | ListChange::ItemRemoved { item, .. }
+            | ListChange::ItemChecked { item, .. }
+            | ListChange::ItemUnchecked { item, .. }
+            | ListChange::ItemAddedAndChecked { item, .. }
+            | ListChange::ItemMarkedMaybe { item, .. } => vec![item.id.as_str()],
+            ListChange::ItemDone { item_id, .. }
+            | ListChange::ItemModified { item_id, .. }
+            | ListChange::ItemToggledRepeatedly { item_id, .. } => vec![item_id.as_str()],
+            ListChange::ItemsRecategorized { item_ids, .. } => {
+                item_ids.iter().map(String::as_str).collect()
+            }
+            ListChange::ChangesByUser { changes, .. } => {
+                changes.iter().flat_map(ListChange::item_ids).collect()
+            }
+            ListChange::ListSharingChanged { .. }
+            | ListChange::ListBecameShared { .. }
+            | ListChange::ListBecamePrivate { .. }
+            | ListChange::ListThresholdCrossed { .. } => Vec::new(),
+        }
+    }
+
+    /// Stable idempotency key for the change, shared by identical changes detected
+    /// within the same `bucket_secs` window so consumers can drop duplicates
+    pub fn dedup_key(&self, timestamp: i64, bucket_secs: u64) -> Result<String> {
+        let payload = serde_json::to_string(self).context("Failed to serialize change")?;
+        let bucket = timestamp.div_euclid(bucket_secs.max(1) as i64);
+        Ok(format!("{:016x}", fnv1a(format!("{}|{}", bucket, payload).as_bytes())))
+    }
+
+    /// Hash of the change's content, the same for identical changes whenever they happen
+    pub fn fingerprint(&self) -> Result<String> {
+        let payload = serde_json::to_string(self).context("Failed to serialize change")?;
+        Ok(format!("{:016x}", fnv1a(payload.as_bytes())))
+    }
+
+    /// Replace every item name in the change, including both sides of a rename
+    pub fn map_item_names(&mut self, f: impl Fn(&str) -> String) {
+        self.map_item_names_with(&f);
+    }
+
+    fn map_item_names_with(&mut self, f: &dyn Fn(&str) -> String) {
+        match self {
+            ListChange::ItemAdded { item, .. }
+            | ListChange::ItemAddedToLists { item, .. }
+            | ListChange::ItemRemoved { item, .. }
+            | ListChange::ItemChecked { item, .. }
+            | ListChange::ItemUnchecked { item, .. }
+            | ListChange::ItemAddedAndChecked { item, .. }
+            | ListChange::ItemMarkedMaybe { item, .. } => {
+                item.name = f(&item.name);
+            }
+            ListChange::ItemModified {
+                item_name, changes, ..
+            } => {
+                *item_name = f(item_name);
+                for change in changes {
+                    if let FieldChange::Name { old, new } = change {
+                        *old = f(old);
+                        *new = f(new);
+                    }
+                }
+            }
+            ListChange::ItemDone { item_name, .. }
+            | ListChange::ItemToggledRepeatedly { item_name, .. } => *item_name = f(item_name),
+            ListChange::ItemsRecategorized { item_names, .. } => {
+                for name in item_names {
+                    *name = f(name);
+                }
+            }
+            ListChange::ChangesByUser { changes, .. } => {
+                for change in changes {
+                    change.map_item_names_with(f);
+                }
+            }
+            ListChange::ListSharingChanged { .. }
+            | ListChange::ListBecameShared { .. }
+            | ListChange::ListBecamePrivate { .. }
+            | ListChange::ListThresholdCrossed { .. } => {}
+        }
+    }
+
+    /// Short snake_case name of the change type, matching the serialized `type` tag
+    pub fn change_type(&self) -> &'static str {
+        match self {
+            ListChange::ItemAdded { .. } => "item_added",
+            ListChange::ItemAddedToLists { .. } => "item_added_to_lists",
+            ListChange::ItemRemoved { .. } => "item_removed",
+            ListChange::ItemChecked { .. } => "item_checked",
+            ListChange::ItemUnchecked { .. } => "item_unchecked",
+            ListChange::ItemAddedAndChecked { .. } => "item_added_and_checked",
+            ListChange::ItemDone { .. } => "item_done",
+            ListChange::ItemModified { .. } => "item_modified",
+            ListChange::ListSharingChanged { .. } => "list_sharing_changed",
+            ListChange::ListBecameShared { .. } => "list_became_shared",
+            ListChange::ListBecamePrivate { .. } => "list_became_private",
+            ListChange::ItemToggledRepeatedly { .. } => "item_toggled_repeatedly",
+            ListChange::ItemMarkedMaybe { .. } => "item_marked_maybe",
+            ListChange::ItemsRecategorized { .. } => "items_recategorized",
+            ListChange::ChangesByUser { .. } => "changes_by_user",
+            ListChange::ListThresholdCrossed { .. } => "list_threshold_crossed",
+        }
+    }
+}
+
+impl FieldChange {
+    /// Whether this is a quantity that was cleared or dropped to zero,
+    /// which usually means the item has run out
+    pub fn is_quantity_depleted(&self) -> bool {
+        match self {
+            FieldChange::Quantity { old, new } => {
+                has_quantity(old.as_deref()) && !has_quantity(new.as_deref())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this gives a quantity to an item that had none
+    pub fn is_quantity_first_set(&self) -> bool {
+        match self {
+            FieldChange::Quantity { old, new } => {
+                old.as_deref().unwrap_or_default().trim().is_empty()
+                    && has_quantity(new.as_deref())
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Number of item changes of each kind in a list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChangeCounts {
+    pub added: usize,
+    pub checked: usize,
+    pub unchecked: usize,
+    pub modified: usize,
+    pub removed: usize,
+}
+
+impl ChangeCounts {
+    /// Count item changes by kind; list-level changes aren't counted
+    pub fn from_changes(changes: &[ListChange]) -> Self {
+        let mut counts = Self::default();
+        for change in changes {
+            match change {
+                ListChange::ItemAdded { .. } => counts.added += 1,
+                ListChange::ItemAddedToLists {
+                    other_list_ids, ..
+                } => counts.added += 1 + other_list_ids.len(),
+                ListChange::ItemChecked { .. } | ListChange::ItemDone { .. } => {
+                    counts.checked += 1
+                }
+                ListChange::ItemUnchecked { .. } => counts.unchecked += 1,
+                ListChange::ItemAddedAndChecked { .. } => {
+                    counts.added += 1;
+                    counts.checked += 1;
+                }
+                ListChange::ItemModified { .. } => counts.modified += 1,
+                ListChange::ItemsRecategorized { item_ids, .. } => {
+                    counts.modified += item_ids.len()
+                }
+                ListChange::ItemRemoved { .. } => counts.removed += 1,
+                ListChange::ChangesByUser { changes, .. } => {
+                    let grouped = Self::from_changes(changes);
+                    counts.added += grouped.added;
+                    counts.checked += grouped.checked;
+                    counts.unchecked += grouped.unchecked;
+                    counts.modified += grouped.modified;
+                    counts.removed += grouped.removed;
+                }
+                ListChange::ListSharingChanged { .. }
+                | ListChange::ListBecameShared { .. }
+                | ListChange::ListBecamePrivate { .. }
+                | ListChange::ItemToggledRepeatedly { .. }
+                | ListChange::ItemMarkedMaybe { .. }
+                | ListChange::ListThresholdCrossed { .. } => {}
+            }
+        }
+        counts
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn total(&self) -> usize {
+        self.added + self.checked + self.unchecked + self.modified + self.removed
+    }
+}
+
+/// Size of a list as it currently stands
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ListTotals {
+    pub total: usize,
+    pub unchecked: usize,
+}
+
+impl ListTotals {
+    pub fn from_items(items: &[ListItem]) -> Self {
+        Self {
+            total: items.len(),
+            unchecked: items.iter().filter(|item| !item.is_checked).count(),
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash; unlike `DefaultHasher` it is stable across Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Whether a quantity is set to something other than zero
+fn has_quantity(quantity: Option<&str>) -> bool {
+    quantity.is_some_and(|q| !q.trim().is_empty() && !is_zero_quantity(q))
+}
+
+/// Whether a free-text quantity such as "0" or "0 gallons" starts with zero
+fn is_zero_quantity(quantity: &str) -> bool {
+    let number: String = quantity
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    number.parse::<f64>().is_ok_and(|n| n == 0.0)
+}
+
 impl ItemInfo {
     pub fn from_list_item(item: &ListItem) -> Self {
         Self {
@@ -96,14 +733,23 @@ pub fn detect_changes(
 ) -> Vec<ListChange> {
     let mut changes = Vec::new();
 
+    // Items without an ID would all collide under the same key, so they are
+    // left out; `fix_empty_item_ids` decides what happens to them beforehand
+    let cached_items: Vec<&DbItem> = cached_items.iter().filter(|i| !i.id.is_empty()).collect();
+    let current_items: Vec<&ListItem> = current_items.iter().filter(|i| !i.id.is_empty()).collect();
+
     // Create lookup maps by item ID
-    let cached_map: HashMap<&str, &DbItem> =
-        cached_items.iter().map(|item| (item.id.as_str(), item)).collect();
-    let current_map: HashMap<&str, &ListItem> =
-        current_items.iter().map(|item| (item.id.as_str(), item)).collect();
+    let cached_map: HashMap<&str, &DbItem> = cached_items
+        .iter()
+        .map(|item| (item.id.as_str(), *item))
+        .collect();
+    let current_map: HashMap<&str, &ListItem> = current_items
+        .iter()
+        .map(|item| (item.id.as_str(), *item))
+        .collect();
 
     // Detect added items (in current but not in cached)
-    for current_item in current_items {
+    for current_item in &current_items {
         if !cached_map.contains_key(current_item.id.as_str()) {
             changes.push(ListChange::ItemAdded {
                 list_id: list_id.to_string(),
@@ -115,19 +761,19 @@ pub fn detect_changes(
     }
 
     // Detect removed items (in cached but not in current)
-    for cached_item in cached_items {
+    for cached_item in &cached_items {
         if !current_map.contains_key(cached_item.id.as_str()) {
             changes.push(ListChange::ItemRemoved {
                 list_id: list_id.to_string(),
                 list_name: list_name.to_string(),
-                item_name: cached_item.name.clone(),
+                item: ItemInfo::from_db_item(cached_item),
                 user_id: cached_item.user_id.clone(),
             });
         }
     }
 
     // Detect modifications (items in both, but with different values)
-    for current_item in current_items {
+    for current_item in &current_items {
         if let Some(cached_item) = cached_map.get(current_item.id.as_str()) {
             // Check for check state changes
             if cached_item.is_checked != current_item.is_checked {
@@ -135,34 +781,603 @@ pub fn detect_changes(
                     changes.push(ListChange::ItemChecked {
                         list_id: list_id.to_string(),
                         list_name: list_name.to_string(),
-                        item_name: current_item.name.clone(),
+                        item: ItemInfo::from_list_item(current_item),
                         user_id: current_item.user_id.clone(),
                     });
                 } else {
                     changes.push(ListChange::ItemUnchecked {
                         list_id: list_id.to_string(),
                         list_name: list_name.to_string(),
-                        item_name: current_item.name.clone(),
+                        item: ItemInfo::from_list_item(current_item),
                         user_id: current_item.user_id.clone(),
                     });
                 }
             }
-
-            // Check for field changes
-            let field_changes = detect_field_changes(cached_item, current_item);
-            if !field_changes.is_empty() {
-                changes.push(ListChange::ItemModified {
-                    list_id: list_id.to_string(),
-                    list_name: list_name.to_string(),
-                    item_name: current_item.name.clone(),
-                    changes: field_changes,
-                    user_id: current_item.user_id.clone(),
+
+            // Check for field changes
+            let field_changes = detect_field_changes(cached_item, current_item);
+            if !field_changes.is_empty() {
+                changes.push(ListChange::ItemModified {
+                    list_id: list_id.to_string(),
+                    list_name: list_name.to_string(),
+                    item_id: current_item.id.clone(),
+                    item_name: current_item.name.clone(),
+                    changes: field_changes,
+                    user_id: current_item.user_id.clone(),
+                });
+            }
+        }
+    }
+
+    changes
+}
+
+/// Handle items AnyList returned without an ID, returning how many there were
+///
+/// With `Skip` they are dropped; with `Name` each gets an ID made from its
+/// name, numbered when several share one, so it can be tracked across syncs.
+pub fn fix_empty_item_ids(items: &mut Vec<ListItem>, policy: EmptyItemIdPolicy) -> usize {
+    let count = items.iter().filter(|item| item.id.is_empty()).count();
+    if count == 0 {
+        return 0;
+    }
+    match policy {
+        EmptyItemIdPolicy::Skip => items.retain(|item| !item.id.is_empty()),
+        EmptyItemIdPolicy::Name => {
+            let mut seen: HashMap<String, usize> = HashMap::new();
+            for item in items.iter_mut().filter(|item| item.id.is_empty()) {
+                let key = item.name.trim().to_lowercase();
+                let n = seen.entry(key.clone()).or_insert(0);
+                *n += 1;
+                item.id = if *n == 1 {
+                    format!("name:{}", key)
+                } else {
+                    format!("name:{}#{}", key, n)
+                };
+            }
+        }
+    }
+    count
+}
+
+/// Lists smaller than this are never considered to have diverged drastically
+const RESEED_MIN_ITEMS: usize = 10;
+
+/// Whether a pass's item changes are too many to be real edits
+///
+/// Every item being removed and re-added, as when AnyList changes its item
+/// IDs, gives twice as many changes as items, while clearing a list gives as
+/// many, so a ratio between 1 and 2 tells them apart. Brand new lists, with no
+/// cached items, never count.
+pub fn is_drastic_divergence(
+    item_changes: usize,
+    cached_items: usize,
+    live_items: usize,
+    ratio: f64,
+) -> bool {
+    let size = cached_items.max(live_items);
+    ratio > 0.0
+        && cached_items > 0
+        && size >= RESEED_MIN_ITEMS
+        && item_changes as f64 > ratio * size as f64
+}
+
+/// Detect a list's unchecked-item count crossing a threshold between syncs
+/// Only the sync where the count crosses produces a change, not every later one
+pub fn detect_threshold_crossing(
+    list_id: &str,
+    list_name: &str,
+    cached_items: &[DbItem],
+    current_items: &[ListItem],
+    threshold: usize,
+) -> Option<ListChange> {
+    let previous = cached_items.iter().filter(|item| !item.is_checked).count();
+    let unchecked_count = current_items.iter().filter(|item| !item.is_checked).count();
+
+    let direction = crossing_direction(previous, unchecked_count, threshold)?;
+
+    Some(ListChange::ListThresholdCrossed {
+        list_id: list_id.to_string(),
+        list_name: list_name.to_string(),
+        category: None,
+        unchecked_count,
+        threshold,
+        direction,
+    })
+}
+
+/// Detect categories whose unchecked-item count crossed their threshold between syncs
+/// `threshold_for` gives a category's threshold, or None for categories not watched;
+/// uncategorized items are never counted
+pub fn detect_category_threshold_crossings(
+    list_id: &str,
+    list_name: &str,
+    cached_items: &[DbItem],
+    current_items: &[ListItem],
+    threshold_for: impl Fn(&str) -> Option<usize>,
+) -> Vec<ListChange> {
+    let previous = unchecked_by_category(
+        cached_items
+            .iter()
+            .map(|item| (item.category.as_deref(), item.is_checked)),
+    );
+    let current = unchecked_by_category(
+        current_items
+            .iter()
+            .map(|item| (item.category.as_deref(), item.is_checked)),
+    );
+
+    let categories: BTreeSet<&str> = previous.keys().chain(current.keys()).copied().collect();
+    categories
+        .into_iter()
+        .filter_map(|category| {
+            let threshold = threshold_for(category)?;
+            let unchecked_count = current.get(category).copied().unwrap_or(0);
+            let before = previous.get(category).copied().unwrap_or(0);
+            let direction = crossing_direction(before, unchecked_count, threshold)?;
+            Some(ListChange::ListThresholdCrossed {
+                list_id: list_id.to_string(),
+                list_name: list_name.to_string(),
+                category: Some(category.to_string()),
+                unchecked_count,
+                threshold,
+                direction,
+            })
+        })
+        .collect()
+}
+
+/// Which way a count moved across a threshold, if it crossed it
+fn crossing_direction(
+    previous: usize,
+    count: usize,
+    threshold: usize,
+) -> Option<ThresholdDirection> {
+    if previous < threshold && count >= threshold {
+        Some(ThresholdDirection::Above)
+    } else if previous >= threshold && count < threshold {
+        Some(ThresholdDirection::Below)
+    } else {
+        None
+    }
+}
+
+/// Number of unchecked items in each category, from (category, is_checked) pairs
+fn unchecked_by_category<'a>(
+    items: impl Iterator<Item = (Option<&'a str>, bool)>,
+) -> HashMap<&'a str, usize> {
+    let mut counts = HashMap::new();
+    for (category, is_checked) in items {
+        match category.map(str::trim) {
+            Some(category) if !category.is_empty() && !is_checked => {
+                *counts.entry(category).or_insert(0) += 1;
+            }
+            _ => {}
+        }
+    }
+    counts
+}
+
+/// Detect a list becoming shared with its first user besides `own_user_id`, or
+/// private again when the last of them is removed
+pub fn detect_privacy_transition(
+    list_id: &str,
+    list_name: &str,
+    cached_user_ids: &[String],
+    current_user_ids: &[String],
+    own_user_id: &str,
+) -> Option<ListChange> {
+    let others = |user_ids: &[String]| -> Vec<String> {
+        let mut others: Vec<String> = user_ids
+            .iter()
+            .filter(|id| *id != own_user_id)
+            .cloned()
+            .collect();
+        others.sort();
+        others.dedup();
+        others
+    };
+    let cached = others(cached_user_ids);
+    let current = others(current_user_ids);
+
+    match (cached.is_empty(), current.is_empty()) {
+        (true, false) => Some(ListChange::ListBecameShared {
+            list_id: list_id.to_string(),
+            list_name: list_name.to_string(),
+            added_users: current,
+        }),
+        (false, true) => Some(ListChange::ListBecamePrivate {
+            list_id: list_id.to_string(),
+            list_name: list_name.to_string(),
+            removed_users: cached,
+        }),
+        _ => None,
+    }
+}
+
+/// Detect users added to or removed from a list's sharing
+pub fn detect_sharing_changes(
+    list_id: &str,
+    list_name: &str,
+    cached_user_ids: &[String],
+    current_user_ids: &[String],
+) -> Option<ListChange> {
+    let cached: HashSet<&str> = cached_user_ids.iter().map(String::as_str).collect();
+    let current: HashSet<&str> = current_user_ids.iter().map(String::as_str).collect();
+
+    let mut added_users: Vec<String> = current
+        .difference(&cached)
+        .map(|id| id.to_string())
+        .collect();
+    let mut removed_users: Vec<String> = cached
+        .difference(&current)
+        .map(|id| id.to_string())
+        .collect();
+
+    if added_users.is_empty() && removed_users.is_empty() {
+        return None;
+    }
+
+    added_users.sort();
+    removed_users.sort();
+
+    Some(ListChange::ListSharingChanged {
+        list_id: list_id.to_string(),
+        list_name: list_name.to_string(),
+        added_users,
+        removed_users,
+    })
+}
+
+/// Drop modifications that changed nothing but an item's category
+pub fn drop_category_only(changes: Vec<ListChange>) -> Vec<ListChange> {
+    changes
+        .into_iter()
+        .filter(|change| {
+            !matches!(change, ListChange::ItemModified { changes, .. } if is_category_only(changes))
+        })
+        .collect()
+}
+
+/// Drop modifications to items that are checked off
+pub fn drop_changes_on_checked(
+    changes: Vec<ListChange>,
+    checked_ids: &HashSet<&str>,
+) -> Vec<ListChange> {
+    changes
+        .into_iter()
+        .filter(|change| {
+            !matches!(
+                change,
+                ListChange::ItemModified { item_id, .. } if checked_ids.contains(item_id.as_str())
+            )
+        })
+        .collect()
+}
+
+/// Remove category field changes, dropping modifications left with no changes
+pub fn strip_category_changes(changes: Vec<ListChange>) -> Vec<ListChange> {
+    changes
+        .into_iter()
+        .filter_map(|change| match change {
+            ListChange::ItemModified {
+                list_id,
+                list_name,
+                item_id,
+                item_name,
+                changes,
+                user_id,
+            } => {
+                let changes: Vec<FieldChange> = changes
+                    .into_iter()
+                    .filter(|c| !matches!(c, FieldChange::Category { .. }))
+                    .collect();
+                if changes.is_empty() {
+                    None
+                } else {
+                    Some(ListChange::ItemModified {
+                        list_id,
+                        list_name,
+                        item_id,
+                        item_name,
+                        changes,
+                        user_id,
+                    })
+                }
+            }
+            other => Some(other),
+        })
+        .collect()
+}
+
+/// Report check-offs as the item being done and unchecks as it being added back,
+/// for lists where checked items count as gone
+pub fn apply_done_semantics(changes: Vec<ListChange>) -> Vec<ListChange> {
+    changes
+        .into_iter()
+        .map(|change| match change {
+            ListChange::ItemChecked {
+                list_id,
+                list_name,
+                item,
+                user_id,
+            } => ListChange::ItemDone {
+                list_id,
+                list_name,
+                item_id: item.id,
+                item_name: item.name,
+                user_id,
+            },
+            ListChange::ItemUnchecked {
+                list_id,
+                list_name,
+                item,
+                user_id,
+            } => ListChange::ItemAdded {
+                list_id,
+                list_name,
+                item,
+                user_id,
+            },
+            other => other,
+        })
+        .collect()
+}
+
+/// Drop category-only modifications from none to a category made within
+/// `window_secs` of the item first being seen, which AnyList does automatically
+///
+/// Items are matched by ID, so a new item sharing its name with an older one
+/// doesn't cause the older item's recategorization to be dropped.
+pub fn suppress_auto_category(
+    changes: Vec<ListChange>,
+    cached_items: &[DbItem],
+    window_secs: i64,
+    now: i64,
+) -> Vec<ListChange> {
+    let cached_map: HashMap<&str, &DbItem> = cached_items
+        .iter()
+        .map(|item| (item.id.as_str(), item))
+        .collect();
+
+    changes
+        .into_iter()
+        .filter(|change| {
+            let ListChange::ItemModified {
+                item_id, changes, ..
+            } = change
+            else {
+                return true;
+            };
+
+            let is_auto_category = matches!(
+                changes.as_slice(),
+                [FieldChange::Category { old: None, .. }]
+            );
+            if !is_auto_category {
+                return true;
+            }
+
+            let recently_added = cached_map.get(item_id.as_str()).is_some_and(|item| {
+                item.category.is_none() && now - item.first_seen <= window_secs
+            });
+
+            !recently_added
+        })
+        .collect()
+}
+
+/// Collapse each user's item changes in a list into one summary
+///
+/// Users with a single item change keep it as is. Changes without a user form
+/// their own group, and list-level changes such as sharing or threshold
+/// changes aren't grouped. The summary takes the place of the group's first
+/// change.
+pub fn group_by_user(changes: Vec<ListChange>) -> Vec<ListChange> {
+    let group_key = |change: &ListChange| {
+        is_user_action(change).then(|| {
+            (
+                change.list_id().to_string(),
+                change.user_id().map(str::to_string),
+            )
+        })
+    };
+
+    let mut counts: HashMap<(String, Option<String>), usize> = HashMap::new();
+    for key in changes.iter().filter_map(group_key) {
+        *counts.entry(key).or_default() += 1;
+    }
+
+    let mut result: Vec<ListChange> = Vec::with_capacity(changes.len());
+    let mut summary_index: HashMap<(String, Option<String>), usize> = HashMap::new();
+
+    for change in changes {
+        let Some(key) = group_key(&change).filter(|key| counts[key] > 1) else {
+            result.push(change);
+            continue;
+        };
+        match summary_index.get(&key) {
+            Some(&index) => {
+                if let ListChange::ChangesByUser { changes, .. } = &mut result[index] {
+                    changes.push(change);
+                }
+            }
+            None => {
+                summary_index.insert(key.clone(), result.len());
+                result.push(ListChange::ChangesByUser {
+                    list_id: key.0,
+                    list_name: change.list_name().to_string(),
+                    user_id: key.1,
+                    changes: vec![change],
+                });
+            }
+        }
+    }
+
+    result
+}
+
+/// Merge adds of the same item name (case-insensitive) to several lists into one change
+///
+/// The merged change takes the place of the first of those adds and is
+/// attributed to their user if they all share one. Adds of a name to a single
+/// list are left as they are.
+pub fn merge_cross_list_adds(changes: Vec<ListChange>) -> Vec<ListChange> {
+    let add_key = |change: &ListChange| match change {
+        ListChange::ItemAdded { item, .. } => Some(item.name.trim().to_lowercase()),
+        _ => None,
+    };
+
+    let mut lists_by_name: HashMap<String, HashSet<&str>> = HashMap::new();
+    for change in &changes {
+        if let Some(key) = add_key(change) {
+            lists_by_name
+                .entry(key)
+                .or_default()
+                .insert(change.list_id());
+        }
+    }
+    let merged_names: HashSet<String> = lists_by_name
+        .into_iter()
+        .filter(|(_, lists)| lists.len() > 1)
+        .map(|(name, _)| name)
+        .collect();
+
+    let mut result: Vec<ListChange> = Vec::with_capacity(changes.len());
+    let mut merged_index: HashMap<String, usize> = HashMap::new();
+    for change in changes {
+        let Some(key) = add_key(&change).filter(|key| merged_names.contains(key)) else {
+            result.push(change);
+            continue;
+        };
+        let ListChange::ItemAdded {
+            list_id,
+            list_name,
+            item,
+            user_id,
+        } = change
+        else {
+            unreachable!("only adds have a merge key");
+        };
+        match merged_index.get(&key) {
+            Some(&index) => {
+                if let ListChange::ItemAddedToLists {
+                    list_id: first_list_id,
+                    other_list_ids,
+                    other_list_names,
+                    user_id: merged_user_id,
+                    ..
+                } = &mut result[index]
+                {
+                    if *first_list_id != list_id && !other_list_ids.contains(&list_id) {
+                        other_list_ids.push(list_id);
+                        other_list_names.push(list_name);
+                    }
+                    if *merged_user_id != user_id {
+                        *merged_user_id = None;
+                    }
+                }
+            }
+            None => {
+                merged_index.insert(key, result.len());
+                result.push(ListChange::ItemAddedToLists {
+                    list_id,
+                    list_name,
+                    other_list_ids: Vec::new(),
+                    other_list_names: Vec::new(),
+                    item,
+                    user_id,
                 });
             }
         }
     }
 
-    changes
+    result
+}
+
+/// Whether the change is a single item action that can be grouped by user
+pub fn is_user_action(change: &ListChange) -> bool {
+    matches!(
+        change,
+        ListChange::ItemAdded { .. }
+            | ListChange::ItemRemoved { .. }
+            | ListChange::ItemChecked { .. }
+            | ListChange::ItemUnchecked { .. }
+            | ListChange::ItemAddedAndChecked { .. }
+            | ListChange::ItemDone { .. }
+            | ListChange::ItemModified { .. }
+    )
+}
+
+/// Collapse category-only modifications into one summary per list
+///
+/// Lists with a single category-only modification keep it as is. The summary
+/// takes the place of the first collapsed change and is attributed to a user
+/// only if every collapsed change was made by that user.
+pub fn summarize_recategorizations(changes: Vec<ListChange>) -> Vec<ListChange> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for change in &changes {
+        if let ListChange::ItemModified {
+            list_id, changes, ..
+        } = change
+        {
+            if is_category_only(changes) {
+                *counts.entry(list_id.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    let mut result: Vec<ListChange> = Vec::with_capacity(changes.len());
+    let mut summary_index: HashMap<String, usize> = HashMap::new();
+
+    for change in changes {
+        match change {
+            ListChange::ItemModified {
+                list_id,
+                list_name,
+                item_id,
+                item_name,
+                changes,
+                user_id,
+            } if is_category_only(&changes) && counts[&list_id] > 1 => {
+                match summary_index.get(&list_id) {
+                    Some(&index) => {
+                        if let ListChange::ItemsRecategorized {
+                            item_ids,
+                            item_names,
+                            user_id: summary_user,
+                            ..
+                        } = &mut result[index]
+                        {
+                            item_ids.push(item_id);
+                            item_names.push(item_name);
+                            if *summary_user != user_id {
+                                *summary_user = None;
+                            }
+                        }
+                    }
+                    None => {
+                        summary_index.insert(list_id.clone(), result.len());
+                        result.push(ListChange::ItemsRecategorized {
+                            list_id,
+                            list_name,
+                            item_ids: vec![item_id],
+                            item_names: vec![item_name],
+                            user_id,
+                        });
+                    }
+                }
+            }
+            other => result.push(other),
+        }
+    }
+
+    result
+}
+
+/// Whether a modification changed nothing but the item's category
+fn is_category_only(changes: &[FieldChange]) -> bool {
+    matches!(changes, [FieldChange::Category { .. }])
 }
 
 /// Detect changes to specific fields
@@ -215,6 +1430,7 @@ mod tests {
             is_checked,
             user_id: Some("test-user".to_string()),
             last_seen: 0,
+            first_seen: 0,
         }
     }
 
@@ -231,6 +1447,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_is_quantity_depleted() {
+        let quantity = |old: Option<&str>, new: Option<&str>| FieldChange::Quantity {
+            old: old.map(str::to_string),
+            new: new.map(str::to_string),
+        };
+
+        assert!(quantity(Some("2"), None).is_quantity_depleted());
+        assert!(quantity(Some("2"), Some("0")).is_quantity_depleted());
+        assert!(quantity(Some("1.5 lbs"), Some("0.0 lbs")).is_quantity_depleted());
+        assert!(quantity(Some("2"), Some("")).is_quantity_depleted());
+        assert!(!quantity(None, Some("2")).is_quantity_depleted());
+        assert!(!quantity(Some("0"), None).is_quantity_depleted());
+        assert!(!quantity(Some("2"), Some("10")).is_quantity_depleted());
+        assert!(!quantity(None, Some("0")).is_quantity_depleted());
+    }
+
+    #[test]
+    fn test_is_quantity_first_set() {
+        let quantity = |old: Option<&str>, new: Option<&str>| FieldChange::Quantity {
+            old: old.map(str::to_string),
+            new: new.map(str::to_string),
+        };
+
+        assert!(quantity(None, Some("2 gallons")).is_quantity_first_set());
+        assert!(quantity(Some(" "), Some("2")).is_quantity_first_set());
+        assert!(!quantity(Some("1"), Some("2")).is_quantity_first_set());
+        assert!(!quantity(Some("0"), Some("2")).is_quantity_first_set());
+        assert!(!quantity(None, Some("0")).is_quantity_first_set());
+        assert!(!quantity(None, None).is_quantity_first_set());
+    }
+
     #[test]
     fn test_detect_added_item() {
         let cached = vec![];
@@ -247,6 +1495,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_detect_ignores_empty_item_ids() {
+        // Two ID-less items would collide, turning one into a modification of the other
+        let cached = vec![create_db_item("", "Milk", false)];
+        let current = vec![
+            create_list_item("", "Bread", false),
+            create_list_item("", "Eggs", true),
+            create_list_item("item-1", "Butter", false),
+        ];
+
+        let changes = detect_changes("list-1", "Groceries", &cached, &current);
+
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], ListChange::ItemAdded { item, .. } if item.id == "item-1"));
+    }
+
+    #[test]
+    fn test_list_totals_reflect_current_items() {
+        let cached = vec![
+            create_db_item("item-1", "Milk", false),
+            create_db_item("item-2", "Bread", false),
+        ];
+        let current = vec![
+            create_list_item("item-1", "Milk", true),
+            create_list_item("item-2", "Bread", false),
+            create_list_item("item-3", "Eggs", false),
+        ];
+
+        let changes = detect_changes("list-1", "Groceries", &cached, &current);
+        assert_eq!(changes.len(), 2);
+
+        // Counted after the check-off and the add
+        assert_eq!(
+            ListTotals::from_items(&current),
+            ListTotals {
+                total: 3,
+                unchecked: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_fix_empty_item_ids() {
+        let items = || {
+            vec![
+                create_list_item("", "Milk", false),
+                create_list_item("item-1", "Bread", false),
+                create_list_item("", " milk ", true),
+            ]
+        };
+
+        let mut skipped = items();
+        assert_eq!(fix_empty_item_ids(&mut skipped, EmptyItemIdPolicy::Skip), 2);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].id, "item-1");
+
+        let mut named = items();
+        assert_eq!(fix_empty_item_ids(&mut named, EmptyItemIdPolicy::Name), 2);
+        let ids: Vec<&str> = named.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, vec!["name:milk", "item-1", "name:milk#2"]);
+
+        // Named items are then tracked like any other
+        let cached = vec![create_db_item("name:milk", "Milk", false)];
+        let changes = detect_changes("list-1", "Groceries", &cached, &named[..1]);
+        assert!(changes.is_empty());
+    }
+
     #[test]
     fn test_detect_removed_item() {
         let cached = vec![create_db_item("item-1", "Milk", false)];
@@ -256,8 +1571,9 @@ mod tests {
 
         assert_eq!(changes.len(), 1);
         match &changes[0] {
-            ListChange::ItemRemoved { item_name, .. } => {
-                assert_eq!(item_name, "Milk");
+            ListChange::ItemRemoved { item, .. } => {
+                assert_eq!(item.id, "item-1");
+                assert_eq!(item.name, "Milk");
             }
             _ => panic!("Expected ItemRemoved"),
         }
@@ -272,8 +1588,8 @@ mod tests {
 
         assert_eq!(changes.len(), 1);
         match &changes[0] {
-            ListChange::ItemChecked { item_name, .. } => {
-                assert_eq!(item_name, "Milk");
+            ListChange::ItemChecked { item, .. } => {
+                assert_eq!(item.name, "Milk");
             }
             _ => panic!("Expected ItemChecked"),
         }
@@ -288,8 +1604,8 @@ mod tests {
 
         assert_eq!(changes.len(), 1);
         match &changes[0] {
-            ListChange::ItemUnchecked { item_name, .. } => {
-                assert_eq!(item_name, "Milk");
+            ListChange::ItemUnchecked { item, .. } => {
+                assert_eq!(item.name, "Milk");
             }
             _ => panic!("Expected ItemUnchecked"),
         }
@@ -307,6 +1623,7 @@ mod tests {
             is_checked: false,
             user_id: Some("test-user".to_string()),
             last_seen: 0,
+            first_seen: 0,
         }];
 
         let current = vec![ListItem {
@@ -366,4 +1683,693 @@ mod tests {
         assert_eq!(changes.len(), 3);
         // Should have: ItemAdded, ItemRemoved, ItemChecked
     }
+
+    #[test]
+    fn test_drop_changes_on_checked() {
+        let mut cached = vec![
+            create_db_item("item-1", "Milk", true),
+            create_db_item("item-2", "Bread", false),
+            create_db_item("item-3", "Eggs", true),
+        ];
+        cached[0].quantity = Some("1".to_string());
+        cached[1].quantity = Some("1".to_string());
+        cached[2].quantity = Some("1".to_string());
+
+        let mut current = vec![
+            create_list_item("item-1", "Milk", true),
+            create_list_item("item-2", "Bread", false),
+            create_list_item("item-3", "Eggs", false),
+        ];
+        for item in &mut current {
+            item.quantity = Some("2".to_string());
+        }
+        let checked_ids: HashSet<&str> = current
+            .iter()
+            .filter(|item| item.is_checked)
+            .map(|item| item.id.as_str())
+            .collect();
+
+        let changes = drop_changes_on_checked(
+            detect_changes("list-1", "Groceries", &cached, &current),
+            &checked_ids,
+        );
+
+        // Milk is still checked off; Eggs was unchecked, so its edit is kept
+        let modified: Vec<&str> = changes
+            .iter()
+            .filter_map(|change| match change {
+                ListChange::ItemModified { item_name, .. } => Some(item_name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(modified, vec!["Bread", "Eggs"]);
+        assert!(changes
+            .iter()
+            .any(|change| matches!(change, ListChange::ItemUnchecked { .. })));
+    }
+
+    #[test]
+    fn test_drop_category_only() {
+        let mut cached = vec![
+            create_db_item("item-1", "Milk", false),
+            create_db_item("item-2", "Bread", false),
+        ];
+        cached[1].quantity = Some("1".to_string());
+
+        let mut current = vec![
+            create_list_item("item-1", "Milk", false),
+            create_list_item("item-2", "Bread", false),
+            create_list_item("item-3", "Eggs", false),
+        ];
+        current[0].category = Some("Dairy".to_string());
+        current[1].category = Some("Bakery".to_string());
+        current[1].quantity = Some("2".to_string());
+
+        let changes = drop_category_only(detect_changes(
+            "list-1",
+            "Groceries",
+            &cached,
+            &current,
+        ));
+
+        // Milk only changed category and is dropped; Bread and the add are kept whole
+        assert_eq!(changes.len(), 2);
+        assert!(changes
+            .iter()
+            .any(|change| matches!(change, ListChange::ItemAdded { .. })));
+        match changes
+            .iter()
+            .find(|change| matches!(change, ListChange::ItemModified { .. }))
+        {
+            Some(ListChange::ItemModified {
+                item_name, changes, ..
+            }) => {
+                assert_eq!(item_name, "Bread");
+                assert_eq!(changes.len(), 2);
+            }
+            _ => panic!("Expected ItemModified"),
+        }
+    }
+
+    #[test]
+    fn test_strip_category_changes() {
+        let mut cached = vec![
+            create_db_item("item-1", "Milk", false),
+            create_db_item("item-2", "Bread", false),
+        ];
+        cached[0].category = Some("Dairy".to_string());
+        cached[1].category = Some("Bakery".to_string());
+        cached[1].quantity = Some("1".to_string());
+
+        let mut current = vec![
+            create_list_item("item-1", "Milk", false),
+            create_list_item("item-2", "Bread", false),
+        ];
+        current[0].category = Some("Fridge".to_string());
+        current[1].category = Some("Pantry".to_string());
+        current[1].quantity = Some("2".to_string());
+
+        let changes = strip_category_changes(detect_changes(
+            "list-1",
+            "Groceries",
+            &cached,
+            &current,
+        ));
+
+        // Milk only changed category and is dropped; Bread keeps its quantity change
+        assert_eq!(changes.len(), 1);
+        match &changes[0] {
+            ListChange::ItemModified {
+                item_name, changes, ..
+            } => {
+                assert_eq!(item_name, "Bread");
+                assert_eq!(changes.len(), 1);
+                assert!(matches!(changes[0], FieldChange::Quantity { .. }));
+            }
+            _ => panic!("Expected ItemModified"),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_names_are_tracked_by_id() {
+        let cached = vec![
+            create_db_item("item-1", "Milk", false),
+            create_db_item("item-2", "Milk", false),
+        ];
+        let mut current = vec![
+            create_list_item("item-1", "Milk", false),
+            create_list_item("item-2", "Milk", true),
+        ];
+        current[0].quantity = Some("2".to_string());
+
+        let changes = detect_changes("list-1", "Groceries", &cached, &current);
+
+        // Each same-name item produces exactly its own change
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().any(|c| matches!(
+            c,
+            ListChange::ItemModified { item_id, .. } if item_id == "item-1"
+        )));
+        assert!(changes
+            .iter()
+            .any(|c| matches!(c, ListChange::ItemChecked { .. })));
+    }
+
+    #[test]
+    fn test_duplicate_name_replacement_is_not_paired() {
+        // One Milk is removed and a different Milk is added: both are reported
+        let cached = vec![create_db_item("item-1", "Milk", false)];
+        let current = vec![create_list_item("item-2", "Milk", false)];
+
+        let changes = detect_changes("list-1", "Groceries", &cached, &current);
+
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(
+            &changes[0],
+            ListChange::ItemAdded { item, .. } if item.id == "item-2"
+        ));
+        assert!(matches!(
+            &changes[1],
+            ListChange::ItemRemoved { item, .. } if item.id == "item-1"
+        ));
+    }
+
+    #[test]
+    fn test_suppress_auto_category_with_duplicate_names() {
+        let now = 1_000;
+        let mut cached = vec![
+            create_db_item("item-1", "Milk", false),
+            create_db_item("item-2", "Milk", false),
+        ];
+        // item-1 has been on the list for a long time, item-2 was just added
+        cached[0].first_seen = now - 3_600;
+        cached[1].first_seen = now - 10;
+
+        let mut current = vec![
+            create_list_item("item-1", "Milk", false),
+            create_list_item("item-2", "Milk", false),
+        ];
+        current[0].category = Some("Dairy".to_string());
+        current[1].category = Some("Dairy".to_string());
+
+        let changes = suppress_auto_category(
+            detect_changes("list-1", "Groceries", &cached, &current),
+            &cached,
+            60,
+            now,
+        );
+
+        // Only the new item's automatic category is dropped
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(
+            &changes[0],
+            ListChange::ItemModified { item_id, .. } if item_id == "item-1"
+        ));
+    }
+
+    #[test]
+    fn test_summarize_recategorizations() {
+        let mut cached = vec![
+            create_db_item("item-1", "Milk", false),
+            create_db_item("item-2", "Bread", false),
+            create_db_item("item-3", "Eggs", false),
+            create_db_item("item-4", "Butter", false),
+        ];
+        cached[3].quantity = Some("1".to_string());
+
+        let mut current = vec![
+            create_list_item("item-1", "Milk", false),
+            create_list_item("item-2", "Bread", false),
+            create_list_item("item-3", "Eggs", false),
+            create_list_item("item-4", "Butter", false),
+        ];
+        current[0].category = Some("Dairy".to_string());
+        current[1].category = Some("Bakery".to_string());
+        current[2].category = Some("Dairy".to_string());
+        current[2].user_id = Some("user-2".to_string());
+        current[3].category = Some("Dairy".to_string());
+        current[3].quantity = Some("2".to_string());
+
+        let changes = summarize_recategorizations(detect_changes(
+            "list-1",
+            "Groceries",
+            &cached,
+            &current,
+        ));
+
+        // Butter also changed quantity and is reported on its own
+        assert_eq!(changes.len(), 2);
+        match &changes[0] {
+            ListChange::ItemsRecategorized {
+                item_ids,
+                item_names,
+                user_id,
+                ..
+            } => {
+                assert_eq!(item_ids, &vec!["item-1", "item-2", "item-3"]);
+                assert_eq!(item_names, &vec!["Milk", "Bread", "Eggs"]);
+                assert_eq!(user_id, &None);
+            }
+            _ => panic!("Expected ItemsRecategorized"),
+        }
+        assert!(matches!(
+            &changes[1],
+            ListChange::ItemModified { item_name, .. } if item_name == "Butter"
+        ));
+    }
+
+    #[test]
+    fn test_single_recategorization_not_summarized() {
+        let cached = vec![create_db_item("item-1", "Milk", false)];
+        let mut current = vec![create_list_item("item-1", "Milk", false)];
+        current[0].category = Some("Dairy".to_string());
+
+        let changes = summarize_recategorizations(detect_changes(
+            "list-1",
+            "Groceries",
+            &cached,
+            &current,
+        ));
+
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(changes[0], ListChange::ItemModified { .. }));
+    }
+
+    #[test]
+    fn test_change_serializes_with_type_tag() {
+        let cached = vec![create_db_item("item-1", "Milk", false)];
+        let current = vec![create_list_item("item-1", "Milk", true)];
+
+        let changes = detect_changes("list-1", "Groceries", &cached, &current);
+        let json = serde_json::to_value(&changes[0]).unwrap();
+
+        assert_eq!(json["type"], changes[0].change_type());
+        assert_eq!(json["item"]["name"], "Milk");
+
+        // Recorded changes can be read back, e.g. to replay them
+        let parsed: ListChange = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, changes[0]);
+    }
+
+    #[test]
+    fn test_dedup_key() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+
+        let checked = |item_id: &str| ListChange::ItemChecked {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: ItemInfo::from_list_item(&create_list_item(item_id, "Milk", true)),
+            user_id: None,
+        };
+        let key = |change: &ListChange, timestamp| change.dedup_key(timestamp, 60).unwrap();
+
+        assert_eq!(key(&checked("item-1"), 120), key(&checked("item-1"), 179));
+        assert_ne!(key(&checked("item-1"), 120), key(&checked("item-1"), 180));
+        assert_ne!(key(&checked("item-1"), 120), key(&checked("item-2"), 120));
+        assert_eq!(key(&checked("item-1"), 120).len(), 16);
+
+        let fingerprint = |change: &ListChange| change.fingerprint().unwrap();
+        assert_eq!(fingerprint(&checked("item-1")), fingerprint(&checked("item-1")));
+        assert_ne!(fingerprint(&checked("item-1")), fingerprint(&checked("item-2")));
+    }
+
+    #[test]
+    fn test_is_drastic_divergence() {
+        let cached: Vec<DbItem> = (0..12)
+            .map(|i| create_db_item(&format!("old-{}", i), &format!("Item {}", i), false))
+            .collect();
+        // The same items under new IDs
+        let live: Vec<ListItem> = (0..12)
+            .map(|i| create_list_item(&format!("new-{}", i), &format!("Item {}", i), false))
+            .collect();
+
+        let reissued = detect_changes("list-1", "Groceries", &cached, &live);
+        assert_eq!(reissued.len(), 24);
+        assert!(is_drastic_divergence(reissued.len(), 12, 12, 1.5));
+
+        // Clearing a list is a real edit
+        let cleared = detect_changes("list-1", "Groceries", &cached, &[]);
+        assert!(!is_drastic_divergence(cleared.len(), 12, 0, 1.5));
+
+        // Disabled, small lists and new lists never count
+        assert!(!is_drastic_divergence(24, 12, 12, 0.0));
+        assert!(!is_drastic_divergence(8, 4, 4, 1.5));
+        assert!(!is_drastic_divergence(24, 0, 24, 0.5));
+    }
+
+    #[test]
+    fn test_detect_threshold_crossing() {
+        let cached = |unchecked: usize| -> Vec<DbItem> {
+            (0..unchecked + 2)
+                .map(|i| create_db_item(&format!("item-{}", i), "Item", i >= unchecked))
+                .collect()
+        };
+        let current = |unchecked: usize| -> Vec<ListItem> {
+            (0..unchecked + 2)
+                .map(|i| create_list_item(&format!("item-{}", i), "Item", i >= unchecked))
+                .collect()
+        };
+        let crossing = |before: usize, after: usize| {
+            detect_threshold_crossing("list-1", "Groceries", &cached(before), &current(after), 3)
+        };
+
+        assert_eq!(
+            crossing(2, 3),
+            Some(ListChange::ListThresholdCrossed {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                category: None,
+                unchecked_count: 3,
+                threshold: 3,
+                direction: ThresholdDirection::Above,
+            })
+        );
+        assert!(matches!(
+            crossing(3, 1),
+            Some(ListChange::ListThresholdCrossed {
+                direction: ThresholdDirection::Below,
+                ..
+            })
+        ));
+
+        // Staying on one side of the threshold is not a crossing
+        assert_eq!(crossing(3, 5), None);
+        assert_eq!(crossing(0, 2), None);
+    }
+
+    #[test]
+    fn test_detect_category_threshold_crossings() {
+        let with_category = |mut item: DbItem, category: &str| {
+            item.category = Some(category.to_string());
+            item
+        };
+        let cached = vec![
+            with_category(create_db_item("item-1", "Apples", false), "Produce"),
+            with_category(create_db_item("item-2", "Milk", false), "Dairy"),
+            with_category(create_db_item("item-3", "Cheese", false), "Dairy"),
+            create_db_item("item-4", "Batteries", false),
+        ];
+        let current: Vec<ListItem> = [
+            ("item-1", "Apples", "Produce", false),
+            ("item-5", "Pears", "Produce", false),
+            ("item-2", "Milk", "Dairy", true),
+            ("item-3", "Cheese", "Dairy", false),
+        ]
+        .iter()
+        .map(|(id, name, category, is_checked)| {
+            let mut item = create_list_item(id, name, *is_checked);
+            item.category = Some(category.to_string());
+            item
+        })
+        .collect();
+
+        let crossings =
+            detect_category_threshold_crossings("list-1", "Groceries", &cached, &current, |_| {
+                Some(2)
+            });
+        assert_eq!(
+            crossings,
+            vec![
+                ListChange::ListThresholdCrossed {
+                    list_id: "list-1".to_string(),
+                    list_name: "Groceries".to_string(),
+                    category: Some("Dairy".to_string()),
+                    unchecked_count: 1,
+                    threshold: 2,
+                    direction: ThresholdDirection::Below,
+                },
+                ListChange::ListThresholdCrossed {
+                    list_id: "list-1".to_string(),
+                    list_name: "Groceries".to_string(),
+                    category: Some("Produce".to_string()),
+                    unchecked_count: 2,
+                    threshold: 2,
+                    direction: ThresholdDirection::Above,
+                },
+            ]
+        );
+
+        // Categories without a threshold are not watched
+        let crossings =
+            detect_category_threshold_crossings("list-1", "Groceries", &cached, &current, |c| {
+                (c == "Produce").then_some(3)
+            });
+        assert!(crossings.is_empty());
+    }
+
+    #[test]
+    fn test_change_counts() {
+        let cached = vec![
+            create_db_item("item-1", "Milk", false),
+            create_db_item("item-2", "Eggs", false),
+            create_db_item("item-3", "Bread", true),
+            create_db_item("item-4", "Butter", false),
+        ];
+        let current = vec![
+            create_list_item("item-1", "Milk", true),
+            create_list_item("item-2", "Eggs", true),
+            create_list_item("item-3", "Bread", false),
+            create_list_item("item-5", "Cheese", false),
+        ];
+
+        let counts = ChangeCounts::from_changes(&detect_changes(
+            "list-1",
+            "Groceries",
+            &cached,
+            &current,
+        ));
+
+        assert_eq!(
+            counts,
+            ChangeCounts {
+                added: 1,
+                checked: 2,
+                unchecked: 1,
+                modified: 0,
+                removed: 1,
+            }
+        );
+        assert!(ChangeCounts::from_changes(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_merge_cross_list_adds() {
+        let added = |list: &str, id: &str, name: &str, user: &str| ListChange::ItemAdded {
+            list_id: format!("list-{}", list),
+            list_name: list.to_string(),
+            item: ItemInfo::from_list_item(&create_list_item(id, name, false)),
+            user_id: Some(user.to_string()),
+        };
+        let removed = ListChange::ItemRemoved {
+            list_id: "list-Costco".to_string(),
+            list_name: "Costco".to_string(),
+            item: ItemInfo::from_list_item(&create_list_item("item-9", "Eggs", false)),
+            user_id: None,
+        };
+
+        let merged = merge_cross_list_adds(vec![
+            added("Costco", "item-1", "Milk", "alice"),
+            added("Costco", "item-2", "Bread", "alice"),
+            removed.clone(),
+            added("Grocery Store", "item-3", "milk ", "alice"),
+            added("Grocery Store", "item-4", "Bread", "bob"),
+            added("Grocery Store", "item-5", "Butter", "bob"),
+        ]);
+
+        assert_eq!(
+            merged,
+            vec![
+                ListChange::ItemAddedToLists {
+                    list_id: "list-Costco".to_string(),
+                    list_name: "Costco".to_string(),
+                    other_list_ids: vec!["list-Grocery Store".to_string()],
+                    other_list_names: vec!["Grocery Store".to_string()],
+                    item: ItemInfo::from_list_item(&create_list_item("item-1", "Milk", false)),
+                    user_id: Some("alice".to_string()),
+                },
+                // Added by different users, so not attributed to either
+                ListChange::ItemAddedToLists {
+                    list_id: "list-Costco".to_string(),
+                    list_name: "Costco".to_string(),
+                    other_list_ids: vec!["list-Grocery Store".to_string()],
+                    other_list_names: vec!["Grocery Store".to_string()],
+                    item: ItemInfo::from_list_item(&create_list_item("item-2", "Bread", false)),
+                    user_id: None,
+                },
+                removed,
+                added("Grocery Store", "item-5", "Butter", "bob"),
+            ]
+        );
+        assert_eq!(merged[0].list_names(), vec!["Costco", "Grocery Store"]);
+        assert_eq!(ChangeCounts::from_changes(&merged).added, 5);
+
+        // The same name added twice to one list isn't merged
+        let single_list = vec![
+            added("Costco", "item-1", "Milk", "alice"),
+            added("Costco", "item-2", "Milk", "alice"),
+        ];
+        assert_eq!(merge_cross_list_adds(single_list.clone()), single_list);
+    }
+
+    #[test]
+    fn test_group_by_user() {
+        let added = |id: &str, name: &str, user: Option<&str>| ListChange::ItemAdded {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: ItemInfo::from_list_item(&create_list_item(id, name, false)),
+            user_id: user.map(str::to_string),
+        };
+        let sharing = ListChange::ListSharingChanged {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            added_users: vec!["user-3".to_string()],
+            removed_users: Vec::new(),
+        };
+
+        let grouped = group_by_user(vec![
+            added("item-1", "Milk", Some("alice")),
+            added("item-2", "Bread", Some("bob")),
+            sharing.clone(),
+            added("item-3", "Eggs", Some("alice")),
+            added("item-4", "Butter", None),
+            added("item-5", "Cheese", None),
+        ]);
+
+        assert_eq!(
+            grouped,
+            vec![
+                ListChange::ChangesByUser {
+                    list_id: "list-1".to_string(),
+                    list_name: "Groceries".to_string(),
+                    user_id: Some("alice".to_string()),
+                    changes: vec![
+                        added("item-1", "Milk", Some("alice")),
+                        added("item-3", "Eggs", Some("alice")),
+                    ],
+                },
+                added("item-2", "Bread", Some("bob")),
+                sharing,
+                ListChange::ChangesByUser {
+                    list_id: "list-1".to_string(),
+                    list_name: "Groceries".to_string(),
+                    user_id: None,
+                    changes: vec![
+                        added("item-4", "Butter", None),
+                        added("item-5", "Cheese", None),
+                    ],
+                },
+            ]
+        );
+        assert_eq!(
+            ChangeCounts::from_changes(&grouped),
+            ChangeCounts {
+                added: 5,
+                ..ChangeCounts::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_apply_done_semantics() {
+        let mut cached_milk = create_db_item("item-1", "Milk", true);
+        cached_milk.quantity = Some("2".to_string());
+        let mut milk = create_list_item("item-1", "Milk", false);
+        milk.quantity = Some("2".to_string());
+        let cached = vec![
+            cached_milk,
+            create_db_item("item-2", "Eggs", false),
+            create_db_item("item-3", "Bread", false),
+        ];
+        let current = vec![
+            milk,
+            create_list_item("item-2", "Eggs", true),
+            create_list_item("item-3", "Bread", false),
+        ];
+        let mut changes = detect_changes("list-1", "Groceries", &cached, &current);
+        changes.sort_by(|a, b| a.item_names().cmp(&b.item_names()));
+
+        let changes = apply_done_semantics(changes);
+
+        assert_eq!(changes.len(), 2);
+        assert!(matches!(
+            &changes[0],
+            ListChange::ItemDone { item_name, .. } if item_name == "Eggs"
+        ));
+        match &changes[1] {
+            ListChange::ItemAdded { item, .. } => {
+                assert_eq!(item.name, "Milk");
+                assert_eq!(item.quantity.as_deref(), Some("2"));
+            }
+            other => panic!("Expected ItemAdded, got {:?}", other),
+        }
+        assert_eq!(changes[0].change_type(), "item_done");
+    }
+
+    #[test]
+    fn test_detect_sharing_changes() {
+        let cached = vec!["user-1".to_string(), "user-2".to_string()];
+        let current = vec![
+            "user-1".to_string(),
+            "user-4".to_string(),
+            "user-3".to_string(),
+        ];
+
+        let change = detect_sharing_changes("list-1", "Groceries", &cached, &current);
+
+        match change {
+            Some(ListChange::ListSharingChanged {
+                added_users,
+                removed_users,
+                ..
+            }) => {
+                assert_eq!(added_users, vec!["user-3", "user-4"]);
+                assert_eq!(removed_users, vec!["user-2"]);
+            }
+            _ => panic!("Expected ListSharingChanged"),
+        }
+    }
+
+    #[test]
+    fn test_detect_privacy_transition() {
+        let owner_only = vec!["owner".to_string()];
+        let shared = vec![
+            "owner".to_string(),
+            "user-2".to_string(),
+            "user-1".to_string(),
+        ];
+        let still_shared = vec!["owner".to_string(), "user-1".to_string()];
+
+        match detect_privacy_transition("list-1", "Groceries", &owner_only, &shared, "owner") {
+            Some(ListChange::ListBecameShared { added_users, .. }) => {
+                assert_eq!(added_users, vec!["user-1", "user-2"]);
+            }
+            other => panic!("Expected ListBecameShared, got {:?}", other),
+        }
+        match detect_privacy_transition("list-1", "Groceries", &still_shared, &owner_only, "owner")
+        {
+            Some(ListChange::ListBecamePrivate { removed_users, .. }) => {
+                assert_eq!(removed_users, vec!["user-1"]);
+            }
+            other => panic!("Expected ListBecamePrivate, got {:?}", other),
+        }
+
+        // Routine changes while the list stays shared aren't transitions
+        assert!(
+            detect_privacy_transition("list-1", "Groceries", &shared, &still_shared, "owner")
+                .is_none()
+        );
+        assert!(
+            detect_privacy_transition("list-1", "Groceries", &[], &owner_only, "owner").is_none()
+        );
+    }
+
+    #[test]
+    fn test_no_sharing_changes() {
+        let users = vec!["user-1".to_string(), "user-2".to_string()];
+
+        let change = detect_sharing_changes("list-1", "Groceries", &users, &users);
+
+        assert!(change.is_none());
+    }
 }
@@ -0,0 +1,137 @@
+use crate::cache::DbHistoryEntry;
+use crate::sync::diff::ChangeCounts;
+use crate::sync::heartbeat::next_occurrence;
+use crate::sync::SyncHandler;
+use chrono::{Local, NaiveTime};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+/// How far back the first digest reaches when none was sent before
+pub const FIRST_DIGEST_PERIOD_SECS: i64 = 86_400;
+
+/// Send the digest every day at the local time of day `at`
+pub async fn run(handler: Arc<SyncHandler>, at: NaiveTime) {
+    loop {
+        let now = Local::now();
+        let next = next_occurrence(&now, at);
+        info!("Next digest at {}", next.format("%Y-%m-%d %H:%M"));
+        tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
+        handler.send_digest().await;
+    }
+}
+
+/// Count recorded changes per list, sorted by list name
+///
+/// Changes by `skip_user_id` are left out, and lists are labelled with the
+/// name of their most recent entry in case they were renamed.
+pub fn summarize(
+    entries: &[DbHistoryEntry],
+    skip_user_id: Option<&str>,
+) -> Vec<(String, ChangeCounts)> {
+    let mut lists: HashMap<&str, (&str, ChangeCounts)> = HashMap::new();
+    for entry in entries {
+        if skip_user_id.is_some() && entry.user_id.as_deref() == skip_user_id {
+            continue;
+        }
+        let (list_name, counts) = lists
+            .entry(entry.list_id.as_str())
+            .or_insert((entry.list_name.as_str(), ChangeCounts::default()));
+        *list_name = entry.list_name.as_str();
+        count_entry(counts, entry);
+    }
+
+    let mut summary: Vec<(String, ChangeCounts)> = lists
+        .into_values()
+        .filter(|(_, counts)| !counts.is_empty())
+        .map(|(list_name, counts)| (list_name.to_string(), counts))
+        .collect();
+    summary.sort_by(|(a, _), (b, _)| a.cmp(b));
+    summary
+}
+
+/// Add a recorded change to the counts by its type; list-level changes aren't counted
+pub fn count_entry(counts: &mut ChangeCounts, entry: &DbHistoryEntry) {
+    match entry.change_type.as_str() {
+        "item_added" => counts.added += 1,
+        "item_checked" | "item_done" => counts.checked += 1,
+        "item_unchecked" => counts.unchecked += 1,
+        "item_added_and_checked" => {
+            counts.added += 1;
+            counts.checked += 1;
+        }
+        "item_modified" => counts.modified += 1,
+        "items_recategorized" => counts.modified += recategorized_count(&entry.payload),
+        "item_removed" => counts.removed += 1,
+        _ => {}
+    }
+}
+
+/// Number of items in a recorded recategorization summary
+fn recategorized_count(payload: &str) -> usize {
+    serde_json::from_str::<serde_json::Value>(payload)
+        .ok()
+        .and_then(|change| change["item_ids"].as_array().map(Vec::len))
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(list_id: &str, list_name: &str, change_type: &str, user_id: &str) -> DbHistoryEntry {
+        DbHistoryEntry {
+            id: 0,
+            list_id: list_id.to_string(),
+            list_name: list_name.to_string(),
+            change_type: change_type.to_string(),
+            user_id: Some(user_id.to_string()),
+            created_at: 0,
+            payload: "{}".to_string(),
+            dedup_key: None,
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let mut recategorized = entry("list-2", "Hardware", "items_recategorized", "alice");
+        recategorized.payload = r#"{"item_ids":["a","b","c"]}"#.to_string();
+        let entries = vec![
+            entry("list-1", "Groceries", "item_added", "alice"),
+            entry("list-1", "Groceries", "item_added", "me"),
+            entry("list-1", "Groceries", "item_checked", "bob"),
+            entry("list-2", "Hardware", "list_sharing_changed", "alice"),
+            recategorized,
+            entry("list-1", "Weekly Groceries", "item_removed", "alice"),
+            entry("list-3", "Quiet", "item_added", "me"),
+        ];
+
+        let summary = summarize(&entries, Some("me"));
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "Hardware".to_string(),
+                    ChangeCounts {
+                        modified: 3,
+                        ..ChangeCounts::default()
+                    }
+                ),
+                (
+                    "Weekly Groceries".to_string(),
+                    ChangeCounts {
+                        added: 1,
+                        checked: 1,
+                        removed: 1,
+                        ..ChangeCounts::default()
+                    }
+                ),
+            ]
+        );
+
+        // Without a user to skip, everyone's changes count
+        let summary = summarize(&entries, None);
+        assert_eq!(summary.len(), 3);
+        assert_eq!(summary[2].1.added, 2);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::cache::models::DbList;
+use crate::cache::{DbItem, SqliteCache};
+use crate::sync::diff::{detect_changes, detect_sharing_changes, ListChange};
+use anyhow::{Context, Result};
+use anylist_rs::ListItem;
+use std::collections::HashSet;
+
+/// How a list's live state differs from the cache
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListDrift {
+    /// The list isn't cached yet
+    New {
+        list_name: String,
+        item_count: usize,
+    },
+    /// The list is cached but no longer exists
+    Deleted { list_name: String },
+    /// The list is cached; these changes would be detected on the next sync
+    Changed {
+        list_name: String,
+        changes: Vec<ListChange>,
+    },
+}
+
+/// A list's cached items and shared users
+struct CachedList {
+    items: Vec<DbItem>,
+    shared_users: Vec<String>,
+}
+
+/// Diff live lists against the cache without modifying it
+pub async fn detect_drift(
+    cache: &SqliteCache,
+    lists: &[anylist_rs::List],
+) -> Result<Vec<ListDrift>> {
+    let mut drift = Vec::new();
+
+    for list in lists {
+        let cached = match cache
+            .get_list(&list.id)
+            .await
+            .context("Failed to get cached list")?
+        {
+            Some(_) => Some(CachedList {
+                items: cache
+                    .get_items(&list.id)
+                    .await
+                    .context("Failed to get cached items")?,
+                shared_users: cache
+                    .get_shared_users(&list.id)
+                    .await
+                    .context("Failed to get cached shared users")?,
+            }),
+            None => None,
+        };
+        let current_users: Vec<String> = list
+            .shared_users
+            .iter()
+            .map(|u| u.user_id.clone())
+            .collect();
+        drift.push(list_drift(
+            &list.id,
+            &list.name,
+            &list.items,
+            &current_users,
+            cached,
+        ));
+    }
+
+    let live_ids: HashSet<&str> = lists.iter().map(|l| l.id.as_str()).collect();
+    let cached_lists = cache
+        .get_all_lists()
+        .await
+        .context("Failed to get cached lists")?;
+    drift.extend(deleted_lists(cached_lists, &live_ids));
+
+    Ok(drift)
+}
+
+/// Classify a live list against its cached state, if it has any
+fn list_drift(
+    list_id: &str,
+    list_name: &str,
+    items: &[ListItem],
+    current_users: &[String],
+    cached: Option<CachedList>,
+) -> ListDrift {
+    let Some(cached) = cached else {
+        return ListDrift::New {
+            list_name: list_name.to_string(),
+            item_count: items.len(),
+        };
+    };
+
+    let mut changes = detect_changes(list_id, list_name, &cached.items, items);
+    if let Some(change) =
+        detect_sharing_changes(list_id, list_name, &cached.shared_users, current_users)
+    {
+        changes.push(change);
+    }
+    ListDrift::Changed {
+        list_name: list_name.to_string(),
+        changes,
+    }
+}
+
+/// Cached lists that no longer exist in the account
+fn deleted_lists(cached_lists: Vec<DbList>, live_ids: &HashSet<&str>) -> Vec<ListDrift> {
+    cached_lists
+        .into_iter()
+        .filter(|list| !live_ids.contains(list.id.as_str()))
+        .map(|list| ListDrift::Deleted {
+            list_name: list.name,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::test_support::live_item;
+
+    fn cached_item(id: &str, name: &str) -> DbItem {
+        DbItem::new(
+            id.to_string(),
+            "list-1".to_string(),
+            name.to_string(),
+            "".to_string(),
+            None,
+            None,
+            false,
+            Some("test-user".to_string()),
+        )
+    }
+
+    #[test]
+    fn test_uncached_list_is_new() {
+        let items = vec![
+            live_item("item-1", "Milk", false),
+            live_item("item-2", "Eggs", false),
+        ];
+        assert_eq!(
+            list_drift("list-1", "Groceries", &items, &[], None),
+            ListDrift::New {
+                list_name: "Groceries".to_string(),
+                item_count: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_cached_list_reports_pending_changes() {
+        let cached = CachedList {
+            items: vec![cached_item("item-1", "Milk")],
+            shared_users: Vec::new(),
+        };
+        let items = vec![
+            live_item("item-1", "Milk", false),
+            live_item("item-2", "Eggs", false),
+        ];
+        let current_users = vec!["user-2".to_string()];
+
+        match list_drift("list-1", "Groceries", &items, &current_users, Some(cached)) {
+            ListDrift::Changed { list_name, changes } => {
+                assert_eq!(list_name, "Groceries");
+                assert_eq!(changes.len(), 2);
+                assert!(
+                    matches!(&changes[0], ListChange::ItemAdded { item, .. } if item.name == "Eggs")
+                );
+                assert!(matches!(&changes[1], ListChange::ListSharingChanged { .. }));
+            }
+            other => panic!("expected changes, got {:?}", other),
+        }
+
+        // A list in sync is still reported, with nothing to detect
+        let cached = CachedList {
+            items: vec![cached_item("item-1", "Milk")],
+            shared_users: Vec::new(),
+        };
+        let items = vec![live_item("item-1", "Milk", false)];
+        assert_eq!(
+            list_drift("list-1", "Groceries", &items, &[], Some(cached)),
+            ListDrift::Changed {
+                list_name: "Groceries".to_string(),
+                changes: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_missing_lists_are_deleted() {
+        let cached_lists = vec![
+            DbList::new("list-1".to_string(), "Groceries".to_string()),
+            DbList::new("list-2".to_string(), "Hardware".to_string()),
+        ];
+        let live_ids = HashSet::from(["list-1"]);
+
+        assert_eq!(
+            deleted_lists(cached_lists, &live_ids),
+            vec![ListDrift::Deleted {
+                list_name: "Hardware".to_string(),
+            }]
+        );
+    }
+}
@@ -1,45 +1,271 @@
-use crate::cache::SqliteCache;
-use crate::config::Config;
-use crate::notify::NtfyClient;
-use crate::sync::diff::{detect_changes, ListChange};
+use crate::cache::{DbHistoryEntry, DbItem, SqliteCache};
+use crate::config::{CategoryChangeMode, Config, OfflineChangePolicy};
+use crate::notify::{ChangeContext, Notifier, ServiceEvent};
+use crate::sync::absent::AbsentLists;
+use crate::sync::batch::ChangeBatcher;
+use crate::sync::cache_health::{CacheHealth, ShadowList};
+use crate::sync::coalesce::AddCheckCoalescer;
+use crate::sync::debounce::EventDebouncer;
+use crate::sync::diff::{
+    apply_done_semantics, detect_category_threshold_crossings, detect_changes,
+    detect_privacy_transition, detect_sharing_changes, detect_threshold_crossing,
+    drop_category_only, drop_changes_on_checked, fix_empty_item_ids, group_by_user,
+    is_drastic_divergence, merge_cross_list_adds, strip_category_changes,
+    summarize_recategorizations, suppress_auto_category, ListChange, ListTotals,
+};
+use crate::sync::digest;
+use crate::sync::held::HoldsChanges;
+use crate::sync::offline::OfflineChanges;
+use crate::sync::recheck::RecheckFilter;
+use crate::sync::session::{EditingSessions, SessionStart};
+use crate::sync::source::ListSource;
+use crate::sync::throttle::ItemThrottle;
+use crate::sync::toggles::{MaybeDecision, MaybeTracker, ToggleDecision, ToggleTracker};
+use crate::sync::undo::RemovalUndoFilter;
+use crate::sync::users::UserNames;
+use crate::sync::watch::{WatchedItemAdded, WatchedItems};
 use anyhow::{Context, Result};
-use anylist_rs::{AnyListClient, SyncEvent};
-use std::collections::HashMap;
+use anylist_rs::{ListItem, SyncEvent};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
 use tracing::{debug, error, info, warn};
 
+/// Longest wait between seeding retries, however many there are
+const MAX_SEED_RETRY_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Counts of changes seen during a sync pass, used to tune filters
+#[derive(Debug, Default)]
+struct PassStats {
+    /// Changes detected by the diff
+    generated: usize,
+    /// Changes dropped because the authenticated user made them
+    filtered_own: usize,
+    /// Changes dropped by suppression rules
+    filtered_by_rules: usize,
+    /// Changes delivered to at least one backend
+    notified: usize,
+}
+
+impl PassStats {
+    fn add(&mut self, other: &PassStats) {
+        self.generated += other.generated;
+        self.filtered_own += other.filtered_own;
+        self.filtered_by_rules += other.filtered_by_rules;
+        self.notified += other.notified;
+    }
+}
+
+/// Latest size of each list by ID, kept with `include_list_totals`
+#[derive(Debug, Default)]
+struct ListSizes(HashMap<String, ListTotals>);
+
+impl ListSizes {
+    /// Record a list's size as fetched, before its changes are sent, so their
+    /// notifications show the list after them
+    fn record(&mut self, list_id: &str, items: &[ListItem]) {
+        self.0
+            .insert(list_id.to_string(), ListTotals::from_items(items));
+    }
+
+    /// Attach the size of the change's list to its context
+    fn attach(&self, change: &ListChange, context: &mut ChangeContext) {
+        context.list_totals = self.0.get(change.list_id()).copied();
+    }
+}
+
 pub struct SyncHandler {
-    client: Arc<AnyListClient>,
+    client: Arc<dyn ListSource>,
     cache: Arc<SqliteCache>,
-    notifier: Arc<NtfyClient>,
+    notifiers: Vec<Arc<dyn Notifier>>,
     config: Arc<Config>,
     authenticated_user_id: String,
-    user_names: Arc<tokio::sync::RwLock<HashMap<String, String>>>,
+    user_names: Arc<UserNames>,
+    cache_health: Mutex<CacheHealth>,
+    toggles: Mutex<ToggleTracker>,
+    maybe: Mutex<MaybeTracker>,
+    list_sizes: Mutex<ListSizes>,
+    coalescer: Mutex<AddCheckCoalescer>,
+    undo_filter: Mutex<RemovalUndoFilter>,
+    recheck_filter: Mutex<RecheckFilter>,
+    throttle: Mutex<ItemThrottle>,
+    sessions: Mutex<EditingSessions>,
+    batcher: Mutex<ChangeBatcher>,
+    watched_items: WatchedItems,
+    debouncer: EventDebouncer,
+    absent_lists: Mutex<AbsentLists>,
+    /// When the last "resync" command was accepted
+    last_resync: Mutex<Option<Instant>>,
+    /// When startup seeded each list, for the post-initialization grace window
+    seeded_at: Mutex<HashMap<String, Instant>>,
+    /// Set while a degraded-service alert is outstanding
+    degraded: AtomicBool,
 }
 
 impl SyncHandler {
     pub fn new(
-        client: Arc<AnyListClient>,
+        client: Arc<dyn ListSource>,
         cache: Arc<SqliteCache>,
-        notifier: Arc<NtfyClient>,
+        notifiers: Vec<Arc<dyn Notifier>>,
         config: Arc<Config>,
-        user_names: Arc<tokio::sync::RwLock<HashMap<String, String>>>,
+        user_names: Arc<UserNames>,
     ) -> Self {
         let authenticated_user_id = client.user_id();
+        let toggles = ToggleTracker::new(
+            config.notifications.toggle_threshold,
+            Duration::from_secs(config.notifications.toggle_window_secs),
+            Duration::from_secs(config.notifications.toggle_cooldown_secs),
+        );
+        let maybe = MaybeTracker::new(Duration::from_secs(config.notifications.maybe_window_secs));
+        let coalescer = AddCheckCoalescer::new(Duration::from_secs(
+            config.notifications.coalesce_add_check_secs,
+        ));
+        let undo_filter = RemovalUndoFilter::new(Duration::from_secs(
+            config.notifications.undo_remove_secs,
+        ));
+        let recheck_filter = RecheckFilter::new(Duration::from_secs(
+            config.notifications.suppress_uncheck_recheck_secs,
+        ));
+        let throttle = ItemThrottle::new(Duration::from_secs(
+            config.notifications.per_item_min_interval_secs,
+        ));
+        let sessions = EditingSessions::new(
+            config.notifications.editing_session_changes,
+            Duration::from_secs(config.notifications.editing_session_window_secs),
+            Duration::from_secs(config.notifications.editing_session_quiet_secs),
+        );
+        let batcher =
+            ChangeBatcher::new(Duration::from_millis(config.notifications.batch_window_ms));
+        let watched_items = WatchedItems::new(&config.notifications.watch_items);
+        let debouncer = EventDebouncer::new(Duration::from_millis(config.sync.event_debounce_ms));
+        let absent_lists = AbsentLists::new(config.sync.deleted_list_confirmations);
         Self {
             client,
             cache,
-            notifier,
+            notifiers,
             config,
             authenticated_user_id,
             user_names,
+            cache_health: Mutex::new(CacheHealth::new()),
+            toggles: Mutex::new(toggles),
+            maybe: Mutex::new(maybe),
+            list_sizes: Mutex::new(ListSizes::default()),
+            coalescer: Mutex::new(coalescer),
+            undo_filter: Mutex::new(undo_filter),
+            recheck_filter: Mutex::new(recheck_filter),
+            throttle: Mutex::new(throttle),
+            sessions: Mutex::new(sessions),
+            batcher: Mutex::new(batcher),
+            watched_items,
+            debouncer,
+            absent_lists: Mutex::new(absent_lists),
+            last_resync: Mutex::new(None),
+            seeded_at: Mutex::new(HashMap::new()),
+            degraded: AtomicBool::new(false),
         }
     }
 
     /// Get the user name for a given user ID
     pub async fn get_user_name(&self, user_id: &str) -> Option<String> {
-        let names = self.user_names.read().await;
-        names.get(user_id).cloned()
+        self.user_names.get(user_id).await
+    }
+
+    /// Send the unchecked items of a list, in response to a remote command
+    pub async fn send_list_snapshot(&self, list_name: &str) -> Result<()> {
+        let mut lists = self
+            .client
+            .get_lists()
+            .await
+            .context("Failed to fetch lists")?;
+        self.handle_empty_item_ids(&mut lists);
+
+        let event = match lists
+            .iter()
+            .find(|list| list.name.eq_ignore_ascii_case(list_name.trim()))
+        {
+            Some(list) => ServiceEvent::ListSnapshot {
+                list_name: list.name.clone(),
+                items: list
+                    .items
+                    .iter()
+                    .filter(|item| !item.is_checked)
+                    .map(|item| item.name.clone())
+                    .collect(),
+            },
+            None => ServiceEvent::UnknownList {
+                list_name: list_name.to_string(),
+                available: lists.iter().map(|list| list.name.clone()).collect(),
+            },
+        };
+
+        self.notify_service(&event).await;
+        Ok(())
+    }
+
+    /// Silence a list's notifications for a while, in response to a remote command
+    pub async fn mute_list(&self, list_name: &str, duration: Duration) -> Result<()> {
+        let muted_until = SqliteCache::timestamp_in(duration);
+        self.cache.set_mute(list_name, muted_until).await?;
+        info!("Muted {} for {}s", list_name.trim(), duration.as_secs());
+        Ok(())
+    }
+
+    /// Lift a list's mute early, in response to a remote command
+    pub async fn unmute_list(&self, list_name: &str) -> Result<()> {
+        if self.cache.clear_mute(list_name).await? {
+            info!("Unmuted {}", list_name.trim());
+        } else {
+            info!("{} was not muted", list_name.trim());
+        }
+        Ok(())
+    }
+
+    /// Send a change held for review, in response to a remote command
+    pub async fn approve_review(&self, review_id: i64) -> Result<()> {
+        let Some(payload) = self.cache.take_pending_review(review_id).await? else {
+            warn!("Review #{} is not pending", review_id);
+            return Ok(());
+        };
+        let change: ListChange = serde_json::from_str(&payload)
+            .with_context(|| format!("Failed to parse change held for review #{}", review_id))?;
+        info!("Review #{} approved", review_id);
+        self.refresh_users_in([&change]).await;
+        self.deliver_changes(std::slice::from_ref(&change), true)
+            .await;
+        Ok(())
+    }
+
+    /// Drop a change held for review, in response to a remote command
+    pub async fn reject_review(&self, review_id: i64) -> Result<()> {
+        if self.cache.take_pending_review(review_id).await?.is_some() {
+            info!("Review #{} rejected", review_id);
+        } else {
+            warn!("Review #{} is not pending", review_id);
+        }
+        Ok(())
+    }
+
+    /// Whether a list's notifications are muted, lifting a mute that has expired
+    async fn is_muted(&self, list_name: &str) -> bool {
+        let muted_until = match self.cache.get_mute(list_name).await {
+            Ok(Some(muted_until)) => muted_until,
+            Ok(None) => return false,
+            Err(e) => {
+                warn!("Failed to read mute for {}: {:#}", list_name, e);
+                return false;
+            }
+        };
+        if SqliteCache::current_timestamp() < muted_until {
+            return true;
+        }
+
+        info!("Mute on {} lifted", list_name);
+        if let Err(e) = self.cache.clear_mute(list_name).await {
+            warn!("Failed to clear expired mute for {}: {:#}", list_name, e);
+        }
+        false
     }
 
     /// Initialize the cache with current list state
@@ -47,23 +273,103 @@ impl SyncHandler {
     pub async fn initialize_cache(&self) -> Result<()> {
         info!("Initializing cache with current list state");
 
-        let lists = self
+        let mut lists = self
             .client
             .get_lists()
             .await
             .context("Failed to fetch initial lists")?;
+        self.handle_empty_item_ids(&mut lists);
 
-        // Update user names mapping
+        // Update user names mapping, before real-time sync starts so the first
+        // events are already attributed by name
         self.update_user_names(&lists).await;
 
+        // Report changes to previously cached lists made while the service
+        // wasn't running, before seeding overwrites the cached state
+        let policy = self.config.sync.offline_change_policy;
         for list in &lists {
-            self.cache
-                .sync_list(list)
-                .await
-                .context(format!("Failed to sync list: {}", list.name))?;
-            debug!("Cached list: {} ({} items)", list.name, list.items.len());
+            match self.cache.get_list(&list.id).await {
+                Ok(Some(_)) => {
+                    let result = self
+                        .process_list_changes(list, Some(policy), &mut Vec::new())
+                        .await;
+                    if let Err(e) = result {
+                        warn!("Failed to reconcile list {}: {:#}", list.name, e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to read cached list {}: {:#}", list.name, e),
+            }
+        }
+
+        // Seed each list, retrying the ones that fail so a single flaky list
+        // doesn't prevent startup
+        let max_retries = self.config.cache.seed_retries;
+        let mut backoff = Duration::from_secs(self.config.cache.seed_retry_backoff_secs);
+        let mut pending: Vec<&anylist_rs::List> = lists.iter().collect();
+        let mut attempt = 0;
+        loop {
+            let mut failed = Vec::new();
+            for list in pending {
+                match self.cache.sync_list(list).await {
+                    Ok(()) => {
+                        debug!("Cached list: {} ({} items)", list.name, list.items.len());
+                        self.seeded_at
+                            .lock()
+                            .await
+                            .insert(list.id.clone(), Instant::now());
+                    }
+                    Err(e) => {
+                        self.cache.warn_if_pool_exhausted(&e);
+                        warn!("Failed to seed list {}: {:#}", list.name, e);
+                        failed.push(list);
+                    }
+                }
+            }
+            pending = failed;
+
+            if pending.is_empty() || attempt >= max_retries {
+                break;
+            }
+            attempt += 1;
+            info!(
+                "Retrying {} list(s) in {}s (retry {} of {})",
+                pending.len(),
+                backoff.as_secs(),
+                attempt,
+                max_retries
+            );
+            tokio::time::sleep(backoff).await;
+            backoff = backoff.saturating_mul(2).min(MAX_SEED_RETRY_BACKOFF);
         }
 
+        let seeded = lists.len() - pending.len();
+        if seeded == 0 && !lists.is_empty() {
+            anyhow::bail!("Failed to seed any of the {} lists", lists.len());
+        }
+
+        if !pending.is_empty() {
+            // Diff the unseeded lists against their live state kept in memory,
+            // so their items aren't all reported as added on the first change
+            let mut cache_health = self.cache_health.lock().await;
+            for list in &pending {
+                cache_health.defer(&list.id, ShadowList::from_list(list, &[]));
+            }
+            warn!(
+                "Cache seeded with {} of {} lists; {} will be written on their next change: {}",
+                seeded,
+                lists.len(),
+                pending.len(),
+                pending
+                    .iter()
+                    .map(|list| list.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            return Ok(());
+        }
+
+        self.record_successful_sync().await;
         info!("Cache initialized with {} lists", lists.len());
         Ok(())
     }
@@ -72,8 +378,13 @@ impl SyncHandler {
     pub async fn handle_event(&self, event: SyncEvent) -> Result<()> {
         match event {
             SyncEvent::ShoppingListsChanged => {
+                self.record_event().await;
+                if !self.debouncer.enter().await {
+                    debug!("Shopping lists changed - already pending, collapsing event");
+                    return Ok(());
+                }
                 info!("Shopping lists changed - processing updates");
-                self.handle_shopping_lists_changed().await?;
+                self.handle_shopping_lists_changed(None).await?;
             }
             SyncEvent::Heartbeat => {
                 debug!("Heartbeat received");
@@ -85,39 +396,225 @@ impl SyncHandler {
         Ok(())
     }
 
+    /// Check every list for changes right away, in response to a remote command
+    ///
+    /// Requests within `resync_min_interval_secs` of the last accepted one are
+    /// ignored, and one arriving while an event is about to be processed joins
+    /// that pass, so the command can't be used to hammer the AnyList API.
+    pub async fn resync(&self) -> Result<()> {
+        let min_interval = Duration::from_secs(self.config.sync.resync_min_interval_secs);
+        {
+            let now = Instant::now();
+            let mut last_resync = self.last_resync.lock().await;
+            if last_resync.is_some_and(|at| now.duration_since(at) < min_interval) {
+                info!(
+                    "Ignoring resync requested within {}s of the last one",
+                    min_interval.as_secs()
+                );
+                return Ok(());
+            }
+            *last_resync = Some(now);
+        }
+        if !self.debouncer.enter().await {
+            debug!("Resync requested - already pending, collapsing request");
+            return Ok(());
+        }
+        info!("Resync requested - processing updates");
+        self.handle_shopping_lists_changed(None).await
+    }
+
+    /// Catch up on changes made while events were missed, such as after
+    /// resubscribing, reporting them per the offline change policy
+    pub async fn catch_up(&self) -> Result<()> {
+        info!("Catching up on missed changes");
+        self.handle_shopping_lists_changed(Some(self.config.sync.offline_change_policy))
+            .await
+    }
+
+    /// Check every list for changes while the WebSocket is down
+    pub async fn poll(&self) -> Result<()> {
+        debug!("Polling lists for changes");
+        self.handle_shopping_lists_changed(None).await
+    }
+
     /// Handle shopping list changes by fetching updates and detecting diffs
-    async fn handle_shopping_lists_changed(&self) -> Result<()> {
+    ///
+    /// With an offline policy, the changes were missed rather than just made
+    /// and are reported per that policy.
+    async fn handle_shopping_lists_changed(
+        &self,
+        offline: Option<OfflineChangePolicy>,
+    ) -> Result<()> {
         // Fetch current lists from API
-        let current_lists = self
+        let mut current_lists = self
             .client
             .get_lists()
             .await
             .context("Failed to fetch updated lists")?;
+        self.handle_empty_item_ids(&mut current_lists);
 
         // Update user names mapping from all lists
         self.update_user_names(&current_lists).await;
 
         // Process each list
+        let mut stats = PassStats::default();
+        let mut failed_lists = 0;
+        let mut held_adds = Vec::new();
         for current_list in &current_lists {
-            if let Err(e) = self.process_list_changes(current_list).await {
-                error!(
-                    "Error processing changes for list {}: {}",
-                    current_list.name, e
-                );
-                // Continue processing other lists even if one fails
+            match self
+                .process_list_changes(current_list, offline, &mut held_adds)
+                .await
+            {
+                Ok(list_stats) => stats.add(&list_stats),
+                Err(e) => {
+                    failed_lists += 1;
+                    self.cache.warn_if_pool_exhausted(&e);
+                    error!(
+                        "Error processing changes for list {}: {}",
+                        current_list.name, e
+                    );
+                    // Continue processing other lists even if one fails
+                }
             }
         }
 
+        if !held_adds.is_empty() {
+            let adds = merge_cross_list_adds(held_adds);
+            info!("Detected {} add(s) across lists", adds.len());
+            stats.notified += self.send_changes(&adds).await;
+        }
+
+        debug!(
+            generated = stats.generated,
+            filtered_own = stats.filtered_own,
+            filtered_by_rules = stats.filtered_by_rules,
+            notified = stats.notified,
+            "Sync pass complete"
+        );
+
         // Check for deleted lists
-        self.detect_deleted_lists(&current_lists).await?;
+        if let Err(e) = self.detect_deleted_lists(&current_lists).await {
+            self.cache.warn_if_pool_exhausted(&e);
+            return Err(e);
+        }
+
+        if failed_lists == 0 {
+            self.record_successful_sync().await;
+        }
 
         Ok(())
     }
 
+    /// Drop or name items AnyList returned without an ID, per `sync.empty_item_ids`
+    fn handle_empty_item_ids(&self, lists: &mut [anylist_rs::List]) {
+        let policy = self.config.sync.empty_item_ids;
+        for list in lists {
+            let count = fix_empty_item_ids(&mut list.items, policy);
+            if count > 0 {
+                warn!(
+                    "List {} has {} item(s) without an ID, handled with empty_item_ids = {:?}",
+                    list.name, count, policy
+                );
+            }
+        }
+    }
+
+    /// Persist the time of a sync pass that processed every list
+    async fn record_successful_sync(&self) {
+        let now = SqliteCache::current_timestamp();
+        if let Err(e) = self.cache.set_last_successful_sync(now).await {
+            warn!("Failed to record last successful sync: {:#}", e);
+        }
+    }
+
+    /// Whether the list was seeded at startup less than `post_init_grace_secs` ago
+    async fn in_post_init_grace(&self, list_id: &str) -> bool {
+        let grace = Duration::from_secs(self.config.sync.post_init_grace_secs);
+        let mut seeded_at = self.seeded_at.lock().await;
+        seeded_at.retain(|_, seeded| seeded.elapsed() < grace);
+        seeded_at.contains_key(list_id)
+    }
+
+    async fn record_event(&self) {
+        let now = SqliteCache::current_timestamp();
+        if let Err(e) = self.cache.set_last_event(now).await {
+            warn!("Failed to record last event time: {:#}", e);
+        }
+    }
+
+    /// Send the scheduled "still alive" notification with the watched list
+    /// count and the time since the last event
+    pub async fn send_heartbeat(&self) {
+        let stats = match self.cache.get_stats().await {
+            Ok(stats) => stats,
+            Err(e) => {
+                error!("Failed to read cache stats for heartbeat: {:#}", e);
+                return;
+            }
+        };
+        let last_event = match self.cache.last_event().await {
+            Ok(last_event) => last_event,
+            Err(e) => {
+                error!("Failed to read last event time for heartbeat: {:#}", e);
+                return;
+            }
+        };
+
+        let now = SqliteCache::current_timestamp();
+        let event = ServiceEvent::Heartbeat {
+            watched_lists: stats.total_lists,
+            last_event_secs: last_event.map(|at| (now - at).max(0) as u64),
+        };
+        info!("Sending heartbeat");
+        self.notify_service(&event).await;
+    }
+
+    /// Send the scheduled digest: one summary per list of the changes recorded
+    /// since the previous digest
+    pub async fn send_digest(&self) {
+        let now = SqliteCache::current_timestamp();
+        let since = match self.cache.last_digest().await {
+            Ok(since) => since.unwrap_or(now - digest::FIRST_DIGEST_PERIOD_SECS),
+            Err(e) => {
+                error!("Failed to read last digest time: {:#}", e);
+                return;
+            }
+        };
+        let entries = match self.cache.history_since(since).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("Failed to read history for digest: {:#}", e);
+                return;
+            }
+        };
+
+        let own_user_id = self
+            .config
+            .notifications
+            .filter_own_changes
+            .then_some(self.authenticated_user_id.as_str());
+        let summary = digest::summarize(&entries, own_user_id);
+        info!("Sending digest for {} list(s)", summary.len());
+        for (list_name, counts) in summary {
+            if self.is_muted(&list_name).await {
+                continue;
+            }
+            let event = ServiceEvent::Digest {
+                list_name,
+                period_secs: (now - since).max(0) as u64,
+                counts,
+            };
+            self.notify_service(&event).await;
+        }
+
+        if let Err(e) = self.cache.set_last_digest(now).await {
+            warn!("Failed to record digest time: {:#}", e);
+        }
+    }
+
     /// Update user names mapping from list shared users
+    /// New or changed names are also persisted so they survive restarts
     async fn update_user_names(&self, lists: &[anylist_rs::List]) {
-        let mut names = self.user_names.write().await;
-
         for list in lists {
             for user in &list.shared_users {
                 // Use full name if available, otherwise email, otherwise user_id
@@ -135,23 +632,101 @@ impl SyncHandler {
                     user.user_id.clone()
                 };
 
-                names.insert(user.user_id.clone(), display_name);
+                if let Err(e) = self.user_names.update(&user.user_id, &display_name).await {
+                    warn!("Failed to persist user name for {}: {}", user.user_id, e);
+                }
+            }
+        }
+
+        debug!(
+            "Updated user names mapping with {} users",
+            self.user_names.len().await
+        );
+    }
+
+    /// Resolve the names of the users a change references
+    ///
+    /// Unknown users are refreshed beforehand, once for a whole batch of
+    /// changes, with `refresh_users_in`.
+    async fn change_context(&self, change: &ListChange) -> ChangeContext {
+        ChangeContext::resolve(change, &self.user_names).await
+    }
+
+    /// Refresh the names of unknown users referenced by any of the changes,
+    /// refetching lists at most once
+    async fn refresh_users_in(&self, changes: impl IntoIterator<Item = &ListChange>) {
+        let user_ids: Vec<&str> = changes.into_iter().flat_map(ListChange::user_ids).collect();
+        self.refresh_unknown_users(&user_ids).await;
+    }
+
+    /// Resolve a user ID to a display name, refreshing names if it is unknown
+    async fn resolve_user(&self, user_id: &str) -> String {
+        self.refresh_unknown_users(&[user_id]).await;
+        self.user_names.resolve(user_id).await
+    }
+
+    /// Refetch lists to learn the names of users not known yet, so a change by
+    /// someone who just joined a list isn't attributed to their raw ID
+    async fn refresh_unknown_users(&self, user_ids: &[&str]) {
+        if !self.config.sync.refresh_unknown_users {
+            return;
+        }
+
+        let mut unknown = Vec::new();
+        for user_id in user_ids {
+            if self.user_names.is_due_for_refresh(user_id).await {
+                unknown.push(*user_id);
             }
         }
+        if unknown.is_empty() {
+            return;
+        }
 
-        debug!("Updated user names mapping with {} users", names.len());
+        debug!(
+            "Refreshing user names for unknown users {}",
+            unknown.join(", ")
+        );
+        match self.client.get_lists().await {
+            Ok(lists) => self.update_user_names(&lists).await,
+            Err(e) => warn!("Failed to refresh user names: {}", e),
+        }
     }
 
     /// Process changes for a single list
-    async fn process_list_changes(&self, current_list: &anylist_rs::List) -> Result<()> {
+    ///
+    /// With `merge_cross_list_adds`, the list's adds are moved to `held_adds` to be
+    /// merged with adds to other lists once every list has been processed.
+    async fn process_list_changes(
+        &self,
+        current_list: &anylist_rs::List,
+        offline: Option<OfflineChangePolicy>,
+        held_adds: &mut Vec<ListChange>,
+    ) -> Result<PassStats> {
         debug!("Processing changes for list: {}", current_list.name);
 
-        // Get cached items for this list
-        let cached_items = self
-            .cache
-            .get_items(&current_list.id)
+        if self.config.notifications.include_list_totals {
+            self.list_sizes
+                .lock()
+                .await
+                .record(&current_list.id, &current_list.items);
+        }
+
+        // Diff against the last notified state if the cache couldn't store it,
+        // otherwise against the cached items for this list
+        let shadow = self
+            .cache_health
+            .lock()
             .await
-            .context("Failed to get cached items")?;
+            .shadow(&current_list.id)
+            .cloned();
+        let cached_items = match &shadow {
+            Some(shadow) => shadow.items.clone(),
+            None => self
+                .cache
+                .get_items(&current_list.id)
+                .await
+                .context("Failed to get cached items")?,
+        };
 
         // Detect changes
         let mut changes = detect_changes(
@@ -161,19 +736,345 @@ impl SyncHandler {
             &current_list.items,
         );
 
+        // So many item changes at once suggests AnyList changed its data model,
+        // such as reissuing item IDs, rather than anyone editing the list
+        if is_drastic_divergence(
+            changes.len(),
+            cached_items.len(),
+            current_list.items.len(),
+            self.config.sync.reseed_change_ratio,
+        ) {
+            warn!(
+                "{} change(s) to {} with {} cached item(s) look like a data model change; \
+                 re-seeding the list without notifying them",
+                changes.len(),
+                current_list.name,
+                cached_items.len()
+            );
+            self.notify_service(&ServiceEvent::ListReseeded {
+                list_name: current_list.name.clone(),
+                changes: changes.len(),
+            })
+            .await;
+            self.persist_list(current_list, &cached_items).await;
+            return Ok(PassStats {
+                generated: changes.len(),
+                filtered_by_rules: changes.len(),
+                ..PassStats::default()
+            });
+        }
+
+        // Detect sharing changes, but only for lists we've seen before so a
+        // newly created list doesn't report all of its users as added
+        let cached_users = match &shadow {
+            Some(shadow) => Some(shadow.shared_users.clone()),
+            None => match self
+                .cache
+                .get_list(&current_list.id)
+                .await
+                .context("Failed to get cached list")?
+            {
+                Some(_) => Some(
+                    self.cache
+                        .get_shared_users(&current_list.id)
+                        .await
+                        .context("Failed to get cached shared users")?,
+                ),
+                None => None,
+            },
+        };
+        let seen_before = cached_users.is_some();
+        if let Some(cached_users) = cached_users {
+            let current_users: Vec<String> = current_list
+                .shared_users
+                .iter()
+                .map(|u| u.user_id.clone())
+                .collect();
+
+            // With sharing_transitions, a list gaining its first other user or losing
+            // its last one is reported as such instead of as a routine change
+            let transition = if self.config.notifications.sharing_transitions {
+                detect_privacy_transition(
+                    &current_list.id,
+                    &current_list.name,
+                    &cached_users,
+                    &current_users,
+                    &self.authenticated_user_id,
+                )
+            } else {
+                None
+            };
+            if let Some(change) = transition.or_else(|| {
+                detect_sharing_changes(
+                    &current_list.id,
+                    &current_list.name,
+                    &cached_users,
+                    &current_users,
+                )
+            }) {
+                changes.push(change);
+            }
+        }
+
+        // Detect the unchecked-item count crossing the list's threshold, again
+        // only for lists we've seen before
+        let list_threshold = self.config.list_threshold(&current_list.name);
+        if let Some(list_threshold) = list_threshold.filter(|_| seen_before) {
+            let crossing = detect_threshold_crossing(
+                &current_list.id,
+                &current_list.name,
+                &cached_items,
+                &current_list.items,
+                list_threshold.threshold,
+            )
+            .filter(|crossing| {
+                matches!(
+                    crossing,
+                    ListChange::ListThresholdCrossed { direction, .. }
+                        if list_threshold.notify_on.includes(*direction)
+                )
+            });
+            changes.extend(crossing);
+        }
+        let category_thresholds = self.config.category_thresholds(&current_list.name);
+        if let Some(category_thresholds) = category_thresholds.filter(|_| seen_before) {
+            let crossings = detect_category_threshold_crossings(
+                &current_list.id,
+                &current_list.name,
+                &cached_items,
+                &current_list.items,
+                |category| category_thresholds.threshold_for(category),
+            );
+            changes.extend(crossings.into_iter().filter(|crossing| {
+                matches!(
+                    crossing,
+                    ListChange::ListThresholdCrossed { direction, .. }
+                        if category_thresholds.notify_on.includes(*direction)
+                )
+            }));
+        }
+
+        let mut stats = PassStats {
+            generated: changes.len(),
+            ..PassStats::default()
+        };
+
+        // Record all detected activity, including changes that won't be notified
+        self.record_history(&changes).await;
+        let audit_changes = changes.clone();
+
+        // Lists in threshold-only mode notify nothing but their crossings
+        if list_threshold.is_some_and(|list_threshold| list_threshold.only_crossings) {
+            let original_count = changes.len();
+            changes.retain(|change| matches!(change, ListChange::ListThresholdCrossed { .. }));
+            stats.filtered_by_rules += original_count - changes.len();
+        }
+
+        // Drop category assignments AnyList made automatically after an add
+        if self.config.notifications.suppress_auto_category {
+            let original_count = changes.len();
+            changes = suppress_auto_category(
+                changes,
+                &cached_items,
+                self.config.notifications.auto_category_window_secs as i64,
+                SqliteCache::current_timestamp(),
+            );
+            stats.filtered_by_rules += original_count - changes.len();
+        }
+
+        // AnyList categorizes items in a wave right after a list is seeded
+        if self.in_post_init_grace(&current_list.id).await {
+            let original_count = changes.len();
+            changes = drop_category_only(changes);
+            stats.filtered_by_rules += original_count - changes.len();
+        }
+
+        // Edits to items already checked off are rarely of interest
+        if self.config.notifications.ignore_changes_on_checked {
+            let original_count = changes.len();
+            changes = drop_changes_on_checked(changes, &checked_item_ids(current_list));
+            stats.filtered_by_rules += original_count - changes.len();
+        }
+
+        // Drop category changes entirely if configured
+        if self.config.notifications.category_change_mode == CategoryChangeMode::Ignore {
+            let original_count = changes.len();
+            changes = strip_category_changes(changes);
+            stats.filtered_by_rules += original_count - changes.len();
+        }
+
+        // Report an item flipped back and forth as a "maybe" once, then leave it alone
+        if self.config.notifications.maybe_toggles {
+            let original_count = changes.len();
+            changes = self.apply_maybe_tracking(changes).await;
+            stats.filtered_by_rules += original_count - changes.len();
+        }
+
+        // Replace rapid check/uncheck churn on an item with a single alert
+        if self.config.notifications.detect_toggling {
+            let original_count = changes.len();
+            changes = self.apply_toggle_tracking(changes).await;
+            stats.filtered_by_rules += original_count - changes.len();
+        }
+
+        // Report check-offs as done and unchecks as re-adds on "done" lists
+        if self
+            .config
+            .list_options(&current_list.name)
+            .is_some_and(|list| list.treat_checked_as_done)
+        {
+            changes = apply_done_semantics(changes);
+        }
+
         // Filter out own changes if configured
         if self.config.notifications.filter_own_changes {
             let original_count = changes.len();
             changes = self.filter_own_changes(changes);
-            let filtered_count = original_count - changes.len();
-            if filtered_count > 0 {
-                debug!(
-                    "Filtered out {} change(s) made by authenticated user in list: {}",
-                    filtered_count, current_list.name
-                );
+            stats.filtered_own += original_count - changes.len();
+        }
+
+        // Personal lists are cached but not notified
+        if self.config.notifications.shared_lists_only && !self.is_shared(current_list) {
+            stats.filtered_by_rules += changes.len();
+            changes.clear();
+        }
+
+        // Collapse bulk recategorization into a single summary
+        if self.config.notifications.summarize_recategorization {
+            changes = summarize_recategorizations(changes);
+        }
+
+        if stats.filtered_own > 0 || stats.filtered_by_rules > 0 {
+            debug!(
+                list = %current_list.name,
+                generated = stats.generated,
+                filtered_own = stats.filtered_own,
+                filtered_by_rules = stats.filtered_by_rules,
+                "Filtered changes"
+            );
+        }
+
+        // Muted lists still update the cache, but notify nothing
+        if self.is_muted(&current_list.name).await && !changes.is_empty() {
+            debug!(
+                "{} is muted, suppressing {} change(s)",
+                current_list.name,
+                changes.len()
+            );
+            stats.filtered_by_rules += changes.len();
+            changes.clear();
+        }
+
+        // Alert on watched items as soon as they are added to any list
+        if !self.watched_items.is_empty() {
+            let watched = self.watched_items.added(&changes);
+            if self.config.notifications.watch_items_replace_add && !watched.is_empty() {
+                changes.retain(|change| {
+                    !matches!(change, ListChange::ItemAdded { item, .. }
+                        if self.watched_items.matches(&item.name))
+                });
+            }
+            for added in watched {
+                self.send_watched_item(added).await;
             }
         }
 
+        // Hold back removals that may be undone by re-adding the item shortly
+        // after; this runs before coalescing, which would otherwise hold the
+        // re-add back and keep it from cancelling the removal
+        if self.config.notifications.undo_remove_secs > 0 && offline.is_none() {
+            changes = self
+                .undo_filter
+                .lock()
+                .await
+                .filter(changes, Instant::now());
+        }
+
+        // Hold back adds that may be checked off shortly after, merging the two
+        if self.config.notifications.coalesce_add_check_secs > 0 && offline.is_none() {
+            changes = self.coalescer.lock().await.coalesce(
+                changes,
+                &checked_item_ids(current_list),
+                Instant::now(),
+            );
+        }
+
+        // Hold back unchecks that may be undone by a recheck shortly after
+        if self.config.notifications.suppress_uncheck_recheck_secs > 0 && offline.is_none() {
+            changes = self
+                .recheck_filter
+                .lock()
+                .await
+                .filter(changes, Instant::now());
+        }
+
+        // Hold changes to items notified within the minimum interval
+        if self.config.notifications.per_item_min_interval_secs > 0 && offline.is_none() {
+            let now = Instant::now();
+            let mut throttle = self.throttle.lock().await;
+            changes = changes
+                .into_iter()
+                .filter_map(|change| throttle.admit(change, now))
+                .collect();
+        }
+
+        // Hold back changes by users in the middle of an editing session
+        if self.config.notifications.editing_session_changes > 0 && offline.is_none() {
+            let (ready, started) = self
+                .sessions
+                .lock()
+                .await
+                .filter(changes, Instant::now());
+            changes = ready;
+            for start in started {
+                self.send_editing_started(start).await;
+            }
+        }
+
+        // Changes missed while offline are reported per the offline policy
+        if let Some(policy) = offline {
+            match OfflineChanges::new(policy, std::mem::take(&mut changes)) {
+                OfflineChanges::Individual(missed) => changes = missed,
+                OfflineChanges::Dropped(0) => {}
+                OfflineChanges::Dropped(count) => info!(
+                    "Dropped {} change(s) to {} made while offline",
+                    count, current_list.name
+                ),
+                OfflineChanges::Summary(counts) if counts.is_empty() => {}
+                OfflineChanges::Summary(counts) => {
+                    info!("{} changed while offline: {:?}", current_list.name, counts);
+                    let event = ServiceEvent::MissedChanges {
+                        list_name: current_list.name.clone(),
+                        offline_secs: self.secs_since_cached(&current_list.id).await,
+                        counts,
+                    };
+                    self.notify_service(&event).await;
+                }
+            }
+        }
+
+        // Summarize each user's changes in one notification
+        if self.config.notifications.group_by_user {
+            changes = group_by_user(changes);
+        }
+
+        // Hold adds back to merge them with adds of the same item to other lists
+        if self.config.notifications.merge_cross_list_adds && offline.is_none() {
+            let (adds, rest): (Vec<_>, Vec<_>) = changes
+                .into_iter()
+                .partition(|change| matches!(change, ListChange::ItemAdded { .. }));
+            held_adds.extend(adds);
+            changes = rest;
+        }
+
+        // Hold item changes to send those within the batch window as one summary
+        if self.config.notifications.batch_window_ms > 0 && offline.is_none() {
+            changes = self.batcher.lock().await.hold(changes, Instant::now());
+        }
+
+        self.refresh_users_in(changes.iter().chain(&audit_changes))
+            .await;
+
         if !changes.is_empty() {
             info!(
                 "Detected {} change(s) in list: {}",
@@ -181,25 +1082,462 @@ impl SyncHandler {
                 current_list.name
             );
 
-            // Send notifications for each change
-            for change in &changes {
-                debug!("Change detected: {:?}", change);
-                if let Err(e) = self.notifier.notify(change).await {
-                    error!("Failed to send notification: {}", e);
-                    // Continue processing other changes even if notification fails
-                }
-            }
+            stats.notified += self.send_changes(&changes).await;
         } else {
             debug!("No changes detected in list: {}", current_list.name);
         }
 
+        // Send every detected change, unfiltered, to backends keeping an audit trail
+        for change in &audit_changes {
+            let context = self.change_context(change).await;
+            for notifier in &self.notifiers {
+                if let Err(e) = notifier.audit(change, &context).await {
+                    warn!("Failed to send {} audit notification: {}", notifier.name(), e);
+                }
+            }
+        }
+
         // Update cache with current state
-        self.cache
-            .sync_list(current_list)
-            .await
-            .context("Failed to sync list to cache")?;
+        self.persist_list(current_list, &cached_items).await;
 
-        Ok(())
+        Ok(stats)
+    }
+
+    /// Whether a list is shared with anyone besides the authenticated user
+    fn is_shared(&self, list: &anylist_rs::List) -> bool {
+        list.shared_users
+            .iter()
+            .any(|user| user.user_id != self.authenticated_user_id)
+    }
+
+    /// Seconds since a list was last written to the cache
+    async fn secs_since_cached(&self, list_id: &str) -> u64 {
+        match self.cache.get_list(list_id).await {
+            Ok(Some(list)) => (SqliteCache::current_timestamp() - list.last_updated).max(0) as u64,
+            _ => 0,
+        }
+    }
+
+    /// Send each change to every backend, returning how many reached at least one
+    async fn send_changes(&self, changes: &[ListChange]) -> usize {
+        self.refresh_users_in(changes).await;
+        // In digest mode changes are only pushed through the digest, while
+        // local backends still record each one
+        if !self.config.notifications.mode.sends_realtime() {
+            debug!(
+                "Digest mode, only recording {} change(s) locally",
+                changes.len()
+            );
+            return self.deliver_changes(changes, false).await;
+        }
+        if self.config.ntfy.review_topic().is_some() {
+            self.hold_for_review(changes).await;
+            return 0;
+        }
+        self.deliver_changes(changes, true).await
+    }
+
+    /// Store changes until they are approved, and ask for a review of each
+    async fn hold_for_review(&self, changes: &[ListChange]) {
+        self.prune_pending_reviews().await;
+        for change in changes {
+            let list_name = change.list_name();
+            let review_id = match serde_json::to_string(change) {
+                Ok(payload) => self.cache.add_pending_review(list_name, &payload).await,
+                Err(e) => Err(e.into()),
+            };
+            let review_id = match review_id {
+                Ok(review_id) => review_id,
+                Err(e) => {
+                    error!("Failed to hold change for review: {:#}", e);
+                    continue;
+                }
+            };
+            debug!("Holding change for review #{}: {:?}", review_id, change);
+            let context = self.change_context(change).await;
+            for notifier in &self.notifiers {
+                if let Err(e) = notifier.request_review(review_id, change, &context).await {
+                    error!(
+                        "Failed to send {} review request #{}: {}",
+                        notifier.name(),
+                        review_id,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    /// Send each change to the backends its lists are routed to, leaving out
+    /// push backends unless `push`, returning how many reached at least one
+    async fn deliver_changes(&self, changes: &[ListChange], push: bool) -> usize {
+        let markers = self.config.notifications.sent_marker_secs > 0;
+        let mut notified = 0;
+        for change in changes {
+            debug!("Change detected: {:?}", change);
+            // A change re-detected after a crash was already sent before the cache caught up
+            let fingerprint = markers.then(|| change.fingerprint().ok()).flatten();
+            if let Some(fingerprint) = &fingerprint {
+                if self.was_recently_sent(fingerprint).await {
+                    info!("Skipping change sent recently: {:?}", change);
+                    continue;
+                }
+            }
+            let mut delivered = false;
+            let list_names = change.list_names();
+            let mut context = self.change_context(change).await;
+            if self.config.notifications.include_list_totals {
+                self.list_sizes.lock().await.attach(change, &mut context);
+            }
+            for notifier in &self.notifiers {
+                if (!push && notifier.is_push())
+                    || !list_names
+                        .iter()
+                        .any(|list_name| self.config.routes_to(list_name, notifier.name()))
+                {
+                    continue;
+                }
+                match notifier.notify(change, &context).await {
+                    Ok(()) => delivered = true,
+                    Err(e) => {
+                        error!("Failed to send {} notification: {}", notifier.name(), e);
+                        // Continue with other backends and changes even if one fails
+                    }
+                }
+            }
+            if delivered {
+                notified += 1;
+                if let Some(fingerprint) = &fingerprint {
+                    self.mark_sent(fingerprint).await;
+                }
+            }
+        }
+        if markers && notified > 0 {
+            self.prune_sent_markers().await;
+        }
+        notified
+    }
+
+    /// Whether an identical change was sent within `sent_marker_secs`
+    /// Cache failures are logged and count as not sent, so nothing is lost
+    async fn was_recently_sent(&self, fingerprint: &str) -> bool {
+        let since = SqliteCache::timestamp_ago(Duration::from_secs(
+            self.config.notifications.sent_marker_secs,
+        ));
+        match self.cache.was_sent_since(fingerprint, since).await {
+            Ok(sent) => sent,
+            Err(e) => {
+                warn!("Failed to check for a recently sent change: {:#}", e);
+                false
+            }
+        }
+    }
+
+    /// Record that a change was sent, so it isn't sent again after a restart
+    async fn mark_sent(&self, fingerprint: &str) {
+        let now = SqliteCache::current_timestamp();
+        if let Err(e) = self.cache.mark_sent(fingerprint, now).await {
+            warn!("Failed to record sent change: {:#}", e);
+        }
+    }
+
+    /// Forget changes sent longer ago than `sent_marker_secs`
+    async fn prune_sent_markers(&self) {
+        let before = SqliteCache::timestamp_ago(Duration::from_secs(
+            self.config.notifications.sent_marker_secs,
+        ));
+        match self.cache.prune_sent_markers(before).await {
+            Ok(0) => {}
+            Ok(pruned) => debug!("Pruned {} sent change marker(s)", pruned),
+            Err(e) => warn!("Failed to prune sent change markers: {:#}", e),
+        }
+    }
+
+    /// Drop reviews left unanswered for longer than `review_expiry_secs`
+    async fn prune_pending_reviews(&self) {
+        let expiry = i64::try_from(self.config.ntfy.review_expiry_secs).unwrap_or(i64::MAX);
+        let before = SqliteCache::current_timestamp().saturating_sub(expiry);
+        match self.cache.prune_pending_reviews(before).await {
+            Ok(0) => {}
+            Ok(pruned) => info!("Dropped {} unanswered review(s)", pruned),
+            Err(e) => warn!("Failed to prune pending reviews: {:#}", e),
+        }
+    }
+
+    /// Send the alert for a watched item added to a list, unless in digest mode
+    async fn send_watched_item(&self, added: WatchedItemAdded) {
+        info!("Watched item {} added to {}", added.item_name, added.list_name);
+        if !self.config.notifications.mode.sends_realtime() {
+            return;
+        }
+        let user_name = match &added.user_id {
+            Some(user_id) => Some(self.resolve_user(user_id).await),
+            None => None,
+        };
+        let event = ServiceEvent::WatchedItemAdded {
+            list_name: added.list_name,
+            item_name: added.item_name,
+            user_name,
+        };
+        self.notify_service(&event).await;
+    }
+
+    /// Announce that a user started an editing session, if pings are enabled
+    async fn send_editing_started(&self, start: SessionStart) {
+        info!("{} is editing {}", start.user_id, start.list_name);
+        if !self.config.notifications.editing_session_ping
+            || !self.config.notifications.mode.sends_realtime()
+        {
+            return;
+        }
+        let event = ServiceEvent::EditingStarted {
+            user_name: self.resolve_user(&start.user_id).await,
+            list_name: start.list_name,
+        };
+        self.notify_service(&event).await;
+    }
+
+    /// Send held adds that weren't checked off within the coalescing window,
+    /// removals and unchecks that weren't undone, throttled changes whose
+    /// item's interval has passed, and summaries of finished editing sessions
+    pub async fn flush_held_changes(&self) {
+        let now = Instant::now();
+        let mut due = Vec::new();
+        for stage in self.held_stages() {
+            due.extend(stage.lock().await.take_expired(now));
+        }
+        if !due.is_empty() {
+            debug!("Releasing {} held change(s)", due.len());
+            self.send_changes(&due).await;
+        }
+    }
+
+    /// The stages holding changes back that `flush_held_changes` releases;
+    /// the batcher is flushed on its own, shorter interval
+    fn held_stages(&self) -> [&Mutex<dyn HoldsChanges>; 5] {
+        [
+            &self.coalescer,
+            &self.undo_filter,
+            &self.recheck_filter,
+            &self.throttle,
+            &self.sessions,
+        ]
+    }
+
+    /// Send a summary for each list whose batch window has closed
+    pub async fn flush_batched_changes(&self) {
+        let due = self.batcher.lock().await.take_expired(Instant::now());
+        if !due.is_empty() {
+            debug!("Releasing {} batched notification(s)", due.len());
+            self.send_changes(&due).await;
+        }
+    }
+
+    /// Send every held change immediately, so none are lost on shutdown
+    pub async fn flush_all_held_changes(&self) {
+        let batcher: &Mutex<dyn HoldsChanges> = &self.batcher;
+        let mut held = Vec::new();
+        for stage in self.held_stages().into_iter().chain([batcher]) {
+            held.extend(stage.lock().await.take_all());
+        }
+        if !held.is_empty() {
+            info!("Sending {} held notification(s)", held.len());
+            self.send_changes(&held).await;
+        }
+    }
+
+    /// Let every backend finish the work it still has running in the background
+    pub async fn shutdown_notifiers(&self) {
+        for notifier in &self.notifiers {
+            notifier.shutdown().await;
+        }
+    }
+
+    /// Write a list's current state to the cache
+    ///
+    /// Write failures are tracked rather than propagated: the state is kept in
+    /// memory as the next diff baseline so the same changes aren't notified
+    /// again, writes back off, and a single alert is sent once failures persist.
+    async fn persist_list(&self, list: &anylist_rs::List, baseline: &[DbItem]) {
+        let now = Instant::now();
+        let attempt = self.cache_health.lock().await.should_attempt_write(now);
+
+        if !attempt {
+            debug!("Cache writes backing off, keeping list {} in memory", list.name);
+            self.cache_health
+                .lock()
+                .await
+                .defer(&list.id, ShadowList::from_list(list, baseline));
+            return;
+        }
+
+        match self.cache.sync_list(list).await {
+            Ok(()) => {
+                if self.cache_health.lock().await.record_success(&list.id) {
+                    info!("Cache writes recovered");
+                    self.mark_recovered().await;
+                }
+            }
+            Err(e) => {
+                self.cache.warn_if_pool_exhausted(&e);
+                error!("Failed to sync list {} to cache: {:#}", list.name, e);
+                let should_alert = self.cache_health.lock().await.record_failure(
+                    &list.id,
+                    ShadowList::from_list(list, baseline),
+                    now,
+                );
+
+                if should_alert {
+                    warn!("Cache writes are failing persistently");
+                    self.mark_degraded(&ServiceEvent::CacheUnavailable {
+                        error: format!("{:#}", e),
+                    })
+                    .await;
+                }
+            }
+        }
+    }
+
+    /// Record changes in the history table
+    /// Failures are logged and don't stop notifications
+    async fn record_history(&self, changes: &[ListChange]) {
+        if changes.is_empty() {
+            return;
+        }
+
+        let bucket_secs = self.config.notifications.dedup_bucket_secs;
+        let entries: Result<Vec<DbHistoryEntry>> = changes
+            .iter()
+            .map(|change| DbHistoryEntry::from_change(change, bucket_secs))
+            .collect();
+        let result = match entries {
+            Ok(entries) => self.cache.record_history(&entries).await,
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = result {
+            self.cache.warn_if_pool_exhausted(&e);
+            warn!("Failed to record change history: {:#}", e);
+        }
+    }
+
+    /// Flag the service as degraded and send the alert if enabled
+    async fn mark_degraded(&self, alert: &ServiceEvent) {
+        self.degraded.store(true, Ordering::SeqCst);
+        if self.config.notifications.alert_on_degraded {
+            self.notify_service(alert).await;
+        }
+    }
+
+    /// Clear the degraded flag, sending the recovery notification once per outage
+    async fn mark_recovered(&self) {
+        if !self.degraded.swap(false, Ordering::SeqCst) {
+            return;
+        }
+
+        info!("Service recovered, monitoring resumed");
+        if self.config.notifications.notify_on_recovery {
+            self.notify_service(&ServiceEvent::Recovered).await;
+        }
+    }
+
+    /// Send a service event to every backend
+    async fn notify_service(&self, event: &ServiceEvent) {
+        for notifier in &self.notifiers {
+            if let Err(e) = notifier.notify_service(event).await {
+                error!("Failed to send {} service notification: {}", notifier.name(), e);
+            }
+        }
+    }
+
+    /// Count check-state changes per item, replacing the one that crosses the
+    /// toggle threshold with an alert and dropping those during the cooldown
+    async fn apply_toggle_tracking(&self, changes: Vec<ListChange>) -> Vec<ListChange> {
+        let now = Instant::now();
+        let mut toggles = self.toggles.lock().await;
+        toggles.prune(now);
+
+        changes
+            .into_iter()
+            .filter_map(|change| {
+                let (list_id, list_name, item, user_id) = match &change {
+                    ListChange::ItemChecked {
+                        list_id,
+                        list_name,
+                        item,
+                        user_id,
+                    }
+                    | ListChange::ItemUnchecked {
+                        list_id,
+                        list_name,
+                        item,
+                        user_id,
+                    } => (list_id, list_name, item, user_id),
+                    _ => return Some(change),
+                };
+
+                match toggles.record(&item.id, now) {
+                    ToggleDecision::Notify => Some(change.clone()),
+                    ToggleDecision::Alert { count } => {
+                        info!("{} toggled {} times in {}", item.name, count, list_name);
+                        Some(ListChange::ItemToggledRepeatedly {
+                            list_id: list_id.clone(),
+                            list_name: list_name.clone(),
+                            item_id: item.id.clone(),
+                            item_name: item.name.clone(),
+                            toggle_count: count,
+                            user_id: user_id.clone(),
+                        })
+                    }
+                    ToggleDecision::Suppress => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Replace the check-state change that flips an item back within the maybe
+    /// window with a "maybe" notice, and drop its check changes until it settles
+    ///
+    /// Only notifications are affected; the cache always stores the item's real state.
+    async fn apply_maybe_tracking(&self, changes: Vec<ListChange>) -> Vec<ListChange> {
+        let now = Instant::now();
+        let mut maybe = self.maybe.lock().await;
+        maybe.prune(now);
+
+        changes
+            .into_iter()
+            .filter_map(|change| {
+                let (list_id, list_name, item, user_id) = match &change {
+                    ListChange::ItemChecked {
+                        list_id,
+                        list_name,
+                        item,
+                        user_id,
+                    }
+                    | ListChange::ItemUnchecked {
+                        list_id,
+                        list_name,
+                        item,
+                        user_id,
+                    } => (list_id, list_name, item, user_id),
+                    _ => return Some(change),
+                };
+
+                match maybe.record(&item.id, now) {
+                    MaybeDecision::Notify => Some(change.clone()),
+                    MaybeDecision::Maybe => {
+                        info!("{} marked as maybe in {}", item.name, list_name);
+                        Some(ListChange::ItemMarkedMaybe {
+                            list_id: list_id.clone(),
+                            list_name: list_name.clone(),
+                            item: item.clone(),
+                            user_id: user_id.clone(),
+                        })
+                    }
+                    MaybeDecision::Suppress => None,
+                }
+            })
+            .collect()
     }
 
     /// Filter out changes made by the authenticated user
@@ -209,10 +1547,22 @@ impl SyncHandler {
             .filter(|change| {
                 let user_id = match change {
                     ListChange::ItemAdded { user_id, .. } => user_id,
+                    ListChange::ItemAddedToLists { user_id, .. } => user_id,
                     ListChange::ItemRemoved { user_id, .. } => user_id,
                     ListChange::ItemChecked { user_id, .. } => user_id,
                     ListChange::ItemUnchecked { user_id, .. } => user_id,
+                    ListChange::ItemAddedAndChecked { user_id, .. } => user_id,
+                    ListChange::ItemDone { user_id, .. } => user_id,
                     ListChange::ItemModified { user_id, .. } => user_id,
+                    ListChange::ItemsRecategorized { user_id, .. } => user_id,
+                    ListChange::ChangesByUser { user_id, .. } => user_id,
+                    ListChange::ItemToggledRepeatedly { user_id, .. } => user_id,
+                    ListChange::ItemMarkedMaybe { user_id, .. } => user_id,
+                    // Sharing changes aren't attributed to a user
+                    ListChange::ListSharingChanged { .. }
+                    | ListChange::ListBecameShared { .. }
+                    | ListChange::ListBecamePrivate { .. } => return true,
+                    ListChange::ListThresholdCrossed { .. } => return true,
                 };
 
                 // Keep the change if user_id doesn't match authenticated user
@@ -228,6 +1578,10 @@ impl SyncHandler {
     }
 
     /// Detect lists that have been deleted
+    ///
+    /// A list missing from a fetch is only removed once it has been missing from
+    /// `sync.deleted_list_confirmations` fetches in a row, so a truncated
+    /// response doesn't wipe its cached state.
     async fn detect_deleted_lists(&self, current_lists: &[anylist_rs::List]) -> Result<()> {
         let cached_lists = self
             .cache
@@ -238,8 +1592,19 @@ impl SyncHandler {
         let current_ids: std::collections::HashSet<_> =
             current_lists.iter().map(|l| l.id.as_str()).collect();
 
+        let mut absent_lists = self.absent_lists.lock().await;
+        let deleted =
+            absent_lists.observe(cached_lists.iter().map(|l| l.id.as_str()), &current_ids);
+
         for cached_list in cached_lists {
-            if !current_ids.contains(cached_list.id.as_str()) {
+            let absent = absent_lists.count(&cached_list.id);
+            if absent > 0 {
+                debug!(
+                    "List {} missing from {} fetch(es) in a row, keeping it cached",
+                    cached_list.name, absent
+                );
+            }
+            if deleted.contains(&cached_list.id) {
                 info!("List deleted: {} ({})", cached_list.name, cached_list.id);
                 self.cache
                     .delete_list(&cached_list.id)
@@ -254,3 +1619,242 @@ impl SyncHandler {
         Ok(())
     }
 }
+
+/// IDs of the list's items that are currently checked off
+fn checked_item_ids(list: &anylist_rs::List) -> HashSet<&str> {
+    list.items
+        .iter()
+        .filter(|item| item.is_checked)
+        .map(|item| item.id.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_support::valid_config;
+    use crate::sync::diff::test_support::{added, checked, live_item, live_list, on_list, removed};
+    use crate::sync::diff::ChangeCounts;
+    use async_trait::async_trait;
+
+    /// Serves the same lists on every fetch
+    struct FixedLists(Vec<anylist_rs::List>);
+
+    #[async_trait]
+    impl ListSource for FixedLists {
+        fn user_id(&self) -> String {
+            "me".to_string()
+        }
+
+        async fn get_lists(&self) -> Result<Vec<anylist_rs::List>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Records what it is asked to send instead of sending it
+    #[derive(Default)]
+    struct RecordingNotifier {
+        changes: std::sync::Mutex<Vec<ListChange>>,
+        events: std::sync::Mutex<Vec<ServiceEvent>>,
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        fn name(&self) -> &str {
+            "ntfy"
+        }
+
+        async fn notify(&self, change: &ListChange, _context: &ChangeContext) -> Result<()> {
+            self.changes.lock().unwrap().push(change.clone());
+            Ok(())
+        }
+
+        async fn notify_service(&self, event: &ServiceEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+
+    /// A handler over an in-memory cache holding `cached`, fetching `live`
+    async fn handler(
+        config: Config,
+        cached: &anylist_rs::List,
+        live: anylist_rs::List,
+    ) -> (SyncHandler, Arc<RecordingNotifier>, Arc<SqliteCache>) {
+        let cache = Arc::new(
+            SqliteCache::new("sqlite::memory:")
+                .await
+                .expect("Failed to create cache"),
+        );
+        cache.sync_list(cached).await.expect("Failed to seed cache");
+        let notifier = Arc::new(RecordingNotifier::default());
+        let handler = SyncHandler::new(
+            Arc::new(FixedLists(vec![live])),
+            cache.clone(),
+            vec![notifier.clone()],
+            Arc::new(config),
+            Arc::new(UserNames::new(cache.clone(), HashMap::new())),
+        );
+        (handler, notifier, cache)
+    }
+
+    /// Groceries as cached before going offline, with Milk and Eggs, and as
+    /// found after: Milk checked off, Eggs removed and Bread added
+    fn offline_lists() -> (anylist_rs::List, anylist_rs::List) {
+        let cached = live_list(vec![
+            live_item("item-1", "Milk", false),
+            live_item("item-2", "Eggs", false),
+        ]);
+        let live = live_list(vec![
+            live_item("item-1", "Milk", true),
+            live_item("item-3", "Bread", false),
+        ]);
+        (cached, live)
+    }
+
+    /// Names and check states of a list's cached items, by name
+    async fn cached_items(cache: &SqliteCache) -> Vec<(String, bool)> {
+        let mut items: Vec<_> = cache
+            .get_items("list-1")
+            .await
+            .expect("Failed to read cache")
+            .into_iter()
+            .map(|item| (item.name, item.is_checked))
+            .collect();
+        items.sort();
+        items
+    }
+
+    fn caught_up_items() -> Vec<(String, bool)> {
+        vec![("Bread".to_string(), false), ("Milk".to_string(), true)]
+    }
+
+    #[tokio::test]
+    async fn test_missed_changes_are_summarized() {
+        let mut config = valid_config();
+        config.sync.offline_change_policy = OfflineChangePolicy::Summarize;
+        let (cached, live) = offline_lists();
+        let (handler, notifier, cache) = handler(config, &cached, live).await;
+
+        handler.catch_up().await.unwrap();
+
+        assert!(notifier.changes.lock().unwrap().is_empty());
+        let events = notifier.events.lock().unwrap().clone();
+        match &events[..] {
+            [ServiceEvent::MissedChanges {
+                list_name, counts, ..
+            }] => {
+                assert_eq!(list_name, "Groceries");
+                assert_eq!(
+                    *counts,
+                    ChangeCounts {
+                        added: 1,
+                        checked: 1,
+                        removed: 1,
+                        ..ChangeCounts::default()
+                    }
+                );
+            }
+            other => panic!("expected a missed changes summary, got {:?}", other),
+        }
+        assert_eq!(cached_items(&cache).await, caught_up_items());
+    }
+
+    #[tokio::test]
+    async fn test_missed_changes_are_sent_individually_after_filters() {
+        let mut config = valid_config();
+        config.sync.offline_change_policy = OfflineChangePolicy::QueueIndividual;
+        let (cached, mut live) = offline_lists();
+        // Added by the authenticated user, so filtered as their own change
+        let mut butter = live_item("item-4", "Butter", false);
+        butter.user_id = Some("me".to_string());
+        live.items.push(butter);
+        let (handler, notifier, cache) = handler(config, &cached, live).await;
+
+        handler.catch_up().await.unwrap();
+
+        let changes = notifier.changes.lock().unwrap().clone();
+        assert_eq!(changes.len(), 3, "{:?}", changes);
+        assert!(changes.contains(&checked("item-1", "Milk")));
+        assert!(changes.contains(&removed("item-2", "Eggs")));
+        assert!(changes.contains(&added("item-3", "Bread")));
+        assert!(notifier.events.lock().unwrap().is_empty());
+
+        let mut expected = caught_up_items();
+        expected.insert(1, ("Butter".to_string(), false));
+        assert_eq!(cached_items(&cache).await, expected);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_missed_changes_still_update_the_cache() {
+        let mut config = valid_config();
+        config.sync.offline_change_policy = OfflineChangePolicy::Drop;
+        let (cached, live) = offline_lists();
+        let (handler, notifier, cache) = handler(config, &cached, live).await;
+
+        handler.catch_up().await.unwrap();
+
+        assert!(notifier.changes.lock().unwrap().is_empty());
+        assert!(notifier.events.lock().unwrap().is_empty());
+        assert_eq!(cached_items(&cache).await, caught_up_items());
+    }
+
+    #[test]
+    fn test_list_totals_count_the_change() {
+        let mut sizes = ListSizes::default();
+        // The fetched list already holds the item just added
+        sizes.record(
+            "list-1",
+            &[
+                live_item("item-1", "Milk", true),
+                live_item("item-2", "Bread", false),
+            ],
+        );
+
+        let mut context = ChangeContext::default();
+        sizes.attach(&added("item-2", "Bread"), &mut context);
+        assert_eq!(
+            context.list_totals,
+            Some(ListTotals {
+                total: 2,
+                unchecked: 1,
+            })
+        );
+
+        // Lists that weren't fetched have no totals
+        sizes.attach(
+            &on_list(added("item-3", "Nails"), "list-2", "Hardware"),
+            &mut context,
+        );
+        assert_eq!(context.list_totals, None);
+    }
+
+    #[tokio::test]
+    async fn test_changes_marked_sent_are_skipped_until_the_marker_expires() {
+        let mut config = valid_config();
+        config.notifications.sent_marker_secs = 60;
+        let list = live_list(Vec::new());
+        let (handler, notifier, cache) = handler(config, &list, list.clone()).await;
+        let change = added("item-1", "Milk");
+        let fingerprint = change.fingerprint().unwrap();
+        let sent = std::slice::from_ref(&change);
+
+        // Sent just before a crash, and detected again after the restart
+        cache
+            .mark_sent(&fingerprint, SqliteCache::current_timestamp())
+            .await
+            .unwrap();
+        assert_eq!(handler.deliver_changes(sent, true).await, 0);
+        assert!(notifier.changes.lock().unwrap().is_empty());
+
+        cache
+            .mark_sent(
+                &fingerprint,
+                SqliteCache::timestamp_ago(Duration::from_secs(61)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(handler.deliver_changes(sent, true).await, 1);
+        assert_eq!(*notifier.changes.lock().unwrap(), vec![change]);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::sync::SyncHandler;
+use chrono::{DateTime, Local, NaiveTime, TimeZone};
+use std::sync::Arc;
+use tracing::info;
+
+/// Send a heartbeat every day at the local time of day `at`
+pub async fn run(handler: Arc<SyncHandler>, at: NaiveTime) {
+    loop {
+        let now = Local::now();
+        let next = next_occurrence(&now, at);
+        info!("Next heartbeat at {}", next.format("%Y-%m-%d %H:%M"));
+        tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
+        handler.send_heartbeat().await;
+    }
+}
+
+/// The first time after `now` that the clock reads `at`
+///
+/// Days on which `at` doesn't exist, because a DST change skips it, are
+/// skipped; if it occurs twice the earlier one is used.
+pub fn next_occurrence<Tz: TimeZone>(now: &DateTime<Tz>, at: NaiveTime) -> DateTime<Tz> {
+    let timezone = now.timezone();
+    let mut date = now.date_naive();
+    loop {
+        if let Some(next) = timezone.from_local_datetime(&date.and_time(at)).earliest() {
+            if next > *now {
+                return next;
+            }
+        }
+        date = date.succ_opt().expect("Date out of range");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+
+        assert_eq!(
+            next_occurrence(&utc("2026-03-02T08:30:00Z"), nine),
+            utc("2026-03-02T09:00:00Z")
+        );
+        // Sending at the scheduled time waits for the next day
+        assert_eq!(
+            next_occurrence(&utc("2026-03-02T09:00:00Z"), nine),
+            utc("2026-03-03T09:00:00Z")
+        );
+        assert_eq!(
+            next_occurrence(&utc("2026-12-31T23:00:00Z"), nine),
+            utc("2027-01-01T09:00:00Z")
+        );
+    }
+}
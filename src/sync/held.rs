@@ -0,0 +1,100 @@
+use crate::sync::diff::ListChange;
+use std::time::{Duration, Instant};
+
+/// A stage of the handler that holds changes back to release them later
+///
+/// The handler flushes every stage the same way: the changes that are due
+/// on each tick, and everything still held on shutdown.
+pub trait HoldsChanges: Send {
+    /// Release the held changes that are due
+    fn take_expired(&mut self, now: Instant) -> Vec<ListChange>;
+
+    /// Release every held change, e.g. on shutdown
+    fn take_all(&mut self) -> Vec<ListChange>;
+}
+
+/// Changes each held for a fixed window from when they arrived, unless taken
+/// back early by a later change that cancels or merges with them
+#[derive(Debug)]
+pub struct HeldWindow {
+    window: Duration,
+    held: Vec<(Instant, ListChange)>,
+}
+
+impl HeldWindow {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            held: Vec::new(),
+        }
+    }
+
+    pub fn hold(&mut self, change: ListChange, now: Instant) {
+        self.held.push((now, change));
+    }
+
+    /// Take back the oldest held change matching the predicate
+    pub fn take_first(&mut self, matches: impl Fn(&ListChange) -> bool) -> Option<ListChange> {
+        let position = self.held.iter().position(|(_, change)| matches(change))?;
+        Some(self.held.remove(position).1)
+    }
+
+    /// Take back the held change to an item
+    pub fn take_item(&mut self, item_id: &str) -> Option<ListChange> {
+        self.take_first(|change| change.item_ids().contains(&item_id))
+    }
+}
+
+impl HoldsChanges for HeldWindow {
+    fn take_expired(&mut self, now: Instant) -> Vec<ListChange> {
+        let window = self.window;
+        let (expired, held) = std::mem::take(&mut self.held)
+            .into_iter()
+            .partition(|(held_at, _)| now.duration_since(*held_at) >= window);
+        self.held = held;
+        expired.into_iter().map(|(_, change)| change).collect()
+    }
+
+    fn take_all(&mut self) -> Vec<ListChange> {
+        std::mem::take(&mut self.held)
+            .into_iter()
+            .map(|(_, change)| change)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::test_support::added;
+
+    #[test]
+    fn test_changes_are_released_after_their_window() {
+        let mut held = HeldWindow::new(Duration::from_secs(30));
+        let start = Instant::now();
+        held.hold(added("item-1", "Milk"), start);
+        held.hold(added("item-2", "Eggs"), start + Duration::from_secs(10));
+
+        assert!(held
+            .take_expired(start + Duration::from_secs(29))
+            .is_empty());
+        assert_eq!(
+            held.take_expired(start + Duration::from_secs(30)),
+            vec![added("item-1", "Milk")]
+        );
+        assert_eq!(held.take_all(), vec![added("item-2", "Eggs")]);
+        assert!(held.take_all().is_empty());
+    }
+
+    #[test]
+    fn test_take_item_takes_back_early() {
+        let mut held = HeldWindow::new(Duration::from_secs(30));
+        let start = Instant::now();
+        held.hold(added("item-1", "Milk"), start);
+        held.hold(added("item-2", "Eggs"), start);
+
+        assert_eq!(held.take_item("item-2"), Some(added("item-2", "Eggs")));
+        assert_eq!(held.take_item("item-2"), None);
+        assert_eq!(held.take_all(), vec![added("item-1", "Milk")]);
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Tracks event-handling tasks that haven't finished yet
+///
+/// Each WebSocket event is handled in its own task; shutdown uses this to
+/// wait for pending notifications to be sent instead of dropping them.
+#[derive(Debug, Default)]
+pub struct InFlight {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+/// Marks a task as in flight until dropped
+pub struct InFlightGuard {
+    in_flight: Arc<InFlight>,
+}
+
+impl InFlight {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Mark a task as started; it counts as in flight until the guard is dropped
+    pub fn start(self: &Arc<Self>) -> InFlightGuard {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard {
+            in_flight: self.clone(),
+        }
+    }
+
+    /// Number of tasks still in flight
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Wait until no tasks are in flight or the timeout elapses
+    /// Returns whether all tasks finished
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let wait = async {
+            loop {
+                let idle = self.idle.notified();
+                if self.count() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait).await.is_ok()
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.in_flight.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.in_flight.idle.notify_waiters();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_idle() {
+        let in_flight = InFlight::new();
+        assert!(in_flight.wait_idle(Duration::from_millis(10)).await);
+
+        let guard = in_flight.start();
+        assert_eq!(in_flight.count(), 1);
+        assert!(!in_flight.wait_idle(Duration::from_millis(10)).await);
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(guard);
+        });
+        assert!(in_flight.wait_idle(Duration::from_secs(5)).await);
+        assert_eq!(in_flight.count(), 0);
+    }
+}
@@ -1,4 +1,28 @@
+pub mod absent;
+pub mod batch;
+pub mod cache_health;
+pub mod coalesce;
+pub mod connection;
+pub mod debounce;
 pub mod diff;
+pub mod digest;
+pub mod drift;
 pub mod handler;
+pub mod held;
+pub mod heartbeat;
+pub mod in_flight;
+pub mod offline;
+pub mod recheck;
+pub mod session;
+pub mod source;
+pub mod throttle;
+pub mod toggles;
+pub mod undo;
+pub mod users;
+pub mod watch;
+pub mod watchdog;
 
 pub use handler::SyncHandler;
+pub use in_flight::InFlight;
+pub use users::UserNames;
+pub use watchdog::{Watchdog, WatchdogAction};
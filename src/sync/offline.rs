@@ -0,0 +1,69 @@
+use crate::config::OfflineChangePolicy;
+use crate::sync::diff::{ChangeCounts, ListChange};
+
+/// What to send for the changes a list went through while events were missed
+#[derive(Debug, Clone, PartialEq)]
+pub enum OfflineChanges {
+    /// Nothing is sent; holds the number of changes dropped
+    Dropped(usize),
+    /// Each change is sent on its own
+    Individual(Vec<ListChange>),
+    /// One summary is sent for the list
+    Summary(ChangeCounts),
+}
+
+impl OfflineChanges {
+    /// Apply the offline change policy to a list's reconciled changes
+    pub fn new(policy: OfflineChangePolicy, changes: Vec<ListChange>) -> Self {
+        match policy {
+            OfflineChangePolicy::Drop => Self::Dropped(changes.len()),
+            OfflineChangePolicy::QueueIndividual => Self::Individual(changes),
+            OfflineChangePolicy::Summarize => Self::Summary(ChangeCounts::from_changes(&changes)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::test_support::{added, checked, removed};
+
+    /// A list where, while offline, Milk was checked off, Eggs removed and
+    /// Bread added
+    fn missed_changes() -> Vec<ListChange> {
+        vec![
+            checked("item-1", "Milk"),
+            removed("item-2", "Eggs"),
+            added("item-3", "Bread"),
+        ]
+    }
+
+    #[test]
+    fn test_drop_policy() {
+        assert_eq!(
+            OfflineChanges::new(OfflineChangePolicy::Drop, missed_changes()),
+            OfflineChanges::Dropped(3)
+        );
+    }
+
+    #[test]
+    fn test_queue_individual_policy() {
+        assert_eq!(
+            OfflineChanges::new(OfflineChangePolicy::QueueIndividual, missed_changes()),
+            OfflineChanges::Individual(missed_changes())
+        );
+    }
+
+    #[test]
+    fn test_summarize_policy() {
+        assert_eq!(
+            OfflineChanges::new(OfflineChangePolicy::Summarize, missed_changes()),
+            OfflineChanges::Summary(ChangeCounts {
+                added: 1,
+                checked: 1,
+                removed: 1,
+                ..ChangeCounts::default()
+            })
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use crate::sync::diff::ListChange;
+use crate::sync::held::{HeldWindow, HoldsChanges};
+use std::time::{Duration, Instant};
+
+/// Drops an uncheck that is undone by a recheck shortly after
+///
+/// Unchecks are held back for the window. A recheck of the same item within
+/// it cancels both out, as the uncheck was most likely a correction; an
+/// uncheck that isn't rechecked is released once the window passes, and any
+/// other change to the item releases it right away, so the recheck that
+/// follows notifies normally.
+#[derive(Debug)]
+pub struct RecheckFilter {
+    held: HeldWindow,
+}
+
+impl RecheckFilter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            held: HeldWindow::new(window),
+        }
+    }
+
+    /// Cancel out or hold back a pass's changes, returning the ones to notify now
+    pub fn filter(&mut self, changes: Vec<ListChange>, now: Instant) -> Vec<ListChange> {
+        let mut ready = Vec::new();
+        for change in changes {
+            match change {
+                change @ ListChange::ItemUnchecked { .. } => self.held.hold(change, now),
+                ListChange::ItemChecked { ref item, .. }
+                    if self.held.take_item(&item.id).is_some() => {}
+                change => {
+                    // Keep a held uncheck ahead of any other change to the same item
+                    let item_ids: Vec<String> =
+                        change.item_ids().into_iter().map(str::to_string).collect();
+                    ready.extend(item_ids.iter().filter_map(|id| self.held.take_item(id)));
+                    ready.push(change);
+                }
+            }
+        }
+        ready
+    }
+}
+
+impl HoldsChanges for RecheckFilter {
+    /// Release the held unchecks whose window passed without a recheck
+    fn take_expired(&mut self, now: Instant) -> Vec<ListChange> {
+        self.held.take_expired(now)
+    }
+
+    fn take_all(&mut self) -> Vec<ListChange> {
+        self.held.take_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::test_support::{checked, unchecked};
+    use crate::sync::diff::FieldChange;
+
+    #[test]
+    fn test_recheck_within_window_cancels_out() {
+        let mut filter = RecheckFilter::new(Duration::from_secs(300));
+        let start = Instant::now();
+
+        assert!(filter
+            .filter(vec![unchecked("item-1", "Milk")], start)
+            .is_empty());
+        assert!(filter
+            .filter(
+                vec![checked("item-1", "Milk")],
+                start + Duration::from_secs(120)
+            )
+            .is_empty());
+
+        assert!(filter.take_all().is_empty());
+    }
+
+    #[test]
+    fn test_uncheck_left_alone_is_released() {
+        let mut filter = RecheckFilter::new(Duration::from_secs(300));
+        let start = Instant::now();
+        filter.filter(vec![unchecked("item-1", "Milk")], start);
+
+        assert!(filter
+            .take_expired(start + Duration::from_secs(120))
+            .is_empty());
+        assert_eq!(
+            filter.take_expired(start + Duration::from_secs(300)),
+            vec![unchecked("item-1", "Milk")]
+        );
+
+        // A recheck after the window notifies normally
+        assert_eq!(
+            filter.filter(
+                vec![checked("item-1", "Milk")],
+                start + Duration::from_secs(400)
+            ),
+            vec![checked("item-1", "Milk")]
+        );
+    }
+
+    #[test]
+    fn test_other_change_releases_uncheck() {
+        let mut filter = RecheckFilter::new(Duration::from_secs(300));
+        let start = Instant::now();
+        filter.filter(vec![unchecked("item-1", "Milk")], start);
+
+        let modified = ListChange::ItemModified {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item_id: "item-1".to_string(),
+            item_name: "Milk".to_string(),
+            changes: vec![FieldChange::Quantity {
+                old: Some("1".to_string()),
+                new: Some("2".to_string()),
+            }],
+            user_id: Some("user-1".to_string()),
+        };
+        assert_eq!(
+            filter.filter(vec![modified.clone()], start),
+            vec![unchecked("item-1", "Milk"), modified]
+        );
+
+        // The uncheck was released, so the recheck isn't swallowed
+        assert_eq!(
+            filter.filter(vec![checked("item-1", "Milk")], start),
+            vec![checked("item-1", "Milk")]
+        );
+    }
+
+    #[test]
+    fn test_other_items_are_independent() {
+        let mut filter = RecheckFilter::new(Duration::from_secs(300));
+        let start = Instant::now();
+        filter.filter(vec![unchecked("item-1", "Milk")], start);
+
+        assert_eq!(
+            filter.filter(vec![checked("item-2", "Eggs")], start),
+            vec![checked("item-2", "Eggs")]
+        );
+        assert_eq!(filter.take_all(), vec![unchecked("item-1", "Milk")]);
+    }
+}
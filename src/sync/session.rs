@@ -0,0 +1,255 @@
+use crate::sync::diff::{is_user_action, ListChange};
+use crate::sync::held::HoldsChanges;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// A list ID and the ID of the user changing it
+type SessionKey = (String, String);
+
+/// Detects bursts of changes by one user to a list and summarizes them
+///
+/// Once a user makes `threshold` item changes to a list within `window`, the
+/// list counts as being edited by them: that change and any further ones by
+/// the same user are held until they stop for `quiet`, then released as one
+/// summary. Changes not attributed to a user are never held.
+#[derive(Debug)]
+pub struct EditingSessions {
+    threshold: usize,
+    window: Duration,
+    quiet: Duration,
+    recent: HashMap<SessionKey, VecDeque<Instant>>,
+    sessions: HashMap<SessionKey, Session>,
+}
+
+#[derive(Debug)]
+struct Session {
+    list_name: String,
+    last_change: Instant,
+    held: Vec<ListChange>,
+}
+
+/// A user who just started an editing session on a list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionStart {
+    pub list_name: String,
+    pub user_id: String,
+}
+
+impl EditingSessions {
+    pub fn new(threshold: usize, window: Duration, quiet: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            quiet,
+            recent: HashMap::new(),
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// Hold back changes made during editing sessions, returning the changes to
+    /// notify now and the sessions that started with this pass
+    pub fn filter(
+        &mut self,
+        changes: Vec<ListChange>,
+        now: Instant,
+    ) -> (Vec<ListChange>, Vec<SessionStart>) {
+        let window = self.window;
+        let mut ready = Vec::new();
+        let mut started = Vec::new();
+        for change in changes {
+            let key = match change.user_id() {
+                Some(user_id) if is_user_action(&change) => {
+                    (change.list_id().to_string(), user_id.to_string())
+                }
+                _ => {
+                    ready.push(change);
+                    continue;
+                }
+            };
+
+            if let Some(session) = self.sessions.get_mut(&key) {
+                session.last_change = now;
+                session.held.push(change);
+                continue;
+            }
+
+            let recent = self.recent.entry(key.clone()).or_default();
+            recent.push_back(now);
+            while recent
+                .front()
+                .is_some_and(|at| now.duration_since(*at) > window)
+            {
+                recent.pop_front();
+            }
+            if recent.len() < self.threshold {
+                ready.push(change);
+                continue;
+            }
+
+            debug!("{} started editing {}", key.1, change.list_name());
+            self.recent.remove(&key);
+            started.push(SessionStart {
+                list_name: change.list_name().to_string(),
+                user_id: key.1.clone(),
+            });
+            self.sessions.insert(
+                key,
+                Session {
+                    list_name: change.list_name().to_string(),
+                    last_change: now,
+                    held: vec![change],
+                },
+            );
+        }
+        (ready, started)
+    }
+}
+
+impl HoldsChanges for EditingSessions {
+    /// Summarize the sessions that have been quiet for long enough
+    fn take_expired(&mut self, now: Instant) -> Vec<ListChange> {
+        let window = self.window;
+        self.recent.retain(|_, recent| {
+            recent
+                .back()
+                .is_some_and(|at| now.duration_since(*at) <= window)
+        });
+
+        let quiet = self.quiet;
+        let ended: Vec<SessionKey> = self
+            .sessions
+            .iter()
+            .filter(|(_, session)| now.duration_since(session.last_change) >= quiet)
+            .map(|(key, _)| key.clone())
+            .collect();
+        ended
+            .into_iter()
+            .filter_map(|key| {
+                let session = self.sessions.remove(&key)?;
+                Some(summarize(key, session))
+            })
+            .collect()
+    }
+
+    fn take_all(&mut self) -> Vec<ListChange> {
+        self.recent.clear();
+        self.sessions
+            .drain()
+            .map(|(key, session)| summarize(key, session))
+            .collect()
+    }
+}
+
+/// One change for a session that held a single change, otherwise a per-user summary
+fn summarize((list_id, user_id): SessionKey, mut session: Session) -> ListChange {
+    if session.held.len() == 1 {
+        return session.held.remove(0);
+    }
+    debug!(
+        "{} finished editing {} with {} change(s)",
+        user_id,
+        session.list_name,
+        session.held.len()
+    );
+    ListChange::ChangesByUser {
+        list_id,
+        list_name: session.list_name,
+        user_id: Some(user_id),
+        changes: session.held,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::test_support::{added, by_user};
+
+    fn added_by(name: &str, user_id: &str) -> ListChange {
+        by_user(added(&format!("id-{}", name), name), user_id)
+    }
+
+    fn sessions() -> EditingSessions {
+        EditingSessions::new(3, Duration::from_secs(60), Duration::from_secs(120))
+    }
+
+    #[test]
+    fn test_burst_is_held_and_summarized() {
+        let mut sessions = sessions();
+        let start = Instant::now();
+
+        let (ready, started) = sessions.filter(
+            vec![added_by("Milk", "alice"), added_by("Eggs", "alice")],
+            start,
+        );
+        assert_eq!(ready.len(), 2);
+        assert!(started.is_empty());
+
+        // The third change within the window starts a session
+        let (ready, started) = sessions.filter(
+            vec![added_by("Bread", "alice"), added_by("Apples", "bob")],
+            start + Duration::from_secs(10),
+        );
+        assert_eq!(ready, vec![added_by("Apples", "bob")]);
+        assert_eq!(
+            started,
+            vec![SessionStart {
+                list_name: "Groceries".to_string(),
+                user_id: "alice".to_string(),
+            }]
+        );
+
+        // Further changes extend the session
+        let (ready, started) = sessions.filter(
+            vec![added_by("Butter", "alice")],
+            start + Duration::from_secs(100),
+        );
+        assert!(ready.is_empty());
+        assert!(started.is_empty());
+        assert!(sessions
+            .take_expired(start + Duration::from_secs(200))
+            .is_empty());
+
+        let summary = sessions.take_expired(start + Duration::from_secs(220));
+        assert_eq!(
+            summary,
+            vec![ListChange::ChangesByUser {
+                list_id: "list-1".to_string(),
+                list_name: "Groceries".to_string(),
+                user_id: Some("alice".to_string()),
+                changes: vec![added_by("Bread", "alice"), added_by("Butter", "alice")],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_slow_changes_never_start_a_session() {
+        let mut sessions = sessions();
+        let start = Instant::now();
+
+        for minute in 0..5 {
+            let (ready, started) = sessions.filter(
+                vec![added_by("Milk", "alice")],
+                start + Duration::from_secs(minute * 61),
+            );
+            assert_eq!(ready.len(), 1);
+            assert!(started.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_take_all_releases_open_sessions() {
+        let mut sessions = sessions();
+        let now = Instant::now();
+        let changes = vec![
+            added_by("Milk", "alice"),
+            added_by("Eggs", "alice"),
+            added_by("Bread", "alice"),
+        ];
+
+        let (ready, _) = sessions.filter(changes, now);
+        assert_eq!(ready.len(), 2);
+        // A session holding a single change releases it unchanged
+        assert_eq!(sessions.take_all(), vec![added_by("Bread", "alice")]);
+    }
+}
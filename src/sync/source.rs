@@ -0,0 +1,26 @@
+use anyhow::Result;
+use anylist_rs::{AnyListClient, List};
+use async_trait::async_trait;
+
+/// Where the sync handler fetches lists from
+///
+/// This is the AnyList client in the service; tests serve fixed lists.
+#[async_trait]
+pub trait ListSource: Send + Sync {
+    /// ID of the user the lists are fetched as
+    fn user_id(&self) -> String;
+
+    /// Fetch every list with its items and shared users
+    async fn get_lists(&self) -> Result<Vec<List>>;
+}
+
+#[async_trait]
+impl ListSource for AnyListClient {
+    fn user_id(&self) -> String {
+        AnyListClient::user_id(self)
+    }
+
+    async fn get_lists(&self) -> Result<Vec<List>> {
+        Ok(AnyListClient::get_lists(self).await?)
+    }
+}
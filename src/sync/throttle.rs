@@ -0,0 +1,322 @@
+use crate::sync::diff::{FieldChange, ListChange};
+use crate::sync::held::HoldsChanges;
+use std::collections::HashMap;
+use std::mem::discriminant;
+use std::time::{Duration, Instant};
+
+/// Limits notifications for a single item to one per interval
+///
+/// A change arriving within the interval is held instead of sent, along with
+/// any later ones, and they are all sent in order once the interval passes.
+/// Consecutive modifications are merged so the held change still reports
+/// each field's original value.
+#[derive(Debug)]
+pub struct ItemThrottle {
+    interval: Duration,
+    last_sent: HashMap<String, Instant>,
+    /// Held changes by item, in the order the items were first held
+    held: Vec<(String, Vec<ListChange>)>,
+}
+
+impl ItemThrottle {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_sent: HashMap::new(),
+            held: Vec::new(),
+        }
+    }
+
+    /// Return the change if it may be sent now, otherwise hold it
+    pub fn admit(&mut self, change: ListChange, now: Instant) -> Option<ListChange> {
+        let item_id = match change.item_ids().as_slice() {
+            [item_id] => item_id.to_string(),
+            // Changes covering several items or none aren't throttled
+            _ => return Some(change),
+        };
+
+        let index = match self.held.iter().position(|(held, _)| *held == item_id) {
+            Some(index) => index,
+            None if self.elapsed(&item_id, now) => {
+                self.last_sent.insert(item_id, now);
+                return Some(change);
+            }
+            None => {
+                self.held.push((item_id, Vec::new()));
+                self.held.len() - 1
+            }
+        };
+
+        let changes = &mut self.held[index].1;
+        match changes.pop() {
+            Some(last) => changes.extend(merge(last, change)),
+            None => changes.push(change),
+        }
+        if changes.is_empty() {
+            self.held.remove(index);
+        }
+        None
+    }
+
+    /// Whether the item's interval has passed since it was last notified
+    fn elapsed(&self, item_id: &str, now: Instant) -> bool {
+        match self.last_sent.get(item_id) {
+            Some(sent) => now.duration_since(*sent) >= self.interval,
+            None => true,
+        }
+    }
+}
+
+impl HoldsChanges for ItemThrottle {
+    /// Release held changes whose item's interval has passed
+    fn take_expired(&mut self, now: Instant) -> Vec<ListChange> {
+        let (due, held): (Vec<_>, Vec<_>) = std::mem::take(&mut self.held)
+            .into_iter()
+            .partition(|(item_id, _)| self.elapsed(item_id, now));
+        self.held = held;
+
+        let mut ready = Vec::new();
+        for (item_id, changes) in due {
+            ready.extend(changes);
+            self.last_sent.insert(item_id, now);
+        }
+
+        // Forget items that are quiet again
+        let interval = self.interval;
+        self.last_sent
+            .retain(|_, sent| now.duration_since(*sent) < interval);
+        ready
+    }
+
+    fn take_all(&mut self) -> Vec<ListChange> {
+        self.held
+            .drain(..)
+            .flat_map(|(_, changes)| changes)
+            .collect()
+    }
+}
+
+/// Combine a held change with a newer one for the same item
+///
+/// Two modifications keep each field's oldest and newest value, and cancel
+/// out if every field ends up where it started; anything else keeps both
+/// changes.
+fn merge(held: ListChange, newer: ListChange) -> Vec<ListChange> {
+    match (held, newer) {
+        (
+            ListChange::ItemModified {
+                changes: fields, ..
+            },
+            ListChange::ItemModified {
+                list_id,
+                list_name,
+                item_id,
+                item_name,
+                changes: newer_fields,
+                user_id,
+            },
+        ) => {
+            let fields = merge_fields(fields, newer_fields);
+            if fields.is_empty() {
+                return Vec::new();
+            }
+            vec![ListChange::ItemModified {
+                list_id,
+                list_name,
+                item_id,
+                item_name,
+                changes: fields,
+                user_id,
+            }]
+        }
+        (held, newer) => vec![held, newer],
+    }
+}
+
+/// Combine two modifications' fields, leaving out fields back where they started
+fn merge_fields(mut fields: Vec<FieldChange>, newer_fields: Vec<FieldChange>) -> Vec<FieldChange> {
+    for field in newer_fields {
+        match fields
+            .iter_mut()
+            .find(|held| discriminant(*held) == discriminant(&field))
+        {
+            Some(held) => *held = merge_field(held, field),
+            None => fields.push(field),
+        }
+    }
+    fields.retain(|field| !is_unchanged(field));
+    fields
+}
+
+/// Keep the old value of the held field and the new value of the newer one
+fn merge_field(held: &FieldChange, newer: FieldChange) -> FieldChange {
+    match (held, newer) {
+        (FieldChange::Name { old, .. }, FieldChange::Name { new, .. }) => FieldChange::Name {
+            old: old.clone(),
+            new,
+        },
+        (FieldChange::Details { old, .. }, FieldChange::Details { new, .. }) => {
+            FieldChange::Details {
+                old: old.clone(),
+                new,
+            }
+        }
+        (FieldChange::Quantity { old, .. }, FieldChange::Quantity { new, .. }) => {
+            FieldChange::Quantity {
+                old: old.clone(),
+                new,
+            }
+        }
+        (FieldChange::Category { old, .. }, FieldChange::Category { new, .. }) => {
+            FieldChange::Category {
+                old: old.clone(),
+                new,
+            }
+        }
+        (_, newer) => newer,
+    }
+}
+
+fn is_unchanged(field: &FieldChange) -> bool {
+    match field {
+        FieldChange::Name { old, new } | FieldChange::Details { old, new } => old == new,
+        FieldChange::Quantity { old, new } | FieldChange::Category { old, new } => old == new,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::ItemInfo;
+
+    fn quantity_change(old: &str, new: &str) -> ListChange {
+        ListChange::ItemModified {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item_id: "item-1".to_string(),
+            item_name: "Milk".to_string(),
+            changes: vec![FieldChange::Quantity {
+                old: Some(old.to_string()),
+                new: Some(new.to_string()),
+            }],
+            user_id: Some("user-1".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_holds_changes_within_interval() {
+        let mut throttle = ItemThrottle::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert_eq!(
+            throttle.admit(quantity_change("1", "2"), start),
+            Some(quantity_change("1", "2"))
+        );
+        assert_eq!(
+            throttle.admit(quantity_change("2", "3"), start + Duration::from_secs(5)),
+            None
+        );
+        assert_eq!(
+            throttle.admit(quantity_change("3", "4"), start + Duration::from_secs(10)),
+            None
+        );
+
+        assert!(throttle
+            .take_expired(start + Duration::from_secs(30))
+            .is_empty());
+        // The held change spans every edit since the last notification
+        assert_eq!(
+            throttle.take_expired(start + Duration::from_secs(60)),
+            vec![quantity_change("2", "4")]
+        );
+
+        // The release counts as the item's latest notification
+        assert_eq!(
+            throttle.admit(quantity_change("4", "5"), start + Duration::from_secs(70)),
+            None
+        );
+        assert_eq!(throttle.take_all(), vec![quantity_change("4", "5")]);
+    }
+
+    #[test]
+    fn test_reverted_modification_is_dropped() {
+        let mut throttle = ItemThrottle::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        throttle.admit(quantity_change("1", "2"), start);
+        throttle.admit(quantity_change("2", "3"), start);
+        throttle.admit(quantity_change("3", "2"), start);
+
+        assert!(throttle
+            .take_expired(start + Duration::from_secs(60))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_held_changes_are_all_sent() {
+        let mut throttle = ItemThrottle::new(Duration::from_secs(60));
+        let start = Instant::now();
+        let removed = ListChange::ItemRemoved {
+            list_id: "list-1".to_string(),
+            list_name: "Groceries".to_string(),
+            item: ItemInfo {
+                id: "item-1".to_string(),
+                name: "Milk".to_string(),
+                details: "".to_string(),
+                quantity: None,
+                category: None,
+                user_id: None,
+            },
+            user_id: None,
+        };
+
+        throttle.admit(quantity_change("1", "2"), start);
+        throttle.admit(quantity_change("2", "3"), start);
+        assert_eq!(throttle.admit(removed.clone(), start), None);
+
+        // The edits aren't lost to the removal that follows them
+        assert_eq!(
+            throttle.take_expired(start + Duration::from_secs(60)),
+            vec![quantity_change("2", "3"), removed]
+        );
+    }
+
+    #[test]
+    fn test_held_items_are_released_in_order() {
+        let mut throttle = ItemThrottle::new(Duration::from_secs(60));
+        let start = Instant::now();
+        let item = |item_id: &str, old: &str, new: &str| {
+            let mut change = quantity_change(old, new);
+            if let ListChange::ItemModified { item_id: id, .. } = &mut change {
+                *id = item_id.to_string();
+            }
+            change
+        };
+
+        let ids = ["item-3", "item-1", "item-4", "item-2", "item-5"];
+        for item_id in ids {
+            throttle.admit(item(item_id, "1", "2"), start);
+        }
+        for item_id in ids {
+            throttle.admit(item(item_id, "2", "3"), start);
+        }
+
+        assert_eq!(
+            throttle.take_all(),
+            ids.map(|item_id| item(item_id, "2", "3")).to_vec()
+        );
+    }
+
+    #[test]
+    fn test_other_items_are_independent() {
+        let mut throttle = ItemThrottle::new(Duration::from_secs(60));
+        let start = Instant::now();
+        let mut other = quantity_change("1", "2");
+        if let ListChange::ItemModified { item_id, .. } = &mut other {
+            *item_id = "item-2".to_string();
+        }
+
+        throttle.admit(quantity_change("1", "2"), start);
+        assert_eq!(throttle.admit(other.clone(), start), Some(other));
+    }
+}
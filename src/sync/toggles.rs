@@ -0,0 +1,221 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// What to do with a check-state change after counting it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToggleDecision {
+    /// Notify the change as usual
+    Notify,
+    /// The item crossed the threshold; send a single toggling alert instead
+    Alert { count: usize },
+    /// The item is cooling down after an alert; drop the change
+    Suppress,
+}
+
+#[derive(Debug, Default)]
+struct ToggleState {
+    transitions: VecDeque<Instant>,
+    muted_until: Option<Instant>,
+}
+
+/// Counts check-state transitions per item within a sliding window
+///
+/// Once an item reaches the threshold, one alert replaces its individual
+/// check notifications and further toggles are muted for the cooldown.
+#[derive(Debug)]
+pub struct ToggleTracker {
+    threshold: usize,
+    window: Duration,
+    cooldown: Duration,
+    items: HashMap<String, ToggleState>,
+}
+
+impl ToggleTracker {
+    pub fn new(threshold: usize, window: Duration, cooldown: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            cooldown,
+            items: HashMap::new(),
+        }
+    }
+
+    /// Record a check-state transition for an item
+    pub fn record(&mut self, item_id: &str, now: Instant) -> ToggleDecision {
+        let window = self.window;
+        let state = self.items.entry(item_id.to_string()).or_default();
+
+        if matches!(state.muted_until, Some(until) if now < until) {
+            return ToggleDecision::Suppress;
+        }
+        state.muted_until = None;
+
+        state.transitions.push_back(now);
+        while let Some(&oldest) = state.transitions.front() {
+            if now.duration_since(oldest) > window {
+                state.transitions.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if state.transitions.len() >= self.threshold {
+            let count = state.transitions.len();
+            state.transitions.clear();
+            state.muted_until = Some(now + self.cooldown);
+            return ToggleDecision::Alert { count };
+        }
+
+        ToggleDecision::Notify
+    }
+
+    /// Forget items with no recent transitions and no active cooldown
+    pub fn prune(&mut self, now: Instant) {
+        let window = self.window;
+        self.items.retain(|_, state| {
+            let muted = matches!(state.muted_until, Some(until) if now < until);
+            let recent = state
+                .transitions
+                .back()
+                .is_some_and(|&last| now.duration_since(last) <= window);
+            muted || recent
+        });
+    }
+}
+
+/// What to do with a check-state change in "maybe" mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaybeDecision {
+    /// Notify the change as usual
+    Notify,
+    /// The item flipped back within the window; report it once as a "maybe"
+    Maybe,
+    /// The item is still toggling after being reported; drop the change
+    Suppress,
+}
+
+#[derive(Debug)]
+struct MaybeState {
+    last_transition: Instant,
+    reported: bool,
+}
+
+/// Treats an item whose check state flips again within the window as a "maybe"
+///
+/// The second transition is reported once, and the item stays quiet until it
+/// has been left alone for a whole window.
+#[derive(Debug)]
+pub struct MaybeTracker {
+    window: Duration,
+    items: HashMap<String, MaybeState>,
+}
+
+impl MaybeTracker {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            items: HashMap::new(),
+        }
+    }
+
+    /// Record a check-state transition for an item
+    pub fn record(&mut self, item_id: &str, now: Instant) -> MaybeDecision {
+        match self.items.get_mut(item_id) {
+            Some(state) if now.duration_since(state.last_transition) <= self.window => {
+                state.last_transition = now;
+                if state.reported {
+                    MaybeDecision::Suppress
+                } else {
+                    state.reported = true;
+                    MaybeDecision::Maybe
+                }
+            }
+            _ => {
+                self.items.insert(
+                    item_id.to_string(),
+                    MaybeState {
+                        last_transition: now,
+                        reported: false,
+                    },
+                );
+                MaybeDecision::Notify
+            }
+        }
+    }
+
+    /// Forget items whose state has settled
+    pub fn prune(&mut self, now: Instant) {
+        let window = self.window;
+        self.items
+            .retain(|_, state| now.duration_since(state.last_transition) <= window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> ToggleTracker {
+        ToggleTracker::new(3, Duration::from_secs(60), Duration::from_secs(600))
+    }
+
+    #[test]
+    fn test_alerts_at_threshold_then_suppresses() {
+        let mut toggles = tracker();
+        let now = Instant::now();
+
+        assert_eq!(toggles.record("item-1", now), ToggleDecision::Notify);
+        assert_eq!(toggles.record("item-1", now), ToggleDecision::Notify);
+        assert_eq!(
+            toggles.record("item-1", now),
+            ToggleDecision::Alert { count: 3 }
+        );
+        assert_eq!(toggles.record("item-1", now), ToggleDecision::Suppress);
+
+        // Other items are tracked separately
+        assert_eq!(toggles.record("item-2", now), ToggleDecision::Notify);
+
+        // After the cooldown the item notifies again
+        let later = now + Duration::from_secs(601);
+        assert_eq!(toggles.record("item-1", later), ToggleDecision::Notify);
+    }
+
+    #[test]
+    fn test_transitions_outside_window_are_forgotten() {
+        let mut toggles = tracker();
+        let now = Instant::now();
+
+        toggles.record("item-1", now);
+        toggles.record("item-1", now);
+        let later = now + Duration::from_secs(61);
+        assert_eq!(toggles.record("item-1", later), ToggleDecision::Notify);
+
+        toggles.prune(later + Duration::from_secs(61));
+        assert!(toggles.items.is_empty());
+    }
+
+    #[test]
+    fn test_maybe_reported_once_until_settled() {
+        let mut maybe = MaybeTracker::new(Duration::from_secs(300));
+        let now = Instant::now();
+
+        assert_eq!(maybe.record("item-1", now), MaybeDecision::Notify);
+        let soon = now + Duration::from_secs(30);
+        assert_eq!(maybe.record("item-1", soon), MaybeDecision::Maybe);
+
+        // Each toggle extends the quiet period
+        let later = soon + Duration::from_secs(200);
+        assert_eq!(maybe.record("item-1", later), MaybeDecision::Suppress);
+        let still_toggling = later + Duration::from_secs(200);
+        assert_eq!(
+            maybe.record("item-1", still_toggling),
+            MaybeDecision::Suppress
+        );
+
+        // Once left alone for a window the item notifies again
+        let settled = still_toggling + Duration::from_secs(301);
+        maybe.prune(settled);
+        assert!(maybe.items.is_empty());
+        assert_eq!(maybe.record("item-1", settled), MaybeDecision::Notify);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::sync::diff::ListChange;
+use crate::sync::held::{HeldWindow, HoldsChanges};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Drops a removal that is undone by re-adding the item shortly after
+///
+/// Removals are held back for the window. Adding the same item back to the
+/// same list within it, matched by ID or by name since a re-added item
+/// usually gets a new ID, cancels both out. Removals that aren't undone are
+/// released once the window passes.
+#[derive(Debug)]
+pub struct RemovalUndoFilter {
+    held: HeldWindow,
+}
+
+impl RemovalUndoFilter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            held: HeldWindow::new(window),
+        }
+    }
+
+    /// Cancel out or hold back a pass's changes, returning the ones to notify now
+    pub fn filter(&mut self, changes: Vec<ListChange>, now: Instant) -> Vec<ListChange> {
+        // Hold this pass's removals first, so a re-add diffed ahead of its
+        // removal in the same pass still cancels it
+        let (removals, others): (Vec<_>, Vec<_>) = changes
+            .into_iter()
+            .partition(|change| matches!(change, ListChange::ItemRemoved { .. }));
+        for removal in removals {
+            self.held.hold(removal, now);
+        }
+
+        let mut ready = Vec::new();
+        for change in others {
+            if let ListChange::ItemAdded { list_id, item, .. } = &change {
+                if let Some(removed) = self.take_matching(list_id, &item.id, &item.name) {
+                    debug!(
+                        "{} was re-added to {} right after its removal, notifying neither",
+                        item.name,
+                        removed.list_name()
+                    );
+                    continue;
+                }
+            }
+            ready.push(change);
+        }
+        ready
+    }
+
+    fn take_matching(&mut self, list_id: &str, item_id: &str, name: &str) -> Option<ListChange> {
+        self.held.take_first(|change| match change {
+            ListChange::ItemRemoved {
+                list_id: removed_list_id,
+                item,
+                ..
+            } => {
+                removed_list_id == list_id
+                    && (item.id == item_id || item.name.trim().eq_ignore_ascii_case(name.trim()))
+            }
+            _ => false,
+        })
+    }
+}
+
+impl HoldsChanges for RemovalUndoFilter {
+    /// Release the held removals whose window passed without a re-add
+    fn take_expired(&mut self, now: Instant) -> Vec<ListChange> {
+        self.held.take_expired(now)
+    }
+
+    fn take_all(&mut self) -> Vec<ListChange> {
+        self.held.take_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::coalesce::AddCheckCoalescer;
+    use crate::sync::diff::test_support::{added, on_list, removed};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_readd_within_window_cancels_out() {
+        let mut filter = RemovalUndoFilter::new(Duration::from_secs(30));
+        let start = Instant::now();
+
+        assert!(filter
+            .filter(vec![removed("item-1", "Milk")], start)
+            .is_empty());
+        // Re-added under a new ID
+        assert!(filter
+            .filter(
+                vec![added("item-2", "milk")],
+                start + Duration::from_secs(10)
+            )
+            .is_empty());
+
+        assert!(filter.take_all().is_empty());
+    }
+
+    #[test]
+    fn test_readd_in_same_pass_cancels_out() {
+        let mut filter = RemovalUndoFilter::new(Duration::from_secs(30));
+
+        let ready = filter.filter(
+            vec![added("item-2", "Milk"), removed("item-1", "Milk")],
+            Instant::now(),
+        );
+
+        assert!(ready.is_empty());
+        assert!(filter.take_all().is_empty());
+    }
+
+    #[test]
+    fn test_removal_left_alone_is_released() {
+        let mut filter = RemovalUndoFilter::new(Duration::from_secs(30));
+        let start = Instant::now();
+        filter.filter(vec![removed("item-1", "Milk")], start);
+
+        assert!(filter
+            .take_expired(start + Duration::from_secs(10))
+            .is_empty());
+        assert_eq!(
+            filter.take_expired(start + Duration::from_secs(30)),
+            vec![removed("item-1", "Milk")]
+        );
+
+        // A re-add after the window notifies normally
+        assert_eq!(
+            filter.filter(
+                vec![added("item-2", "Milk")],
+                start + Duration::from_secs(40)
+            ),
+            vec![added("item-2", "Milk")]
+        );
+    }
+
+    #[test]
+    fn test_readd_cancels_out_ahead_of_coalescing() {
+        // The handler runs this filter before the add/check coalescer
+        let mut filter = RemovalUndoFilter::new(Duration::from_secs(30));
+        let mut coalescer = AddCheckCoalescer::new(Duration::from_secs(30));
+        let start = Instant::now();
+        let mut pass =
+            |changes, now| coalescer.coalesce(filter.filter(changes, now), &HashSet::new(), now);
+
+        assert!(pass(vec![removed("item-1", "Milk")], start).is_empty());
+        assert!(pass(
+            vec![added("item-2", "Milk")],
+            start + Duration::from_secs(10)
+        )
+        .is_empty());
+
+        assert!(filter.take_all().is_empty());
+        assert!(coalescer.take_all().is_empty());
+    }
+
+    #[test]
+    fn test_other_items_and_lists_are_independent() {
+        let mut filter = RemovalUndoFilter::new(Duration::from_secs(30));
+        let start = Instant::now();
+        filter.filter(vec![removed("item-1", "Milk")], start);
+
+        let unrelated = vec![
+            added("item-2", "Eggs"),
+            on_list(added("item-3", "Milk"), "list-2", "Groceries"),
+        ];
+        assert_eq!(filter.filter(unrelated.clone(), start), unrelated);
+        assert_eq!(filter.take_all(), vec![removed("item-1", "Milk")]);
+    }
+}
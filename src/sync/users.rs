@@ -0,0 +1,223 @@
+use crate::cache::SqliteCache;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, warn};
+
+/// Initial delay before retrying a lookup for an unknown user
+const MISS_BASE_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Upper bound for the delay between lookups for an unknown user
+const MISS_MAX_BACKOFF: Duration = Duration::from_secs(3600);
+
+/// A remembered failed lookup for a user ID
+#[derive(Debug, Clone)]
+struct Miss {
+    attempts: u32,
+    retry_at: Instant,
+}
+
+/// Shared user-id to display-name mapping
+///
+/// Names are served from memory first. Unknown IDs fall back to a single
+/// lookup in the persisted `users` table; misses are remembered with an
+/// exponential backoff so a burst of changes by an unknown user doesn't
+/// repeat the lookup for every change.
+pub struct UserNames {
+    names: RwLock<HashMap<String, String>>,
+    misses: Mutex<HashMap<String, Miss>>,
+    cache: Arc<SqliteCache>,
+}
+
+impl UserNames {
+    pub fn new(cache: Arc<SqliteCache>, initial: HashMap<String, String>) -> Self {
+        Self {
+            names: RwLock::new(initial),
+            misses: Mutex::new(HashMap::new()),
+            cache,
+        }
+    }
+
+    /// Get the in-memory display name for a user ID
+    pub async fn get(&self, user_id: &str) -> Option<String> {
+        self.names.read().await.get(user_id).cloned()
+    }
+
+    /// Number of known users
+    pub async fn len(&self) -> usize {
+        self.names.read().await.len()
+    }
+
+    /// Record a display name, persisting it if it is new or changed
+    pub async fn update(&self, user_id: &str, display_name: &str) -> Result<()> {
+        if self.get(user_id).await.as_deref() == Some(display_name) {
+            return Ok(());
+        }
+
+        self.names
+            .write()
+            .await
+            .insert(user_id.to_string(), display_name.to_string());
+        self.misses.lock().await.remove(user_id);
+
+        self.cache.upsert_user_name(user_id, display_name).await
+    }
+
+    /// Resolve a user ID to a display name, falling back to the ID itself
+    pub async fn resolve(&self, user_id: &str) -> String {
+        if let Some(name) = self.get(user_id).await {
+            return name;
+        }
+
+        if let Some(name) = self.lookup_persisted(user_id).await {
+            return name;
+        }
+
+        user_id.to_string()
+    }
+
+    /// Whether a user ID is unknown and due for another lookup
+    ///
+    /// Unknown IDs are remembered as misses, so this is true at most once per
+    /// backoff period and callers can afford to refetch names when it is.
+    pub async fn is_due_for_refresh(&self, user_id: &str) -> bool {
+        if self.get(user_id).await.is_some() || self.in_backoff(user_id).await {
+            return false;
+        }
+        self.lookup_persisted(user_id).await.is_none()
+    }
+
+    /// Whether a failed lookup for a user ID is too recent to retry
+    async fn in_backoff(&self, user_id: &str) -> bool {
+        self.misses
+            .lock()
+            .await
+            .get(user_id)
+            .is_some_and(|miss| Instant::now() < miss.retry_at)
+    }
+
+    /// Look up a user in the persisted table, honoring miss backoff
+    async fn lookup_persisted(&self, user_id: &str) -> Option<String> {
+        if self.in_backoff(user_id).await {
+            return None;
+        }
+
+        match self.cache.get_user_name(user_id).await {
+            Ok(Some(name)) => {
+                debug!("Resolved user {} from cache", user_id);
+                self.names
+                    .write()
+                    .await
+                    .insert(user_id.to_string(), name.clone());
+                self.misses.lock().await.remove(user_id);
+                Some(name)
+            }
+            Ok(None) => {
+                self.record_miss(user_id).await;
+                None
+            }
+            Err(e) => {
+                warn!("Failed to look up user {} in cache: {}", user_id, e);
+                self.record_miss(user_id).await;
+                None
+            }
+        }
+    }
+
+    /// Remember a failed lookup, doubling the backoff on each repeat
+    async fn record_miss(&self, user_id: &str) {
+        let mut misses = self.misses.lock().await;
+        let attempts = misses.get(user_id).map(|m| m.attempts + 1).unwrap_or(1);
+        let backoff = miss_backoff(attempts);
+
+        debug!(
+            "Unknown user {}, next lookup in {}s",
+            user_id,
+            backoff.as_secs()
+        );
+
+        misses.insert(
+            user_id.to_string(),
+            Miss {
+                attempts,
+                retry_at: Instant::now() + backoff,
+            },
+        );
+    }
+}
+
+/// Backoff for the given number of consecutive misses
+fn miss_backoff(attempts: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+    MISS_BASE_BACKOFF
+        .saturating_mul(factor)
+        .min(MISS_MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_miss_backoff() {
+        assert_eq!(miss_backoff(1), Duration::from_secs(30));
+        assert_eq!(miss_backoff(2), Duration::from_secs(60));
+        assert_eq!(miss_backoff(3), Duration::from_secs(120));
+        assert_eq!(miss_backoff(20), MISS_MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_falls_back_to_persisted_names() {
+        let cache = Arc::new(
+            SqliteCache::new("sqlite::memory:")
+                .await
+                .expect("Failed to create cache"),
+        );
+        cache
+            .upsert_user_name("user-1", "Alice")
+            .await
+            .expect("Failed to upsert user name");
+
+        let names = UserNames::new(cache, HashMap::new());
+
+        assert_eq!(names.resolve("user-1").await, "Alice");
+        assert_eq!(names.get("user-1").await.as_deref(), Some("Alice"));
+
+        // Unknown users fall back to the raw ID and are remembered as misses
+        assert_eq!(names.resolve("user-2").await, "user-2");
+        assert!(names.misses.lock().await.contains_key("user-2"));
+
+        // A later update clears the miss
+        names
+            .update("user-2", "Bob")
+            .await
+            .expect("Failed to update user name");
+        assert_eq!(names.resolve("user-2").await, "Bob");
+        assert!(!names.misses.lock().await.contains_key("user-2"));
+    }
+
+    #[tokio::test]
+    async fn test_is_due_for_refresh() {
+        let cache = Arc::new(
+            SqliteCache::new("sqlite::memory:")
+                .await
+                .expect("Failed to create cache"),
+        );
+        cache
+            .upsert_user_name("user-1", "Alice")
+            .await
+            .expect("Failed to upsert user name");
+
+        let names = UserNames::new(cache, HashMap::new());
+
+        // Persisted users are known
+        assert!(!names.is_due_for_refresh("user-1").await);
+
+        // Unknown users are due once, then wait out the miss backoff
+        assert!(names.is_due_for_refresh("user-2").await);
+        assert!(!names.is_due_for_refresh("user-2").await);
+        assert_eq!(names.resolve("user-2").await, "user-2");
+    }
+}
@@ -0,0 +1,158 @@
+use crate::sync::diff::ListChange;
+
+/// Item names watched across every list, matched case-insensitively
+///
+/// Patterns match the whole item name; `*` stands for any run of characters
+/// and `?` for a single one.
+#[derive(Debug, Clone, Default)]
+pub struct WatchedItems {
+    patterns: Vec<Vec<char>>,
+}
+
+/// A watched item that was just added to a list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchedItemAdded {
+    pub list_name: String,
+    pub item_name: String,
+    pub user_id: Option<String>,
+}
+
+impl WatchedItems {
+    pub fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .map(|pattern| pattern.trim())
+                .filter(|pattern| !pattern.is_empty())
+                .map(|pattern| pattern.to_lowercase().chars().collect())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether an item name matches any watched pattern
+    pub fn matches(&self, name: &str) -> bool {
+        let name: Vec<char> = name.trim().to_lowercase().chars().collect();
+        self.patterns
+            .iter()
+            .any(|pattern| glob_match(pattern, &name))
+    }
+
+    /// Watched items among the added items of `changes`
+    pub fn added(&self, changes: &[ListChange]) -> Vec<WatchedItemAdded> {
+        changes
+            .iter()
+            .filter_map(|change| match change {
+                ListChange::ItemAdded {
+                    list_name,
+                    item,
+                    user_id,
+                    ..
+                } if self.matches(&item.name) => Some(WatchedItemAdded {
+                    list_name: list_name.clone(),
+                    item_name: item.name.clone(),
+                    user_id: user_id.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Match `text` against a pattern where `*` is any run of characters and `?` any one
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::diff::ItemInfo;
+
+    fn watched(patterns: &[&str]) -> WatchedItems {
+        WatchedItems::new(&patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_matches() {
+        let items = watched(&["Prescription refill", "insulin*", "?pi?en", " "]);
+
+        assert!(items.matches("prescription REFILL"));
+        assert!(!items.matches("Prescription refill pickup"));
+        assert!(items.matches("Insulin"));
+        assert!(items.matches("Insulin pens (2)"));
+        assert!(items.matches("EpiPen"));
+        assert!(!items.matches("Epi Pen"));
+        assert!(!items.matches("Milk"));
+        assert!(!watched(&[" "]).matches(""));
+        assert!(watched(&["*milk*"]).matches("Oat milk, unsweetened"));
+    }
+
+    #[test]
+    fn test_added() {
+        let items = watched(&["insulin*"]);
+        let item = |name: &str| ItemInfo {
+            id: format!("id-{}", name),
+            name: name.to_string(),
+            details: "".to_string(),
+            quantity: None,
+            category: None,
+            user_id: None,
+        };
+        let changes = vec![
+            ListChange::ItemAdded {
+                list_id: "list-1".to_string(),
+                list_name: "Pharmacy".to_string(),
+                item: item("Insulin pens"),
+                user_id: Some("alice".to_string()),
+            },
+            ListChange::ItemAdded {
+                list_id: "list-1".to_string(),
+                list_name: "Pharmacy".to_string(),
+                item: item("Bandages"),
+                user_id: None,
+            },
+            ListChange::ItemChecked {
+                list_id: "list-1".to_string(),
+                list_name: "Pharmacy".to_string(),
+                item: item("Insulin pens"),
+                user_id: None,
+            },
+        ];
+
+        assert_eq!(
+            items.added(&changes),
+            vec![WatchedItemAdded {
+                list_name: "Pharmacy".to_string(),
+                item_name: "Insulin pens".to_string(),
+                user_id: Some("alice".to_string()),
+            }]
+        );
+    }
+}
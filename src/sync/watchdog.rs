@@ -0,0 +1,170 @@
+use crate::config::WatchdogConfig;
+use anylist_rs::SyncEvent;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How often the in-flight task count is checked
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What the service should do after a watchdog check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogAction {
+    /// Nothing changed
+    None,
+    /// The in-flight count stayed above the high-water mark for the sustain period
+    Tripped { in_flight: usize },
+    /// The count dropped back to the mark after the watchdog tripped
+    Recovered { skipped_refreshes: usize },
+}
+
+#[derive(Debug, Default)]
+struct WatchdogState {
+    over_since: Option<Instant>,
+    tripped: bool,
+}
+
+/// Watches the number of in-flight event tasks for sustained overload
+///
+/// While tripped with load shedding enabled, new events are skipped. Every
+/// sync pass fetches all lists, so one catch-up pass on recovery covers the
+/// skipped refreshes.
+#[derive(Debug)]
+pub struct Watchdog {
+    high_water_mark: usize,
+    sustain: Duration,
+    shed_load: bool,
+    state: Mutex<WatchdogState>,
+    shedding: AtomicBool,
+    skipped_refreshes: AtomicUsize,
+}
+
+impl Watchdog {
+    pub fn new(config: &WatchdogConfig) -> Self {
+        Self {
+            high_water_mark: config.high_water_mark,
+            sustain: Duration::from_secs(config.sustain_secs),
+            shed_load: config.shed_load,
+            state: Mutex::new(WatchdogState::default()),
+            shedding: AtomicBool::new(false),
+            skipped_refreshes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Whether an incoming event should be handled
+    pub fn admit(&self, event: &SyncEvent) -> bool {
+        if !self.shedding.load(Ordering::SeqCst) {
+            return true;
+        }
+        if matches!(event, SyncEvent::ShoppingListsChanged) {
+            self.skipped_refreshes.fetch_add(1, Ordering::SeqCst);
+        }
+        false
+    }
+
+    /// Compare the in-flight task count against the high-water mark
+    pub fn check(&self, in_flight: usize, now: Instant) -> WatchdogAction {
+        if self.high_water_mark == 0 {
+            return WatchdogAction::None;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if in_flight > self.high_water_mark {
+            let since = *state.over_since.get_or_insert(now);
+            if !state.tripped && now.duration_since(since) >= self.sustain {
+                state.tripped = true;
+                self.shedding.store(self.shed_load, Ordering::SeqCst);
+                return WatchdogAction::Tripped { in_flight };
+            }
+            return WatchdogAction::None;
+        }
+
+        state.over_since = None;
+        if !state.tripped {
+            return WatchdogAction::None;
+        }
+        state.tripped = false;
+        self.shedding.store(false, Ordering::SeqCst);
+        WatchdogAction::Recovered {
+            skipped_refreshes: self.skipped_refreshes.swap(0, Ordering::SeqCst),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watchdog(shed_load: bool) -> Watchdog {
+        Watchdog::new(&WatchdogConfig {
+            high_water_mark: 2,
+            sustain_secs: 10,
+            shed_load,
+            resubscribe: false,
+        })
+    }
+
+    #[test]
+    fn test_trips_after_sustained_overload() {
+        let watchdog = watchdog(true);
+        let start = Instant::now();
+
+        assert_eq!(watchdog.check(3, start), WatchdogAction::None);
+        assert_eq!(
+            watchdog.check(3, start + Duration::from_secs(5)),
+            WatchdogAction::None
+        );
+        assert_eq!(
+            watchdog.check(4, start + Duration::from_secs(10)),
+            WatchdogAction::Tripped { in_flight: 4 }
+        );
+        // Only trips once per overload
+        assert_eq!(
+            watchdog.check(4, start + Duration::from_secs(20)),
+            WatchdogAction::None
+        );
+
+        assert!(!watchdog.admit(&SyncEvent::Heartbeat));
+        assert!(!watchdog.admit(&SyncEvent::ShoppingListsChanged));
+        assert!(!watchdog.admit(&SyncEvent::ShoppingListsChanged));
+
+        assert_eq!(
+            watchdog.check(2, start + Duration::from_secs(21)),
+            WatchdogAction::Recovered {
+                skipped_refreshes: 2
+            }
+        );
+        assert!(watchdog.admit(&SyncEvent::ShoppingListsChanged));
+    }
+
+    #[test]
+    fn test_brief_spike_does_not_trip() {
+        let watchdog = watchdog(true);
+        let start = Instant::now();
+
+        assert_eq!(watchdog.check(5, start), WatchdogAction::None);
+        assert_eq!(
+            watchdog.check(1, start + Duration::from_secs(5)),
+            WatchdogAction::None
+        );
+        // The sustain period restarts after dropping below the mark
+        assert_eq!(
+            watchdog.check(5, start + Duration::from_secs(12)),
+            WatchdogAction::None
+        );
+        assert!(watchdog.admit(&SyncEvent::Heartbeat));
+    }
+
+    #[test]
+    fn test_no_shedding_when_disabled() {
+        let watchdog = watchdog(false);
+        let start = Instant::now();
+
+        watchdog.check(3, start);
+        assert!(matches!(
+            watchdog.check(3, start + Duration::from_secs(10)),
+            WatchdogAction::Tripped { .. }
+        ));
+        assert!(watchdog.admit(&SyncEvent::ShoppingListsChanged));
+    }
+}